@@ -41,6 +41,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     max_delay: Duration::from_secs(10),
                     exponential_backoff: true,
                     backoff_multiplier: 1.5,
+                    ..Default::default()
                 })
         )
         .add_rule(
@@ -4,7 +4,7 @@
 
 use ferrisfetcher::{
     FerrisFetcherBuilder, ExtractionRuleBuilder, ExtractionType,
-    Config, RetryPolicy, RateLimit
+    Config, ProgressReporter, RetryPolicy, RateLimit
 };
 use std::time::Duration;
 
@@ -76,11 +76,13 @@ async fn advanced_configuration() -> Result<(), Box<dyn std::error::Error>> {
             max_delay: Duration::from_secs(5),
             exponential_backoff: true,
             backoff_multiplier: 2.0,
+            ..Default::default()
         })
         .with_rate_limit(RateLimit {
             requests_per_period: 2,
             period: Duration::from_secs(1),
             delay_between_requests: Duration::from_millis(500),
+            adaptive: None,
         });
 
     let fetcher = FerrisFetcherBuilder::new()
@@ -178,12 +180,12 @@ async fn concurrent_scraping() -> Result<(), Box<dyn std::error::Error>> {
 
     let start_time = std::time::Instant::now();
     
-    let results = fetcher.scrape_multiple_with_progress(&urls, |completed, total, data| {
-        let percentage = (completed as f64 / total as f64) * 100.0;
-        println!("    📊 Progress: {}/{} ({:.1}%) - {} - {}ms", 
-            completed, total, percentage, 
-            data.url, data.scrape_time_ms);
-    }).await?;
+    let reporter = ProgressReporter::new(Duration::from_millis(500)).on_report(|report| {
+        println!("    📊 Progress: {}/{} ({:.1}%) - {} in flight, {:.2} pages/s",
+            report.finished(), report.total, report.fraction() * 100.0,
+            report.in_flight, report.rate);
+    });
+    let results = fetcher.scrape_multiple_with_progress(&urls, &reporter).await?;
 
     let elapsed = start_time.elapsed();
     
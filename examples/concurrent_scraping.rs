@@ -2,8 +2,7 @@
 //! 
 //! This example demonstrates how to scrape multiple URLs concurrently with progress reporting.
 
-use ferrisfetcher::{FerrisFetcher, Config};
-use std::sync::Arc;
+use ferrisfetcher::{FerrisFetcher, Config, ProgressReporter};
 use std::time::Duration;
 
 #[tokio::main]
@@ -74,22 +73,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Method 2: Concurrent scraping with progress reporting
     println!("\n📊 Method 2: Concurrent scraping with progress reporting");
     
-    let progress_callback = Arc::new(|completed: usize, total: usize, data: &ferrisfetcher::ScrapedData| {
-        let percentage = (completed as f64 / total as f64) * 100.0;
-        println!("  Progress: {}/{} ({:.1}%) - {} - {}", 
-            completed, total, percentage, 
-            data.url, 
-            data.title.as_deref().unwrap_or("No title")
+    let reporter = ProgressReporter::new(Duration::from_millis(250)).on_report(|report| {
+        let eta = report.eta.map_or("unknown".to_string(), |eta| format!("{:.1}s", eta.as_secs_f64()));
+        println!("  Progress: {}/{} ({:.1}%) - {} running, {} queued, {} failed - {:.2} pages/s, ETA {}",
+            report.finished(), report.total, report.fraction() * 100.0,
+            report.in_flight, report.queued, report.failed,
+            report.rate, eta
         );
     });
 
     let start_time = std::time::Instant::now();
     
-    // We need to clone the callback for the async call
-    let callback = progress_callback.clone();
-    match fetcher.scrape_multiple_with_progress(&urls, move |completed, total, data| {
-        callback(completed, total, data);
-    }).await {
+    match fetcher.scrape_multiple_with_progress(&urls, &reporter).await {
         Ok(results) => {
             let elapsed = start_time.elapsed();
             println!("\n✅ Progress-based scraping completed {} URLs in {:?}", results.len(), elapsed);
@@ -0,0 +1,32 @@
+//! Procedural macros for FerrisFetcher
+//!
+//! Use them through the `ferrisfetcher` crate, which re-exports them.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// Validate a CSS selector at compile time and return a `&'static CssSelector`
+///
+/// The selector is parsed once, on first use. Invalid selectors are compile
+/// errors pointing at the literal.
+#[proc_macro]
+pub fn selector(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+    let source = literal.value();
+
+    if let Err(e) = scraper::Selector::parse(&source) {
+        let message = format!("invalid CSS selector '{}': {}", source, e);
+        return syn::Error::new(literal.span(), message).to_compile_error().into();
+    }
+
+    quote! {
+        {
+            static SELECTOR: ::std::sync::OnceLock<::ferrisfetcher::CssSelector> = ::std::sync::OnceLock::new();
+            SELECTOR.get_or_init(|| {
+                ::ferrisfetcher::CssSelector::parse(#literal).expect("selector validated at compile time")
+            })
+        }
+    }
+    .into()
+}
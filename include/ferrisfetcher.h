@@ -0,0 +1,44 @@
+/*
+ * C ABI of FerrisFetcher, built with the `ffi` feature.
+ *
+ * Strings passed in are borrowed for the duration of a call. Strings handed
+ * out are owned by the caller and must be released with ff_string_free.
+ */
+
+#ifndef FERRISFETCHER_H
+#define FERRISFETCHER_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes returned by ff_scrape */
+#define FF_OK 0               /* the call succeeded */
+#define FF_INVALID_ARGUMENT 1 /* null or non-UTF-8 argument, invalid URL or rules_json; nothing was fetched */
+#define FF_NETWORK 2          /* the request failed to connect, send or complete */
+#define FF_TIMEOUT 3          /* the request timed out */
+#define FF_EXTRACTION 4       /* the page could not be parsed, extracted or validated */
+#define FF_REFUSED 5          /* refused by robots.txt, a rate limit or a quota */
+#define FF_CANCELLED 6        /* the scrape was aborted or cancelled */
+#define FF_INTERNAL 99        /* internal failure (e.g. a panic); the request may already have been sent */
+
+/*
+ * Scrape `url` with the extraction rules in `rules_json` (may be NULL).
+ *
+ * On FF_OK, `*out_json` receives the scraped page as JSON; on any other code
+ * it receives {"error", "category", "code"}. Release it with ff_string_free.
+ * `out_json` may be NULL when only the code is of interest.
+ */
+int ff_scrape(const char *url, const char *rules_json, char **out_json);
+
+/* Release a string handed out by this library; NULL is ignored */
+void ff_string_free(char *ptr);
+
+/* Library version; static, do not free */
+const char *ff_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FERRISFETCHER_H */
@@ -0,0 +1,269 @@
+//! Basic accessibility audit of parsed pages
+
+use crate::html_parser::HtmlParser;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Maximum length of the element snippet recorded on an issue
+const SNIPPET_LEN: usize = 120;
+
+/// Kind of accessibility problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IssueKind {
+    /// Image without an `alt` attribute
+    MissingAlt,
+    /// Link without an accessible name
+    EmptyLink,
+    /// Button without an accessible name
+    EmptyButton,
+    /// Heading level skipped (e.g. `h2` followed by `h4`)
+    HeadingOrder,
+    /// Page without an `h1`
+    MissingH1,
+    /// Form control without a label
+    MissingLabel,
+    /// `<html>` element without a `lang` attribute
+    MissingLang,
+}
+
+/// A single accessibility problem found on a page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessibilityIssue {
+    /// Kind of problem
+    pub kind: IssueKind,
+    /// Human-readable description
+    pub message: String,
+    /// Truncated HTML of the offending element
+    pub element: Option<String>,
+}
+
+/// Accessibility problems found on a page
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessibilityReport {
+    /// All issues, in document order per check
+    pub issues: Vec<AccessibilityIssue>,
+}
+
+impl AccessibilityReport {
+    /// Check whether no issues were found
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Count issues of a kind
+    pub fn count(&self, kind: IssueKind) -> usize {
+        self.issues.iter().filter(|issue| issue.kind == kind).count()
+    }
+
+    fn push(&mut self, kind: IssueKind, message: String, element: Option<ElementRef>) {
+        self.issues.push(AccessibilityIssue {
+            kind,
+            message,
+            element: element.map(snippet),
+        });
+    }
+}
+
+/// Run all accessibility checks on a parsed page
+pub fn audit(parser: &HtmlParser) -> AccessibilityReport {
+    let mut report = AccessibilityReport::default();
+
+    check_lang(parser, &mut report);
+    check_images(parser, &mut report);
+    check_links_and_buttons(parser, &mut report);
+    check_headings(parser, &mut report);
+    check_labels(parser, &mut report);
+
+    report
+}
+
+fn check_lang(parser: &HtmlParser, report: &mut AccessibilityReport) {
+    let has_lang = parser.select_first("html")
+        .and_then(|html| html.value().attr("lang"))
+        .is_some_and(|lang| !lang.trim().is_empty());
+
+    if !has_lang {
+        report.push(IssueKind::MissingLang, "Document language is not declared".to_string(), None);
+    }
+}
+
+fn check_images(parser: &HtmlParser, report: &mut AccessibilityReport) {
+    for image in parser.select("img, input[type='image']").unwrap_or_default() {
+        if image.value().attr("alt").is_none() && !is_hidden(image) {
+            let src = image.value().attr("src").unwrap_or_default();
+            report.push(IssueKind::MissingAlt, format!("Image '{}' has no alt attribute", src), Some(image));
+        }
+    }
+}
+
+fn check_links_and_buttons(parser: &HtmlParser, report: &mut AccessibilityReport) {
+    for link in parser.select("a[href]").unwrap_or_default() {
+        if !has_accessible_name(link) && !is_hidden(link) {
+            let href = link.value().attr("href").unwrap_or_default();
+            report.push(IssueKind::EmptyLink, format!("Link to '{}' has no accessible name", href), Some(link));
+        }
+    }
+
+    for button in parser.select("button, [role='button']").unwrap_or_default() {
+        if !has_accessible_name(button) && !is_hidden(button) {
+            report.push(IssueKind::EmptyButton, "Button has no accessible name".to_string(), Some(button));
+        }
+    }
+}
+
+fn check_headings(parser: &HtmlParser, report: &mut AccessibilityReport) {
+    let headings = parser.select("h1, h2, h3, h4, h5, h6").unwrap_or_default();
+
+    if !headings.iter().any(|heading| heading.value().name() == "h1") {
+        report.push(IssueKind::MissingH1, "Page has no h1 heading".to_string(), None);
+    }
+
+    let mut previous = 0;
+    for heading in headings {
+        let level = heading_level(heading);
+        if previous > 0 && level > previous + 1 {
+            report.push(
+                IssueKind::HeadingOrder,
+                format!("Heading level skipped from h{} to h{}", previous, level),
+                Some(heading),
+            );
+        }
+        previous = level;
+    }
+}
+
+fn check_labels(parser: &HtmlParser, report: &mut AccessibilityReport) {
+    let labelled_ids: HashSet<String> = parser.select_attr("label[for]", "for")
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    for control in parser.select("input, select, textarea").unwrap_or_default() {
+        let element = control.value();
+        if matches!(element.attr("type"), Some("hidden" | "submit" | "button" | "reset" | "image")) {
+            continue;
+        }
+
+        let labelled = element.id().is_some_and(|id| labelled_ids.contains(id))
+            || ["aria-label", "aria-labelledby", "title"].iter().any(|attr| {
+                element.attr(attr).is_some_and(|value| !value.trim().is_empty())
+            })
+            || control.ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| ancestor.value().name() == "label");
+
+        if !labelled {
+            let name = element.attr("name").unwrap_or_default();
+            report.push(
+                IssueKind::MissingLabel,
+                format!("Form control '{}' has no label", name),
+                Some(control),
+            );
+        }
+    }
+}
+
+/// Check whether an element has a name exposed to assistive technology
+fn has_accessible_name(element: ElementRef) -> bool {
+    let value = element.value();
+    if ["aria-label", "aria-labelledby", "title"].iter().any(|attr| {
+        value.attr(attr).is_some_and(|name| !name.trim().is_empty())
+    }) {
+        return true;
+    }
+
+    if element.text().any(|text| !text.trim().is_empty()) {
+        return true;
+    }
+
+    let image_selector = Selector::parse("img[alt], svg[aria-label], input[type='image'][alt]")
+        .expect("static selector is valid");
+    element.select(&image_selector).any(|image| {
+        let name = image.value().attr("alt").or_else(|| image.value().attr("aria-label"));
+        name.is_some_and(|name| !name.trim().is_empty())
+    })
+}
+
+/// Check whether an element is hidden from assistive technology
+fn is_hidden(element: ElementRef) -> bool {
+    element.value().attr("aria-hidden") == Some("true") || element.value().attr("hidden").is_some()
+}
+
+/// Numeric level of a heading element
+fn heading_level(heading: ElementRef) -> u8 {
+    heading.value().name()[1..].parse().unwrap_or(0)
+}
+
+/// Truncated outer HTML of an element
+fn snippet(element: ElementRef) -> String {
+    let html = element.html();
+    match html.char_indices().nth(SNIPPET_LEN) {
+        Some((index, _)) => format!("{}...", &html[..index]),
+        None => html,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_finds_issues() {
+        let html = r#"
+        <html>
+        <body>
+            <h2>Intro</h2>
+            <h4>Details</h4>
+            <img src="/hero.jpg">
+            <img src="/spacer.gif" alt="">
+            <a href="/cart"><i class="icon-cart"></i></a>
+            <a href="/home"><img src="/logo.png" alt="Home"></a>
+            <button></button>
+            <button aria-label="Close">x</button>
+            <form>
+                <input type="text" name="email">
+                <label for="name">Name</label><input id="name" name="name">
+                <label>Phone <input name="phone"></label>
+                <input type="hidden" name="token">
+                <input type="submit" value="Send">
+            </form>
+        </body>
+        </html>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let report = audit(&parser);
+
+        assert_eq!(report.count(IssueKind::MissingLang), 1);
+        assert_eq!(report.count(IssueKind::MissingH1), 1);
+        assert_eq!(report.count(IssueKind::HeadingOrder), 1);
+        assert_eq!(report.count(IssueKind::MissingAlt), 1);
+        assert_eq!(report.count(IssueKind::EmptyLink), 1);
+        assert_eq!(report.count(IssueKind::EmptyButton), 1);
+        assert_eq!(report.count(IssueKind::MissingLabel), 1);
+
+        let label_issue = report.issues.iter().find(|i| i.kind == IssueKind::MissingLabel).unwrap();
+        assert!(label_issue.message.contains("email"));
+        assert!(label_issue.element.as_ref().unwrap().starts_with("<input"));
+    }
+
+    #[test]
+    fn test_audit_clean_page() {
+        let html = r#"
+        <html lang="en">
+        <body>
+            <h1>Title</h1>
+            <h2>Section</h2>
+            <h3>Subsection</h3>
+            <h2>Another section</h2>
+            <img src="/a.png" alt="A chart">
+            <a href="/more">Read more</a>
+        </body>
+        </html>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        assert!(audit(&parser).is_clean());
+    }
+}
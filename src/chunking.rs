@@ -0,0 +1,281 @@
+//! Splitting page text into chunks for embedding and retrieval pipelines
+
+use crate::html_parser::HtmlParser;
+use scraper::{ElementRef, Node};
+use serde::{Deserialize, Serialize};
+
+/// Elements whose text forms a standalone block of content
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "li", "blockquote", "pre", "td", "th", "dt", "dd", "figcaption", "caption",
+];
+
+/// Elements whose text is never part of the readable content
+const HIDDEN_ELEMENTS: &[&str] = &["script", "style", "noscript", "template"];
+
+/// A chunk of readable page text with its source metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextChunk {
+    /// Position of the chunk within the page
+    pub index: usize,
+    /// Chunk text, with paragraphs separated by blank lines
+    pub text: String,
+    /// Number of tokens (whitespace-separated words) in the chunk
+    pub token_count: usize,
+    /// Headings the chunk is nested under, outermost first
+    pub headings: Vec<String>,
+    /// URL of the page the chunk was taken from
+    pub url: String,
+    /// Title of the page the chunk was taken from
+    pub title: Option<String>,
+}
+
+/// A heading or paragraph-level block of text in document order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextBlock {
+    /// Heading level (1-6), or `None` for body text
+    pub heading_level: Option<usize>,
+    /// Whitespace-normalised text
+    pub text: String,
+}
+
+/// Collect the headings and paragraph-level blocks of a document
+///
+/// Blocks nested inside other blocks (a `p` inside an `li`) are folded into
+/// their outermost block. Documents without any block markup yield their
+/// visible text as a single block.
+pub fn text_blocks(parser: &HtmlParser) -> Vec<TextBlock> {
+    let document = parser.document();
+    let root = parser.select_first("body").unwrap_or_else(|| document.root_element());
+    let mut blocks = Vec::new();
+
+    for node in root.descendants() {
+        let element = match ElementRef::wrap(node) {
+            Some(element) => element,
+            None => continue,
+        };
+
+        let name = element.value().name();
+        if !is_block(name) {
+            continue;
+        }
+
+        let nested = node.ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|ancestor| {
+                let name = ancestor.value().name();
+                is_block(name) || HIDDEN_ELEMENTS.contains(&name)
+            });
+        if nested {
+            continue;
+        }
+
+        let text = readable_text(element);
+        if !text.is_empty() {
+            blocks.push(TextBlock { heading_level: heading_level(name), text });
+        }
+    }
+
+    if blocks.is_empty() {
+        let text = parser.visible_text();
+        if !text.is_empty() {
+            blocks.push(TextBlock { heading_level: None, text });
+        }
+    }
+
+    blocks
+}
+
+/// Split blocks into chunks of at most `max_tokens` tokens
+///
+/// Every heading starts a new chunk. Within a section, paragraphs are packed
+/// together until the limit is reached, and paragraphs longer than the limit
+/// are split between words. Consecutive chunks of the same section repeat the
+/// last `overlap` tokens of the previous chunk; `overlap` is capped below
+/// `max_tokens` so chunking always makes progress.
+pub fn chunk_blocks(
+    blocks: &[TextBlock],
+    max_tokens: usize,
+    overlap: usize,
+    url: &str,
+    title: Option<&str>,
+) -> Vec<TextChunk> {
+    let mut chunker = Chunker {
+        max_tokens: max_tokens.max(1),
+        overlap: overlap.min(max_tokens.saturating_sub(1)),
+        url,
+        title,
+        headings: Vec::new(),
+        words: Vec::new(),
+        fresh: 0,
+        chunks: Vec::new(),
+    };
+
+    for block in blocks {
+        match block.heading_level {
+            Some(level) => {
+                chunker.flush(false);
+                while chunker.headings.last().is_some_and(|(open, _)| *open >= level) {
+                    chunker.headings.pop();
+                }
+                chunker.headings.push((level, block.text.clone()));
+            }
+            None => chunker.push_paragraph(&block.text),
+        }
+    }
+    chunker.flush(false);
+
+    chunker.chunks
+}
+
+/// Incremental state of [`chunk_blocks`]
+struct Chunker<'a> {
+    max_tokens: usize,
+    overlap: usize,
+    url: &'a str,
+    title: Option<&'a str>,
+    /// Open headings as (level, text)
+    headings: Vec<(usize, String)>,
+    /// Words of the pending chunk, flagged when they start a paragraph
+    words: Vec<(&'a str, bool)>,
+    /// Number of pending words not carried over from the previous chunk
+    fresh: usize,
+    chunks: Vec<TextChunk>,
+}
+
+impl<'a> Chunker<'a> {
+    fn push_paragraph(&mut self, text: &'a str) {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.is_empty() {
+            return;
+        }
+
+        if self.fresh > 0 && self.words.len() + words.len() > self.max_tokens {
+            self.flush(true);
+        }
+
+        for (i, word) in words.into_iter().enumerate() {
+            if self.words.len() >= self.max_tokens {
+                self.flush(true);
+            }
+            self.words.push((word, i == 0));
+            self.fresh += 1;
+        }
+    }
+
+    /// Emit the pending words as a chunk, optionally carrying the overlap forward
+    fn flush(&mut self, carry_overlap: bool) {
+        if self.fresh == 0 {
+            self.words.clear();
+            return;
+        }
+
+        let mut text = String::new();
+        for (i, (word, starts_paragraph)) in self.words.iter().enumerate() {
+            if i > 0 {
+                text.push_str(if *starts_paragraph { "\n\n" } else { " " });
+            }
+            text.push_str(word);
+        }
+
+        self.chunks.push(TextChunk {
+            index: self.chunks.len(),
+            text,
+            token_count: self.words.len(),
+            headings: self.headings.iter().map(|(_, heading)| heading.clone()).collect(),
+            url: self.url.to_string(),
+            title: self.title.map(str::to_string),
+        });
+
+        let keep = if carry_overlap { self.overlap.min(self.words.len()) } else { 0 };
+        self.words.drain(..self.words.len() - keep);
+        if let Some(first) = self.words.first_mut() {
+            first.1 = false;
+        }
+        self.fresh = 0;
+    }
+}
+
+/// Heading level of an element name, if it is a heading
+fn heading_level(name: &str) -> Option<usize> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+/// Check whether an element name is a heading or paragraph-level block
+fn is_block(name: &str) -> bool {
+    heading_level(name).is_some() || BLOCK_ELEMENTS.contains(&name)
+}
+
+/// Whitespace-normalised text of an element, skipping hidden elements
+fn readable_text(element: ElementRef) -> String {
+    let mut text = String::new();
+    for node in element.descendants() {
+        if let Node::Text(fragment) = node.value() {
+            let hidden = node.ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|el| HIDDEN_ELEMENTS.contains(&el.value().name()));
+            if !hidden {
+                text.push_str(fragment);
+                text.push(' ');
+            }
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_blocks() {
+        let html = r#"
+        <body>
+            <h1>Guide</h1>
+            <p>Intro text.</p>
+            <ul><li><p>Nested item</p></li></ul>
+            <script>var ignored = 1;</script>
+        </body>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let blocks = text_blocks(&parser);
+        assert_eq!(blocks, vec![
+            TextBlock { heading_level: Some(1), text: "Guide".to_string() },
+            TextBlock { heading_level: None, text: "Intro text.".to_string() },
+            TextBlock { heading_level: None, text: "Nested item".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_chunk_blocks() {
+        let blocks = vec![
+            TextBlock { heading_level: Some(1), text: "Guide".to_string() },
+            TextBlock { heading_level: None, text: "one two three".to_string() },
+            TextBlock { heading_level: None, text: "four five".to_string() },
+            TextBlock { heading_level: Some(2), text: "Install".to_string() },
+            TextBlock { heading_level: None, text: "a b c d e f g".to_string() },
+            TextBlock { heading_level: Some(2), text: "Usage".to_string() },
+            TextBlock { heading_level: None, text: "run it".to_string() },
+        ];
+
+        let chunks = chunk_blocks(&blocks, 5, 2, "https://example.com", Some("Docs"));
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+        assert_eq!(texts, vec!["one two three\n\nfour five", "a b c d e", "d e f g", "run it"]);
+
+        assert_eq!(chunks[0].headings, vec!["Guide".to_string()]);
+        assert_eq!(chunks[2].headings, vec!["Guide".to_string(), "Install".to_string()]);
+        assert_eq!(chunks[3].headings, vec!["Guide".to_string(), "Usage".to_string()]);
+        assert_eq!(chunks[3].index, 3);
+        assert_eq!(chunks[3].title.as_deref(), Some("Docs"));
+        assert!(chunks.iter().all(|chunk| chunk.token_count <= 5));
+    }
+}
@@ -0,0 +1,177 @@
+//! Page type detection for scraped pages
+
+use crate::html_parser::HtmlParser;
+use crate::types::{PageType, ScrapedData};
+use serde_json::Value;
+use std::fmt::Debug;
+
+/// Classifies a scraped page into a [`PageType`]
+///
+/// The configured classifier runs on every scrape and its answer is stored in
+/// [`ScrapedData::page_type`].
+pub trait PageClassifier: Debug + Send + Sync {
+    /// Classify a page from its parsed document and the response data
+    fn classify(&self, parser: &HtmlParser, data: &ScrapedData) -> PageType;
+}
+
+/// Phrases in the title or main heading that mark an error page
+const ERROR_PHRASES: &[&str] = &["404", "not found", "page not found", "error", "access denied", "forbidden"];
+
+/// Phrases on buttons that mark a product page
+const CART_PHRASES: &[&str] = &["add to cart", "add to basket", "add to bag", "buy now"];
+
+/// Word count above which a page with an `<article>` element counts as an article
+const ARTICLE_MIN_WORDS: usize = 300;
+
+/// Number of repeated items from which a page counts as a listing
+const LISTING_MIN_ITEMS: usize = 3;
+
+/// Default classifier based on status codes, structured data and page structure
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicClassifier;
+
+impl PageClassifier for HeuristicClassifier {
+    fn classify(&self, parser: &HtmlParser, data: &ScrapedData) -> PageType {
+        if Self::is_error(parser, data) {
+            PageType::Error
+        } else if Self::is_login(parser) {
+            PageType::Login
+        } else if Self::is_listing(parser) {
+            PageType::Listing
+        } else if Self::is_product(parser) {
+            PageType::Product
+        } else if Self::is_article(parser) {
+            PageType::Article
+        } else {
+            PageType::Other
+        }
+    }
+}
+
+impl HeuristicClassifier {
+    /// Error status codes, or a short page whose title or heading reads like an error
+    fn is_error(parser: &HtmlParser, data: &ScrapedData) -> bool {
+        if data.status().is_error() {
+            return true;
+        }
+
+        let headline = [parser.title(), parser.select_first_text("h1")]
+            .into_iter()
+            .flatten()
+            .map(|text| text.to_lowercase())
+            .collect::<Vec<_>>();
+        let short_page = parser.visible_text().split_whitespace().count() < 150;
+
+        short_page && headline.iter().any(|text| ERROR_PHRASES.iter().any(|phrase| text.contains(phrase)))
+    }
+
+    /// A form with a password field and only a handful of other inputs
+    fn is_login(parser: &HtmlParser) -> bool {
+        parser.forms().iter().any(|form| {
+            let visible: Vec<_> = form.inputs
+                .iter()
+                .filter(|input| !matches!(input.input_type.as_str(), "hidden" | "submit" | "button" | "checkbox"))
+                .collect();
+            visible.iter().any(|input| input.input_type == "password") && visible.len() <= 3
+        })
+    }
+
+    /// Several repeated product or article items
+    fn is_listing(parser: &HtmlParser) -> bool {
+        let product_items = parser.count("[itemtype*='schema.org/Product']")
+            .max(parser.count(".product, .product-item, .product-card"));
+        let article_items = parser.count("article");
+        let item_list = json_ld_types(parser).iter().any(|kind| kind == "ItemList");
+
+        product_items >= LISTING_MIN_ITEMS || article_items >= LISTING_MIN_ITEMS || item_list
+    }
+
+    /// Product structured data, Open Graph type or an add-to-cart button
+    fn is_product(parser: &HtmlParser) -> bool {
+        if json_ld_types(parser).iter().any(|kind| kind == "Product")
+            || parser.meta_property("og:type").is_some_and(|kind| kind.eq_ignore_ascii_case("product"))
+            || parser.has_selector("[itemtype*='schema.org/Product'], [itemprop='price']")
+        {
+            return true;
+        }
+
+        parser.select_text("button, input[type='submit'], a")
+            .unwrap_or_default()
+            .iter()
+            .any(|text| {
+                let text = text.to_lowercase();
+                CART_PHRASES.iter().any(|phrase| text.contains(phrase))
+            })
+    }
+
+    /// Article structured data, Open Graph type or a long `<article>` element
+    fn is_article(parser: &HtmlParser) -> bool {
+        json_ld_types(parser)
+            .iter()
+            .any(|kind| matches!(kind.as_str(), "Article" | "NewsArticle" | "BlogPosting" | "TechArticle"))
+            || parser.meta_property("og:type").is_some_and(|kind| kind.eq_ignore_ascii_case("article"))
+            || parser.select_first_text("article")
+                .is_some_and(|text| text.split_whitespace().count() >= ARTICLE_MIN_WORDS)
+    }
+}
+
+/// Collect the `@type` values of a page's JSON-LD, including `@graph` entries
+fn json_ld_types(parser: &HtmlParser) -> Vec<String> {
+    fn collect(value: &Value, types: &mut Vec<String>) {
+        match value {
+            Value::Array(items) => items.iter().for_each(|item| collect(item, types)),
+            Value::Object(object) => {
+                match object.get("@type") {
+                    Some(Value::String(kind)) => types.push(kind.clone()),
+                    Some(Value::Array(kinds)) => {
+                        types.extend(kinds.iter().filter_map(|kind| kind.as_str().map(str::to_string)))
+                    }
+                    _ => {}
+                }
+                if let Some(graph) = object.get("@graph") {
+                    collect(graph, types);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut types = Vec::new();
+    for value in parser.json_ld() {
+        collect(&value, &mut types);
+    }
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(html: &str, status_code: u16) -> PageType {
+        let parser = HtmlParser::new(html).unwrap();
+        let mut data = ScrapedData::new("https://example.com".to_string());
+        data.status_code = status_code;
+        HeuristicClassifier.classify(&parser, &data)
+    }
+
+    #[test]
+    fn test_heuristic_classifier() {
+        assert_eq!(classify("<h1>Welcome</h1>", 404), PageType::Error);
+        assert_eq!(classify("<title>Page Not Found</title><p>Sorry.</p>", 200), PageType::Error);
+
+        let login = r#"<form><input name="user"><input type="password" name="pass"><input type="submit"></form>"#;
+        assert_eq!(classify(login, 200), PageType::Login);
+
+        let product = r#"<h1>Widget</h1><span itemprop="price">10</span><button>Add to cart</button>"#;
+        assert_eq!(classify(product, 200), PageType::Product);
+
+        let listing = r#"<div class="product">A</div><div class="product">B</div><div class="product">C</div>"#;
+        assert_eq!(classify(listing, 200), PageType::Listing);
+
+        let article = r#"<script type="application/ld+json">{"@graph": [{"@type": "BlogPosting"}]}</script>
+                         <article><h1>Post</h1><p>Hello</p></article>"#;
+        assert_eq!(classify(article, 200), PageType::Article);
+
+        assert_eq!(classify("<p>Just some text</p>", 200), PageType::Other);
+    }
+}
@@ -1,11 +1,33 @@
 //! Configuration management for FerrisFetcher
 
+use crate::classify::{HeuristicClassifier, PageClassifier};
+use crate::clock::{Clock, ManualClock, RandomSource, SystemClock};
+use crate::cost::CostModel;
 use crate::error::{FerrisFetcherError, Result};
-use crate::types::{HttpMethod, RateLimit, RetryPolicy};
+use crate::health::HealthPolicy;
+use crate::http_cache::HttpCache;
+#[cfg(feature = "llm")]
+use crate::llm::LlmExtractor;
+use crate::metadata::MetadataExtractor;
+use crate::robots::RobotsPolicy;
+use crate::text_analysis::KeywordAnalyzer;
+use crate::types::{ErrorHook, HttpMethod, MetadataLevel, RateLimit, RetryDecision, RetryPolicy};
+use crate::validation::ValidationReport;
+use chrono::{DateTime, Utc};
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use std::time::Duration;
 use url::Url;
 
+/// Redirect limit used unless configured otherwise
+const DEFAULT_MAX_REDIRECTS: usize = 5;
+
+/// Documents of at least this many bytes are parsed off the async runtime by default
+const DEFAULT_PARSE_OFFLOAD_THRESHOLD: usize = 256 * 1024;
+
 /// Main configuration for FerrisFetcher
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -15,10 +37,14 @@ pub struct Config {
     pub timeout: Duration,
     /// Maximum number of concurrent requests
     pub max_concurrent_requests: usize,
-    /// Rate limiting configuration
+    /// Rate limit applied to each host separately
     pub rate_limit: Option<RateLimit>,
+    /// Rate limits replacing `rate_limit` for specific hosts and their subdomains
+    pub host_rate_limits: HashMap<String, RateLimit>,
     /// Retry policy for failed requests
     pub retry_policy: RetryPolicy,
+    /// Callback overriding the retry policy for failed attempts
+    pub on_error: Option<ErrorHook>,
     /// Custom headers to send with every request
     pub headers: HeaderMap,
     /// Whether to follow redirects
@@ -49,6 +75,47 @@ pub struct Config {
     pub gzip: bool,
     /// Deflate compression
     pub deflate: bool,
+    /// Inline `<template>` and declarative shadow DOM content before extraction
+    pub inline_templates: bool,
+    /// Fetch same-origin iframes and attach them as child results
+    pub fetch_iframes: bool,
+    /// Return the page's declared `rel=canonical` version instead of the requested URL
+    pub follow_canonical: bool,
+    /// Additional hosts whose iframes may be fetched
+    pub iframe_allowed_hosts: Vec<String>,
+    /// Automatic metadata computed per scrape
+    pub metadata_level: MetadataLevel,
+    /// Custom metadata extractors run after the built-in metadata extraction
+    pub metadata_extractors: Vec<Arc<dyn MetadataExtractor>>,
+    /// Keyword analysis recorded as `top_keywords` metadata, if enabled
+    pub keyword_analysis: Option<KeywordAnalyzer>,
+    /// Log replacement selector suggestions when a previously matching rule breaks
+    pub suggest_rule_repairs: bool,
+    /// Classifier recording each page's type, if enabled
+    pub page_classifier: Option<Arc<dyn PageClassifier>>,
+    /// Pricing applied to per-host traffic in cost reports
+    pub cost_model: Option<Arc<dyn CostModel>>,
+    /// Scoring of per-host health and pausing of failing hosts
+    pub health_policy: HealthPolicy,
+    /// Parse documents of at least this many bytes on the blocking thread pool
+    pub parse_offload_threshold: Option<usize>,
+    /// Maximum number of documents parsed on the blocking thread pool at once
+    pub parse_threads: usize,
+    /// Whether robots.txt rules and crawl delays are honoured
+    pub robots_policy: RobotsPolicy,
+    /// Cache of GET responses, revalidated with `ETag` / `Last-Modified`
+    pub http_cache: Option<Arc<HttpCache>>,
+    /// Approximate bytes of bodies, queued results and cached results above which intake pauses
+    pub memory_budget: Option<usize>,
+    /// Identifier of the job, recorded on every scraped page
+    pub run_id: Option<String>,
+    /// Clock stamping scraped pages, request statistics and crawl reports
+    pub clock: Arc<dyn Clock>,
+    /// Randomness behind retry jitter and shuffled batch schedules
+    pub random: RandomSource,
+    /// LLM extractor filling fields the extraction rules did not produce
+    #[cfg(feature = "llm")]
+    pub llm_fallback: Option<LlmExtractor>,
 }
 
 impl Default for Config {
@@ -71,10 +138,12 @@ impl Default for Config {
             timeout: Duration::from_secs(30),
             max_concurrent_requests: 10,
             rate_limit: Some(RateLimit::default()),
+            host_rate_limits: HashMap::new(),
             retry_policy: RetryPolicy::default(),
+            on_error: None,
             headers,
             follow_redirects: true,
-            max_redirects: 5,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
             cookie_jar: true,
             proxy: None,
             default_method: HttpMethod::Get,
@@ -87,6 +156,27 @@ impl Default for Config {
             brotli: true,
             gzip: true,
             deflate: true,
+            inline_templates: false,
+            fetch_iframes: false,
+            follow_canonical: false,
+            iframe_allowed_hosts: Vec::new(),
+            metadata_level: MetadataLevel::default(),
+            metadata_extractors: Vec::new(),
+            keyword_analysis: None,
+            suggest_rule_repairs: false,
+            page_classifier: Some(Arc::new(HeuristicClassifier)),
+            cost_model: None,
+            health_policy: HealthPolicy::default(),
+            parse_offload_threshold: Some(DEFAULT_PARSE_OFFLOAD_THRESHOLD),
+            parse_threads: std::thread::available_parallelism().map_or(4, |threads| threads.get()),
+            robots_policy: RobotsPolicy::default(),
+            http_cache: None,
+            memory_budget: None,
+            run_id: None,
+            clock: Arc::new(SystemClock),
+            random: RandomSource::system(),
+            #[cfg(feature = "llm")]
+            llm_fallback: None,
         }
     }
 }
@@ -118,13 +208,22 @@ impl Config {
         self
     }
     
-    /// Set rate limiting
+    /// Set the rate limit applied to each host
     pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
         self.rate_limit = Some(rate_limit);
         self
     }
     
-    /// Disable rate limiting
+    /// Set the rate limit of a host and its subdomains; Unicode host names
+    /// are stored in their punycode form
+    pub fn with_host_rate_limit(mut self, host: impl Into<String>, rate_limit: RateLimit) -> Self {
+        let host = host.into();
+        let host = crate::idn::host_to_ascii(&host).unwrap_or_else(|| host.to_lowercase());
+        self.host_rate_limits.insert(host, rate_limit);
+        self
+    }
+    
+    /// Disable the rate limit applied to hosts without their own limit
     pub fn without_rate_limit(mut self) -> Self {
         self.rate_limit = None;
         self
@@ -136,6 +235,93 @@ impl Config {
         self
     }
     
+    /// Parse documents of at least `bytes` on the blocking thread pool
+    pub fn with_parse_offload_threshold(mut self, bytes: usize) -> Self {
+        self.parse_offload_threshold = Some(bytes);
+        self
+    }
+    
+    /// Always parse documents on the async runtime
+    pub fn without_parse_offload(mut self) -> Self {
+        self.parse_offload_threshold = None;
+        self
+    }
+    
+    /// Set how many documents may be parsed on the blocking thread pool at once
+    pub fn with_parse_threads(mut self, threads: usize) -> Self {
+        self.parse_threads = threads;
+        self
+    }
+    
+    /// Set how host health is scored and when failing hosts are paused
+    pub fn with_health_policy(mut self, health_policy: HealthPolicy) -> Self {
+        self.health_policy = health_policy;
+        self
+    }
+    
+    /// Set which automatic metadata is computed per scrape
+    pub fn with_metadata_level(mut self, metadata_level: MetadataLevel) -> Self {
+        self.metadata_level = metadata_level;
+        self
+    }
+    
+    /// Add a custom metadata extractor, run on every scrape
+    pub fn with_metadata_extractor(mut self, extractor: impl MetadataExtractor + 'static) -> Self {
+        self.metadata_extractors.push(Arc::new(extractor));
+        self
+    }
+    
+    /// Set whether robots.txt rules and crawl delays are honoured
+    pub fn with_robots_policy(mut self, robots_policy: RobotsPolicy) -> Self {
+        self.robots_policy = robots_policy;
+        self
+    }
+    
+    /// Cache GET responses, honouring `Cache-Control` and revalidating stale entries
+    pub fn with_http_cache(mut self, cache: HttpCache) -> Self {
+        self.http_cache = Some(Arc::new(cache));
+        self
+    }
+    
+    /// Pause starting new scrapes while more than `bytes` of scraped data are held
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+    
+    /// Tag every scraped page with the job's identifier, which also keys [`ScrapedData::id`]
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+    
+    /// Read the current time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Draw retry jitter and batch shuffles from a generator seeded with `seed`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.random = RandomSource::seeded(seed);
+        self
+    }
+    
+    /// Stamp everything with a clock fixed at `start` and seed all randomness,
+    /// so repeated runs against the same responses produce the same output
+    pub fn deterministic(self, start: DateTime<Utc>, seed: u64) -> Self {
+        self.with_clock(Arc::new(ManualClock::new(start))).with_seed(seed)
+    }
+    
+    /// Decide per failed attempt whether to retry, skip the URL or abort the batch
+    pub fn with_on_error<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&FerrisFetcherError, &str, u32) -> RetryDecision + Send + Sync + 'static,
+    {
+        self.on_error = Some(ErrorHook::new(hook));
+        self
+    }
+    
     /// Add a custom header
     pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
         let header_name = name.parse::<reqwest::header::HeaderName>()
@@ -197,37 +383,170 @@ impl Config {
         self
     }
     
-    /// Validate the configuration
+    /// Inline `<template>` and declarative shadow DOM content before extraction
+    pub fn with_template_inlining(mut self) -> Self {
+        self.inline_templates = true;
+        self
+    }
+    
+    /// Fetch a page's `rel=canonical` URL when it differs from the requested one and
+    /// return that result instead, recording the requested URL in `original_url`
+    pub fn follow_canonical(mut self, follow: bool) -> Self {
+        self.follow_canonical = follow;
+        self
+    }
+    
+    /// Fetch same-origin iframes and attach them to results as child entries
+    pub fn with_iframes(mut self) -> Self {
+        self.fetch_iframes = true;
+        self
+    }
+    
+    /// Allow fetching iframes served from another host, given in Unicode or punycode form
+    pub fn with_iframe_host(mut self, host: impl Into<String>) -> Self {
+        self.fetch_iframes = true;
+        let host = host.into();
+        self.iframe_allowed_hosts.push(crate::idn::host_to_ascii(&host).unwrap_or(host));
+        self
+    }
+    
+    /// Check whether an iframe URL embedded in `page_url` may be fetched
+    pub fn allows_iframe(&self, page_url: &Url, frame_url: &Url) -> bool {
+        if !self.fetch_iframes {
+            return false;
+        }
+        
+        page_url.origin() == frame_url.origin()
+            || frame_url.host_str().is_some_and(|host| {
+                self.iframe_allowed_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host))
+            })
+    }
+    
+    /// Record the most frequent keywords and phrases of each page
+    pub fn with_keyword_analysis(mut self, analyzer: KeywordAnalyzer) -> Self {
+        self.keyword_analysis = Some(analyzer);
+        self
+    }
+    
+    /// Log replacement selector suggestions when a previously matching rule breaks
+    pub fn with_rule_repair_suggestions(mut self) -> Self {
+        self.suggest_rule_repairs = true;
+        self
+    }
+    
+    /// Use a custom page classifier
+    pub fn with_page_classifier(mut self, classifier: impl PageClassifier + 'static) -> Self {
+        self.page_classifier = Some(Arc::new(classifier));
+        self
+    }
+    
+    /// Disable page type detection
+    pub fn without_page_classification(mut self) -> Self {
+        self.page_classifier = None;
+        self
+    }
+    
+    /// Price per-host traffic with a cost model (e.g. proxy bandwidth pricing)
+    pub fn with_cost_model(mut self, model: impl CostModel + 'static) -> Self {
+        self.cost_model = Some(Arc::new(model));
+        self
+    }
+    
+    /// Ask an LLM for fields that the extraction rules did not produce
+    #[cfg(feature = "llm")]
+    pub fn with_llm_fallback(mut self, extractor: LlmExtractor) -> Self {
+        self.llm_fallback = Some(extractor);
+        self
+    }
+    
+    /// Hash of the configuration's settings, e.g. for caching fetchers per configuration
+    ///
+    /// Hooks, classifiers and other callbacks contribute only their `Debug`
+    /// output, so two different closures hash the same.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        format!("{:?}", self).hash(&mut hasher);
+        hasher.finish()
+    }
+    
+    /// Validate the configuration, failing with every problem found
     pub fn validate(&self) -> Result<()> {
+        let report = self.validation_report();
+        if report.is_ok() {
+            Ok(())
+        } else {
+            Err(FerrisFetcherError::ConfigError(report.to_string()))
+        }
+    }
+    
+    /// Collect all configuration problems, including conflicting options
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        
         if self.timeout.is_zero() {
-            return Err(FerrisFetcherError::ConfigError("Timeout cannot be zero".to_string()));
+            report.config_issue("Timeout cannot be zero");
         }
         
         if self.max_concurrent_requests == 0 {
-            return Err(FerrisFetcherError::ConfigError("Max concurrent requests must be greater than 0".to_string()));
+            report.config_issue("Max concurrent requests must be greater than 0");
         }
         
         if self.max_redirects == 0 && self.follow_redirects {
-            return Err(FerrisFetcherError::ConfigError("Max redirects must be greater than 0 when following redirects".to_string()));
+            report.config_issue("Max redirects must be greater than 0 when following redirects");
+        }
+        
+        if !self.follow_redirects && self.max_redirects != DEFAULT_MAX_REDIRECTS {
+            report.config_issue("Max redirects is set but redirects are disabled");
         }
         
-        if let Some(rate_limit) = &self.rate_limit {
+        for rate_limit in self.rate_limit.iter().chain(self.host_rate_limits.values()) {
             if rate_limit.requests_per_period == 0 {
-                return Err(FerrisFetcherError::ConfigError("Rate limit requests per period must be greater than 0".to_string()));
+                report.config_issue("Rate limit requests per period must be greater than 0");
+            }
+            if rate_limit.period.is_zero() {
+                report.config_issue("Rate limit period cannot be zero");
+            }
+            if let Some(adaptive) = &rate_limit.adaptive {
+                if !(adaptive.decrease > 0.0 && adaptive.decrease < 1.0) {
+                    report.config_issue("Adaptive rate decrease must be between 0 and 1 (exclusive)");
+                }
+                if !(adaptive.min_fraction > 0.0 && adaptive.min_fraction <= 1.0) {
+                    report.config_issue("Adaptive rate minimum fraction must be between 0 (exclusive) and 1");
+                }
+                if adaptive.increase <= 0.0 || adaptive.latency_spike <= 1.0 {
+                    report.config_issue("Adaptive rate increase must be positive and latency spike greater than 1");
+                }
             }
         }
         
         if self.retry_policy.max_attempts == 0 {
-            return Err(FerrisFetcherError::ConfigError("Retry policy max attempts must be greater than 0".to_string()));
+            report.config_issue("Retry policy max attempts must be greater than 0");
+        }
+        
+        if !self.fetch_iframes && !self.iframe_allowed_hosts.is_empty() {
+            report.config_issue("Iframe hosts are allowed but iframe fetching is disabled");
         }
         
-        Ok(())
+        if self.parse_offload_threshold.is_some() && self.parse_threads == 0 {
+            report.config_issue("Parse threads must be greater than 0 when parsing is offloaded");
+        }
+        
+        if !(self.health_policy.smoothing > 0.0 && self.health_policy.smoothing <= 1.0) {
+            report.config_issue("Health policy smoothing must be between 0 (exclusive) and 1");
+        }
+        
+        if self.memory_budget == Some(0) {
+            report.config_issue("Memory budget must be greater than 0");
+        }
+        
+        report
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::AdaptiveRate;
 
     #[test]
     fn test_default_config() {
@@ -261,6 +580,31 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validation_report_collects_all_issues() {
+        let mut config = Config::new()
+            .with_timeout(Duration::from_secs(0))
+            .with_max_redirects(10)
+            .without_redirects();
+        config.max_concurrent_requests = 0;
+
+        let report = config.validation_report();
+        assert_eq!(report.len(), 3);
+        assert!(report.to_string().contains("redirects are disabled"));
+
+        let adaptive = AdaptiveRate { decrease: 1.5, ..AdaptiveRate::default() };
+        let config = Config::new().with_host_rate_limit("example.com", RateLimit::default().with_adaptive(adaptive));
+        assert!(config.validation_report().to_string().contains("Adaptive rate decrease"));
+        assert!(Config::new().with_rate_limit(RateLimit::default().with_adaptive(AdaptiveRate::default())).validate().is_ok());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let config = Config::new().with_timeout(Duration::from_secs(5));
+        assert_eq!(config.fingerprint(), config.clone().fingerprint());
+        assert_ne!(config.fingerprint(), Config::new().fingerprint());
+    }
+
     #[test]
     fn test_custom_headers() {
         let config = Config::new()
@@ -273,4 +617,24 @@ mod tests {
             "test-value"
         );
     }
+
+    #[test]
+    fn test_iframe_policy() {
+        let page = Url::parse("https://example.com/page").unwrap();
+        let same_origin = Url::parse("https://example.com/widget").unwrap();
+        let youtube = Url::parse("https://www.youtube.com/embed/abc").unwrap();
+        let other = Url::parse("https://ads.example.net/frame").unwrap();
+
+        let config = Config::new();
+        assert!(!config.allows_iframe(&page, &same_origin));
+
+        let config = Config::new().with_iframe_host("www.youtube.com");
+        assert!(config.allows_iframe(&page, &same_origin));
+        assert!(config.allows_iframe(&page, &youtube));
+        assert!(!config.allows_iframe(&page, &other));
+
+        let config = Config::new().with_iframe_host("Bücher.de").with_host_rate_limit("münchen.de", RateLimit::default());
+        assert!(config.allows_iframe(&page, &Url::parse("https://bücher.de/widget").unwrap()));
+        assert!(config.host_rate_limits.contains_key("xn--mnchen-3ya.de"));
+    }
 }
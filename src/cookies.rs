@@ -0,0 +1,354 @@
+//! Cookie jar shared by all requests of a client
+//!
+//! [`CookieJar`] stores cookies set by responses and sends them back on later
+//! requests, following RFC 6265 domain and path matching. Cookies can be
+//! listed per URL and moved in and out of the jar as Netscape `cookies.txt`
+//! (the format of browser export extensions and `curl -c`) or as JSON, so a
+//! session from a manual login can be injected before scraping.
+
+use crate::error::{FerrisFetcherError, Result};
+use chrono::{DateTime, Utc};
+use cookie_store::{CookieDomain, CookieExpiration};
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use url::Url;
+
+/// Prefix curl and browsers put before `cookies.txt` lines of HttpOnly cookies
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// A single cookie as stored in the jar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookieRecord {
+    /// Cookie name
+    pub name: String,
+    /// Cookie value
+    pub value: String,
+    /// Host the cookie belongs to, without a leading dot
+    pub domain: String,
+    /// Whether subdomains of `domain` receive the cookie too
+    #[serde(default)]
+    pub include_subdomains: bool,
+    /// Path prefix the cookie is sent for
+    #[serde(default = "default_path")]
+    pub path: String,
+    /// Only sent over HTTPS
+    #[serde(default)]
+    pub secure: bool,
+    /// Hidden from scripts
+    #[serde(default)]
+    pub http_only: bool,
+    /// Expiry time; `None` for session cookies
+    #[serde(default)]
+    pub expires: Option<DateTime<Utc>>,
+}
+
+fn default_path() -> String {
+    "/".to_string()
+}
+
+impl CookieRecord {
+    /// Create a host-only session cookie for the root path
+    pub fn new(name: impl Into<String>, value: impl Into<String>, domain: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            domain: domain.into().trim_start_matches('.').to_string(),
+            include_subdomains: false,
+            path: default_path(),
+            secure: false,
+            http_only: false,
+            expires: None,
+        }
+    }
+
+    /// Parse one line of a Netscape `cookies.txt` file; `None` for comments and blank lines
+    pub fn from_netscape_line(line: &str) -> Option<Result<Self>> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(rest) => (rest, true),
+            None => (line, false),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 {
+            return Some(Err(FerrisFetcherError::ParseError(format!(
+                "cookies.txt line has {} fields, expected 7: {}", fields.len(), line
+            ))));
+        }
+        let expires = match fields[4].trim().parse::<i64>() {
+            Ok(0) => None,
+            Ok(seconds) => DateTime::from_timestamp(seconds, 0),
+            Err(_) => return Some(Err(FerrisFetcherError::ParseError(format!(
+                "invalid cookie expiry '{}'", fields[4]
+            )))),
+        };
+
+        Some(Ok(Self {
+            name: fields[5].to_string(),
+            value: fields[6].to_string(),
+            domain: fields[0].trim_start_matches('.').to_string(),
+            include_subdomains: fields[1].eq_ignore_ascii_case("TRUE"),
+            path: fields[2].to_string(),
+            secure: fields[3].eq_ignore_ascii_case("TRUE"),
+            http_only,
+            expires,
+        }))
+    }
+
+    /// Format the cookie as one Netscape `cookies.txt` line
+    pub fn to_netscape_line(&self) -> String {
+        let flag = |value: bool| if value { "TRUE" } else { "FALSE" };
+        format!(
+            "{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            if self.http_only { HTTP_ONLY_PREFIX } else { "" },
+            if self.include_subdomains { "." } else { "" },
+            self.domain,
+            flag(self.include_subdomains),
+            self.path,
+            flag(self.secure),
+            self.expires.map_or(0, |expires| expires.timestamp()),
+            self.name,
+            self.value,
+        )
+    }
+
+    /// Whether the cookie has expired
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires <= Utc::now())
+    }
+
+    /// `Set-Cookie` header value recreating this cookie
+    fn set_cookie_header(&self) -> String {
+        let mut header = format!("{}={}; Path={}", self.name, self.value, self.path);
+        if self.include_subdomains {
+            header.push_str(&format!("; Domain={}", self.domain));
+        }
+        if let Some(expires) = self.expires {
+            header.push_str(&expires.format("; Expires=%a, %d %b %Y %H:%M:%S GMT").to_string());
+        }
+        if self.secure {
+            header.push_str("; Secure");
+        }
+        if self.http_only {
+            header.push_str("; HttpOnly");
+        }
+        header
+    }
+
+    /// Request URL the cookie could have been set by
+    fn origin_url(&self) -> Result<Url> {
+        Ok(Url::parse(&format!("https://{}{}", self.domain, self.path))?)
+    }
+
+    fn from_stored(cookie: &cookie_store::Cookie<'_>) -> Option<Self> {
+        let (domain, include_subdomains) = match &cookie.domain {
+            CookieDomain::HostOnly(domain) => (domain.clone(), false),
+            CookieDomain::Suffix(domain) => (domain.clone(), true),
+            CookieDomain::NotPresent | CookieDomain::Empty => return None,
+        };
+        Some(Self {
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            domain,
+            include_subdomains,
+            path: cookie.path.as_ref().to_string(),
+            secure: cookie.secure().unwrap_or(false),
+            http_only: cookie.http_only().unwrap_or(false),
+            expires: match &cookie.expires {
+                CookieExpiration::AtUtc(at) => DateTime::from_timestamp(at.unix_timestamp(), 0),
+                CookieExpiration::SessionEnd => None,
+            },
+        })
+    }
+}
+
+/// Thread-safe cookie store used as the HTTP client's cookie provider
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    store: Mutex<cookie_store::CookieStore>,
+}
+
+impl CookieJar {
+    /// Create an empty jar
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cookies that would be sent with a request to `url`
+    pub fn cookies_for(&self, url: &str) -> Result<Vec<CookieRecord>> {
+        let url = Url::parse(url)?;
+        let store = self.store.lock().unwrap();
+        Ok(store.matches(&url).into_iter().filter_map(CookieRecord::from_stored).collect())
+    }
+
+    /// All unexpired cookies in the jar
+    pub fn all(&self) -> Vec<CookieRecord> {
+        let store = self.store.lock().unwrap();
+        store.iter_unexpired().filter_map(CookieRecord::from_stored).collect()
+    }
+
+    /// Number of unexpired cookies
+    pub fn len(&self) -> usize {
+        self.store.lock().unwrap().iter_unexpired().count()
+    }
+
+    /// Whether the jar holds no unexpired cookies
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add a cookie, replacing one with the same domain, path and name
+    ///
+    /// An expired cookie removes the stored one instead and returns `false`.
+    pub fn insert(&self, cookie: &CookieRecord) -> Result<bool> {
+        let mut store = self.store.lock().unwrap();
+        if cookie.is_expired() {
+            store.remove(&cookie.domain, &cookie.path, &cookie.name);
+            return Ok(false);
+        }
+        store.parse(&cookie.set_cookie_header(), &cookie.origin_url()?)
+            .map_err(|e| FerrisFetcherError::ParseError(format!("invalid cookie '{}': {}", cookie.name, e)))?;
+        Ok(true)
+    }
+
+    /// Remove every cookie
+    pub fn clear(&self) {
+        self.store.lock().unwrap().clear();
+    }
+
+    /// Add the cookies of a Netscape `cookies.txt` file, returning how many were stored
+    ///
+    /// Expired cookies are skipped; any malformed line fails the whole import
+    /// before the jar is changed.
+    pub fn import_netscape(&self, text: &str) -> Result<usize> {
+        let cookies = text.lines()
+            .filter_map(CookieRecord::from_netscape_line)
+            .collect::<Result<Vec<_>>>()?;
+        self.insert_all(&cookies)
+    }
+
+    /// Write all unexpired cookies as a Netscape `cookies.txt` file
+    pub fn export_netscape(&self) -> String {
+        let mut text = String::from("# Netscape HTTP Cookie File\n");
+        for cookie in self.all() {
+            text.push_str(&cookie.to_netscape_line());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Add cookies from a JSON array of [`CookieRecord`]s, returning how many were stored
+    pub fn import_json(&self, json: &str) -> Result<usize> {
+        let cookies: Vec<CookieRecord> = serde_json::from_str(json)?;
+        self.insert_all(&cookies)
+    }
+
+    /// Write all unexpired cookies as a JSON array of [`CookieRecord`]s
+    pub fn export_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.all())?)
+    }
+
+    fn insert_all(&self, cookies: &[CookieRecord]) -> Result<usize> {
+        let mut stored = 0;
+        for cookie in cookies {
+            if self.insert(cookie)? {
+                stored += 1;
+            }
+        }
+        Ok(stored)
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|header| header.to_str().ok())
+            .filter_map(|header| cookie::Cookie::parse(header.to_string()).ok());
+        self.store.lock().unwrap().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self.store.lock().unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+
+    #[test]
+    fn test_netscape_round_trip() {
+        let text = "# Netscape HTTP Cookie File\n\
+            .example.com\tTRUE\t/\tTRUE\t4102444800\tsession\tabc123\n\
+            #HttpOnly_shop.example.com\tFALSE\t/cart\tFALSE\t0\tcart\t42\n\
+            old.example.com\tFALSE\t/\tFALSE\t1000\tstale\tx\n\
+            \n";
+        let jar = CookieJar::new();
+        assert_eq!(jar.import_netscape(text).unwrap(), 2);
+
+        let cookies = jar.cookies_for("https://shop.example.com/cart/items").unwrap();
+        let mut names: Vec<&str> = cookies.iter().map(|cookie| cookie.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["cart", "session"]);
+        let cart = cookies.iter().find(|cookie| cookie.name == "cart").unwrap();
+        assert!(cart.http_only && !cart.include_subdomains && cart.expires.is_none());
+
+        // Secure cookies are not sent over plain HTTP, path-scoped ones only below their path
+        let plain = jar.cookies_for("http://shop.example.com/").unwrap();
+        assert!(plain.is_empty());
+
+        let exported = jar.export_netscape();
+        let copy = CookieJar::new();
+        assert_eq!(copy.import_netscape(&exported).unwrap(), 2);
+        let mut original = jar.all();
+        let mut restored = copy.all();
+        original.sort_by(|a, b| a.name.cmp(&b.name));
+        restored.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(original, restored);
+
+        assert!(jar.import_netscape("example.com\tFALSE\t/\n").is_err());
+    }
+
+    #[test]
+    fn test_json_round_trip_and_request_header() {
+        let jar = CookieJar::new();
+        let json = r#"[{"name": "token", "value": "t0k", "domain": "api.example.com"}]"#;
+        assert_eq!(jar.import_json(json).unwrap(), 1);
+
+        let url = Url::parse("https://api.example.com/v1").unwrap();
+        assert_eq!(jar.cookies(&url).unwrap(), "token=t0k");
+        assert!(jar.cookies(&Url::parse("https://other.example.com/").unwrap()).is_none());
+
+        let copy = CookieJar::new();
+        copy.import_json(&jar.export_json().unwrap()).unwrap();
+        assert_eq!(copy.all(), jar.all());
+    }
+
+    #[test]
+    fn test_response_cookies_are_stored() {
+        let jar = CookieJar::new();
+        let url = Url::parse("https://example.com/login").unwrap();
+        let header = HeaderValue::from_static("sid=xyz; Path=/; HttpOnly");
+        jar.set_cookies(&mut std::iter::once(&header), &url);
+
+        let cookies = jar.cookies_for("https://example.com/account").unwrap();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0], CookieRecord { http_only: true, ..CookieRecord::new("sid", "xyz", "example.com") });
+
+        jar.clear();
+        assert!(jar.is_empty());
+    }
+}
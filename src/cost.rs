@@ -0,0 +1,164 @@
+//! Cost accounting for metered scraping (e.g. proxy bandwidth pricing)
+
+use crate::types::{HostStats, RequestStats};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Bytes in a gigabyte as used by bandwidth pricing
+const BYTES_PER_GB: f64 = 1_000_000_000.0;
+
+/// Prices the traffic sent to a host
+pub trait CostModel: Debug + Send + Sync {
+    /// Cost of the requests and bytes recorded for a host
+    fn cost(&self, host: &str, stats: &HostStats) -> f64;
+
+    /// Currency the costs are expressed in
+    fn currency(&self) -> &str {
+        "USD"
+    }
+}
+
+/// Cost model charging per downloaded gigabyte and per request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BandwidthCost {
+    /// Price per gigabyte downloaded
+    pub price_per_gb: f64,
+    /// Price per request
+    pub price_per_request: f64,
+    /// Per-gigabyte prices overriding the default for specific hosts
+    pub host_prices_per_gb: HashMap<String, f64>,
+    /// Currency of the prices
+    pub currency: String,
+}
+
+impl BandwidthCost {
+    /// Create a cost model charging `price_per_gb` per downloaded gigabyte, in USD
+    pub fn new(price_per_gb: f64) -> Self {
+        Self {
+            price_per_gb,
+            currency: "USD".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Also charge a fixed price per request
+    pub fn with_price_per_request(mut self, price: f64) -> Self {
+        self.price_per_request = price;
+        self
+    }
+
+    /// Charge a different per-gigabyte price for a host (e.g. one routed through premium proxies)
+    pub fn with_host_price(mut self, host: &str, price_per_gb: f64) -> Self {
+        self.host_prices_per_gb.insert(host.to_lowercase(), price_per_gb);
+        self
+    }
+
+    /// Set the currency of the prices
+    pub fn with_currency(mut self, currency: &str) -> Self {
+        self.currency = currency.to_string();
+        self
+    }
+}
+
+impl CostModel for BandwidthCost {
+    fn cost(&self, host: &str, stats: &HostStats) -> f64 {
+        let price_per_gb = self.host_prices_per_gb
+            .get(host)
+            .copied()
+            .unwrap_or(self.price_per_gb);
+        stats.bytes as f64 / BYTES_PER_GB * price_per_gb + stats.requests as f64 * self.price_per_request
+    }
+
+    fn currency(&self) -> &str {
+        &self.currency
+    }
+}
+
+/// Traffic and cost attributed to a single host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostCost {
+    /// Host name
+    pub host: String,
+    /// Requests made to the host
+    pub requests: u64,
+    /// Bytes downloaded from the host
+    pub bytes: u64,
+    /// Cost attributed to the host
+    pub cost: f64,
+}
+
+/// Traffic and cost per host, most expensive first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostReport {
+    /// Per-host breakdown
+    pub hosts: Vec<HostCost>,
+    /// Requests across all hosts
+    pub total_requests: u64,
+    /// Bytes across all hosts
+    pub total_bytes: u64,
+    /// Cost across all hosts
+    pub total_cost: f64,
+    /// Currency of the costs
+    pub currency: String,
+}
+
+impl CostReport {
+    /// Price the per-host statistics with a cost model
+    pub fn new(stats: &RequestStats, model: &dyn CostModel) -> Self {
+        let mut hosts: Vec<HostCost> = stats.hosts
+            .iter()
+            .map(|(host, host_stats)| HostCost {
+                host: host.clone(),
+                requests: host_stats.requests,
+                bytes: host_stats.bytes,
+                cost: model.cost(host, host_stats),
+            })
+            .collect();
+        hosts.sort_by(|a, b| b.cost.total_cmp(&a.cost).then_with(|| a.host.cmp(&b.host)));
+
+        Self {
+            total_requests: hosts.iter().map(|host| host.requests).sum(),
+            total_bytes: hosts.iter().map(|host| host.bytes).sum(),
+            total_cost: hosts.iter().map(|host| host.cost).sum(),
+            currency: model.currency().to_string(),
+            hosts,
+        }
+    }
+
+    /// Get the cost attributed to a host
+    pub fn host_cost(&self, host: &str) -> Option<f64> {
+        self.hosts
+            .iter()
+            .find(|entry| entry.host.eq_ignore_ascii_case(host))
+            .map(|entry| entry.cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_report() {
+        let mut stats = RequestStats::new();
+        stats.record_host("Example.com", true, Some(2_000_000_000));
+        stats.record_host("example.com", false, None);
+        stats.record_host("shop.example.org", true, Some(500_000_000));
+        stats.record_bytes("shop.example.org", 500_000_000);
+
+        let model = BandwidthCost::new(5.0)
+            .with_price_per_request(0.01)
+            .with_host_price("shop.example.org", 12.0)
+            .with_currency("EUR");
+        let report = CostReport::new(&stats, &model);
+
+        assert_eq!(report.hosts.len(), 2);
+        assert_eq!(report.hosts[0].host, "shop.example.org");
+        assert!((report.hosts[0].cost - 12.01).abs() < 1e-9);
+        assert!((report.host_cost("example.com").unwrap() - 10.02).abs() < 1e-9);
+        assert_eq!(report.total_requests, 3);
+        assert_eq!(report.total_bytes, 3_000_000_000);
+        assert_eq!(report.currency, "EUR");
+    }
+}
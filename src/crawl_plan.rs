@@ -0,0 +1,433 @@
+//! Two-phase crawling: enumerate a site cheaply, then fetch with known totals
+//!
+//! [`Crawler::plan`] lists pages from the seeds and the sites' sitemaps
+//! (named in robots.txt, or else `/sitemap.xml`) and sizes each page with a
+//! `HEAD` request, without downloading any page bodies. The resulting
+//! [`CrawlPlan`] estimates the traffic and cost of the job before committing
+//! to it; [`Crawler::execute`] then fetches the planned pages with accurate
+//! progress totals, stopping once a byte budget is spent.
+
+use crate::cost::{CostModel, CostReport};
+use crate::crawler::{site_host, CrawlOptions, CrawledPage, Crawler};
+use crate::error::Result;
+use crate::robots::RobotsPolicy;
+use crate::types::{Discovery, HostStats, HttpMethod, RequestStats};
+use futures::future::BoxFuture;
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt};
+use reqwest::header::CONTENT_LENGTH;
+use std::collections::{HashSet, VecDeque};
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// Most sitemap files read per plan, sitemap indexes included
+const MAX_SITEMAPS: usize = 50;
+
+/// A page the fetch phase will request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedPage {
+    /// URL of the page
+    pub url: String,
+    /// Whether the page is a seed or came from a sitemap
+    pub discovered_via: Discovery,
+    /// Sitemap listing the page
+    pub sitemap: Option<String>,
+    /// Status of the `HEAD` request, if it was answered
+    pub status: Option<u16>,
+    /// `Content-Length` reported by the `HEAD` request
+    pub content_length: Option<u64>,
+}
+
+/// Pages of a crawl enumerated ahead of fetching them
+#[derive(Debug, Clone, Default)]
+pub struct CrawlPlan {
+    /// Pages in fetch order: seeds first, then sitemap entries
+    pub pages: Vec<PlannedPage>,
+    /// Sitemap files read while planning
+    pub sitemaps: Vec<String>,
+    /// Pages left out because robots.txt disallows them
+    pub disallowed: usize,
+}
+
+impl CrawlPlan {
+    /// Number of planned pages
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Whether nothing is planned
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Estimated bytes of the fetch phase; pages of unknown size count as
+    /// the average of the known sizes
+    pub fn estimated_bytes(&self) -> u64 {
+        self.estimated_sizes().iter().map(|(_, bytes)| bytes).sum()
+    }
+
+    /// Price the estimated traffic of the fetch phase, one request per page
+    pub fn estimated_cost(&self, model: &dyn CostModel) -> CostReport {
+        let mut stats = RequestStats::new();
+        for (url, bytes) in self.estimated_sizes() {
+            let host = Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)).unwrap_or_default();
+            let host_stats: &mut HostStats = stats.hosts.entry(host).or_default();
+            host_stats.requests += 1;
+            host_stats.bytes += bytes;
+        }
+        CostReport::new(&stats, model)
+    }
+
+    /// Each page's URL with its known or estimated size
+    fn estimated_sizes(&self) -> Vec<(&str, u64)> {
+        let known: Vec<u64> = self.pages.iter().filter_map(|page| page.content_length).collect();
+        let average = known.iter().sum::<u64>().checked_div(known.len() as u64).unwrap_or(0);
+        self.pages
+            .iter()
+            .map(|page| (page.url.as_str(), page.content_length.unwrap_or(average)))
+            .collect()
+    }
+}
+
+/// A page fetched by [`Crawler::execute`], with the progress of the job
+#[derive(Debug)]
+pub struct PlanProgress {
+    /// The fetched page
+    pub page: CrawledPage,
+    /// Pages finished so far, this one included
+    pub completed: usize,
+    /// Pages in the plan
+    pub total: usize,
+    /// Body bytes downloaded so far
+    pub bytes_fetched: u64,
+}
+
+/// Progress of a running fetch phase
+struct ExecuteState {
+    crawler: Crawler,
+    pending: VecDeque<PlannedPage>,
+    in_flight: FuturesUnordered<BoxFuture<'static, CrawledPage>>,
+    concurrency: usize,
+    byte_budget: Option<u64>,
+    completed: usize,
+    total: usize,
+    bytes_fetched: u64,
+}
+
+impl ExecuteState {
+    /// Start fetches up to the concurrency limit unless the budget is spent
+    fn schedule(&mut self) {
+        while self.in_flight.len() < self.concurrency {
+            if self.byte_budget.is_some_and(|budget| self.bytes_fetched >= budget) {
+                if !self.pending.is_empty() {
+                    info!("Byte budget spent, skipping {} planned pages", self.pending.len());
+                    self.pending.clear();
+                }
+                return;
+            }
+            let Some(planned) = self.pending.pop_front() else { return };
+            let crawler = self.crawler.clone();
+            self.in_flight.push(Box::pin(async move {
+                let result = crawler.fetch(&planned.url, 0).await.map(|mut data| {
+                    data.set_origin(0, planned.sitemap.clone(), planned.discovered_via);
+                    data
+                });
+                CrawledPage {
+                    canonical_url: planned.url.clone(),
+                    url: planned.url,
+                    depth: 0,
+                    parent: planned.sitemap,
+                    result,
+                }
+            }));
+        }
+    }
+}
+
+impl Crawler {
+    /// Enumerate the pages of a crawl without downloading them
+    ///
+    /// Seeds come first, followed by the entries of each seed site's
+    /// sitemaps that pass the option filters, up to `max_pages` in total.
+    /// Links inside pages are not followed, so `max_depth` does not apply.
+    /// Pages that robots.txt disallows are counted but not planned unless
+    /// the configuration ignores robots.txt.
+    pub async fn plan(&self, seeds: &[&str], options: &CrawlOptions) -> Result<CrawlPlan> {
+        let mut plan = CrawlPlan::default();
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+        let mut seed_hosts = HashSet::new();
+        let mut origins = Vec::new();
+
+        for seed in seeds {
+            let url = Url::parse(seed)?;
+            if let Some(host) = url.host_str() {
+                seed_hosts.insert(site_host(host));
+            }
+            let origin = url.origin().ascii_serialization();
+            if !origins.contains(&origin) {
+                origins.push(origin);
+            }
+            candidates.push((url.to_string(), Discovery::Seed, None));
+        }
+
+        let client = self.fetcher().client();
+        let mut sitemap_queue = VecDeque::new();
+        for origin in &origins {
+            let listed = client.robots_rules(origin).await?.sitemaps().to_vec();
+            if listed.is_empty() {
+                sitemap_queue.push_back(format!("{}/sitemap.xml", origin));
+            }
+            sitemap_queue.extend(listed);
+        }
+
+        let mut read = HashSet::new();
+        while let Some(sitemap) = sitemap_queue.pop_front() {
+            if read.len() >= MAX_SITEMAPS {
+                warn!("Sitemap limit of {} reached, ignoring the rest", MAX_SITEMAPS);
+                break;
+            }
+            if !read.insert(sitemap.clone()) {
+                continue;
+            }
+            let Some(entries) = self.read_sitemap(&sitemap).await else { continue };
+            plan.sitemaps.push(sitemap.clone());
+            match entries {
+                SitemapEntries::Index(nested) => sitemap_queue.extend(nested),
+                SitemapEntries::Pages(pages) => {
+                    candidates.extend(pages.into_iter().map(|page| (page, Discovery::Sitemap, Some(sitemap.clone()))));
+                }
+            }
+        }
+
+        let mut accepted = Vec::new();
+        for (url, discovered_via, sitemap) in candidates {
+            if accepted.len() >= options.max_pages {
+                break;
+            }
+            if discovered_via == Discovery::Sitemap {
+                let host = Url::parse(&url).ok().and_then(|url| url.host_str().map(site_host));
+                if options.same_domain && !host.is_some_and(|host| seed_hosts.contains(&host)) {
+                    continue;
+                }
+                if !options.accepts(&url) {
+                    continue;
+                }
+            }
+            if !seen.insert(options.canonical_url(&url)) {
+                continue;
+            }
+            let config = self.fetcher().config();
+            if config.robots_policy == RobotsPolicy::Respect {
+                let rules = client.robots_rules(&url).await?;
+                if !rules.is_allowed(&config.user_agent, &path_and_query(&url)) {
+                    plan.disallowed += 1;
+                    continue;
+                }
+            }
+            accepted.push((url, discovered_via, sitemap));
+        }
+
+        let concurrency = self.fetcher().max_concurrent_requests().max(1);
+        plan.pages = stream::iter(accepted)
+            .map(|(url, discovered_via, sitemap)| async move {
+                let (status, content_length) = match client.request(&url, HttpMethod::Head, None, None).await {
+                    Ok(response) => {
+                        let length = response.headers()
+                            .get(CONTENT_LENGTH)
+                            .and_then(|value| value.to_str().ok())
+                            .and_then(|value| value.parse().ok());
+                        (Some(response.status().as_u16()), length)
+                    }
+                    Err(e) => {
+                        debug!("HEAD {} failed: {}", url, e);
+                        (None, None)
+                    }
+                };
+                PlannedPage { url, discovered_via, sitemap, status, content_length }
+            })
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        info!("Planned {} pages from {} sitemap(s), {} disallowed", plan.pages.len(), plan.sitemaps.len(), plan.disallowed);
+        Ok(plan)
+    }
+
+    /// Fetch the pages of a plan, reporting progress against its total
+    ///
+    /// Pages are yielded as they finish, up to `max_concurrent_requests` at a
+    /// time. Once the downloaded body bytes reach `byte_budget`, no further
+    /// pages are started and the stream ends after the ones in flight.
+    pub fn execute(&self, plan: CrawlPlan, byte_budget: Option<u64>) -> impl Stream<Item = PlanProgress> + Send + 'static {
+        let state = ExecuteState {
+            crawler: self.clone(),
+            total: plan.pages.len(),
+            pending: plan.pages.into(),
+            in_flight: FuturesUnordered::new(),
+            concurrency: self.fetcher().max_concurrent_requests().max(1),
+            byte_budget,
+            completed: 0,
+            bytes_fetched: 0,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            state.schedule();
+            let page = state.in_flight.next().await?;
+            state.completed += 1;
+            if let Ok(data) = &page.result {
+                state.bytes_fetched += data.content.len() as u64;
+            }
+            let progress = PlanProgress {
+                page,
+                completed: state.completed,
+                total: state.total,
+                bytes_fetched: state.bytes_fetched,
+            };
+            Some((progress, state))
+        })
+    }
+
+    /// Read a sitemap file, `None` if it is unavailable
+    async fn read_sitemap(&self, url: &str) -> Option<SitemapEntries> {
+        if url.ends_with(".gz") {
+            warn!("Skipping compressed sitemap {}", url);
+            return None;
+        }
+        let response = match self.fetcher().client().get(url).await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                debug!("Sitemap {} returned {}", url, response.status());
+                return None;
+            }
+            Err(e) => {
+                debug!("Failed to fetch sitemap {}: {}", url, e);
+                return None;
+            }
+        };
+        match response.text().await {
+            Ok(text) => Some(SitemapEntries::parse(&text)),
+            Err(e) => {
+                warn!("Failed to read sitemap {}: {}", url, e);
+                None
+            }
+        }
+    }
+}
+
+/// URLs listed by a sitemap file
+#[derive(Debug, PartialEq, Eq)]
+enum SitemapEntries {
+    /// A `<sitemapindex>` pointing at further sitemaps
+    Index(Vec<String>),
+    /// A `<urlset>` listing pages
+    Pages(Vec<String>),
+}
+
+impl SitemapEntries {
+    /// Collect the `<loc>` values of a sitemap document
+    fn parse(xml: &str) -> Self {
+        let mut locations = Vec::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find("<loc>") {
+            rest = &rest[start + "<loc>".len()..];
+            let Some(end) = rest.find("</loc>") else { break };
+            let location = rest[..end].trim();
+            let location = location
+                .strip_prefix("<![CDATA[")
+                .and_then(|location| location.strip_suffix("]]>"))
+                .unwrap_or(location)
+                .trim()
+                .replace("&amp;", "&");
+            if Url::parse(&location).is_ok() {
+                locations.push(location);
+            }
+            rest = &rest[end..];
+        }
+
+        if xml.contains("<sitemapindex") {
+            SitemapEntries::Index(locations)
+        } else {
+            SitemapEntries::Pages(locations)
+        }
+    }
+}
+
+/// Path and query of a URL as matched by robots.txt rules
+fn path_and_query(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(url) => match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::cost::BandwidthCost;
+    use crate::robots::RobotsPolicy;
+    use crate::scraper::FerrisFetcher;
+    use crate::test_support::{serve, StubResponse};
+
+    /// Serve robots.txt, a sitemap index with one sitemap, and 100-byte pages
+    async fn serve_site() -> String {
+        serve(|request| {
+            let site = format!("http://{}", request.header("Host").unwrap_or_default());
+            StubResponse::html(match request.path.as_str() {
+                "/robots.txt" => format!("User-agent: *\nDisallow: /secret\nSitemap: {}/sitemap-index.xml\n", site),
+                "/sitemap-index.xml" => format!(
+                    "<sitemapindex><sitemap><loc>{}/pages.xml</loc></sitemap></sitemapindex>", site
+                ),
+                "/pages.xml" => format!(
+                    "<urlset><url><loc>{0}/</loc></url><url><loc>{0}/a?x=1&amp;y=2</loc></url>\
+                     <url><loc>{0}/secret</loc></url><url><loc>https://other.example/</loc></url>\
+                     <url><loc><![CDATA[{0}/b]]></loc></url></urlset>",
+                    site
+                ),
+                _ => "x".repeat(100),
+            })
+        })
+    }
+
+    #[test]
+    fn test_parse_sitemap() {
+        let index = SitemapEntries::parse("<sitemapindex><sitemap><loc> https://a.example/s.xml </loc></sitemap></sitemapindex>");
+        assert_eq!(index, SitemapEntries::Index(vec!["https://a.example/s.xml".to_string()]));
+
+        let pages = SitemapEntries::parse("<urlset><url><loc>https://a.example/?p=1&amp;q=2</loc></url><url><loc>relative</loc></url></urlset>");
+        assert_eq!(pages, SitemapEntries::Pages(vec!["https://a.example/?p=1&q=2".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_plan_and_execute() {
+        let site = serve_site().await;
+        let config = Config::default().without_rate_limit().with_robots_policy(RobotsPolicy::Respect);
+        let crawler = Crawler::new(FerrisFetcher::with_config(config).unwrap());
+
+        let plan = crawler.plan(&[&format!("{}/", site)], &CrawlOptions::new()).await.unwrap();
+        let urls: Vec<&str> = plan.pages.iter().map(|page| page.url.trim_start_matches(&site)).collect();
+        assert_eq!(urls, ["/", "/a?x=1&y=2", "/b"]);
+        assert_eq!(plan.sitemaps.len(), 2);
+        assert_eq!(plan.disallowed, 1);
+        assert_eq!(plan.pages[1].discovered_via, Discovery::Sitemap);
+        assert_eq!(plan.pages[1].sitemap, Some(format!("{}/pages.xml", site)));
+        assert!(plan.pages.iter().all(|page| page.status == Some(200) && page.content_length == Some(100)));
+        assert_eq!(plan.estimated_bytes(), 300);
+        assert_eq!(plan.estimated_cost(&BandwidthCost::new(0.0).with_price_per_request(0.5)).total_cost, 1.5);
+
+        let progress: Vec<PlanProgress> = crawler.execute(plan.clone(), None).collect().await;
+        assert_eq!(progress.len(), 3);
+        assert_eq!(progress.last().map(|last| (last.completed, last.total, last.bytes_fetched)), Some((3, 3, 300)));
+        let sitemap_page = progress.iter().find(|step| step.page.url.ends_with("/b")).unwrap();
+        let data = sitemap_page.page.result.as_ref().unwrap();
+        assert_eq!(data.discovered_via, Some(Discovery::Sitemap));
+
+        let limited = Crawler::new(FerrisFetcher::with_config(Config::default().without_rate_limit().with_max_concurrent_requests(1)).unwrap());
+        let progress: Vec<PlanProgress> = limited.execute(plan, Some(150)).collect().await;
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[1].total, 3);
+    }
+}
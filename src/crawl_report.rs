@@ -0,0 +1,264 @@
+//! Summary reports of crawler runs
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{FerrisFetcherError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Number of hosts listed in [`CrawlReport::slow_hosts`]
+const SLOW_HOSTS_LIMIT: usize = 5;
+
+/// Why the crawler did not fetch a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// Disallowed by robots.txt
+    Robots,
+    /// Rejected by URL filters
+    Filter,
+}
+
+/// Fetch timings for a single host
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostTiming {
+    /// Host name
+    pub host: String,
+    /// Pages fetched from the host
+    pub pages: u64,
+    /// Average fetch time in milliseconds
+    pub avg_time_ms: f64,
+    /// Slowest fetch time in milliseconds
+    pub max_time_ms: u64,
+}
+
+/// Final summary of a crawler run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrawlReport {
+    /// When the crawler started recording
+    pub started_at: DateTime<Utc>,
+    /// When the report was produced
+    pub finished_at: DateTime<Utc>,
+    /// Wall-clock duration in milliseconds
+    pub duration_ms: u64,
+    /// Pages fetched successfully
+    pub pages_fetched: u64,
+    /// URLs skipped because robots.txt disallowed them
+    pub skipped_robots: u64,
+    /// URLs skipped by URL filters
+    pub skipped_filtered: u64,
+    /// Failed fetches per error category
+    pub errors: BTreeMap<String, u64>,
+    /// Pages fetched per crawl depth
+    pub depth_distribution: BTreeMap<usize, u64>,
+    /// Hosts with the highest average fetch time, slowest first
+    pub slow_hosts: Vec<HostTiming>,
+}
+
+impl CrawlReport {
+    /// Total number of failed fetches
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    /// Serialize the report as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Render the report as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"utf-8\"><title>Crawl report</title></head>\n<body>\n<h1>Crawl report</h1>\n",
+        );
+
+        html.push_str("<table>\n");
+        let summary = [
+            ("Started", self.started_at.to_rfc3339()),
+            ("Finished", self.finished_at.to_rfc3339()),
+            ("Duration", format!("{:.1} s", self.duration_ms as f64 / 1000.0)),
+            ("Pages fetched", self.pages_fetched.to_string()),
+            ("Skipped (robots.txt)", self.skipped_robots.to_string()),
+            ("Skipped (filters)", self.skipped_filtered.to_string()),
+            ("Errors", self.error_count().to_string()),
+        ];
+        for (label, value) in summary {
+            html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, escape_html(&value)));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Errors by category</h2>\n<table>\n<tr><th>Category</th><th>Count</th></tr>\n");
+        for (category, count) in &self.errors {
+            html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape_html(category), count));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Depth distribution</h2>\n<table>\n<tr><th>Depth</th><th>Pages</th></tr>\n");
+        for (depth, pages) in &self.depth_distribution {
+            html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", depth, pages));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Slowest hosts</h2>\n<table>\n<tr><th>Host</th><th>Pages</th><th>Average (ms)</th><th>Max (ms)</th></tr>\n");
+        for host in &self.slow_hosts {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{:.0}</td><td>{}</td></tr>\n",
+                escape_html(&host.host), host.pages, host.avg_time_ms, host.max_time_ms
+            ));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+
+        html
+    }
+}
+
+/// Running tally of crawler activity from which reports are produced
+#[derive(Debug, Clone)]
+pub struct CrawlLog {
+    clock: Arc<dyn Clock>,
+    started_at: DateTime<Utc>,
+    pages_fetched: u64,
+    skipped: HashMap<SkipReason, u64>,
+    errors: BTreeMap<String, u64>,
+    depth_distribution: BTreeMap<usize, u64>,
+    /// Per-host (pages, total time in ms, max time in ms)
+    host_times: HashMap<String, (u64, u64, u64)>,
+}
+
+impl Default for CrawlLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrawlLog {
+    /// Start a new log
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Start a new log timed by `clock`
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            started_at: clock.now(),
+            clock,
+            pages_fetched: 0,
+            skipped: HashMap::new(),
+            errors: BTreeMap::new(),
+            depth_distribution: BTreeMap::new(),
+            host_times: HashMap::new(),
+        }
+    }
+
+    /// Record a successfully fetched page
+    pub fn record_page(&mut self, url: &str, depth: usize, elapsed: Duration) {
+        self.pages_fetched += 1;
+        *self.depth_distribution.entry(depth).or_insert(0) += 1;
+
+        if let Some(host) = url::Url::parse(url).ok().as_ref().and_then(|url| url.host_str()) {
+            let elapsed_ms = elapsed.as_millis() as u64;
+            let entry = self.host_times.entry(host.to_lowercase()).or_insert((0, 0, 0));
+            entry.0 += 1;
+            entry.1 += elapsed_ms;
+            entry.2 = entry.2.max(elapsed_ms);
+        }
+    }
+
+    /// Record a URL that was not fetched
+    pub fn record_skip(&mut self, reason: SkipReason) {
+        *self.skipped.entry(reason).or_insert(0) += 1;
+    }
+
+    /// Record a failed fetch
+    pub fn record_error(&mut self, error: &FerrisFetcherError) {
+        *self.errors.entry(error.category().to_string()).or_insert(0) += 1;
+    }
+
+    /// Produce a report of everything recorded so far
+    pub fn report(&self) -> CrawlReport {
+        let finished_at = self.clock.now();
+
+        let mut slow_hosts: Vec<HostTiming> = self.host_times
+            .iter()
+            .map(|(host, (pages, total_ms, max_ms))| HostTiming {
+                host: host.clone(),
+                pages: *pages,
+                avg_time_ms: *total_ms as f64 / *pages as f64,
+                max_time_ms: *max_ms,
+            })
+            .collect();
+        slow_hosts.sort_by(|a, b| b.avg_time_ms.total_cmp(&a.avg_time_ms).then_with(|| a.host.cmp(&b.host)));
+        slow_hosts.truncate(SLOW_HOSTS_LIMIT);
+
+        CrawlReport {
+            started_at: self.started_at,
+            finished_at,
+            duration_ms: (finished_at - self.started_at).num_milliseconds().max(0) as u64,
+            pages_fetched: self.pages_fetched,
+            skipped_robots: self.skipped.get(&SkipReason::Robots).copied().unwrap_or(0),
+            skipped_filtered: self.skipped.get(&SkipReason::Filter).copied().unwrap_or(0),
+            errors: self.errors.clone(),
+            depth_distribution: self.depth_distribution.clone(),
+            slow_hosts,
+        }
+    }
+}
+
+/// Escape text for inclusion in HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crawl_report() {
+        let mut log = CrawlLog::new();
+        log.record_page("https://example.com/", 0, Duration::from_millis(100));
+        log.record_page("https://example.com/a", 1, Duration::from_millis(300));
+        log.record_page("https://slow.example.org/b", 1, Duration::from_millis(900));
+        log.record_skip(SkipReason::Filter);
+        log.record_skip(SkipReason::Robots);
+        log.record_skip(SkipReason::Filter);
+        log.record_error(&FerrisFetcherError::NetworkError("reset".to_string()));
+
+        let report = log.report();
+        assert_eq!(report.pages_fetched, 3);
+        assert_eq!(report.skipped_filtered, 2);
+        assert_eq!(report.skipped_robots, 1);
+        assert_eq!(report.errors.get("Network"), Some(&1));
+        assert_eq!(report.depth_distribution.get(&1), Some(&2));
+        assert_eq!(report.slow_hosts[0].host, "slow.example.org");
+        assert_eq!(report.slow_hosts[1].avg_time_ms, 200.0);
+        assert_eq!(report.slow_hosts[1].max_time_ms, 300);
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["pages_fetched"], 3);
+
+        let html = report.to_html();
+        assert!(html.contains("<td>slow.example.org</td>"));
+        assert!(html.contains("<th>Skipped (filters)</th><td>2</td>"));
+    }
+
+    #[test]
+    fn test_crawl_report_clock() {
+        use crate::clock::ManualClock;
+        use chrono::TimeZone;
+
+        let start = Utc.with_ymd_and_hms(2025, 3, 1, 8, 0, 0).unwrap();
+        let clock = Arc::new(ManualClock::new(start));
+        let log = CrawlLog::with_clock(clock.clone());
+        clock.advance(Duration::from_secs(42));
+
+        let report = log.report();
+        assert_eq!(report.started_at, start);
+        assert_eq!(report.finished_at, start + chrono::Duration::seconds(42));
+        assert_eq!(report.duration_ms, 42_000);
+    }
+}
@@ -0,0 +1,613 @@
+//! Crawling utilities built on top of the main scraper
+
+use crate::crawl_report::{CrawlLog, CrawlReport, SkipReason};
+use crate::error::{FerrisFetcherError, Result};
+use crate::html_parser::HtmlParser;
+use crate::notify::Notifications;
+use crate::scraper::FerrisFetcher;
+use crate::types::{Discovery, ScrapedData};
+use futures::future::BoxFuture;
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt};
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// Standard pages most sites link to from their home page or footer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageKind {
+    Contact,
+    About,
+    Privacy,
+    Careers,
+}
+
+impl PageKind {
+    /// Phrases that identify the page in link text
+    fn text_keywords(&self) -> &'static [&'static str] {
+        match self {
+            PageKind::Contact => &["contact", "get in touch", "reach us", "kontakt", "contacto"],
+            PageKind::About => &["about", "who we are", "our story", "our team", "company", "impressum"],
+            PageKind::Privacy => &["privacy", "data protection", "datenschutz", "cookie policy"],
+            PageKind::Careers => &["careers", "jobs", "join us", "work with us", "we're hiring", "karriere"],
+        }
+    }
+
+    /// Path fragments that identify the page in its URL
+    fn path_keywords(&self) -> &'static [&'static str] {
+        match self {
+            PageKind::Contact => &["contact", "kontakt", "contacto", "support"],
+            PageKind::About => &["about", "company", "team", "who-we-are", "impressum"],
+            PageKind::Privacy => &["privacy", "datenschutz", "gdpr", "cookie"],
+            PageKind::Careers => &["careers", "career", "jobs", "join", "hiring", "karriere"],
+        }
+    }
+
+    /// Score how likely a link points at this kind of page (0 = not at all)
+    pub fn score(&self, url: &Url, link_text: &str) -> u32 {
+        let text = link_text.to_lowercase();
+        let mut score = 0;
+
+        if self.text_keywords().iter().any(|keyword| text.contains(keyword)) {
+            score += 2;
+            if self.text_keywords().iter().any(|keyword| text.trim() == *keyword) {
+                score += 1;
+            }
+        }
+
+        let segments: Vec<String> = url.path_segments()
+            .map(|segments| segments.map(|s| s.to_lowercase()).collect())
+            .unwrap_or_default();
+        for keyword in self.path_keywords() {
+            if segments.iter().any(|segment| segment.trim_end_matches(".html") == *keyword) {
+                score += 2;
+                break;
+            } else if segments.iter().any(|segment| segment.contains(keyword)) {
+                score += 1;
+                break;
+            }
+        }
+
+        score
+    }
+}
+
+/// How URLs differing only in a `www.` host prefix are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WwwHandling {
+    /// `example.com` and `www.example.com` are different pages
+    #[default]
+    Distinct,
+    /// Treat both as the same page, canonically without `www.`
+    WithoutWww,
+    /// Treat both as the same page, canonically with `www.`
+    WithWww,
+}
+
+/// How URLs differing only in a trailing path slash are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrailingSlash {
+    /// `/docs` and `/docs/` are different pages
+    #[default]
+    Distinct,
+    /// Treat both as the same page, canonically without the slash
+    Without,
+    /// Treat both as the same page, canonically with the slash; paths
+    /// ending in a file name such as `/a.html` are left alone
+    With,
+}
+
+/// Limits and URL filters of a recursive crawl
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    /// Deepest link level followed; seeds are depth 0
+    pub max_depth: usize,
+    /// Maximum number of pages fetched, failures included
+    pub max_pages: usize,
+    /// Only follow links to the hosts of the seed URLs (ignoring `www.`)
+    pub same_domain: bool,
+    /// Only follow links matching at least one of these patterns, if any
+    pub include: Vec<Regex>,
+    /// Never follow links matching any of these patterns
+    pub exclude: Vec<Regex>,
+    /// Whether `www.` host variants count as the same page
+    pub www: WwwHandling,
+    /// Whether trailing-slash variants count as the same page
+    pub trailing_slash: TrailingSlash,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            max_pages: 100,
+            same_domain: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            www: WwwHandling::default(),
+            trailing_slash: TrailingSlash::default(),
+        }
+    }
+}
+
+impl CrawlOptions {
+    /// Create default options: depth 2, 100 pages, seed hosts only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the deepest link level followed
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the maximum number of pages fetched
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Also follow links to other hosts
+    pub fn allow_external(mut self) -> Self {
+        self.same_domain = false;
+        self
+    }
+
+    /// Only follow links matching a regular expression (may be repeated)
+    pub fn include_matching(mut self, pattern: &str) -> Result<Self> {
+        self.include.push(compile_pattern(pattern)?);
+        Ok(self)
+    }
+
+    /// Never follow links matching a regular expression (may be repeated)
+    pub fn exclude_matching(mut self, pattern: &str) -> Result<Self> {
+        self.exclude.push(compile_pattern(pattern)?);
+        Ok(self)
+    }
+
+    /// Choose how `www.` host variants are deduplicated
+    pub fn with_www(mut self, www: WwwHandling) -> Self {
+        self.www = www;
+        self
+    }
+
+    /// Choose how trailing-slash variants are deduplicated
+    pub fn with_trailing_slash(mut self, trailing_slash: TrailingSlash) -> Self {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+
+    /// Canonical form of a URL under the `www.` and trailing-slash settings,
+    /// used to recognise pages already seen
+    pub fn canonical_url(&self, url: &str) -> String {
+        let Ok(mut parsed) = Url::parse(url) else { return url.to_string() };
+        if let Some(host) = parsed.host_str().map(str::to_string) {
+            let bare = host.strip_prefix("www.").unwrap_or(&host);
+            let canonical = match self.www {
+                WwwHandling::Distinct => None,
+                WwwHandling::WithoutWww => Some(bare.to_string()),
+                WwwHandling::WithWww => Some(format!("www.{}", bare)),
+            };
+            if let Some(canonical) = canonical.filter(|canonical| *canonical != host) {
+                // Hosts that are IP addresses have no `www.` variant
+                if parsed.host().is_some_and(|host| matches!(host, url::Host::Domain(_))) {
+                    let _ = parsed.set_host(Some(&canonical));
+                }
+            }
+        }
+
+        let path = parsed.path().to_string();
+        let last_segment = path.rsplit('/').next().unwrap_or_default();
+        let canonical_path = match self.trailing_slash {
+            TrailingSlash::Without if path.len() > 1 => path.trim_end_matches('/').to_string(),
+            TrailingSlash::With if !path.ends_with('/') && !last_segment.contains('.') => format!("{}/", path),
+            _ => path.clone(),
+        };
+        if canonical_path != path {
+            parsed.set_path(if canonical_path.is_empty() { "/" } else { &canonical_path });
+        }
+        parsed.to_string()
+    }
+
+    /// Whether the URL filters accept a link
+    pub(crate) fn accepts(&self, url: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|pattern| pattern.is_match(url)))
+            && !self.exclude.iter().any(|pattern| pattern.is_match(url))
+    }
+}
+
+/// A page reached by [`Crawler::crawl`]
+#[derive(Debug)]
+pub struct CrawledPage {
+    /// URL of the page, as it was found
+    pub url: String,
+    /// Canonical form of the URL used to deduplicate pages (see [`CrawlOptions::canonical_url`])
+    pub canonical_url: String,
+    /// Number of links followed from a seed to reach the page
+    pub depth: usize,
+    /// Page the link was found on (`None` for seeds)
+    pub parent: Option<String>,
+    /// Scraped page or the error that prevented it
+    pub result: Result<ScrapedData>,
+}
+
+/// Progress of a running crawl
+struct CrawlState {
+    crawler: Crawler,
+    options: CrawlOptions,
+    concurrency: usize,
+    seed_hosts: HashSet<String>,
+    seen: HashSet<String>,
+    queue: VecDeque<(String, usize, Option<String>)>,
+    in_flight: FuturesUnordered<BoxFuture<'static, CrawledPage>>,
+    scheduled: usize,
+}
+
+impl CrawlState {
+    /// Start fetches from the front of the queue up to the concurrency and page limits
+    fn schedule(&mut self) {
+        while self.in_flight.len() < self.concurrency && self.scheduled < self.options.max_pages {
+            let Some((url, depth, parent)) = self.queue.pop_front() else { break };
+            self.scheduled += 1;
+            let crawler = self.crawler.clone();
+            let canonical_url = self.options.canonical_url(&url);
+            self.in_flight.push(Box::pin(async move {
+                let via = if parent.is_some() { Discovery::Link } else { Discovery::Seed };
+                let result = crawler.fetch(&url, depth).await.map(|mut data| {
+                    data.set_origin(depth, parent.clone(), via);
+                    data
+                });
+                CrawledPage { url, canonical_url, depth, parent, result }
+            }));
+        }
+    }
+
+    /// Queue the unseen links of a fetched page that pass the filters
+    fn enqueue_links(&mut self, page: &CrawledPage) {
+        let Ok(data) = &page.result else { return };
+        if page.depth >= self.options.max_depth {
+            return;
+        }
+        let links = match Crawler::page_links(data) {
+            Ok(links) => links,
+            Err(e) => {
+                warn!("Failed to parse links of {}: {}", page.url, e);
+                return;
+            }
+        };
+
+        for link in links {
+            let canonical = self.options.canonical_url(&link);
+            if self.seen.contains(&canonical) {
+                continue;
+            }
+            let host = Url::parse(&link).ok().and_then(|url| url.host_str().map(site_host));
+            if self.options.same_domain && !host.is_some_and(|host| self.seed_hosts.contains(&host)) {
+                continue;
+            }
+            self.seen.insert(canonical);
+            if !self.options.accepts(&link) {
+                self.crawler.log.lock().expect("crawl log poisoned").record_skip(SkipReason::Filter);
+                continue;
+            }
+            self.queue.push_back((link, page.depth + 1, Some(page.url.clone())));
+        }
+    }
+}
+
+/// Crawler driving a [`FerrisFetcher`] across the pages of a site
+#[derive(Debug, Clone)]
+pub struct Crawler {
+    /// Scraper used to fetch pages
+    fetcher: FerrisFetcher,
+    /// Activity recorded for the crawl report, shared between clones
+    log: Arc<Mutex<CrawlLog>>,
+    /// Alerts raised when a job finishes
+    notifications: Option<Arc<Notifications>>,
+}
+
+impl Crawler {
+    /// Create a crawler using the given scraper
+    pub fn new(fetcher: FerrisFetcher) -> Self {
+        Self {
+            log: Arc::new(Mutex::new(CrawlLog::with_clock(fetcher.config().clock.clone()))),
+            fetcher,
+            notifications: None,
+        }
+    }
+
+    /// Notify on job completion and error-rate anomalies (see [`Crawler::finish_job`])
+    pub fn with_notifications(mut self, notifications: Notifications) -> Self {
+        self.notifications = Some(Arc::new(notifications));
+        self
+    }
+
+    /// Produce the report for a finished job and send its notifications
+    pub async fn finish_job(&self, job: &str) -> CrawlReport {
+        let report = self.report();
+        if let Some(notifications) = &self.notifications {
+            notifications.job_finished(job, &report).await;
+        }
+        report
+    }
+
+    /// Summarise the pages fetched, skipped and failed since creation or the last reset
+    pub fn report(&self) -> CrawlReport {
+        self.log.lock().expect("crawl log poisoned").report()
+    }
+
+    /// Start a new crawl report
+    pub fn reset_report(&self) {
+        *self.log.lock().expect("crawl log poisoned") = CrawlLog::with_clock(self.fetcher.config().clock.clone());
+    }
+
+    /// Get the underlying scraper
+    pub fn fetcher(&self) -> &FerrisFetcher {
+        &self.fetcher
+    }
+
+    /// Locate pages of the given kind on the site of `start_url`
+    ///
+    /// Scores same-site links on the start page by link text and URL; if
+    /// nothing matches, the site's home page is checked as well. Returns
+    /// candidate URLs, best match first.
+    pub async fn find_pages(&self, start_url: &str, kind: PageKind) -> Result<Vec<String>> {
+        info!("Looking for {:?} pages from {}", kind, start_url);
+
+        let start = Url::parse(start_url)?;
+        let mut candidates = self.rank_page(start.as_str(), 0, kind).await?;
+
+        let home = start.join("/")?;
+        if candidates.is_empty() && home != start {
+            debug!("No {:?} links on {}, trying {}", kind, start, home);
+            match self.rank_page(home.as_str(), 0, kind).await {
+                Ok(home_candidates) => candidates = home_candidates,
+                Err(e) => warn!("Failed to scrape home page {}: {}", home, e),
+            }
+        }
+
+        Ok(candidates.into_iter().map(|(url, _)| url).collect())
+    }
+
+    /// Crawl breadth-first from seed URLs, following links up to the option limits
+    ///
+    /// Pages are yielded as they finish, up to `max_concurrent_requests` at a
+    /// time; links are only followed from successfully scraped pages. Each URL
+    /// is fetched at most once.
+    pub fn crawl(&self, seeds: &[&str], options: CrawlOptions) -> impl Stream<Item = CrawledPage> + Send + 'static {
+        let mut state = CrawlState {
+            crawler: self.clone(),
+            concurrency: self.fetcher.max_concurrent_requests().max(1),
+            seed_hosts: HashSet::new(),
+            seen: HashSet::new(),
+            queue: VecDeque::new(),
+            in_flight: FuturesUnordered::new(),
+            scheduled: 0,
+            options,
+        };
+        for seed in seeds {
+            let seed = match Url::parse(seed) {
+                Ok(mut url) => {
+                    url.set_fragment(None);
+                    if let Some(host) = url.host_str() {
+                        state.seed_hosts.insert(site_host(host));
+                    }
+                    url.to_string()
+                }
+                Err(_) => seed.to_string(),
+            };
+            if state.seen.insert(state.options.canonical_url(&seed)) {
+                state.queue.push_back((seed, 0, None));
+            }
+        }
+        info!("Crawling from {} seed(s), max depth {}, max {} pages", state.queue.len(), state.options.max_depth, state.options.max_pages);
+
+        stream::unfold(state, |mut state| async move {
+            state.schedule();
+            let page = state.in_flight.next().await?;
+            state.enqueue_links(&page);
+            Some((page, state))
+        })
+    }
+
+    /// Absolute `http(s)` links of a scraped page without fragments
+    fn page_links(data: &ScrapedData) -> Result<Vec<String>> {
+        let parser = HtmlParser::with_base_url(&data.content, &data.url)?;
+        Ok(parser
+            .links()
+            .iter()
+            .filter_map(|href| parser.resolve_url(href))
+            .filter_map(|link| Url::parse(&link).ok())
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .map(|mut url| {
+                url.set_fragment(None);
+                url.to_string()
+            })
+            .collect())
+    }
+
+    /// Scrape a page and rank its links for a page kind
+    async fn rank_page(&self, url: &str, depth: usize, kind: PageKind) -> Result<Vec<(String, u32)>> {
+        let data = self.fetch(url, depth).await?;
+        let parser = HtmlParser::with_base_url(&data.content, &data.url)?;
+        Ok(Self::rank_links(&parser, kind))
+    }
+
+    /// Scrape a page, recording the outcome in the crawl report
+    pub(crate) async fn fetch(&self, url: &str, depth: usize) -> Result<ScrapedData> {
+        let start = Instant::now();
+        let result = self.fetcher.scrape(url).await;
+
+        let mut log = self.log.lock().expect("crawl log poisoned");
+        match &result {
+            Ok(_) => log.record_page(url, depth, start.elapsed()),
+            Err(FerrisFetcherError::RobotsDisallowed(_)) => log.record_skip(SkipReason::Robots),
+            Err(e) => log.record_error(e),
+        }
+
+        result
+    }
+
+    /// Rank same-site links on a parsed page by how well they match a page kind
+    fn rank_links(parser: &HtmlParser, kind: PageKind) -> Vec<(String, u32)> {
+        let site_host = parser.base_url()
+            .and_then(|url| url.host_str())
+            .map(|host| host.trim_start_matches("www.").to_string());
+
+        let mut scores: HashMap<String, u32> = HashMap::new();
+        for link in parser.link_infos() {
+            let Ok(mut url) = Url::parse(&link.url) else { continue };
+            if !matches!(url.scheme(), "http" | "https") {
+                continue;
+            }
+
+            let link_host = url.host_str().map(|host| host.trim_start_matches("www.").to_string());
+            if site_host.is_some() && link_host != site_host {
+                continue;
+            }
+
+            let score = kind.score(&url, &link.text);
+            if score > 0 {
+                url.set_fragment(None);
+                let entry = scores.entry(url.to_string()).or_insert(0);
+                *entry = (*entry).max(score);
+            }
+        }
+
+        let mut ranked: Vec<(String, u32)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+    }
+}
+
+/// Compile a crawl URL filter
+fn compile_pattern(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| FerrisFetcherError::ConfigError(format!("Invalid crawl URL pattern '{}': {}", pattern, e)))
+}
+
+/// Host name for same-site comparisons
+pub(crate) fn site_host(host: &str) -> String {
+    host.trim_start_matches("www.").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::test_support::{serve, StubResponse};
+
+    /// Serve a small linked site until the test ends
+    async fn serve_site() -> String {
+        serve(|request| {
+            let links: &[&str] = match request.path.as_str() {
+                "/" => &["/a", "/b#top", "https://other.example/x", "/private/z", "mailto:me@example.com"],
+                "/a" => &["/c", "/"],
+                "/b" => &["d"],
+                "/c" => &["/e"],
+                _ => &[],
+            };
+            StubResponse::html(links.iter().map(|link| format!("<a href=\"{}\">link</a>", link)).collect::<String>())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_crawl_breadth_first() {
+        let site = serve_site().await;
+        let fetcher = FerrisFetcher::with_config(Config::default().without_rate_limit()).unwrap();
+        let crawler = Crawler::new(fetcher);
+
+        let options = CrawlOptions::new().exclude_matching("/private/").unwrap();
+        let pages: Vec<CrawledPage> = crawler.crawl(&[&format!("{}/", site)], options).collect().await;
+        let mut found: Vec<(String, usize, Option<String>)> = pages
+            .iter()
+            .map(|page| (page.url.trim_start_matches(&site).to_string(), page.depth, page.parent.clone()))
+            .collect();
+        found.sort();
+
+        let root = Some(format!("{}/", site));
+        assert_eq!(found, vec![
+            ("/".to_string(), 0, None),
+            ("/a".to_string(), 1, root.clone()),
+            ("/b".to_string(), 1, root),
+            ("/c".to_string(), 2, Some(format!("{}/a", site))),
+            ("/d".to_string(), 2, Some(format!("{}/b", site))),
+        ]);
+        assert!(pages.iter().all(|page| page.result.is_ok() && page.canonical_url == page.url));
+        for page in &pages {
+            let data = page.result.as_ref().unwrap();
+            assert_eq!((data.depth, &data.parent_url), (page.depth, &page.parent));
+            let via = if page.parent.is_some() { Discovery::Link } else { Discovery::Seed };
+            assert_eq!(data.discovered_via, Some(via));
+        }
+
+        let report = crawler.report();
+        assert_eq!(report.pages_fetched, 5);
+        assert_eq!(report.skipped_filtered, 1);
+        assert_eq!(report.depth_distribution[&2], 2);
+
+        let limited: Vec<CrawledPage> = crawler.crawl(&[&site], CrawlOptions::new().with_max_pages(2)).collect().await;
+        assert_eq!(limited.len(), 2);
+        assert_eq!(limited[0].depth, 0);
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        let distinct = CrawlOptions::new();
+        assert_eq!(distinct.canonical_url("https://www.example.com/docs/"), "https://www.example.com/docs/");
+
+        let merged = CrawlOptions::new().with_www(WwwHandling::WithoutWww).with_trailing_slash(TrailingSlash::Without);
+        assert_eq!(merged.canonical_url("https://www.example.com/docs/?q=1"), "https://example.com/docs?q=1");
+        assert_eq!(merged.canonical_url("https://example.com/docs"), "https://example.com/docs");
+        assert_eq!(merged.canonical_url("https://www.example.com"), "https://example.com/");
+
+        let slashed = CrawlOptions::new().with_www(WwwHandling::WithWww).with_trailing_slash(TrailingSlash::With);
+        assert_eq!(slashed.canonical_url("https://example.com/docs"), "https://www.example.com/docs/");
+        assert_eq!(slashed.canonical_url("https://example.com/a.html"), "https://www.example.com/a.html");
+        assert_eq!(slashed.canonical_url("http://127.0.0.1:8080/docs"), "http://127.0.0.1:8080/docs/");
+    }
+
+    #[test]
+    fn test_page_kind_score() {
+        let url = Url::parse("https://example.com/contact-us").unwrap();
+        assert!(PageKind::Contact.score(&url, "Contact") > PageKind::Contact.score(&url, ""));
+        assert_eq!(PageKind::Careers.score(&url, "Contact"), 0);
+
+        let url = Url::parse("https://example.com/legal/privacy.html").unwrap();
+        assert_eq!(PageKind::Privacy.score(&url, "Privacy Policy"), 4);
+    }
+
+    #[test]
+    fn test_rank_links() {
+        let html = r#"
+        <nav>
+            <a href="/products">Products</a>
+            <a href="/company/about-us">About us</a>
+            <a href="/team#leadership">Our team</a>
+        </nav>
+        <footer>
+            <a href="/jobs">We're hiring!</a>
+            <a href="https://www.example.com/contact">Contact</a>
+            <a href="https://other.example.org/contact">Partner contact</a>
+            <a href="mailto:hello@example.com">Contact by mail</a>
+        </footer>
+        "#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/").unwrap();
+
+        let contact = Crawler::rank_links(&parser, PageKind::Contact);
+        assert_eq!(contact, vec![("https://www.example.com/contact".to_string(), 5)]);
+
+        let about = Crawler::rank_links(&parser, PageKind::About);
+        assert_eq!(about.len(), 2);
+        assert!(about.iter().any(|(url, _)| url == "https://example.com/company/about-us"));
+        assert!(about.iter().any(|(url, _)| url == "https://example.com/team"));
+
+        let careers = Crawler::rank_links(&parser, PageKind::Careers);
+        assert_eq!(careers[0].0, "https://example.com/jobs");
+
+        assert!(Crawler::rank_links(&parser, PageKind::Privacy).is_empty());
+    }
+}
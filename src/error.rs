@@ -1,5 +1,7 @@
 //! Error types for FerrisFetcher
 
+use crate::quota::QuotaStatus;
+use crate::validation::ValidationReport;
 use thiserror::Error;
 use std::time::Duration;
 
@@ -44,6 +46,32 @@ pub enum FerrisFetcherError {
     
     #[error("Network error: {0}")]
     NetworkError(String),
+    
+    #[error("Aborted by error hook: {0}")]
+    Aborted(String),
+    
+    #[error("Scrape of {url} panicked: {message}")]
+    Panicked {
+        /// URL whose scrape panicked
+        url: String,
+        /// Panic message
+        message: String,
+    },
+    
+    #[error("Validation failed: {0}")]
+    ValidationFailed(ValidationReport),
+    
+    #[error("Disallowed by robots.txt: {0}")]
+    RobotsDisallowed(String),
+    
+    #[error("Redirect loop or too many redirects: {}", chain.join(" -> "))]
+    RedirectLoop {
+        /// URLs visited, starting with the requested one and ending with the hop that was refused
+        chain: Vec<String>,
+    },
+    
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(QuotaStatus),
 }
 
 /// Result type alias for convenience
@@ -66,6 +94,12 @@ impl FerrisFetcherError {
             FerrisFetcherError::JsonError(_) => false,
             FerrisFetcherError::RetryExhausted => false,
             FerrisFetcherError::InvalidSelector(_) => false,
+            FerrisFetcherError::Aborted(_) => false,
+            FerrisFetcherError::Panicked { .. } => false,
+            FerrisFetcherError::ValidationFailed(_) => false,
+            FerrisFetcherError::RobotsDisallowed(_) => false,
+            FerrisFetcherError::RedirectLoop { .. } => false,
+            FerrisFetcherError::QuotaExceeded(_) => false,
         }
     }
     
@@ -85,6 +119,12 @@ impl FerrisFetcherError {
             FerrisFetcherError::TaskCancelled => "Cancellation",
             FerrisFetcherError::InvalidSelector(_) => "Selector",
             FerrisFetcherError::NetworkError(_) => "Network",
+            FerrisFetcherError::Aborted(_) => "Aborted",
+            FerrisFetcherError::Panicked { .. } => "Panic",
+            FerrisFetcherError::ValidationFailed(_) => "Validation",
+            FerrisFetcherError::RobotsDisallowed(_) => "Robots",
+            FerrisFetcherError::RedirectLoop { .. } => "Redirect",
+            FerrisFetcherError::QuotaExceeded(_) => "Quota",
         }
     }
 }
@@ -0,0 +1,783 @@
+//! Writing scraped data to files
+//!
+//! A [`RecordFormat`] encodes records; [`FileExporter`] writes them to a
+//! single writer and [`ShardedExporter`] spreads them over files by host,
+//! date and size. [`RecordSink`]s send records to remote destinations.
+
+use crate::error::Result;
+use async_trait::async_trait;
+use crate::types::ScrapedData;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use futures::{Stream, StreamExt};
+use tracing::debug;
+
+/// Destination for scraped records
+pub trait Exporter {
+    /// Write a single record
+    fn export(&mut self, data: &ScrapedData) -> Result<()>;
+
+    /// Flush buffered output and complete the written files
+    fn finish(&mut self) -> Result<()>;
+
+    /// Write several records
+    fn export_all(&mut self, records: &[ScrapedData]) -> Result<()> {
+        for data in records {
+            self.export(data)?;
+        }
+        Ok(())
+    }
+}
+
+/// Export every record of a stream, then finish the exporter
+///
+/// Returns the number of records written.
+pub async fn export_stream<E, S>(exporter: &mut E, records: S) -> Result<usize>
+where
+    E: Exporter + ?Sized,
+    S: Stream<Item = ScrapedData>,
+{
+    futures::pin_mut!(records);
+    let mut count = 0;
+    while let Some(data) = records.next().await {
+        exporter.export(&data)?;
+        count += 1;
+    }
+    exporter.finish()?;
+    Ok(count)
+}
+
+/// Asynchronous destination for scraped records, such as a remote service
+#[async_trait]
+pub trait RecordSink: Send {
+    /// Queue a single record, sending a batch when enough are pending
+    async fn send(&mut self, data: &ScrapedData) -> Result<()>;
+
+    /// Send all pending records
+    async fn flush(&mut self) -> Result<()>;
+
+    /// Queue several records
+    async fn send_all(&mut self, records: &[ScrapedData]) -> Result<()> {
+        for data in records {
+            self.send(data).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Encoding of records in a streamed output file
+pub trait RecordFormat: Send + Sync {
+    /// File extension, without the dot
+    fn extension(&self) -> &str;
+
+    /// Encode a single record
+    fn encode(&self, data: &ScrapedData) -> Result<Vec<u8>>;
+
+    /// Bytes written at the start of every file
+    fn header(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Bytes written at the end of every file
+    fn footer(&self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+/// One JSON object per line
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonlFormat;
+
+impl RecordFormat for JsonlFormat {
+    fn extension(&self) -> &str {
+        "jsonl"
+    }
+
+    fn encode(&self, data: &ScrapedData) -> Result<Vec<u8>> {
+        let mut line = serde_json::to_vec(data)?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
+
+/// WARC/1.1 `response` records rebuilt from the scraped status, headers and content
+///
+/// The body is the decoded page content, so `Content-Encoding`,
+/// `Transfer-Encoding` and `Content-Length` headers are rewritten to match it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WarcFormat;
+
+impl RecordFormat for WarcFormat {
+    fn extension(&self) -> &str {
+        "warc"
+    }
+
+    fn encode(&self, data: &ScrapedData) -> Result<Vec<u8>> {
+        let reason = reqwest::StatusCode::from_u16(data.status_code)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or("");
+        let mut http = format!("HTTP/1.1 {} {}\r\n", data.status_code, reason);
+
+        let mut headers: Vec<_> = data.headers
+            .iter()
+            .filter(|(name, _)| !matches!(name.as_str(), "content-encoding" | "transfer-encoding" | "content-length"))
+            .collect();
+        headers.sort();
+        for (name, value) in headers {
+            http.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        http.push_str(&format!("content-length: {}\r\n\r\n", data.content.len()));
+
+        let mut block = http.into_bytes();
+        block.extend_from_slice(data.content.as_bytes());
+
+        let id: [u8; 16] = rand::random();
+        let mut record = format!(
+            "WARC/1.1\r\nWARC-Type: response\r\nWARC-Record-ID: <urn:uuid:{}>\r\nWARC-Date: {}\r\n\
+             WARC-Target-URI: {}\r\nContent-Type: application/http; msgtype=response\r\nContent-Length: {}\r\n\r\n",
+            uuid_v4(id),
+            data.timestamp.format("%Y-%m-%dT%H:%M:%SZ"),
+            data.url,
+            block.len()
+        ).into_bytes();
+        record.extend_from_slice(&block);
+        record.extend_from_slice(b"\r\n\r\n");
+        Ok(record)
+    }
+}
+
+/// Format random bytes as a version 4 UUID
+fn uuid_v4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// XML document with one element per record and configurable element names
+///
+/// ```xml
+/// <records>
+///   <record url="..." status="200" timestamp="..." title="...">
+///     <price>...</price>
+///   </record>
+/// </records>
+/// ```
+///
+/// Fields with several values repeat their element. Field names that are not
+/// valid XML names are sanitised unless mapped explicitly.
+#[derive(Debug, Clone)]
+pub struct XmlFormat {
+    root: String,
+    record: String,
+    fields: HashMap<String, String>,
+    include_metadata: bool,
+}
+
+impl Default for XmlFormat {
+    fn default() -> Self {
+        Self {
+            root: "records".to_string(),
+            record: "record".to_string(),
+            fields: HashMap::new(),
+            include_metadata: false,
+        }
+    }
+}
+
+impl XmlFormat {
+    /// Create a format with `<records>` and `<record>` elements
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the root element name
+    pub fn root_element(mut self, name: &str) -> Self {
+        self.root = xml_name(name);
+        self
+    }
+
+    /// Set the per-record element name
+    pub fn record_element(mut self, name: &str) -> Self {
+        self.record = xml_name(name);
+        self
+    }
+
+    /// Write an extracted field under a different element name
+    pub fn map_field(mut self, field: &str, element: &str) -> Self {
+        self.fields.insert(field.to_string(), xml_name(element));
+        self
+    }
+
+    /// Also write the scraped metadata as a `<_metadata>` element of JSON values
+    pub fn with_metadata(mut self) -> Self {
+        self.include_metadata = true;
+        self
+    }
+}
+
+impl RecordFormat for XmlFormat {
+    fn extension(&self) -> &str {
+        "xml"
+    }
+
+    fn header(&self) -> Vec<u8> {
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<{}>\n", self.root).into_bytes()
+    }
+
+    fn footer(&self) -> Vec<u8> {
+        format!("</{}>\n", self.root).into_bytes()
+    }
+
+    fn encode(&self, data: &ScrapedData) -> Result<Vec<u8>> {
+        let mut xml = format!(
+            "  <{} url=\"{}\" status=\"{}\" timestamp=\"{}\"",
+            self.record,
+            escape_xml(&data.url),
+            data.status_code,
+            data.timestamp.to_rfc3339()
+        );
+        if let Some(title) = &data.title {
+            xml.push_str(&format!(" title=\"{}\"", escape_xml(title)));
+        }
+        xml.push_str(">\n");
+
+        let mut fields: Vec<_> = data.extracted_data.iter().collect();
+        fields.sort_by(|a, b| a.0.cmp(b.0));
+        for (field, values) in fields {
+            let element = self.fields.get(field).cloned().unwrap_or_else(|| xml_name(field));
+            for value in values {
+                xml.push_str(&format!("    <{0}>{1}</{0}>\n", element, escape_xml(value)));
+            }
+        }
+
+        if self.include_metadata {
+            let mut metadata: Vec<_> = data.metadata.iter().collect();
+            metadata.sort_by(|a, b| a.0.cmp(b.0));
+            xml.push_str("    <_metadata>\n");
+            for (key, value) in metadata {
+                xml.push_str(&format!("      <{0}>{1}</{0}>\n", xml_name(key), escape_xml(&value.to_string())));
+            }
+            xml.push_str("    </_metadata>\n");
+        }
+
+        xml.push_str(&format!("  </{}>\n", self.record));
+        Ok(xml.into_bytes())
+    }
+}
+
+/// Columns written before the extracted fields in tabular output
+pub(crate) const FIXED_COLUMNS: [&str; 4] = ["url", "title", "status_code", "timestamp"];
+
+/// Crawl topology columns, written when any record came from a crawl
+pub(crate) const TOPOLOGY_COLUMNS: [&str; 3] = ["depth", "parent_url", "discovered_via"];
+
+/// Columns of a table holding the given records: the fixed columns, the
+/// topology columns for crawled records, then every extracted field, sorted by name
+pub(crate) fn table_columns(records: &[ScrapedData]) -> Vec<String> {
+    let fields: BTreeSet<&String> = records.iter().flat_map(|data| data.extracted_data.keys()).collect();
+    let crawled = records.iter().any(|data| data.discovered_via.is_some());
+    let topology: &[&str] = if crawled { &TOPOLOGY_COLUMNS } else { &[] };
+    FIXED_COLUMNS
+        .iter()
+        .chain(topology)
+        .map(|column| column.to_string())
+        .chain(fields.into_iter().filter(|field| !FIXED_COLUMNS.contains(&field.as_str()) && !topology.contains(&field.as_str())).cloned())
+        .collect()
+}
+
+/// Value of a named column for tabular output
+///
+/// Extracted fields take precedence, with multiple values joined by newlines;
+/// otherwise `id`, `run_id`, `url`, `host`, `original_url`, `final_url`, `title`, `status_code`, `timestamp`,
+/// `depth`, `parent_url` and `discovered_via` refer to the record itself.
+pub fn column_value(data: &ScrapedData, column: &str) -> String {
+    if let Some(values) = data.extracted_data.get(column) {
+        return values.join("\n");
+    }
+
+    match column {
+        "id" => data.id(),
+        "run_id" => data.run_id.clone().unwrap_or_default(),
+        "url" => data.url.clone(),
+        "host" => url::Url::parse(&data.url).ok().and_then(|url| url.host_str().map(str::to_string)).unwrap_or_default(),
+        "original_url" => data.original_url.clone().unwrap_or_default(),
+        "final_url" => data.final_url.clone(),
+        "title" => data.title.clone().unwrap_or_default(),
+        "status_code" => data.status_code.to_string(),
+        "timestamp" => data.timestamp.to_rfc3339(),
+        "depth" => data.depth.to_string(),
+        "parent_url" => data.parent_url.clone().unwrap_or_default(),
+        "discovered_via" => data.discovered_via.map(|via| via.as_str().to_string()).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Escape text for XML content and attribute values
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Turn a field name into a valid XML element name
+fn xml_name(name: &str) -> String {
+    let mut element: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' })
+        .collect();
+    if !element.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        element.insert(0, '_');
+    }
+    element
+}
+
+/// JSON Lines exporter, one record per line
+pub type JsonLinesExporter<W> = FileExporter<W, JsonlFormat>;
+
+/// Exporter writing every record to a single writer
+#[derive(Debug)]
+pub struct FileExporter<W: Write, F: RecordFormat> {
+    writer: W,
+    format: F,
+    started: bool,
+}
+
+impl<F: RecordFormat> FileExporter<BufWriter<File>, F> {
+    /// Create (or truncate) a file and export to it
+    pub fn create(path: impl AsRef<Path>, format: F) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?), format))
+    }
+}
+
+impl<W: Write, F: RecordFormat> FileExporter<W, F> {
+    /// Export to an existing writer
+    pub fn new(writer: W, format: F) -> Self {
+        Self { writer, format, started: false }
+    }
+
+    /// Get the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn start(&mut self) -> Result<()> {
+        if !self.started {
+            self.writer.write_all(&self.format.header())?;
+            self.started = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write, F: RecordFormat> Exporter for FileExporter<W, F> {
+    fn export(&mut self, data: &ScrapedData) -> Result<()> {
+        self.start()?;
+        self.writer.write_all(&self.format.encode(data)?)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.start()?;
+        self.writer.write_all(&self.format.footer())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// CSV exporter with one column per extracted field
+///
+/// Columns are `url`, `title`, `status_code` and `timestamp` followed by the
+/// extracted fields in name order, multiple values joined by newlines. Unless
+/// set with [`CsvExporter::with_columns`], [`Exporter::export_all`] uses the
+/// fields of all records and [`Exporter::export`] those of the first one.
+#[derive(Debug)]
+pub struct CsvExporter<W: Write> {
+    writer: W,
+    columns: Option<Vec<String>>,
+    delimiter: char,
+    started: bool,
+}
+
+impl CsvExporter<BufWriter<File>> {
+    /// Create (or truncate) a file and export to it
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> CsvExporter<W> {
+    /// Export to an existing writer
+    pub fn new(writer: W) -> Self {
+        Self { writer, columns: None, delimiter: ',', started: false }
+    }
+
+    /// Write exactly these columns
+    pub fn with_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Separate fields with another character, such as a tab
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Get the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn start(&mut self, records: &[ScrapedData]) -> Result<()> {
+        if self.started {
+            return Ok(());
+        }
+        let columns = self.columns.get_or_insert_with(|| table_columns(records)).clone();
+        self.write_row(columns.iter().map(String::as_str))?;
+        self.started = true;
+        Ok(())
+    }
+
+    fn write_row<'a>(&mut self, cells: impl Iterator<Item = &'a str>) -> Result<()> {
+        let mut line = String::new();
+        for (index, cell) in cells.enumerate() {
+            if index > 0 {
+                line.push(self.delimiter);
+            }
+            line.push_str(&csv_field(cell, self.delimiter));
+        }
+        line.push_str("\r\n");
+        self.writer.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Exporter for CsvExporter<W> {
+    fn export(&mut self, data: &ScrapedData) -> Result<()> {
+        self.start(std::slice::from_ref(data))?;
+        let values: Vec<String> = self
+            .columns
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|column| column_value(data, column))
+            .collect();
+        self.write_row(values.iter().map(String::as_str))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.start(&[])?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn export_all(&mut self, records: &[ScrapedData]) -> Result<()> {
+        self.start(records)?;
+        for data in records {
+            self.export(data)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quote a CSV field if it holds the delimiter, quotes or line breaks
+fn csv_field(value: &str, delimiter: char) -> String {
+    if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// How records are distributed over output files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardStrategy {
+    /// One directory per host
+    Host,
+    /// One directory per UTC scrape date (`YYYY-MM-DD`)
+    Date,
+    /// Start a new file once the current one would exceed this many bytes
+    Size(u64),
+}
+
+/// An output file currently being written
+#[derive(Debug)]
+struct OpenShard {
+    writer: BufWriter<File>,
+    bytes: u64,
+    part: usize,
+}
+
+/// Exporter spreading records over files by host, date and size
+///
+/// Files are written as `<dir>/<host>/<date>/<prefix>-<part>.<ext>`, with
+/// the host and date levels present only for the enabled strategies.
+#[derive(Debug)]
+pub struct ShardedExporter<F: RecordFormat> {
+    dir: PathBuf,
+    prefix: String,
+    format: F,
+    by_host: bool,
+    by_date: bool,
+    max_bytes: Option<u64>,
+    open: HashMap<PathBuf, OpenShard>,
+    files: Vec<PathBuf>,
+}
+
+impl<F: RecordFormat> ShardedExporter<F> {
+    /// Export into `dir`, writing a single rotating file until strategies are added
+    pub fn new(dir: impl Into<PathBuf>, format: F) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: "part".to_string(),
+            format,
+            by_host: false,
+            by_date: false,
+            max_bytes: None,
+            open: HashMap::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Set the file name prefix (default `part`)
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Add a sharding strategy
+    pub fn shard(mut self, strategy: ShardStrategy) -> Self {
+        match strategy {
+            ShardStrategy::Host => self.by_host = true,
+            ShardStrategy::Date => self.by_date = true,
+            ShardStrategy::Size(max_bytes) => self.max_bytes = Some(max_bytes.max(1)),
+        }
+        self
+    }
+
+    /// Get the files written so far, in creation order
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Directory a record is written to
+    fn shard_dir(&self, data: &ScrapedData) -> PathBuf {
+        let mut dir = self.dir.clone();
+        if self.by_host {
+            let host = url::Url::parse(&data.url)
+                .ok()
+                .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+                .unwrap_or_else(|| "unknown".to_string());
+            dir.push(host);
+        }
+        if self.by_date {
+            dir.push(data.timestamp.format("%Y-%m-%d").to_string());
+        }
+        dir
+    }
+
+    fn open_part(&mut self, dir: &Path, part: usize) -> Result<OpenShard> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{:05}.{}", self.prefix, part, self.format.extension()));
+        debug!("Opening export shard {}", path.display());
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let header = self.format.header();
+        writer.write_all(&header)?;
+        self.files.push(path);
+        Ok(OpenShard { writer, bytes: header.len() as u64, part })
+    }
+
+    fn close(&self, mut shard: OpenShard) -> Result<()> {
+        shard.writer.write_all(&self.format.footer())?;
+        shard.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<F: RecordFormat> Exporter for ShardedExporter<F> {
+    fn export(&mut self, data: &ScrapedData) -> Result<()> {
+        let dir = self.shard_dir(data);
+        let record = self.format.encode(data)?;
+
+        let rotate = match (self.open.get(&dir), self.max_bytes) {
+            (Some(shard), Some(max_bytes)) => {
+                shard.bytes > self.format.header().len() as u64
+                    && shard.bytes + record.len() as u64 + self.format.footer().len() as u64 > max_bytes
+            }
+            _ => false,
+        };
+        if rotate {
+            if let Some(shard) = self.open.remove(&dir) {
+                let part = shard.part + 1;
+                self.close(shard)?;
+                let next = self.open_part(&dir, part)?;
+                self.open.insert(dir.clone(), next);
+            }
+        }
+
+        if !self.open.contains_key(&dir) {
+            let shard = self.open_part(&dir, 0)?;
+            self.open.insert(dir.clone(), shard);
+        }
+
+        let shard = self.open.get_mut(&dir).expect("shard was just opened");
+        shard.writer.write_all(&record)?;
+        shard.bytes += record.len() as u64;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        for (_, shard) in std::mem::take(&mut self.open) {
+            self.close(shard)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Discovery;
+
+    fn record(url: &str) -> ScrapedData {
+        let mut data = ScrapedData::new(url.to_string());
+        data.add_extracted_data("title", vec!["Hello".to_string()]);
+        data
+    }
+
+    #[test]
+    fn test_file_exporter_jsonl() {
+        let mut exporter = FileExporter::new(Vec::new(), JsonlFormat);
+        exporter.export_all(&[record("https://a.example/"), record("https://b.example/")]).unwrap();
+        exporter.finish().unwrap();
+
+        let output = String::from_utf8(exporter.into_inner()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["url"], "https://a.example/");
+    }
+
+    #[test]
+    fn test_warc_format() {
+        let mut data = record("https://a.example/");
+        data.status_code = 200;
+        data.content = "<p>héllo</p>".to_string();
+        data.headers.insert("content-type".to_string(), "text/html".to_string());
+        data.headers.insert("content-encoding".to_string(), "gzip".to_string());
+
+        let warc = String::from_utf8(WarcFormat.encode(&data).unwrap()).unwrap();
+        let (head, block) = warc.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("WARC/1.1\r\nWARC-Type: response\r\n"));
+        assert!(head.contains("WARC-Target-URI: https://a.example/\r\n"));
+
+        let block = block.strip_suffix("\r\n\r\n").unwrap();
+        assert!(head.ends_with(&format!("Content-Length: {}", block.len())));
+        assert!(block.starts_with("HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: 13\r\n\r\n"));
+        assert!(!block.contains("gzip"));
+        assert_eq!(uuid_v4([0xff; 16]), "ffffffff-ffff-4fff-bfff-ffffffffffff");
+    }
+
+    #[test]
+    fn test_xml_format() {
+        let mut data = record("https://a.example/?a=1&b=2");
+        data.add_extracted_data("price", vec!["<10>".to_string()]);
+        data.add_extracted_data("2nd image", vec!["a.png".to_string(), "b.png".to_string()]);
+
+        let format = XmlFormat::new().root_element("products").record_element("product").map_field("title", "name");
+        let mut exporter = FileExporter::new(Vec::new(), format);
+        exporter.export(&data).unwrap();
+        exporter.finish().unwrap();
+
+        let xml = String::from_utf8(exporter.into_inner()).unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<products>\n"));
+        assert!(xml.contains("<product url=\"https://a.example/?a=1&amp;b=2\""));
+        assert!(xml.contains("<name>Hello</name>"));
+        assert!(xml.contains("<price>&lt;10&gt;</price>"));
+        assert!(xml.contains("<_2nd_image>a.png</_2nd_image>\n    <_2nd_image>b.png</_2nd_image>"));
+        assert!(xml.ends_with("</products>\n"));
+    }
+
+    #[test]
+    fn test_sharded_exporter() {
+        let dir = std::env::temp_dir().join(format!("ferrisfetcher-export-test-{}", std::process::id()));
+        let line_len = JsonlFormat.encode(&record("https://a.example/1")).unwrap().len() as u64;
+
+        let mut exporter = ShardedExporter::new(&dir, JsonlFormat)
+            .with_prefix("results")
+            .shard(ShardStrategy::Host)
+            .shard(ShardStrategy::Size(line_len * 2 + 20));
+        for url in ["https://a.example/1", "https://a.example/2", "https://a.example/3", "https://B.example/1"] {
+            exporter.export(&record(url)).unwrap();
+        }
+        exporter.finish().unwrap();
+
+        assert_eq!(exporter.files().len(), 3);
+        let first = fs::read_to_string(dir.join("a.example/results-00000.jsonl")).unwrap();
+        assert_eq!(first.lines().count(), 2);
+        let second = fs::read_to_string(dir.join("a.example/results-00001.jsonl")).unwrap();
+        assert_eq!(second.lines().count(), 1);
+        assert!(dir.join("b.example/results-00000.jsonl").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_csv_exporter() {
+        let mut quoted = record("https://b.example/");
+        quoted.add_extracted_data("price", vec!["1,50".to_string(), "say \"hi\"".to_string()]);
+
+        let mut exporter = CsvExporter::new(Vec::new());
+        exporter.export_all(&[record("https://a.example/"), quoted]).unwrap();
+        exporter.finish().unwrap();
+
+        let output = String::from_utf8(exporter.into_inner()).unwrap();
+        let mut lines = output.split("\r\n");
+        assert_eq!(lines.next(), Some("url,title,status_code,timestamp,price"));
+        assert!(lines.next().unwrap().starts_with("https://a.example/,Hello,0,"));
+        assert!(lines.next().unwrap().ends_with(",\"1,50\nsay \"\"hi\"\"\""));
+
+        let mut tabbed = CsvExporter::new(Vec::new()).with_columns(["url", "title"]).with_delimiter('\t');
+        tabbed.export(&record("https://a.example/")).unwrap();
+        tabbed.finish().unwrap();
+        assert_eq!(String::from_utf8(tabbed.into_inner()).unwrap(), "url\ttitle\r\nhttps://a.example/\tHello\r\n");
+
+        let mut crawled = record("https://a.example/b");
+        crawled.set_origin(1, Some("https://a.example/".to_string()), Discovery::Link);
+        let mut exporter = CsvExporter::new(Vec::new());
+        exporter.export_all(&[record("https://a.example/"), crawled]).unwrap();
+        let output = String::from_utf8(exporter.into_inner()).unwrap();
+        let mut lines = output.split("\r\n");
+        assert_eq!(lines.next(), Some("url,title,status_code,timestamp,depth,parent_url,discovered_via"));
+        assert!(lines.next().unwrap().ends_with(",0,,"));
+        assert!(lines.next().unwrap().ends_with(",1,https://a.example/,link"));
+    }
+
+    #[tokio::test]
+    async fn test_export_stream() {
+        let records = futures::stream::iter(vec![record("https://a.example/"), record("https://b.example/")]);
+        let mut exporter = JsonLinesExporter::new(Vec::new(), JsonlFormat);
+        assert_eq!(export_stream(&mut exporter, records).await.unwrap(), 2);
+        assert_eq!(String::from_utf8(exporter.into_inner()).unwrap().lines().count(), 2);
+    }
+}
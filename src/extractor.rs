@@ -1,16 +1,38 @@
 //! Data extraction engine for structured data extraction
 
 use crate::error::{FerrisFetcherError, Result};
-use crate::html_parser::HtmlParser;
-use crate::types::{ExtractionRule, ExtractionType};
+use crate::html_parser::{CssSelector, HtmlParser};
+use crate::json_extractor::{self, JsonExtractor, JsonPath};
+use crate::repair::{self, RuleAnchor, SelectorSuggestion};
+use crate::types::{Aggregate, ExtractionRule, ExtractionType, ItemRule, RuleCondition, RuleSetVersion, ScrapedData, Transform};
+use crate::validation::ValidationReport;
+use dashmap::DashMap;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+/// Maximum number of replacement selectors suggested for a broken rule
+const MAX_REPAIR_SUGGESTIONS: usize = 3;
+
 /// Data extraction engine with configurable rules
 #[derive(Debug, Clone)]
 pub struct DataExtractor {
     /// Extraction rules indexed by name
     rules: HashMap<String, ExtractionRule>,
+    /// Item rules indexed by name
+    item_rules: HashMap<String, ItemRule>,
+    /// Version of the rule set the rules were loaded from, if any
+    rule_set: Option<RuleSetVersion>,
+    /// Last matched element per rule, recorded when repair suggestions are enabled
+    anchors: Option<Arc<DashMap<String, RuleAnchor>>>,
+    /// Compiled `UrlMatches` condition patterns of the rules
+    url_patterns: HashMap<String, Regex>,
 }
 
 impl DataExtractor {
@@ -18,6 +40,10 @@ impl DataExtractor {
     pub fn new() -> Self {
         Self {
             rules: HashMap::new(),
+            item_rules: HashMap::new(),
+            rule_set: None,
+            anchors: None,
+            url_patterns: HashMap::new(),
         }
     }
 
@@ -30,14 +56,170 @@ impl DataExtractor {
         extractor
     }
 
+    /// Create a data extractor from a versioned rule set
+    pub fn from_rule_set(rule_set: RuleSet) -> Self {
+        let mut extractor = Self::with_rules(rule_set.rules.clone());
+        for rule in &rule_set.item_rules {
+            extractor.add_item_rule(rule.clone());
+        }
+        extractor.rule_set = Some(rule_set.version_info());
+        extractor
+    }
+
+    /// Create a data extractor from JSON holding a [`RuleSet`] or a list of rules
+    pub fn from_json(json: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RuleFile {
+            RuleSet(RuleSet),
+            Rules(Vec<ExtractionRule>),
+        }
+
+        Ok(match serde_json::from_str(json)? {
+            RuleFile::RuleSet(rule_set) => Self::from_rule_set(rule_set),
+            RuleFile::Rules(rules) => Self::with_rules(rules),
+        })
+    }
+
+    /// Create a data extractor from a JSON rule file (see [`DataExtractor::from_json`])
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Create a data extractor from one-line rules (see [`crate::rule_dsl`])
+    pub fn from_dsl(text: &str) -> Result<Self> {
+        Ok(Self::with_rules(crate::rule_dsl::parse_rules(text)?))
+    }
+
+    /// Carry the remembered rule matches of another extractor over to this one
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn share_anchors_with(&mut self, other: &DataExtractor) {
+        self.anchors = other.anchors.clone();
+    }
+
+    /// Get the version of the rule set this extractor was built from
+    pub fn rule_set_version(&self) -> Option<&RuleSetVersion> {
+        self.rule_set.as_ref()
+    }
+
+    /// Remember what each rule matched and log replacement selector
+    /// suggestions when a previously matching rule stops matching
+    pub fn with_repair_suggestions(mut self) -> Self {
+        self.enable_repair_suggestions();
+        self
+    }
+
+    /// Enable repair suggestions (see [`DataExtractor::with_repair_suggestions`])
+    pub fn enable_repair_suggestions(&mut self) {
+        if self.anchors.is_none() {
+            self.anchors = Some(Arc::new(DashMap::new()));
+        }
+    }
+
+    /// Get the element a rule last matched, if repair suggestions are enabled
+    pub fn rule_anchor(&self, name: &str) -> Option<RuleAnchor> {
+        self.anchors.as_ref()?.get(name).map(|anchor| anchor.clone())
+    }
+
+    /// Seed the anchor of a rule, e.g. from a previous run
+    pub fn set_rule_anchor(&mut self, name: &str, anchor: RuleAnchor) {
+        self.enable_repair_suggestions();
+        if let Some(anchors) = &self.anchors {
+            anchors.insert(name.to_string(), anchor);
+        }
+    }
+
+    /// Suggest replacement selectors for a rule from its recorded anchor
+    pub fn suggest_repairs(&self, parser: &HtmlParser, rule_name: &str) -> Vec<SelectorSuggestion> {
+        self.rule_anchor(rule_name)
+            .map(|anchor| repair::suggest_selectors(parser, &anchor, MAX_REPAIR_SUGGESTIONS))
+            .unwrap_or_default()
+    }
+
+    /// Record the anchor of a matching rule, or log repair suggestions for a broken one
+    fn track_rule_result(&self, parser: &HtmlParser, rule: &ExtractionRule, matched: bool) {
+        let anchors = match &self.anchors {
+            Some(anchors) => anchors,
+            None => return,
+        };
+
+        if matched {
+            let element = match &rule.compiled_selector {
+                Some(compiled) if compiled.as_str() == rule.selector => parser.select_first_compiled(compiled),
+                _ => parser.select(&rule.selector).ok().and_then(|elements| elements.into_iter().next()),
+            };
+            if let Some(element) = element {
+                anchors.insert(rule.name.clone(), RuleAnchor::from_element(element));
+            }
+            return;
+        }
+
+        let suggestions = self.suggest_repairs(parser, &rule.name);
+        if suggestions.is_empty() {
+            return;
+        }
+
+        let listed: Vec<String> = suggestions
+            .iter()
+            .map(|suggestion| format!("'{}' (score {:.2}, {} matches)", suggestion.selector, suggestion.score, suggestion.matches))
+            .collect();
+        warn!(
+            "Rule '{}' with selector '{}' matched nothing; suggested replacements: {}",
+            rule.name, rule.selector, listed.join(", ")
+        );
+    }
+
     /// Add an extraction rule
-    pub fn add_rule(&mut self, rule: ExtractionRule) {
-        self.rules.insert(rule.name.clone(), rule);
+    ///
+    /// The rule's CSS selector, transforms and URL patterns are compiled once
+    /// here rather than for every page.
+    pub fn add_rule(&mut self, mut rule: ExtractionRule) {
+        for condition in &rule.conditions {
+            if let RuleCondition::UrlMatches(pattern) = condition {
+                if !self.url_patterns.contains_key(pattern) {
+                    if let Ok(regex) = Regex::new(pattern) {
+                        self.url_patterns.insert(pattern.clone(), regex);
+                    }
+                }
+            }
+        }
+        Self::compile_rule(&mut rule);
+        if self.rules.insert(rule.name.clone(), rule).is_some() {
+            self.prune_url_patterns();
+        }
     }
 
     /// Remove an extraction rule by name
     pub fn remove_rule(&mut self, name: &str) -> Option<ExtractionRule> {
-        self.rules.remove(name)
+        let removed = self.rules.remove(name);
+        if removed.is_some() {
+            self.prune_url_patterns();
+        }
+        removed
+    }
+
+    /// Compile a rule's CSS selector and transforms, unless already done
+    ///
+    /// Whatever does not compile is left out; validation reports it, and
+    /// extraction falls back to compiling it and failing the rule.
+    fn compile_rule(rule: &mut ExtractionRule) {
+        let stale = rule.compiled_selector.as_ref().is_none_or(|compiled| compiled.as_str() != rule.selector);
+        if stale && !matches!(rule.extraction_type, ExtractionType::JsonPath) {
+            rule.compiled_selector = CssSelector::parse(&rule.selector).ok();
+        }
+        if !rule.compiled_transforms.as_ref().is_some_and(|chain| chain.compiled_from(&rule.transforms)) {
+            rule.compiled_transforms = TransformChain::compile(rule).ok();
+        }
+    }
+
+    /// Drop the compiled URL patterns no remaining rule refers to
+    fn prune_url_patterns(&mut self) {
+        let rules = &self.rules;
+        self.url_patterns.retain(|pattern, _| {
+            rules.values().flat_map(|rule| &rule.conditions).any(|condition| {
+                matches!(condition, RuleCondition::UrlMatches(used) if used == pattern)
+            })
+        });
     }
 
     /// Get an extraction rule by name
@@ -50,78 +232,368 @@ impl DataExtractor {
         &self.rules
     }
 
+    /// Add an item rule extracting a list of records
+    ///
+    /// The transforms of its fields are compiled once here.
+    pub fn add_item_rule(&mut self, mut rule: ItemRule) {
+        if rule.compiled_selector.as_ref().is_none_or(|compiled| compiled.as_str() != rule.selector) {
+            rule.compiled_selector = CssSelector::parse(&rule.selector).ok();
+        }
+        for field in &mut rule.fields {
+            Self::compile_rule(field);
+        }
+        self.item_rules.insert(rule.name.clone(), rule);
+    }
+
+    /// Get all item rules
+    pub fn item_rules(&self) -> &HashMap<String, ItemRule> {
+        &self.item_rules
+    }
+
+    /// Hash of the rules and rule set version, for keying cached results
+    pub fn fingerprint(&self) -> u64 {
+        let mut rules: Vec<_> = self.rules.iter().collect();
+        rules.sort_by(|a, b| a.0.cmp(b.0));
+        let mut item_rules: Vec<_> = self.item_rules.iter().collect();
+        item_rules.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut hasher = DefaultHasher::new();
+        format!("{:?}{:?}{:?}", rules, item_rules, self.rule_set).hash(&mut hasher);
+        hasher.finish()
+    }
+
     /// Extract data using all configured rules
     pub fn extract_all(&self, parser: &HtmlParser) -> Result<HashMap<String, Vec<String>>> {
-        let mut results = HashMap::new();
+        Ok(self.extract_all_detailed(parser).values)
+    }
+
+    /// Extract data using only the rules belonging to any of the given groups
+    pub fn extract_groups(&self, parser: &HtmlParser, groups: &[&str]) -> Result<HashMap<String, Vec<String>>> {
+        Ok(self.extract_groups_detailed(parser, groups).values)
+    }
+
+    /// Extract data using all configured rules, reporting rules that failed
+    pub fn extract_all_detailed(&self, parser: &HtmlParser) -> ExtractionOutcome {
+        self.extract_matching(parser, |_| true)
+    }
+
+    /// Extract the records of all item rules
+    pub fn extract_items(&self, parser: &HtmlParser) -> Result<HashMap<String, Vec<HashMap<String, String>>>> {
+        self.item_rules
+            .iter()
+            .map(|(name, rule)| Ok((name.clone(), self.extract_item_rule(parser, rule)?)))
+            .collect()
+    }
+
+    /// Extract data using the rules in the given groups, reporting rules that failed
+    pub fn extract_groups_detailed(&self, parser: &HtmlParser, groups: &[&str]) -> ExtractionOutcome {
+        self.extract_matching(parser, |rule_groups| {
+            rule_groups.iter().any(|group| groups.contains(&group.as_str()))
+        })
+    }
+
+    /// Extract data using the rules whose groups are accepted by a filter
+    fn extract_matching<F>(&self, parser: &HtmlParser, filter: F) -> ExtractionOutcome
+    where
+        F: Fn(&[String]) -> bool,
+    {
+        let mut outcome = ExtractionOutcome::default();
         
-        for (name, rule) in &self.rules {
+        let html_rules = self.rules.iter().filter(|(_, rule)| !matches!(rule.extraction_type, ExtractionType::JsonPath));
+        for (name, rule) in html_rules.filter(|(_, rule)| filter(&rule.groups)) {
+            match self.conditions_met(parser, rule) {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!("Skipping rule '{}': conditions not met", name);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to evaluate conditions for rule '{}': {}", name, e);
+                    outcome.failed_rules.insert(name.clone(), e.to_string());
+                    continue;
+                }
+            }
+
             match self.extract_by_rule(parser, rule) {
                 Ok(values) => {
-                    if !values.is_empty() {
-                        results.insert(name.clone(), values.clone());
+                    self.track_rule_result(parser, rule, !values.is_empty());
+                    if values.is_empty() {
+                        outcome.empty_rules.push(name.clone());
+                    } else {
                         debug!("Extracted {} values for rule '{}'", values.len(), name);
+                        outcome.values.insert(name.clone(), values);
                     }
                 }
                 Err(e) => {
                     warn!("Failed to extract data for rule '{}': {}", name, e);
+                    outcome.failed_rules.insert(name.clone(), e.to_string());
+                }
+            }
+        }
+        
+        for (name, rule) in self.item_rules.iter().filter(|(_, rule)| filter(&rule.groups)) {
+            match self.extract_item_rule(parser, rule) {
+                Ok(items) if items.is_empty() => outcome.empty_rules.push(name.clone()),
+                Ok(items) => {
+                    debug!("Extracted {} items for rule '{}'", items.len(), name);
+                    outcome.items.insert(name.clone(), items);
+                }
+                Err(e) => {
+                    warn!("Failed to extract items for rule '{}': {}", name, e);
+                    outcome.failed_rules.insert(name.clone(), e.to_string());
                 }
             }
         }
         
-        info!("Extracted data for {} rules", results.len());
-        Ok(results)
+        outcome.empty_rules.sort();
+        info!("Extracted data for {} rules", outcome.values.len() + outcome.items.len());
+        outcome
+    }
+
+    /// Check whether all of a rule's page conditions hold
+    pub fn conditions_met(&self, parser: &HtmlParser, rule: &ExtractionRule) -> Result<bool> {
+        for condition in &rule.conditions {
+            let met = match condition {
+                RuleCondition::SelectorExists(selector) => !parser.select(selector)?.is_empty(),
+                RuleCondition::UrlMatches(pattern) => self.url_matches(rule, pattern, parser.base_url())?,
+            };
+
+            if !met {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
     }
 
-    /// Extract data using a specific rule
+    /// Extract data using a specific rule, applying its aggregation
     pub fn extract_by_rule(&self, parser: &HtmlParser, rule: &ExtractionRule) -> Result<Vec<String>> {
+        let values = self.extract_positioned(parser, rule)?
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        Ok(match &rule.aggregate {
+            Some(aggregate) => aggregate.apply(values),
+            None => values,
+        })
+    }
+
+    /// Extract data using a specific rule, pairing each value with the
+    /// document-order index of the selector match it came from
+    pub fn extract_positioned(&self, parser: &HtmlParser, rule: &ExtractionRule) -> Result<Vec<(usize, String)>> {
+        if matches!(rule.extraction_type, ExtractionType::JsonPath) {
+            return Err(FerrisFetcherError::ExtractionError(
+                format!("JSONPath rule '{}' only applies to JSON documents", rule.name)
+            ));
+        }
         debug!("Extracting data with rule '{}' using selector '{}'", rule.name, rule.selector);
-        
-        let values = match rule.extraction_type {
-            ExtractionType::Text => {
-                if rule.multiple {
-                    parser.select_text(&rule.selector)?
-                } else {
-                    parser.select_first_text(&rule.selector)
-                        .map(|text| vec![text])
-                        .unwrap_or_default()
+        match &rule.compiled_selector {
+            Some(compiled) if compiled.as_str() == rule.selector => Self::extract_from(rule, parser.select_compiled(compiled)),
+            _ => Self::extract_from(rule, parser.select(&rule.selector)?),
+        }
+    }
+
+    /// Extract the records of an item rule, evaluating its field rules
+    /// relative to each container element
+    pub fn extract_item_rule(&self, parser: &HtmlParser, rule: &ItemRule) -> Result<Vec<HashMap<String, String>>> {
+        debug!("Extracting items with rule '{}' using selector '{}'", rule.name, rule.selector);
+
+        let mut fields = Vec::with_capacity(rule.fields.len());
+        for field in &rule.fields {
+            if !self.conditions_met(parser, field)? {
+                continue;
+            }
+            let selector = match (field.selector.trim(), &field.compiled_selector) {
+                ("" | ":scope", _) => None,
+                (_, Some(compiled)) if compiled.as_str() == field.selector => Some(Cow::Borrowed(compiled.selector())),
+                (selector, _) => Some(Cow::Owned(Selector::parse(selector).map_err(|e| FerrisFetcherError::InvalidSelector(
+                    format!("{} (field '{}' of item rule '{}'): {:?}", selector, field.name, rule.name, e)
+                ))?)),
+            };
+            fields.push((field, selector));
+        }
+
+        let containers = match &rule.compiled_selector {
+            Some(compiled) if compiled.as_str() == rule.selector => parser.select_compiled(compiled),
+            _ => parser.select(&rule.selector)?,
+        };
+        let mut items = Vec::new();
+        for container in containers {
+            if rule.max_items.is_some_and(|max| items.len() >= max) {
+                break;
+            }
+
+            let mut item = HashMap::new();
+            for (field, selector) in &fields {
+                let positioned = match selector {
+                    Some(selector) => Self::extract_from(field, container.select(selector.as_ref()))?,
+                    None => Self::extract_from(field, std::iter::once(container))?,
+                };
+                let values: Vec<String> = positioned.into_iter().map(|(_, value)| value).collect();
+                let values = match &field.aggregate {
+                    Some(aggregate) => aggregate.apply(values),
+                    None => values,
+                };
+                if !values.is_empty() {
+                    item.insert(field.name.clone(), values.join("\n"));
                 }
             }
-            ExtractionType::Html => {
-                if rule.multiple {
-                    parser.select_html(&rule.selector)?
-                } else {
-                    parser.select_first_html(&rule.selector)
-                        .map(|html| vec![html])
-                        .unwrap_or_default()
+
+            if !item.is_empty() {
+                items.push(item);
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Extract the values of a rule from candidate elements, pairing each
+    /// value with the index of the element it came from
+    fn extract_from<'a>(
+        rule: &ExtractionRule,
+        elements: impl IntoIterator<Item = ElementRef<'a>>,
+    ) -> Result<Vec<(usize, String)>> {
+        let attr_name = match rule.extraction_type {
+            ExtractionType::Attribute => Some(rule.attribute.as_ref()
+                .ok_or_else(|| FerrisFetcherError::ExtractionError(
+                    format!("Attribute extraction requires attribute name for rule '{}'", rule.name)
+                ))?),
+            _ => None,
+        };
+
+        let values = elements.into_iter().map(|element| {
+            Ok(match rule.extraction_type {
+                ExtractionType::Text => {
+                    Some(element.text().collect::<String>().trim().to_string())
+                        .filter(|text| !text.is_empty())
+                }
+                ExtractionType::Html | ExtractionType::OuterHtml => Some(element.html()),
+                ExtractionType::Attribute => {
+                    attr_name.and_then(|attr| element.value().attr(attr))
+                        .map(|value| value.to_string())
                 }
+                ExtractionType::Attributes => {
+                    Some(serde_json::to_string(&HtmlParser::element_attributes(element))?)
+                }
+                ExtractionType::JsonPath => None,
+            })
+        });
+        Self::select_values(rule, values)
+    }
+
+    /// Apply a rule's transforms, skip and match limit to candidate values,
+    /// pairing each kept value with its candidate's index
+    fn select_values(
+        rule: &ExtractionRule,
+        candidates: impl IntoIterator<Item = Result<Option<String>>>,
+    ) -> Result<Vec<(usize, String)>> {
+        let compiled;
+        let transforms = match &rule.compiled_transforms {
+            Some(chain) if chain.compiled_from(&rule.transforms) => chain,
+            _ => {
+                compiled = TransformChain::compile(rule)?;
+                &compiled
             }
-            ExtractionType::Attribute => {
-                let attr_name = rule.attribute.as_ref()
-                    .ok_or_else(|| FerrisFetcherError::ExtractionError(
-                        format!("Attribute extraction requires attribute name for rule '{}'", rule.name)
-                    ))?;
-                
-                if rule.multiple {
-                    parser.select_attr(&rule.selector, attr_name)?
+        };
+        let limit = if rule.multiple { rule.max_matches } else { Some(1) };
+        let mut values = Vec::new();
+        let mut skipped = 0;
+
+        for (position, value) in candidates.into_iter().enumerate() {
+            if limit.is_some_and(|limit| values.len() >= limit) {
+                break;
+            }
+
+            if let Some(value) = value?.and_then(|value| transforms.apply(value)) {
+                if skipped < rule.skip {
+                    skipped += 1;
                 } else {
-                    parser.select_first_attr(&rule.selector, attr_name)
-                        .map(|attr| vec![attr])
-                        .unwrap_or_default()
+                    values.push((position, value));
                 }
             }
-            ExtractionType::OuterHtml => {
-                if rule.multiple {
-                    parser.select_outer_html(&rule.selector)?
-                } else {
-                    parser.select_first(&rule.selector)
-                        .map(|element| element.html())
-                        .map(|html| vec![html])
-                        .unwrap_or_default()
+        }
+
+        Ok(values)
+    }
+
+    /// Extract data from a JSON document using all JSONPath rules, reporting rules that failed
+    pub fn extract_json_detailed(&self, document: &JsonExtractor) -> ExtractionOutcome {
+        self.extract_json_matching(document, |_| true)
+    }
+
+    /// Extract data from a JSON document using the JSONPath rules in the given groups
+    pub fn extract_json_groups_detailed(&self, document: &JsonExtractor, groups: &[&str]) -> ExtractionOutcome {
+        self.extract_json_matching(document, |rule_groups| {
+            rule_groups.iter().any(|group| groups.contains(&group.as_str()))
+        })
+    }
+
+    fn extract_json_matching<F>(&self, document: &JsonExtractor, filter: F) -> ExtractionOutcome
+    where
+        F: Fn(&[String]) -> bool,
+    {
+        let mut outcome = ExtractionOutcome::default();
+        let json_rules = self.rules.iter().filter(|(_, rule)| matches!(rule.extraction_type, ExtractionType::JsonPath));
+        for (name, rule) in json_rules.filter(|(_, rule)| filter(&rule.groups)) {
+            let values = self.json_conditions_met(document, rule)
+                .and_then(|met| if met { self.extract_json_by_rule(document, rule).map(Some) } else { Ok(None) });
+            match values {
+                Ok(None) => debug!("Skipping rule '{}': conditions not met", name),
+                Ok(Some(values)) if values.is_empty() => outcome.empty_rules.push(name.clone()),
+                Ok(Some(values)) => {
+                    outcome.values.insert(name.clone(), values);
+                }
+                Err(e) => {
+                    warn!("Failed to extract JSON for rule '{}': {}", name, e);
+                    outcome.failed_rules.insert(name.clone(), e.to_string());
                 }
             }
+        }
+        outcome.empty_rules.sort();
+        outcome
+    }
+
+    /// Check a rule's `UrlMatches` condition against the document URL, false without one
+    ///
+    /// Patterns of added rules are compiled already; others, e.g. of a rule
+    /// passed to [`DataExtractor::conditions_met`] directly, are compiled here.
+    fn url_matches(&self, rule: &ExtractionRule, pattern: &str, url: Option<&url::Url>) -> Result<bool> {
+        let compiled;
+        let regex = match self.url_patterns.get(pattern) {
+            Some(regex) => regex,
+            None => {
+                compiled = Regex::new(pattern).map_err(|e| FerrisFetcherError::ExtractionError(
+                    format!("Invalid URL pattern '{}' for rule '{}': {}", pattern, rule.name, e)
+                ))?;
+                &compiled
+            }
         };
+        Ok(url.is_some_and(|url| regex.is_match(url.as_str())))
+    }
 
-        Ok(values)
+    /// Check a JSONPath rule's conditions; `SelectorExists` takes a JSONPath expression here
+    fn json_conditions_met(&self, document: &JsonExtractor, rule: &ExtractionRule) -> Result<bool> {
+        for condition in &rule.conditions {
+            let met = match condition {
+                RuleCondition::SelectorExists(path) => !document.select(path)?.is_empty(),
+                RuleCondition::UrlMatches(pattern) => self.url_matches(rule, pattern, document.base_url())?,
+            };
+            if !met {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Extract data from a JSON document using a JSONPath rule, applying its aggregation
+    pub fn extract_json_by_rule(&self, document: &JsonExtractor, rule: &ExtractionRule) -> Result<Vec<String>> {
+        let path = JsonPath::parse(&rule.selector)?;
+        let candidates = path.select(document.document()).into_iter().map(|value| Ok(json_extractor::value_to_string(value)));
+        let values = Self::select_values(rule, candidates)?.into_iter().map(|(_, value)| value).collect();
+        Ok(match &rule.aggregate {
+            Some(aggregate) => aggregate.apply(values),
+            None => values,
+        })
     }
 
     /// Extract data by rule name
@@ -143,26 +615,21 @@ impl DataExtractor {
 
     /// Extract text content using a CSS selector (convenience method)
     pub fn extract_text(&self, parser: &HtmlParser, selector: &str, multiple: bool) -> Result<Vec<String>> {
-        let rule = ExtractionRule {
-            name: format!("temp_text_{}", selector.len()),
-            selector: selector.to_string(),
-            extraction_type: ExtractionType::Text,
-            multiple,
-            attribute: None,
-        };
+        let rule = ExtractionRuleBuilder::new(&format!("temp_text_{}", selector.len()), selector)
+            .extraction_type(ExtractionType::Text)
+            .multiple(multiple)
+            .build();
         
         self.extract_by_rule(parser, &rule)
     }
 
     /// Extract attribute values using a CSS selector (convenience method)
     pub fn extract_attr(&self, parser: &HtmlParser, selector: &str, attr: &str, multiple: bool) -> Result<Vec<String>> {
-        let rule = ExtractionRule {
-            name: format!("temp_attr_{}", selector.len()),
-            selector: selector.to_string(),
-            extraction_type: ExtractionType::Attribute,
-            multiple,
-            attribute: Some(attr.to_string()),
-        };
+        let rule = ExtractionRuleBuilder::new(&format!("temp_attr_{}", selector.len()), selector)
+            .extraction_type(ExtractionType::Attribute)
+            .multiple(multiple)
+            .attribute(attr)
+            .build();
         
         self.extract_by_rule(parser, &rule)
     }
@@ -172,55 +639,456 @@ impl DataExtractor {
         self.rules.contains_key(name)
     }
 
-    /// Get the number of configured rules
+    /// Get the names of all rule groups, sorted
+    pub fn groups(&self) -> Vec<String> {
+        let mut groups: Vec<String> = self.rules
+            .values()
+            .flat_map(|rule| rule.groups.iter().cloned())
+            .collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
+    /// Get the rules belonging to a group
+    pub fn rules_in_group(&self, group: &str) -> Vec<&ExtractionRule> {
+        self.rules
+            .values()
+            .filter(|rule| rule.groups.iter().any(|g| g == group))
+            .collect()
+    }
+
+    /// Get the number of configured rules, item rules included
     pub fn rule_count(&self) -> usize {
-        self.rules.len()
+        self.rules.len() + self.item_rules.len()
     }
 
     /// Clear all rules
     pub fn clear_rules(&mut self) {
         self.rules.clear();
+        self.item_rules.clear();
+        self.url_patterns.clear();
     }
 
-    /// Validate all rules
+    /// Validate all rules, failing with every problem found
     pub fn validate_rules(&self) -> Result<()> {
-        for (name, rule) in &self.rules {
-            if rule.selector.is_empty() {
-                return Err(FerrisFetcherError::ExtractionError(
-                    format!("Rule '{}' has empty selector", name)
-                ));
+        let report = self.validation_report();
+        if report.is_ok() {
+            Ok(())
+        } else {
+            Err(FerrisFetcherError::ExtractionError(report.to_string()))
+        }
+    }
+
+    /// Collect all problems with the rules, in rule name order
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut rules: Vec<&ExtractionRule> = self.rules.values().collect();
+        rules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut report = ValidationReport::new();
+        for rule in rules {
+            Self::validate_rule(rule, &rule.name, false, &mut report);
+        }
+
+        let mut item_rules: Vec<&ItemRule> = self.item_rules.values().collect();
+        item_rules.sort_by(|a, b| a.name.cmp(&b.name));
+        for rule in item_rules {
+            Self::validate_item_rule(rule, &mut report);
+        }
+        report
+    }
+
+    /// Record the problems of an item rule and its fields
+    fn validate_item_rule(rule: &ItemRule, report: &mut ValidationReport) {
+        let name = rule.name.as_str();
+        if name.is_empty() {
+            report.rule_issue(name, "Rule has empty name");
+        }
+
+        if rule.selector.is_empty() {
+            report.rule_issue(name, "Selector is empty");
+        } else if let Err(e) = scraper::Selector::parse(&rule.selector) {
+            report.rule_issue(name, format!("Invalid selector '{}': {:?}", rule.selector, e));
+        }
+
+        if rule.fields.is_empty() {
+            report.rule_issue(name, "Item rule has no fields");
+        }
+
+        for field in &rule.fields {
+            Self::validate_rule(field, &format!("{}.{}", rule.name, field.name), true, report);
+        }
+    }
+
+    /// Record the problems of a single rule under `name`; item fields may
+    /// have an empty selector to read their container
+    fn validate_rule(rule: &ExtractionRule, name: &str, is_field: bool, report: &mut ValidationReport) {
+        if rule.name.is_empty() {
+            report.rule_issue(name, "Rule has empty name");
+        }
+
+        let json = matches!(rule.extraction_type, ExtractionType::JsonPath);
+        if rule.selector.is_empty() {
+            if !is_field {
+                report.rule_issue(name, "Selector is empty");
             }
-            
-            if rule.name.is_empty() {
-                return Err(FerrisFetcherError::ExtractionError(
-                    "Rule has empty name".to_string()
-                ));
+        } else if json {
+            if is_field {
+                report.rule_issue(name, "JSONPath rules cannot be item fields");
+            } else if let Err(e) = JsonPath::parse(&rule.selector) {
+                report.rule_issue(name, e.to_string());
             }
-            
-            if matches!(rule.extraction_type, ExtractionType::Attribute) && rule.attribute.is_none() {
-                return Err(FerrisFetcherError::ExtractionError(
-                    format!("Rule '{}' with Attribute extraction type requires attribute name", name)
-                ));
+        } else if let Err(e) = scraper::Selector::parse(&rule.selector) {
+            report.rule_issue(name, format!("Invalid selector '{}': {:?}", rule.selector, e));
+        }
+
+        if matches!(rule.extraction_type, ExtractionType::Attribute) && rule.attribute.is_none() {
+            report.rule_issue(name, "Attribute extraction type requires attribute name");
+        }
+
+        if rule.max_matches == Some(0) {
+            report.rule_issue(name, "max_matches of 0 never extracts anything");
+        }
+
+        for pattern in rule.transforms.iter().filter_map(Transform::pattern) {
+            if let Err(e) = Regex::new(pattern) {
+                report.rule_issue(name, format!("Invalid transform pattern '{}': {}", pattern, e));
+            }
+        }
+
+        for condition in &rule.conditions {
+            match condition {
+                RuleCondition::SelectorExists(selector) if json => {
+                    if let Err(e) = JsonPath::parse(selector) {
+                        report.rule_issue(name, format!("Invalid condition: {}", e));
+                    }
+                }
+                RuleCondition::SelectorExists(selector) => {
+                    if let Err(e) = scraper::Selector::parse(selector) {
+                        report.rule_issue(name, format!("Invalid condition selector '{}': {:?}", selector, e));
+                    }
+                }
+                RuleCondition::UrlMatches(pattern) => {
+                    if let Err(e) = Regex::new(pattern) {
+                        report.rule_issue(name, format!("Invalid URL pattern '{}': {}", pattern, e));
+                    }
+                }
             }
         }
-        
-        Ok(())
     }
 }
 
+/// A rule's transforms with their regular expressions compiled
+///
+/// [`DataExtractor::add_rule`] compiles it once per rule and keeps it in
+/// [`ExtractionRule::compiled_transforms`].
+#[derive(Debug, Clone)]
+pub struct TransformChain {
+    steps: Vec<(Transform, Option<Regex>)>,
+}
+
+impl TransformChain {
+    /// Compile the transforms of a rule, failing on an invalid pattern
+    pub fn compile(rule: &ExtractionRule) -> Result<Self> {
+        let steps = rule.transforms
+            .iter()
+            .map(|transform| {
+                let regex = transform.pattern()
+                    .map(|pattern| Regex::new(pattern).map_err(|e| FerrisFetcherError::ExtractionError(
+                        format!("Invalid transform pattern '{}' for rule '{}': {}", pattern, rule.name, e)
+                    )))
+                    .transpose()?;
+                Ok((transform.clone(), regex))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { steps })
+    }
+
+    /// Whether the chain was compiled from `transforms`
+    fn compiled_from(&self, transforms: &[Transform]) -> bool {
+        self.steps.iter().map(|(transform, _)| transform).eq(transforms)
+    }
+
+    /// Run the transforms over a value, `None` if it was dropped
+    fn apply(&self, mut value: String) -> Option<String> {
+        for (transform, regex) in &self.steps {
+            value = match (transform, regex) {
+                (Transform::Capture(_), Some(regex)) => {
+                    let captures = regex.captures(&value)?;
+                    captures.get(1).or_else(|| captures.get(0))?.as_str().to_string()
+                }
+                (Transform::Replace { replacement, .. }, Some(regex)) => {
+                    regex.replace_all(&value, replacement.as_str()).into_owned()
+                }
+                (Transform::Trim, _) => value.trim().to_string(),
+                (Transform::Lowercase, _) => value.to_lowercase(),
+                (Transform::DecodeEntities, _) => decode_entities(&value),
+                _ => value,
+            };
+        }
+        Some(value).filter(|value| self.steps.is_empty() || !value.is_empty())
+    }
+}
+
+/// Decode numeric and common named HTML character references, leaving unknown ones as they are
+pub(crate) fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]).filter(|entity| entity.len() <= 10);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
 impl Default for DataExtractor {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Values extracted by a set of rules, along with the rules that failed
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionOutcome {
+    /// Extracted values by rule name, omitting rules that matched nothing
+    pub values: HashMap<String, Vec<String>>,
+    /// Records extracted by item rules, omitting rules that matched nothing
+    pub items: HashMap<String, Vec<HashMap<String, String>>>,
+    /// Error messages of rules that could not be evaluated, by rule name
+    pub failed_rules: HashMap<String, String>,
+    /// Rules whose conditions held but whose selector matched nothing
+    pub empty_rules: Vec<String>,
+}
+
+/// A named, versioned collection of extraction rules
+///
+/// Bump `version` whenever selectors or field names change and register a
+/// [`RuleMigration`] describing the change, so results stored under an older
+/// version can be upgraded with [`RuleSet::migrate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSet {
+    /// Name of the rule set
+    pub name: String,
+    /// Current version of the rule set
+    pub version: u32,
+    /// Extraction rules in this version
+    pub rules: Vec<ExtractionRule>,
+    /// Item rules in this version
+    #[serde(default)]
+    pub item_rules: Vec<ItemRule>,
+    /// Migrations from earlier versions, one per version step
+    #[serde(default)]
+    pub migrations: Vec<RuleMigration>,
+}
+
+impl RuleSet {
+    /// Create an empty rule set
+    pub fn new(name: &str, version: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            version,
+            rules: Vec::new(),
+            item_rules: Vec::new(),
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Add an extraction rule
+    pub fn rule(mut self, rule: ExtractionRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Add multiple extraction rules
+    pub fn rules(mut self, rules: Vec<ExtractionRule>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    /// Add an item rule
+    pub fn item_rule(mut self, rule: ItemRule) -> Self {
+        self.item_rules.push(rule);
+        self
+    }
+
+    /// Register a migration from an earlier version
+    pub fn migration(mut self, migration: RuleMigration) -> Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    /// Get the name/version marker recorded on scraped data
+    pub fn version_info(&self) -> RuleSetVersion {
+        RuleSetVersion::new(self.name.clone(), self.version)
+    }
+
+    /// Upgrade extracted data produced by an older version of this rule set
+    ///
+    /// Returns `Ok(true)` if the data was migrated, `Ok(false)` if it was
+    /// already at the current version or carries no rule set marker. Fails,
+    /// leaving the data unchanged, if the migrations do not form a forward
+    /// chain to the current version or a step cannot be applied.
+    pub fn migrate(&self, data: &mut ScrapedData) -> Result<bool> {
+        let stored = match &data.rule_set {
+            Some(stored) => stored.clone(),
+            None => return Ok(false),
+        };
+
+        if stored.name != self.name {
+            return Err(FerrisFetcherError::ExtractionError(
+                format!("Cannot migrate data from rule set '{}' with rule set '{}'", stored.name, self.name)
+            ));
+        }
+
+        if stored.version > self.version {
+            return Err(FerrisFetcherError::ExtractionError(
+                format!("Data version {} is newer than rule set '{}' version {}", stored.version, self.name, self.version)
+            ));
+        }
+
+        if stored.version == self.version {
+            return Ok(false);
+        }
+
+        let mut chain = Vec::new();
+        let mut version = stored.version;
+        while version < self.version {
+            let migration = self.migrations
+                .iter()
+                .find(|migration| migration.from_version == version)
+                .ok_or_else(|| FerrisFetcherError::ExtractionError(
+                    format!("No migration registered for rule set '{}' from version {}", self.name, version)
+                ))?;
+            if migration.to_version <= version || migration.to_version > self.version {
+                return Err(FerrisFetcherError::ExtractionError(format!(
+                    "Migration of rule set '{}' from version {} to {} must move forward to at most version {}",
+                    self.name, version, migration.to_version, self.version
+                )));
+            }
+            chain.push(migration);
+            version = migration.to_version;
+        }
+
+        // Migrate a copy so that a failing step leaves the data untouched
+        let mut extracted_data = data.extracted_data.clone();
+        for migration in chain {
+            migration.apply(&mut extracted_data)?;
+            debug!("Migrated '{}' data from version {} to {}", self.name, migration.from_version, migration.to_version);
+        }
+
+        data.extracted_data = extracted_data;
+        data.rule_set = Some(RuleSetVersion::new(self.name.clone(), version));
+        Ok(true)
+    }
+}
+
+/// Field changes between two consecutive rule set versions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleMigration {
+    /// Version the migration upgrades from
+    pub from_version: u32,
+    /// Version the migration upgrades to
+    pub to_version: u32,
+    /// Fields renamed in the new version (old name -> new name)
+    #[serde(default)]
+    pub renamed: HashMap<String, String>,
+    /// Fields dropped in the new version
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+impl RuleMigration {
+    /// Create a migration between two versions
+    pub fn new(from_version: u32, to_version: u32) -> Self {
+        Self {
+            from_version,
+            to_version,
+            ..Default::default()
+        }
+    }
+
+    /// Record a renamed field
+    pub fn rename(mut self, old_name: &str, new_name: &str) -> Self {
+        self.renamed.insert(old_name.to_string(), new_name.to_string());
+        self
+    }
+
+    /// Record a removed field
+    pub fn remove(mut self, name: &str) -> Self {
+        self.removed.push(name.to_string());
+        self
+    }
+
+    /// Apply the migration to extracted data in place
+    ///
+    /// Renames happen together, so fields can swap names. Fails without
+    /// changing the data if a rename would overwrite another field.
+    pub fn apply(&self, extracted_data: &mut HashMap<String, Vec<String>>) -> Result<()> {
+        let mut migrated = extracted_data.clone();
+        for name in &self.removed {
+            migrated.remove(name);
+        }
+
+        let renamed: Vec<(&String, Vec<String>)> = self.renamed
+            .iter()
+            .filter_map(|(old_name, new_name)| migrated.remove(old_name).map(|values| (new_name, values)))
+            .collect();
+        for (new_name, values) in renamed {
+            if migrated.insert(new_name.clone(), values).is_some() {
+                return Err(FerrisFetcherError::ExtractionError(format!(
+                    "Migration from version {} to {} renames a field onto existing field '{}'",
+                    self.from_version, self.to_version, new_name
+                )));
+            }
+        }
+
+        *extracted_data = migrated;
+        Ok(())
+    }
+}
+
 /// Builder for creating extraction rules
 pub struct ExtractionRuleBuilder {
     name: String,
     selector: String,
+    compiled_selector: Option<CssSelector>,
     extraction_type: ExtractionType,
     multiple: bool,
     attribute: Option<String>,
+    groups: Vec<String>,
+    conditions: Vec<RuleCondition>,
+    skip: usize,
+    max_matches: Option<usize>,
+    aggregate: Option<Aggregate>,
+    transforms: Vec<Transform>,
 }
 
 impl ExtractionRuleBuilder {
@@ -229,12 +1097,24 @@ impl ExtractionRuleBuilder {
         Self {
             name: name.to_string(),
             selector: selector.to_string(),
+            compiled_selector: None,
             extraction_type: ExtractionType::Text,
             multiple: false,
             attribute: None,
+            groups: Vec::new(),
+            conditions: Vec::new(),
+            skip: 0,
+            max_matches: None,
+            aggregate: None,
+            transforms: Vec::new(),
         }
     }
 
+    /// Create a builder for a rule using a pre-parsed selector, e.g. from [`crate::selector!`]
+    pub fn new_compiled(name: &str, selector: &CssSelector) -> Self {
+        Self { compiled_selector: Some(selector.clone()), ..Self::new(name, selector) }
+    }
+
     /// Set the extraction type
     pub fn extraction_type(mut self, extraction_type: ExtractionType) -> Self {
         self.extraction_type = extraction_type;
@@ -253,6 +1133,48 @@ impl ExtractionRuleBuilder {
         self
     }
 
+    /// Add the rule to a named group
+    pub fn group(mut self, group: &str) -> Self {
+        self.groups.push(group.to_string());
+        self
+    }
+
+    /// Only run the rule on pages where the selector matches something
+    pub fn only_if_selector_exists(mut self, selector: &str) -> Self {
+        self.conditions.push(RuleCondition::SelectorExists(selector.to_string()));
+        self
+    }
+
+    /// Only run the rule on pages whose URL matches the regular expression
+    pub fn only_if_url_matches(mut self, pattern: &str) -> Self {
+        self.conditions.push(RuleCondition::UrlMatches(pattern.to_string()));
+        self
+    }
+
+    /// Skip the first `count` matches
+    pub fn skip(mut self, count: usize) -> Self {
+        self.skip = count;
+        self
+    }
+
+    /// Keep at most `max` matches (for multiple extraction)
+    pub fn max_matches(mut self, max: usize) -> Self {
+        self.max_matches = Some(max);
+        self
+    }
+
+    /// Reduce the matched values to a single value
+    pub fn aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregate = Some(aggregate);
+        self
+    }
+
+    /// Append a post-processing step applied to each matched value
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
     /// Build the extraction rule
     pub fn build(self) -> ExtractionRule {
         ExtractionRule {
@@ -261,6 +1183,14 @@ impl ExtractionRuleBuilder {
             extraction_type: self.extraction_type,
             multiple: self.multiple,
             attribute: self.attribute,
+            groups: self.groups,
+            conditions: self.conditions,
+            skip: self.skip,
+            max_matches: self.max_matches,
+            aggregate: self.aggregate,
+            transforms: self.transforms,
+            compiled_selector: self.compiled_selector,
+            compiled_transforms: None,
         }
     }
 }
@@ -269,6 +1199,28 @@ impl ExtractionRuleBuilder {
 pub mod presets {
     use super::*;
 
+    /// A predefined rule set, selectable by name
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub enum Preset {
+        /// Rules from [`article`]
+        Article,
+        /// Rules from [`product`]
+        Product,
+        /// Rules from [`social_media`]
+        SocialMedia,
+    }
+
+    impl Preset {
+        /// Get the extraction rules of the preset
+        pub fn rules(&self) -> Vec<ExtractionRule> {
+            match self {
+                Preset::Article => article(),
+                Preset::Product => product(),
+                Preset::SocialMedia => social_media(),
+            }
+        }
+    }
+
     /// Create rules for basic article extraction
     pub fn article() -> Vec<ExtractionRule> {
         vec![
@@ -371,9 +1323,12 @@ mod tests {
             .multiple(true)
             .build();
         
+        let empty_fingerprint = extractor.fingerprint();
         extractor.add_rule(rule);
         assert_eq!(extractor.rule_count(), 1);
         assert!(extractor.has_rule("test"));
+        assert_ne!(extractor.fingerprint(), empty_fingerprint);
+        assert_eq!(extractor.fingerprint(), extractor.clone().fingerprint());
     }
 
     #[test]
@@ -506,5 +1461,452 @@ mod tests {
             .build();
         extractor.add_rule(invalid_rule);
         assert!(extractor.validate_rules().is_err());
+
+        // Every problem is reported, not only the first
+        extractor.add_rule(ExtractionRuleBuilder::new("broken", "div[").build());
+        extractor.add_rule(
+            ExtractionRuleBuilder::new("link", "a")
+                .extraction_type(ExtractionType::Attribute)
+                .only_if_url_matches("(")
+                .build(),
+        );
+        let report = extractor.validation_report();
+        assert_eq!(report.len(), 4);
+        assert_eq!(report.rule_issues("link").len(), 2);
+        assert_eq!(report.rule_issues("test").len(), 0);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let rules = r#"[{"name": "title", "selector": "h1", "extraction_type": "Text", "multiple": false, "attribute": null}]"#;
+        let extractor = DataExtractor::from_json(rules).unwrap();
+        assert!(extractor.has_rule("title"));
+        assert!(extractor.rule_set_version().is_none());
+
+        let rule_set = serde_json::to_string(&RuleSet::new("articles", 2).rules(presets::article())).unwrap();
+        let extractor = DataExtractor::from_json(&rule_set).unwrap();
+        assert_eq!(extractor.rule_count(), 5);
+        assert_eq!(extractor.rule_set_version().unwrap().version, 2);
+
+        assert!(DataExtractor::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_rule_set_versioning() {
+        let rule_set = RuleSet::new("products", 2)
+            .rule(ExtractionRuleBuilder::new("title", "h1").build());
+
+        let extractor = DataExtractor::from_rule_set(rule_set);
+        assert_eq!(extractor.rule_count(), 1);
+        assert_eq!(extractor.rule_set_version(), Some(&RuleSetVersion::new("products", 2)));
+    }
+
+    #[test]
+    fn test_rule_set_migrate() {
+        let rule_set = RuleSet::new("products", 3)
+            .migration(RuleMigration::new(1, 2).rename("name", "title"))
+            .migration(RuleMigration::new(2, 3).remove("legacy_sku"));
+
+        let mut data = ScrapedData::new("https://example.com".to_string());
+        data.add_extracted_data("name", vec!["Widget".to_string()]);
+        data.add_extracted_data("legacy_sku", vec!["123".to_string()]);
+        data.rule_set = Some(RuleSetVersion::new("products", 1));
+
+        assert!(rule_set.migrate(&mut data).unwrap());
+        assert_eq!(data.get_first_value("title"), Some(&"Widget".to_string()));
+        assert!(data.get_extracted_values("legacy_sku").is_none());
+        assert_eq!(data.rule_set, Some(RuleSetVersion::new("products", 3)));
+
+        // Already current
+        assert!(!rule_set.migrate(&mut data).unwrap());
+    }
+
+    #[test]
+    fn test_rule_set_migrate_missing_step() {
+        let rule_set = RuleSet::new("products", 3)
+            .migration(RuleMigration::new(1, 2));
+
+        let mut data = ScrapedData::new("https://example.com".to_string());
+        data.rule_set = Some(RuleSetVersion::new("products", 1));
+        assert!(rule_set.migrate(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_rule_set_migrate_invalid_chain() {
+        let mut data = ScrapedData::new("https://example.com".to_string());
+        data.add_extracted_data("name", vec!["Widget".to_string()]);
+        data.rule_set = Some(RuleSetVersion::new("products", 1));
+
+        let stuck = RuleSet::new("products", 3).migration(RuleMigration::new(1, 1));
+        assert!(stuck.migrate(&mut data).is_err());
+        let overshoot = RuleSet::new("products", 2).migration(RuleMigration::new(1, 5));
+        assert!(overshoot.migrate(&mut data).is_err());
+
+        // The first step applies, the missing second step leaves the data untouched
+        let broken = RuleSet::new("products", 3).migration(RuleMigration::new(1, 2).rename("name", "title"));
+        assert!(broken.migrate(&mut data).is_err());
+        assert_eq!(data.get_first_value("name"), Some(&"Widget".to_string()));
+        assert_eq!(data.rule_set, Some(RuleSetVersion::new("products", 1)));
+    }
+
+    #[test]
+    fn test_rule_migration_renames() {
+        let mut extracted_data = HashMap::from([
+            ("a".to_string(), vec!["1".to_string()]),
+            ("b".to_string(), vec!["2".to_string()]),
+        ]);
+        RuleMigration::new(1, 2).rename("a", "b").rename("b", "a").apply(&mut extracted_data).unwrap();
+        assert_eq!(extracted_data["a"], ["2"]);
+        assert_eq!(extracted_data["b"], ["1"]);
+
+        let collision = RuleMigration::new(1, 2).rename("a", "b");
+        assert!(collision.apply(&mut extracted_data).is_err());
+        assert_eq!(extracted_data["a"], ["2"]);
+        assert_eq!(extracted_data["b"], ["1"]);
+    }
+
+    #[test]
+    fn test_extract_groups() {
+        let html = r#"
+        <head><meta name="description" content="Cheap widgets"></head>
+        <body><h1>Widget</h1><span class="price">$10</span></body>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let extractor = DataExtractor::with_rules(vec![
+            ExtractionRuleBuilder::new("description", "meta[name='description']")
+                .extraction_type(ExtractionType::Attribute)
+                .attribute("content")
+                .group("seo")
+                .build(),
+            ExtractionRuleBuilder::new("price", ".price")
+                .group("pricing")
+                .build(),
+            ExtractionRuleBuilder::new("title", "h1")
+                .group("seo")
+                .group("pricing")
+                .build(),
+        ]);
+
+        assert_eq!(extractor.groups(), vec!["pricing".to_string(), "seo".to_string()]);
+        assert_eq!(extractor.rules_in_group("seo").len(), 2);
+
+        let results = extractor.extract_groups(&parser, &["pricing"]).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.contains_key("price"));
+        assert!(!results.contains_key("description"));
+
+        let results = extractor.extract_all(&parser).unwrap();
+        assert_eq!(results.len(), 3);
+    }
+
+    #[test]
+    fn test_conditional_rules() {
+        let html = r#"
+        <div class="product">
+            <span class="price">$20</span>
+            <span class="sale-price">$15</span>
+        </div>
+        "#;
+
+        let parser = HtmlParser::with_base_url(html, "https://shop.example.com/products/1").unwrap();
+        let extractor = DataExtractor::with_rules(vec![
+            ExtractionRuleBuilder::new("sale_price", ".sale-price")
+                .only_if_selector_exists(".sale-badge")
+                .build(),
+            ExtractionRuleBuilder::new("price", ".price")
+                .only_if_url_matches(r"/products/\d+")
+                .build(),
+            ExtractionRuleBuilder::new("blog_price", ".price")
+                .only_if_url_matches(r"/blog/")
+                .build(),
+        ]);
+
+        let results = extractor.extract_all(&parser).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results.get("price").unwrap(), &vec!["$20".to_string()]);
+
+        // URL conditions never hold without a known page URL
+        let parser = HtmlParser::new(html).unwrap();
+        let results = extractor.extract_all(&parser).unwrap();
+        assert!(results.is_empty());
+
+        // Patterns are compiled when rules are added, and invalid ones fail validation
+        assert_eq!(extractor.url_patterns.len(), 2);
+        let mut pruned = extractor.clone();
+        pruned.remove_rule("blog_price");
+        assert_eq!(pruned.url_patterns.keys().collect::<Vec<_>>(), [r"/products/\d+"]);
+        pruned.add_rule(ExtractionRuleBuilder::new("price", ".price").only_if_url_matches("/shop/").build());
+        assert_eq!(pruned.url_patterns.keys().collect::<Vec<_>>(), ["/shop/"]);
+        pruned.clear_rules();
+        assert!(pruned.url_patterns.is_empty());
+        assert!(extractor.validate_rules().is_ok());
+        let broken = ExtractionRuleBuilder::new("broken", ".price").only_if_url_matches("(").build();
+        let parser = HtmlParser::with_base_url(html, "https://shop.example.com/").unwrap();
+        assert!(extractor.conditions_met(&parser, &broken).is_err());
+        let extractor = DataExtractor::with_rules(vec![broken]);
+        assert!(extractor.url_patterns.is_empty());
+        assert!(extractor.validation_report().rule_issues("broken").iter().any(|issue| issue.message.starts_with("Invalid URL pattern '('")));
+        assert!(extractor.validate_rules().is_err());
+    }
+
+    #[test]
+    fn test_skip_and_max_matches() {
+        let html = r#"
+        <ul>
+            <li>Header</li>
+            <li>One</li>
+            <li>Two</li>
+            <li>Three</li>
+        </ul>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let extractor = DataExtractor::new();
+
+        let rule = ExtractionRuleBuilder::new("items", "li")
+            .multiple(true)
+            .skip(1)
+            .max_matches(2)
+            .build();
+        assert_eq!(extractor.extract_by_rule(&parser, &rule).unwrap(), vec!["One", "Two"]);
+
+        let rule = ExtractionRuleBuilder::new("first_item", "li")
+            .skip(1)
+            .build();
+        assert_eq!(extractor.extract_by_rule(&parser, &rule).unwrap(), vec!["One"]);
+    }
+
+    #[test]
+    fn test_extract_aggregated() {
+        let html = r#"<ul><li>Red</li><li>Blue</li><li>Red</li><li class="n">2</li><li class="n">3.5</li></ul>"#;
+        let parser = HtmlParser::new(html).unwrap();
+        let extractor = DataExtractor::new();
+
+        let rule = ExtractionRuleBuilder::new("colors", "li:not(.n)")
+            .multiple(true)
+            .aggregate(Aggregate::Join(", ".to_string()))
+            .build();
+        assert_eq!(extractor.extract_by_rule(&parser, &rule).unwrap(), vec!["Red, Blue, Red"]);
+
+        let rule = ExtractionRuleBuilder::new("total", "li.n").multiple(true).aggregate(Aggregate::Sum).build();
+        assert_eq!(extractor.extract_by_rule(&parser, &rule).unwrap(), vec!["5.5"]);
+    }
+
+    #[test]
+    fn test_extract_transformed() {
+        let html = r#"<ul>
+            <li data-sku=" SKU-12 ">Price: $1,299.00</li>
+            <li data-sku="sku-7">Call us</li>
+            <li data-sku="Tom &amp;amp; Jerry&#x21; &bogus;">Price: $5</li>
+        </ul>"#;
+        let parser = HtmlParser::new(html).unwrap();
+        let extractor = DataExtractor::new();
+
+        let rule = ExtractionRuleBuilder::new("prices", "li")
+            .multiple(true)
+            .transform(Transform::Capture(r"\$([\d,.]+)".to_string()))
+            .transform(Transform::Replace { pattern: ",".to_string(), replacement: String::new() })
+            .aggregate(Aggregate::Sum)
+            .build();
+        assert_eq!(extractor.extract_by_rule(&parser, &rule).unwrap(), vec!["1304"]);
+
+        let rule = ExtractionRuleBuilder::new("skus", "li")
+            .extraction_type(ExtractionType::Attribute)
+            .attribute("data-sku")
+            .multiple(true)
+            .transform(Transform::Trim)
+            .transform(Transform::Lowercase)
+            .transform(Transform::DecodeEntities)
+            .build();
+        assert_eq!(
+            extractor.extract_by_rule(&parser, &rule).unwrap(),
+            vec!["sku-12", "sku-7", "tom & jerry! &bogus;"]
+        );
+
+        let rule = ExtractionRuleBuilder::new("bad", "li").transform(Transform::Capture("(".to_string())).build();
+        assert!(extractor.extract_by_rule(&parser, &rule).is_err());
+
+        // Added rules keep their compiled transforms; invalid ones are left to validation
+        let mut extractor = DataExtractor::with_rules(vec![rule]);
+        extractor.add_rule(ExtractionRuleBuilder::new("sku", "li").transform(Transform::Trim).build());
+        assert!(extractor.get_rule("sku").unwrap().compiled_transforms.is_some());
+        assert!(extractor.get_rule("bad").unwrap().compiled_transforms.is_none());
+        assert_eq!(extractor.validation_report().rule_issues("bad").len(), 1);
+
+        // Transforms edited after compiling are compiled again
+        let mut rule = extractor.get_rule("sku").unwrap().clone();
+        rule.transforms.push(Transform::Lowercase);
+        assert_eq!(extractor.extract_by_rule(&parser, &rule).unwrap(), vec!["price: $1,299.00"]);
+    }
+
+    #[test]
+    fn test_extract_items() {
+        let html = r#"
+        <div class="product-card" data-sku="A1"><h2>Mug</h2><span class="price">$8</span><i class="tag">red</i><i class="tag">tall</i></div>
+        <div class="product-card" data-sku="B2"><h2>Cup</h2></div>
+        <div class="product-card"></div>
+        <div class="product-card" data-sku="C3"><h2>Jug</h2></div>
+        "#;
+        let parser = HtmlParser::new(html).unwrap();
+
+        let rule = ItemRule::new("products", ".product-card")
+            .field(ExtractionRuleBuilder::new("name", "h2").build())
+            .field(ExtractionRuleBuilder::new("price", ".price").build())
+            .field(ExtractionRuleBuilder::new("tags", ".tag").multiple(true).build())
+            .field(ExtractionRuleBuilder::new("sku", "").extraction_type(ExtractionType::Attribute).attribute("data-sku").build())
+            .max_items(2);
+        let mut extractor = DataExtractor::new();
+        extractor.add_item_rule(rule);
+        assert!(extractor.validate_rules().is_ok());
+        assert!(extractor.item_rules()["products"].fields.iter().all(|field| field.compiled_transforms.is_some()));
+        assert_eq!(extractor.item_rules()["products"].compiled_selector.as_ref().map(CssSelector::as_str), Some(".product-card"));
+        assert!(extractor.item_rules()["products"].fields[..3].iter().all(|field| field.compiled_selector.is_some()));
+
+        let outcome = extractor.extract_all_detailed(&parser);
+        let products = &outcome.items["products"];
+        assert_eq!(products.len(), 2);
+        assert_eq!(products[0]["name"], "Mug");
+        assert_eq!(products[0]["price"], "$8");
+        assert_eq!(products[0]["tags"], "red\ntall");
+        assert_eq!(products[0]["sku"], "A1");
+        assert_eq!(products[1]["name"], "Cup");
+        assert!(!products[1].contains_key("price"));
+
+        // Empty containers are dropped rather than counted
+        let all = extractor.extract_item_rule(&parser, &ItemRule { max_items: None, ..extractor.item_rules()["products"].clone() }).unwrap();
+        assert_eq!(all.len(), 3);
+
+        // A selector edited after the rule was added is parsed afresh
+        let mut edited = extractor.item_rules()["products"].clone();
+        edited.selector = "div[data-sku]".to_string();
+        edited.fields[0].selector = "span".to_string();
+        let edited = extractor.extract_item_rule(&parser, &edited).unwrap();
+        assert_eq!(edited.len(), 2);
+        assert_eq!(edited[0]["name"], "$8");
+        assert_eq!(edited[1]["sku"], "B2");
+        assert!(!edited[1].contains_key("name"));
+
+        let mut broken = DataExtractor::new();
+        broken.add_item_rule(ItemRule::new("empty", ".card"));
+        broken.add_item_rule(ItemRule::new("bad", ".card").field(ExtractionRuleBuilder::new("x", "div[[").build()));
+        let report = broken.validation_report();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report.rule_issues("bad.x").len(), 1);
+    }
+
+    #[test]
+    fn test_extract_attributes() {
+        let html = r#"<div class="card" data-id="7" data-color="red">Card</div>"#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let rule = ExtractionRuleBuilder::new("card", ".card")
+            .extraction_type(ExtractionType::Attributes)
+            .build();
+
+        let extractor = DataExtractor::new();
+        let result = extractor.extract_by_rule(&parser, &rule).unwrap();
+        assert_eq!(result.len(), 1);
+
+        let attributes: serde_json::Value = serde_json::from_str(&result[0]).unwrap();
+        assert_eq!(attributes["data-id"], "7");
+        assert_eq!(attributes["data-color"], "red");
+    }
+
+    #[test]
+    fn test_extract_positioned() {
+        let html = r#"
+        <ul>
+            <li>Ad</li>
+            <li></li>
+            <li>Item A</li>
+            <li>Item B</li>
+        </ul>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let rule = ExtractionRuleBuilder::new("items", "li")
+            .multiple(true)
+            .skip(1)
+            .build();
+
+        let extractor = DataExtractor::new();
+        let result = extractor.extract_positioned(&parser, &rule).unwrap();
+        assert_eq!(result, vec![(2, "Item A".to_string()), (3, "Item B".to_string())]);
+    }
+
+    #[test]
+    fn test_repair_suggestions() {
+        let extractor = DataExtractor::with_rules(vec![
+            ExtractionRuleBuilder::new("price", ".price").build(),
+        ]).with_repair_suggestions();
+
+        let parser = HtmlParser::new(r#"<span class="price" itemprop="price">$10</span>"#).unwrap();
+        assert!(extractor.extract_all(&parser).unwrap().contains_key("price"));
+        assert_eq!(extractor.rule_anchor("price").unwrap().text, "$10");
+
+        let parser = HtmlParser::new(r#"<span class="cost" itemprop="price">$10</span>"#).unwrap();
+        assert!(extractor.extract_all(&parser).unwrap().is_empty());
+
+        let suggestions = extractor.suggest_repairs(&parser, "price");
+        assert_eq!(suggestions[0].selector, "span.cost");
+
+        // Anchors are kept from the last successful match
+        assert_eq!(extractor.rule_anchor("price").unwrap().attributes["class"], "price");
+    }
+
+    #[test]
+    fn test_extract_all_detailed_reports_failures() {
+        let parser = HtmlParser::new(r#"<a href="/x">Link</a>"#).unwrap();
+        let extractor = DataExtractor::with_rules(vec![
+            ExtractionRuleBuilder::new("link", "a").build(),
+            ExtractionRuleBuilder::new("broken", "a")
+                .extraction_type(ExtractionType::Attribute)
+                .build(),
+            ExtractionRuleBuilder::new("bad_condition", "a")
+                .only_if_url_matches("(")
+                .build(),
+        ]);
+
+        let outcome = extractor.extract_all_detailed(&parser);
+        assert_eq!(outcome.values.get("link").unwrap(), &vec!["Link".to_string()]);
+        assert_eq!(outcome.failed_rules.len(), 2);
+        assert!(outcome.failed_rules["broken"].contains("requires attribute name"));
+        assert!(outcome.failed_rules.contains_key("bad_condition"));
+        assert!(outcome.empty_rules.is_empty());
+
+        let extractor = DataExtractor::with_rules(vec![ExtractionRuleBuilder::new("price", ".price").build()]);
+        assert_eq!(extractor.extract_all_detailed(&parser).empty_rules, vec!["price".to_string()]);
+    }
+
+    #[test]
+    fn test_extract_json() {
+        let document = JsonExtractor::with_base_url(
+            r#"{"items": [{"price": "1.50"}, {"price": "2.25"}, {"price": null}], "next": "/page/2"}"#,
+            "https://api.example.com/items",
+        ).unwrap();
+        let json_rule = |name: &str, path: &str| ExtractionRuleBuilder::new(name, path).extraction_type(ExtractionType::JsonPath);
+        let extractor = DataExtractor::with_rules(vec![
+            json_rule("total", "$.items[*].price").multiple(true).aggregate(Aggregate::Sum).build(),
+            json_rule("prices", "$.items[*].price").multiple(true).skip(1).build(),
+            json_rule("next", "$.next").only_if_url_matches("/items$").build(),
+            json_rule("missing", "$.cursor").build(),
+            json_rule("broken", "$.items[").build(),
+            ExtractionRuleBuilder::new("heading", "h1").build(),
+        ]);
+
+        let outcome = extractor.extract_json_detailed(&document);
+        assert_eq!(outcome.values["total"], ["3.75"]);
+        assert_eq!(outcome.values["prices"], ["2.25"]);
+        assert_eq!(outcome.values["next"], ["/page/2"]);
+        assert_eq!(outcome.empty_rules, ["missing"]);
+        assert!(outcome.failed_rules.contains_key("broken") && !outcome.values.contains_key("heading"));
+
+        // JSONPath rules are validated as JSONPath and skipped on HTML pages
+        assert_eq!(extractor.validation_report().issues.len(), 1);
+        let parser = HtmlParser::new("<h1>Title</h1>").unwrap();
+        let outcome = extractor.extract_all_detailed(&parser);
+        assert_eq!(outcome.values.len(), 1);
+        assert!(outcome.failed_rules.is_empty());
     }
 }
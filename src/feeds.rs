@@ -0,0 +1,364 @@
+//! RSS and Atom feed parsing
+//!
+//! [`Feed::parse`] reads RSS 2.0, RSS 1.0 (RDF) and Atom documents into typed
+//! [`FeedEntry`]s. [`FerrisFetcher::scrape_feed`] fetches a feed directly or
+//! follows the first feed an HTML page advertises with
+//! `<link rel="alternate" type="application/rss+xml">` (see
+//! [`HtmlParser::feed_links`]).
+//!
+//! The parser scans tags rather than building a full XML tree: CDATA sections,
+//! comments and character references are handled, namespaces are not.
+//!
+//! [`FerrisFetcher::scrape_feed`]: crate::FerrisFetcher::scrape_feed
+//! [`HtmlParser::feed_links`]: crate::HtmlParser::feed_links
+
+use crate::error::{FerrisFetcherError, Result};
+use crate::extractor::decode_entities;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Syndication format of a feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeedFormat {
+    /// RSS 2.0 or RSS 1.0 (RDF)
+    Rss,
+    /// Atom 1.0
+    Atom,
+}
+
+impl FeedFormat {
+    /// Detect the format from the document's root element, `None` if it is not a feed
+    pub fn detect(xml: &str) -> Option<Self> {
+        match root_name(xml)? {
+            "rss" | "rdf:RDF" => Some(FeedFormat::Rss),
+            "feed" => Some(FeedFormat::Atom),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed RSS or Atom feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feed {
+    /// Format the feed was published in
+    pub format: FeedFormat,
+    /// Title of the channel or feed
+    pub title: Option<String>,
+    /// Website the feed belongs to
+    pub link: Option<String>,
+    /// Items or entries in document order
+    pub entries: Vec<FeedEntry>,
+}
+
+/// One item of an RSS feed or entry of an Atom feed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    /// Entry title
+    pub title: Option<String>,
+    /// Absolute URL of the entry's page
+    pub link: Option<String>,
+    /// Publication date, falling back to the last update
+    pub published: Option<DateTime<Utc>>,
+    /// Summary or content as published, which may contain HTML
+    pub summary: Option<String>,
+}
+
+impl Feed {
+    /// Parse an RSS or Atom document, resolving relative links against `base_url`
+    pub fn parse(xml: &str, base_url: Option<&str>) -> Result<Self> {
+        let format = FeedFormat::detect(xml)
+            .ok_or_else(|| FerrisFetcherError::ParseError("Document is not an RSS or Atom feed".to_string()))?;
+        let base = base_url.and_then(|url| Url::parse(url).ok());
+        let resolve = |link: String| match &base {
+            Some(base) => base.join(&link).map(|url| url.to_string()).unwrap_or(link),
+            None => link,
+        };
+
+        let (entry_tag, parse_entry): (&str, fn(&str) -> FeedEntry) = match format {
+            FeedFormat::Rss => ("item", rss_entry),
+            FeedFormat::Atom => ("entry", atom_entry),
+        };
+        // Feed-level fields come before the first entry
+        let channel = elements(xml, "channel").into_iter().next().map_or(xml, |channel| channel.inner);
+        let head = channel.find(&format!("<{}", entry_tag)).map_or(channel, |end| &channel[..end]);
+        let link = match format {
+            FeedFormat::Rss => child_text(head, &["link"]),
+            FeedFormat::Atom => atom_link(head),
+        };
+
+        let entries = elements(xml, entry_tag)
+            .into_iter()
+            .map(|entry| {
+                let mut entry = parse_entry(entry.inner);
+                entry.link = entry.link.map(&resolve);
+                entry
+            })
+            .collect();
+
+        Ok(Feed { format, title: child_text(head, &["title"]), link: link.map(&resolve), entries })
+    }
+}
+
+fn rss_entry(item: &str) -> FeedEntry {
+    let guid = child_text(item, &["guid"]).filter(|guid| Url::parse(guid).is_ok());
+    FeedEntry {
+        title: child_text(item, &["title"]),
+        link: child_text(item, &["link"]).or(guid),
+        published: child_text(item, &["pubDate", "dc:date"]).and_then(|date| parse_date(&date)),
+        summary: child_text(item, &["description", "content:encoded"]),
+    }
+}
+
+fn atom_entry(entry: &str) -> FeedEntry {
+    FeedEntry {
+        title: child_text(entry, &["title"]),
+        link: atom_link(entry),
+        published: child_text(entry, &["published", "updated"]).and_then(|date| parse_date(&date)),
+        summary: child_text(entry, &["summary", "content"]),
+    }
+}
+
+/// The `href` of an Atom `alternate` link, or of the first link if none is marked
+fn atom_link(xml: &str) -> Option<String> {
+    let links = elements(xml, "link");
+    links
+        .iter()
+        .find(|link| attribute(link.attrs, "rel").is_none_or(|rel| rel == "alternate"))
+        .or(links.first())
+        .and_then(|link| attribute(link.attrs, "href"))
+}
+
+/// Parse an RFC 2822 (RSS) or RFC 3339 (Atom) date
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// An element found by [`elements`]
+struct Element<'a> {
+    attrs: &'a str,
+    inner: &'a str,
+}
+
+/// Top-level elements named `name` in `xml`, skipping CDATA sections and comments
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<Element<'a>> {
+    let mut found = Vec::new();
+    let mut position = 0;
+    while let Some(offset) = xml[position..].find('<') {
+        let start = position + offset;
+        if let Some(length) = skipped_markup(&xml[start..]) {
+            position = start + length;
+            continue;
+        }
+        position = start + 1;
+        let Some(after) = xml[start + 1..].strip_prefix(name) else { continue };
+        // `<item` must not match `<items`
+        if !after.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            continue;
+        }
+        let Some(tag_end) = after.find('>') else { break };
+        let content_start = start + 1 + name.len() + tag_end + 1;
+        let attrs = after[..tag_end].trim();
+        if let Some(attrs) = attrs.strip_suffix('/') {
+            found.push(Element { attrs: attrs.trim_end(), inner: "" });
+            position = content_start;
+            continue;
+        }
+        let Some(length) = closing_tag(&xml[content_start..], name) else { break };
+        found.push(Element { attrs, inner: &xml[content_start..content_start + length] });
+        position = content_start + length;
+    }
+    found
+}
+
+/// Offset of the `</name>` closing `xml`'s enclosing element
+fn closing_tag(xml: &str, name: &str) -> Option<usize> {
+    let mut position = 0;
+    while let Some(offset) = xml[position..].find('<') {
+        let start = position + offset;
+        if let Some(length) = skipped_markup(&xml[start..]) {
+            position = start + length;
+            continue;
+        }
+        let is_close = xml[start..]
+            .strip_prefix("</")
+            .and_then(|rest| rest.strip_prefix(name))
+            .is_some_and(|rest| rest.trim_start().starts_with('>'));
+        if is_close {
+            return Some(start);
+        }
+        position = start + 1;
+    }
+    None
+}
+
+/// Length of a CDATA section, comment or processing instruction at the start of `xml`
+fn skipped_markup(xml: &str) -> Option<usize> {
+    let (open, close) = [("<![CDATA[", "]]>"), ("<!--", "-->"), ("<?", "?>")]
+        .into_iter()
+        .find(|(open, _)| xml.starts_with(open))?;
+    Some(xml[open.len()..].find(close).map_or(xml.len(), |end| open.len() + end + close.len()))
+}
+
+/// Text of the first child among `names` that is not empty
+fn child_text(xml: &str, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        elements(xml, name).into_iter().map(|element| text(element.inner)).find(|text| !text.is_empty())
+    })
+}
+
+/// Character data of an element, with CDATA unwrapped and references decoded
+fn text(inner: &str) -> String {
+    let mut text = String::new();
+    let mut rest = inner;
+    while let Some(start) = rest.find("<![CDATA[") {
+        text.push_str(&decode_entities(&rest[..start]));
+        rest = &rest[start + "<![CDATA[".len()..];
+        let end = rest.find("]]>").unwrap_or(rest.len());
+        text.push_str(&rest[..end]);
+        rest = &rest[(end + "]]>".len()).min(rest.len())..];
+    }
+    text.push_str(&decode_entities(rest));
+    text.trim().to_string()
+}
+
+/// Value of the attribute `name` in a tag's attribute list
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    let mut position = 0;
+    while let Some(offset) = attrs[position..].find(name) {
+        let start = position + offset;
+        position = start + name.len();
+        let at_boundary = attrs[..start].ends_with(|c: char| c.is_whitespace()) || start == 0;
+        let Some(value) = attrs[position..].trim_start().strip_prefix('=') else { continue };
+        if !at_boundary {
+            continue;
+        }
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &value[1..];
+        return value.find(quote).map(|end| decode_entities(&value[..end]));
+    }
+    None
+}
+
+/// Name of the document's root element
+fn root_name(xml: &str) -> Option<&str> {
+    let mut position = 0;
+    while let Some(offset) = xml[position..].find('<') {
+        let start = position + offset;
+        if let Some(length) = skipped_markup(&xml[start..]) {
+            position = start + length;
+            continue;
+        }
+        let rest = &xml[start + 1..];
+        if rest.starts_with('!') {
+            // A doctype declaration
+            position = start + rest.find('>')? + 2;
+            continue;
+        }
+        let end = rest.find(|c: char| c == '>' || c == '/' || c.is_whitespace())?;
+        return Some(&rest[..end]);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <!-- generated -->
+        <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+          <channel>
+            <title>Example &amp; Co</title>
+            <link>https://example.com/</link>
+            <item>
+              <title><![CDATA[First <post>]]></title>
+              <link>/posts/1</link>
+              <pubDate>Tue, 10 Jun 2025 04:00:00 GMT</pubDate>
+              <description>&lt;p&gt;Hello&lt;/p&gt;</description>
+            </item>
+            <item>
+              <title>Second</title>
+              <guid isPermaLink="true">https://example.com/posts/2</guid>
+              <content:encoded><![CDATA[<p>Full text</p>]]></content:encoded>
+            </item>
+          </channel>
+        </rss>"#;
+
+        let feed = Feed::parse(xml, Some("https://example.com/feed.xml")).unwrap();
+        assert_eq!(feed.format, FeedFormat::Rss);
+        assert_eq!(feed.title.as_deref(), Some("Example & Co"));
+        assert_eq!(feed.link.as_deref(), Some("https://example.com/"));
+        assert_eq!(feed.entries.len(), 2);
+
+        let first = &feed.entries[0];
+        assert_eq!(first.title.as_deref(), Some("First <post>"));
+        assert_eq!(first.link.as_deref(), Some("https://example.com/posts/1"));
+        assert_eq!(first.published.unwrap().to_rfc3339(), "2025-06-10T04:00:00+00:00");
+        assert_eq!(first.summary.as_deref(), Some("<p>Hello</p>"));
+
+        let second = &feed.entries[1];
+        assert_eq!(second.link.as_deref(), Some("https://example.com/posts/2"));
+        assert_eq!(second.published, None);
+        assert_eq!(second.summary.as_deref(), Some("<p>Full text</p>"));
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let xml = r#"<?xml version="1.0"?>
+        <feed xmlns="http://www.w3.org/2005/Atom">
+          <title type="text">Example Blog</title>
+          <link rel="self" href="https://example.com/atom.xml"/>
+          <link href="https://example.com/"/>
+          <entry>
+            <title>Launch</title>
+            <link rel="edit" href="/edit/1"/>
+            <link rel="alternate" type="text/html" href="/posts/launch"/>
+            <updated>2025-06-11T08:30:00+02:00</updated>
+            <published>2025-06-10T08:30:00+02:00</published>
+            <summary>We launched</summary>
+          </entry>
+          <entry>
+            <title>Update</title>
+            <link href='https://example.com/posts/update'/>
+            <updated>2025-06-12T00:00:00Z</updated>
+            <content type="html">&lt;b&gt;New&lt;/b&gt;</content>
+          </entry>
+        </feed>"#;
+
+        let feed = Feed::parse(xml, Some("https://example.com/atom.xml")).unwrap();
+        assert_eq!(feed.format, FeedFormat::Atom);
+        assert_eq!(feed.title.as_deref(), Some("Example Blog"));
+        assert_eq!(feed.link.as_deref(), Some("https://example.com/"));
+
+        let launch = &feed.entries[0];
+        assert_eq!(launch.link.as_deref(), Some("https://example.com/posts/launch"));
+        assert_eq!(launch.published.unwrap().to_rfc3339(), "2025-06-10T06:30:00+00:00");
+        assert_eq!(launch.summary.as_deref(), Some("We launched"));
+
+        let update = &feed.entries[1];
+        assert_eq!(update.link.as_deref(), Some("https://example.com/posts/update"));
+        assert_eq!(update.published.unwrap().to_rfc3339(), "2025-06-12T00:00:00+00:00");
+        assert_eq!(update.summary.as_deref(), Some("<b>New</b>"));
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let rdf = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+            <channel><title>Old</title><items/></channel>
+            <item><title>One</title><link>https://example.com/1</link><dc:date>2025-01-01T00:00:00Z</dc:date></item>
+        </rdf:RDF>"#;
+        let feed = Feed::parse(rdf, None).unwrap();
+        assert_eq!((feed.format, feed.title.as_deref()), (FeedFormat::Rss, Some("Old")));
+        assert_eq!(feed.entries[0].published.unwrap().to_rfc3339(), "2025-01-01T00:00:00+00:00");
+
+        assert_eq!(FeedFormat::detect("<!DOCTYPE html><html><head></head></html>"), None);
+        assert!(Feed::parse("<html><body>Not a feed</body></html>", None).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! C ABI for embedding the scraper (`ffi` feature)
+//!
+//! A deliberately small, stable surface for Go, C++ and other hosts; the
+//! matching declarations are in `include/ferrisfetcher.h`. Build the library
+//! with `cargo rustc --release --lib --features ffi --crate-type cdylib` (or
+//! `staticlib`).
+//!
+//! ```c
+//! char *json = NULL;
+//! int code = ff_scrape("https://example.com", "[{\"name\":\"title\",\"selector\":\"h1\"}]", &json);
+//! if (code == FF_OK) puts(json); else fprintf(stderr, "%d: %s\n", code, json);
+//! ff_string_free(json);
+//! ```
+//!
+//! Ownership: strings passed in are borrowed for the duration of the call.
+//! Strings handed out are owned by the caller and must be released with
+//! [`ff_string_free`], never with the host's `free`. Every call is
+//! thread-safe; scrapes share one fetcher, and with it the connection pool,
+//! rate limiting and statistics, on a runtime started by the first call.
+
+use crate::config::Config;
+use crate::error::{FerrisFetcherError, Result};
+use crate::extractor::DataExtractor;
+use crate::scraper::FerrisFetcher;
+use crate::types::ScrapedData;
+use serde_json::json;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+/// The call succeeded
+pub const FF_OK: c_int = 0;
+/// An argument was null, not UTF-8, or invalid (URL, `rules_json`, selector)
+pub const FF_INVALID_ARGUMENT: c_int = 1;
+/// The request failed to connect, send or complete
+pub const FF_NETWORK: c_int = 2;
+/// The request timed out
+pub const FF_TIMEOUT: c_int = 3;
+/// The page could not be parsed, extracted or validated
+pub const FF_EXTRACTION: c_int = 4;
+/// The request was refused by robots.txt, a rate limit or a quota
+pub const FF_REFUSED: c_int = 5;
+/// The scrape was aborted or cancelled
+pub const FF_CANCELLED: c_int = 6;
+/// The library failed internally (e.g. panicked); the request may already have been sent
+pub const FF_INTERNAL: c_int = 99;
+
+/// Fetcher shared by all calls, with the runtime driving it
+struct Embedded {
+    runtime: Runtime,
+    fetcher: FerrisFetcher,
+}
+
+static EMBEDDED: OnceLock<Embedded> = OnceLock::new();
+
+/// The shared fetcher, started on first use
+fn embedded() -> Result<&'static Embedded> {
+    if let Some(embedded) = EMBEDDED.get() {
+        return Ok(embedded);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    let fetcher = {
+        let _guard = runtime.enter();
+        FerrisFetcher::with_config(Config::default())?
+    };
+    Ok(EMBEDDED.get_or_init(|| Embedded { runtime, fetcher }))
+}
+
+/// Status code reported for an error
+pub fn error_code(error: &FerrisFetcherError) -> c_int {
+    match error {
+        FerrisFetcherError::HttpError(error) if error.is_timeout() => FF_TIMEOUT,
+        FerrisFetcherError::HttpError(_) => FF_NETWORK,
+        FerrisFetcherError::ParseError(_) => FF_EXTRACTION,
+        FerrisFetcherError::ExtractionError(_) => FF_EXTRACTION,
+        FerrisFetcherError::ConfigError(_) => FF_INVALID_ARGUMENT,
+        FerrisFetcherError::RateLimitExceeded(_) => FF_REFUSED,
+        FerrisFetcherError::TimeoutError(_) => FF_TIMEOUT,
+        FerrisFetcherError::InvalidUrl(_) => FF_INVALID_ARGUMENT,
+        FerrisFetcherError::IoError(_) => FF_NETWORK,
+        FerrisFetcherError::JsonError(_) => FF_EXTRACTION,
+        FerrisFetcherError::RetryExhausted => FF_NETWORK,
+        FerrisFetcherError::TaskCancelled => FF_CANCELLED,
+        FerrisFetcherError::InvalidSelector(_) => FF_INVALID_ARGUMENT,
+        FerrisFetcherError::NetworkError(_) => FF_NETWORK,
+        FerrisFetcherError::Aborted(_) => FF_CANCELLED,
+        FerrisFetcherError::Panicked { .. } => FF_INTERNAL,
+        FerrisFetcherError::ValidationFailed(_) => FF_EXTRACTION,
+        FerrisFetcherError::RobotsDisallowed(_) => FF_REFUSED,
+        FerrisFetcherError::RedirectLoop { .. } => FF_NETWORK,
+        FerrisFetcherError::QuotaExceeded(_) => FF_REFUSED,
+    }
+}
+
+/// Borrow a string argument, failing on null or invalid UTF-8
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string valid for `'a`.
+unsafe fn borrow_str<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    if ptr.is_null() {
+        return Err(FerrisFetcherError::ConfigError(format!("{} is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FerrisFetcherError::ConfigError(format!("{} is not valid UTF-8", name)))
+}
+
+/// Scrape `url`, adding the rules in `rules_json` to the fetcher's for this call
+///
+/// Rules that fail to parse or validate are reported as a configuration
+/// error, before anything is fetched.
+fn scrape(url: &str, rules_json: Option<&str>) -> Result<ScrapedData> {
+    let embedded = embedded()?;
+    let mut fetcher = embedded.fetcher.handle();
+    if let Some(rules_json) = rules_json {
+        let extractor = DataExtractor::from_json(rules_json)
+            .and_then(|extractor| extractor.validate_rules().map(|()| extractor))
+            .map_err(|error| FerrisFetcherError::ConfigError(format!("Invalid rules_json: {}", error)))?;
+        for rule in extractor.rules().values() {
+            fetcher.add_extraction_rule(rule.clone());
+        }
+    }
+    embedded.runtime.block_on(fetcher.scrape(url))
+}
+
+/// JSON handed out for a failed call
+fn error_json(code: c_int, message: &str, category: &str) -> String {
+    json!({ "error": message, "category": category, "code": code }).to_string()
+}
+
+/// Hand `json` to the caller through `out`, if given
+///
+/// # Safety
+///
+/// `out` must be null or valid for writing a pointer.
+unsafe fn hand_out(out: *mut *mut c_char, json: String) {
+    if out.is_null() {
+        return;
+    }
+    // JSON escapes control characters, so serialized text holds no NUL byte
+    *out = CString::new(json).map_or(ptr::null_mut(), CString::into_raw);
+}
+
+/// Scrape a page, returning a status code and handing out JSON through `out_json`
+///
+/// `rules_json` is null or extraction rules as accepted by
+/// [`DataExtractor::from_json`] (an array of rules or a rule set). On
+/// [`FF_OK`], `*out_json` receives the [`ScrapedData`] of the page; on any
+/// other code it receives `{"error", "category", "code"}`. Either way the
+/// string must be released with [`ff_string_free`]. `out_json` may be null
+/// when only the code is of interest.
+///
+/// # Safety
+///
+/// `url` and `rules_json` must be null or NUL-terminated strings valid for
+/// the duration of the call, and `out_json` must be null or valid for
+/// writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ff_scrape(url: *const c_char, rules_json: *const c_char, out_json: *mut *mut c_char) -> c_int {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let url = borrow_str(url, "url")?;
+        let rules_json = if rules_json.is_null() { None } else { Some(borrow_str(rules_json, "rules_json")?) };
+        scrape(url, rules_json)
+    }));
+    let (code, json) = match result {
+        Ok(Ok(data)) => match serde_json::to_string(&data) {
+            Ok(json) => (FF_OK, json),
+            Err(error) => (FF_INTERNAL, error_json(FF_INTERNAL, &error.to_string(), "JSON")),
+        },
+        Ok(Err(error)) => {
+            let code = error_code(&error);
+            (code, error_json(code, &error.to_string(), error.category()))
+        }
+        Err(_) => (FF_INTERNAL, error_json(FF_INTERNAL, "scrape panicked", "Internal")),
+    };
+    hand_out(out_json, json);
+    code
+}
+
+/// Release a string handed out by this library; null is ignored
+///
+/// # Safety
+///
+/// `ptr` must be null or a string handed out by this library that has not
+/// been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn ff_string_free(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+/// Version of the library as a static NUL-terminated string; do not free it
+#[no_mangle]
+pub extern "C" fn ff_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::serve_page;
+
+    fn call(url: Option<&str>, rules_json: Option<&str>) -> (c_int, serde_json::Value) {
+        let url = url.map(|url| CString::new(url).unwrap());
+        let rules_json = rules_json.map(|rules| CString::new(rules).unwrap());
+        let mut out = ptr::null_mut();
+        let code = unsafe {
+            ff_scrape(
+                url.as_ref().map_or(ptr::null(), |url| url.as_ptr()),
+                rules_json.as_ref().map_or(ptr::null(), |rules| rules.as_ptr()),
+                &mut out,
+            )
+        };
+        let json = unsafe { CStr::from_ptr(out) }.to_str().unwrap().parse().unwrap();
+        unsafe { ff_string_free(out) };
+        (code, json)
+    }
+
+    #[test]
+    fn test_ff_scrape() {
+        let base = serve_page("<html><title>Embedded</title><h1>Hello</h1></html>");
+
+        let (code, page) = call(Some(&format!("{}/a", base)), Some(r#"[{"name": "heading", "selector": "h1", "extraction_type": "Text", "multiple": false}]"#));
+        assert_eq!(code, FF_OK);
+        assert_eq!(page["title"], "Embedded");
+        assert_eq!(page["extracted_data"]["heading"], serde_json::json!(["Hello"]));
+
+        let (code, error) = call(None, None);
+        assert_eq!((code, error["category"].as_str()), (FF_INVALID_ARGUMENT, Some("Configuration")));
+        let (code, _) = call(Some("not a url"), None);
+        assert_eq!(code, FF_INVALID_ARGUMENT);
+        let (code, error) = call(Some(&base), Some("{"));
+        assert_eq!((code, error["code"].as_i64()), (FF_INVALID_ARGUMENT, Some(FF_INVALID_ARGUMENT.into())));
+        let (code, _) = call(Some(&base), Some(r#"[{"name": "broken", "selector": "", "extraction_type": "Text", "multiple": false}]"#));
+        assert_eq!(code, FF_INVALID_ARGUMENT);
+        let (code, _) = call(Some("http://127.0.0.1:1/"), None);
+        assert_eq!(code, FF_NETWORK);
+
+        assert_eq!(unsafe { CStr::from_ptr(ff_version()) }.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+        unsafe { ff_string_free(ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,289 @@
+//! Technology fingerprinting of scraped pages
+
+use crate::html_parser::HtmlParser;
+use crate::types::ScrapedData;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Category of a detected technology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TechCategory {
+    Cms,
+    Ecommerce,
+    Framework,
+    JavaScriptLibrary,
+    Analytics,
+    Cdn,
+    Hosting,
+    WebServer,
+    Language,
+}
+
+/// A technology detected on a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Technology {
+    /// Technology name (e.g. "WordPress")
+    pub name: String,
+    /// Technology category
+    pub category: TechCategory,
+    /// Version, when the evidence exposes one
+    pub version: Option<String>,
+    /// What the detection was based on
+    pub evidence: String,
+}
+
+/// Technologies detected on a single page
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TechStack {
+    /// The page URL
+    pub url: String,
+    /// Detected technologies, one entry per name
+    pub technologies: Vec<Technology>,
+}
+
+impl TechStack {
+    /// Check whether a technology was detected (case-insensitive)
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Get a detected technology by name (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&Technology> {
+        self.technologies.iter().find(|tech| tech.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Get the technologies in a category
+    pub fn by_category(&self, category: TechCategory) -> Vec<&Technology> {
+        self.technologies.iter().filter(|tech| tech.category == category).collect()
+    }
+
+    /// Record a detection, keeping the first one per name but filling in a missing version
+    fn add(&mut self, name: &str, category: TechCategory, version: Option<String>, evidence: String) {
+        if let Some(existing) = self.technologies.iter_mut().find(|tech| tech.name == name) {
+            if existing.version.is_none() {
+                existing.version = version;
+            }
+            return;
+        }
+
+        self.technologies.push(Technology {
+            name: name.to_string(),
+            category,
+            version,
+            evidence,
+        });
+    }
+}
+
+/// Where a signature looks for evidence
+enum Source {
+    /// Header value containing a pattern (empty pattern: header present)
+    Header(&'static str, &'static str),
+    /// `<meta name="generator">` starting with a pattern
+    Generator(&'static str),
+    /// `<script src>` containing a pattern
+    Script(&'static str),
+    /// Cookie name starting with a pattern
+    Cookie(&'static str),
+    /// Raw HTML containing a pattern
+    Html(&'static str),
+}
+
+struct Signature {
+    name: &'static str,
+    category: TechCategory,
+    source: Source,
+}
+
+const fn sig(name: &'static str, category: TechCategory, source: Source) -> Signature {
+    Signature { name, category, source }
+}
+
+const SIGNATURES: &[Signature] = &[
+    sig("WordPress", TechCategory::Cms, Source::Generator("wordpress")),
+    sig("WordPress", TechCategory::Cms, Source::Script("/wp-content/")),
+    sig("WordPress", TechCategory::Cms, Source::Script("/wp-includes/")),
+    sig("Drupal", TechCategory::Cms, Source::Generator("drupal")),
+    sig("Drupal", TechCategory::Cms, Source::Header("x-drupal-cache", "")),
+    sig("Joomla", TechCategory::Cms, Source::Generator("joomla")),
+    sig("Wix", TechCategory::Cms, Source::Generator("wix.com")),
+    sig("Wix", TechCategory::Cms, Source::Header("x-wix-request-id", "")),
+    sig("Squarespace", TechCategory::Cms, Source::Script("squarespace.com")),
+    sig("Ghost", TechCategory::Cms, Source::Generator("ghost")),
+    sig("Hugo", TechCategory::Cms, Source::Generator("hugo")),
+    sig("Shopify", TechCategory::Ecommerce, Source::Script("cdn.shopify.com")),
+    sig("Shopify", TechCategory::Ecommerce, Source::Header("x-shopid", "")),
+    sig("Shopify", TechCategory::Ecommerce, Source::Cookie("_shopify")),
+    sig("WooCommerce", TechCategory::Ecommerce, Source::Script("woocommerce")),
+    sig("Magento", TechCategory::Ecommerce, Source::Script("/static/version")),
+    sig("Magento", TechCategory::Ecommerce, Source::Cookie("x-magento")),
+    sig("Next.js", TechCategory::Framework, Source::Script("/_next/")),
+    sig("Next.js", TechCategory::Framework, Source::Header("x-powered-by", "next.js")),
+    sig("Nuxt", TechCategory::Framework, Source::Script("/_nuxt/")),
+    sig("Gatsby", TechCategory::Framework, Source::Generator("gatsby")),
+    sig("Angular", TechCategory::Framework, Source::Html("ng-version=")),
+    sig("React", TechCategory::JavaScriptLibrary, Source::Script("react")),
+    sig("React", TechCategory::JavaScriptLibrary, Source::Html("data-reactroot")),
+    sig("React", TechCategory::JavaScriptLibrary, Source::Script("/_next/")),
+    sig("Vue.js", TechCategory::JavaScriptLibrary, Source::Script("vue")),
+    sig("Vue.js", TechCategory::JavaScriptLibrary, Source::Html("data-v-app")),
+    sig("jQuery", TechCategory::JavaScriptLibrary, Source::Script("jquery")),
+    sig("Google Analytics", TechCategory::Analytics, Source::Script("google-analytics.com")),
+    sig("Google Analytics", TechCategory::Analytics, Source::Script("googletagmanager.com/gtag/js")),
+    sig("Google Analytics", TechCategory::Analytics, Source::Cookie("_ga")),
+    sig("Google Tag Manager", TechCategory::Analytics, Source::Script("googletagmanager.com/gtm.js")),
+    sig("Meta Pixel", TechCategory::Analytics, Source::Script("connect.facebook.net")),
+    sig("Hotjar", TechCategory::Analytics, Source::Script("hotjar.com")),
+    sig("Plausible", TechCategory::Analytics, Source::Script("plausible.io")),
+    sig("Cloudflare", TechCategory::Cdn, Source::Header("cf-ray", "")),
+    sig("Cloudflare", TechCategory::Cdn, Source::Header("server", "cloudflare")),
+    sig("Fastly", TechCategory::Cdn, Source::Header("x-served-by", "cache-")),
+    sig("Amazon CloudFront", TechCategory::Cdn, Source::Header("x-amz-cf-id", "")),
+    sig("Vercel", TechCategory::Hosting, Source::Header("x-vercel-id", "")),
+    sig("Netlify", TechCategory::Hosting, Source::Header("server", "netlify")),
+    sig("Nginx", TechCategory::WebServer, Source::Header("server", "nginx")),
+    sig("Apache", TechCategory::WebServer, Source::Header("server", "apache")),
+    sig("Microsoft IIS", TechCategory::WebServer, Source::Header("server", "microsoft-iis")),
+    sig("PHP", TechCategory::Language, Source::Header("x-powered-by", "php")),
+    sig("PHP", TechCategory::Language, Source::Cookie("phpsessid")),
+    sig("ASP.NET", TechCategory::Language, Source::Header("x-powered-by", "asp.net")),
+    sig("Express", TechCategory::Framework, Source::Header("x-powered-by", "express")),
+];
+
+/// Identify the technologies used by a scraped page
+pub fn fingerprint(data: &ScrapedData) -> TechStack {
+    let headers: HashMap<String, String> = data.headers
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.clone()))
+        .collect();
+
+    let (generator, scripts) = match HtmlParser::new(&data.content) {
+        Ok(parser) => (
+            parser.meta_tag("generator"),
+            parser.select_attr("script[src]", "src").unwrap_or_default(),
+        ),
+        Err(_) => (None, Vec::new()),
+    };
+    let scripts: Vec<String> = scripts.iter().map(|src| src.to_lowercase()).collect();
+    let cookies = cookie_names(&headers);
+    let html = data.content.to_lowercase();
+
+    let mut stack = TechStack {
+        url: data.url.clone(),
+        technologies: Vec::new(),
+    };
+
+    for signature in SIGNATURES {
+        let detection = match &signature.source {
+            Source::Header(name, pattern) => headers.get(*name)
+                .filter(|value| value.to_lowercase().contains(pattern))
+                .map(|value| (version_after(value, '/'), format!("header {}: {}", name, value))),
+            Source::Generator(pattern) => generator.as_ref()
+                .filter(|value| value.to_lowercase().starts_with(pattern))
+                .map(|value| (version_after(value, ' '), format!("generator: {}", value))),
+            Source::Script(pattern) => scripts.iter()
+                .find(|src| src.contains(pattern))
+                .map(|src| (None, format!("script: {}", src))),
+            Source::Cookie(pattern) => cookies.iter()
+                .find(|cookie| cookie.starts_with(pattern))
+                .map(|cookie| (None, format!("cookie: {}", cookie))),
+            Source::Html(pattern) => html.contains(pattern)
+                .then(|| (None, format!("html: {}", pattern))),
+        };
+
+        if let Some((version, evidence)) = detection {
+            stack.add(signature.name, signature.category, version, evidence);
+        }
+    }
+
+    stack
+}
+
+/// Lowercased names of cookies set by the response
+fn cookie_names(headers: &HashMap<String, String>) -> Vec<String> {
+    headers.get("set-cookie")
+        .map(|value| {
+            value.split(['\n', ','])
+                .filter_map(|cookie| cookie.split('=').next())
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty() && !name.contains(' '))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Extract a version number following a separator, e.g. "nginx/1.25" or "WordPress 6.4"
+fn version_after(value: &str, separator: char) -> Option<String> {
+    let (_, rest) = value.split_once(separator)?;
+    let version: String = rest.trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(version)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(headers: &[(&str, &str)], html: &str) -> ScrapedData {
+        let mut data = ScrapedData::new("https://example.com/".to_string());
+        data.content = html.to_string();
+        data.headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        data
+    }
+
+    #[test]
+    fn test_fingerprint_wordpress() {
+        let data = page(
+            &[("server", "nginx/1.25.3"), ("x-powered-by", "PHP/8.2.1"), ("cf-ray", "8a1b-AMS")],
+            r#"
+            <head>
+                <meta name="generator" content="WordPress 6.4.2">
+                <script src="https://example.com/wp-includes/js/jquery/jquery.min.js"></script>
+                <script async src="https://www.googletagmanager.com/gtag/js?id=G-123"></script>
+            </head>
+            "#,
+        );
+
+        let stack = fingerprint(&data);
+        assert_eq!(stack.get("WordPress").unwrap().version, Some("6.4.2".to_string()));
+        assert_eq!(stack.get("nginx").unwrap().version, Some("1.25.3".to_string()));
+        assert_eq!(stack.get("PHP").unwrap().version, Some("8.2.1".to_string()));
+        assert!(stack.has("jQuery"));
+        assert!(stack.has("Google Analytics"));
+        assert!(stack.has("Cloudflare"));
+        assert!(!stack.has("Shopify"));
+        assert_eq!(stack.by_category(TechCategory::Cms).len(), 1);
+    }
+
+    #[test]
+    fn test_fingerprint_shopify_next() {
+        let data = page(
+            &[("X-ShopId", "12345"), ("set-cookie", "_shopify_y=abc; Path=/")],
+            r#"<div id="__next"></div><script src="/_next/static/chunks/main.js"></script>"#,
+        );
+
+        let stack = fingerprint(&data);
+        let shopify = stack.get("Shopify").unwrap();
+        assert_eq!(shopify.category, TechCategory::Ecommerce);
+        assert!(shopify.evidence.starts_with("header"));
+        assert!(stack.has("Next.js"));
+        assert!(stack.has("React"));
+    }
+
+    #[test]
+    fn test_version_after() {
+        assert_eq!(version_after("Apache/2.4.57 (Ubuntu)", '/'), Some("2.4.57".to_string()));
+        assert_eq!(version_after("cloudflare", '/'), None);
+        assert_eq!(version_after("Drupal 10 (https://www.drupal.org)", ' '), Some("10".to_string()));
+    }
+}
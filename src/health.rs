@@ -0,0 +1,227 @@
+//! Rolling per-host health scores
+//!
+//! [`HealthTracker`] keeps exponentially weighted error rates and latencies
+//! per host. Hosts scoring below [`HealthPolicy::unhealthy_below`] can be
+//! moved to the back of batches, and hosts failing repeatedly are paused for
+//! a while so requests to them stop tying up concurrency slots.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How host health is scored and acted upon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthPolicy {
+    /// Weight of the newest request in the rolling averages (0.0-1.0)
+    pub smoothing: f64,
+    /// Average latency up to which a host is not penalised
+    pub latency_target: Duration,
+    /// Score below which a host counts as unhealthy
+    pub unhealthy_below: f64,
+    /// Consecutive failures after which a host is paused, if pausing is enabled
+    pub pause_after_failures: Option<u32>,
+    /// How long a failing host is paused
+    pub pause_duration: Duration,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            smoothing: 0.2,
+            latency_target: Duration::from_secs(2),
+            unhealthy_below: 0.5,
+            pause_after_failures: None,
+            pause_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+impl HealthPolicy {
+    /// Pause a host for `duration` after `failures` consecutive failures
+    pub fn with_pause(mut self, failures: u32, duration: Duration) -> Self {
+        self.pause_after_failures = Some(failures.max(1));
+        self.pause_duration = duration;
+        self
+    }
+
+    /// Set the score below which a host is unhealthy
+    pub fn with_unhealthy_below(mut self, score: f64) -> Self {
+        self.unhealthy_below = score.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Health of a single host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostHealth {
+    /// Overall score from 0.0 (failing) to 1.0 (healthy)
+    pub score: f64,
+    /// Rolling share of failed requests
+    pub error_rate: f64,
+    /// Rolling average latency in milliseconds
+    pub avg_latency_ms: f64,
+    /// Requests observed
+    pub requests: u64,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// When a pause on the host ends, if paused
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+impl Default for HostHealth {
+    fn default() -> Self {
+        Self {
+            score: 1.0,
+            error_rate: 0.0,
+            avg_latency_ms: 0.0,
+            requests: 0,
+            consecutive_failures: 0,
+            paused_until: None,
+        }
+    }
+}
+
+/// Tracked state of a host
+#[derive(Debug, Default)]
+struct HostState {
+    error_rate: f64,
+    avg_latency_ms: f64,
+    requests: u64,
+    consecutive_failures: u32,
+    paused_until: Option<Instant>,
+}
+
+/// Thread-safe tracker of per-host health
+#[derive(Debug, Default)]
+pub struct HealthTracker {
+    policy: HealthPolicy,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl HealthTracker {
+    /// Create a tracker applying the given policy
+    pub fn new(policy: HealthPolicy) -> Self {
+        Self {
+            policy,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the policy
+    pub fn policy(&self) -> &HealthPolicy {
+        &self.policy
+    }
+
+    /// Record the outcome and latency of a request to a host
+    pub fn record(&self, host: &str, success: bool, latency: Duration) {
+        let mut hosts = self.hosts.lock().expect("health tracker lock poisoned");
+        let state = hosts.entry(host.to_lowercase()).or_default();
+
+        let weight = if state.requests == 0 { 1.0 } else { self.policy.smoothing };
+        let failed = if success { 0.0 } else { 1.0 };
+        state.error_rate += weight * (failed - state.error_rate);
+        state.avg_latency_ms += weight * (latency.as_secs_f64() * 1000.0 - state.avg_latency_ms);
+        state.requests += 1;
+
+        if success {
+            state.consecutive_failures = 0;
+            return;
+        }
+        state.consecutive_failures += 1;
+        if let Some(limit) = self.policy.pause_after_failures {
+            if state.consecutive_failures >= limit {
+                warn!("Pausing {} for {:?} after {} consecutive failures", host, self.policy.pause_duration, state.consecutive_failures);
+                state.paused_until = Some(Instant::now() + self.policy.pause_duration);
+                state.consecutive_failures = 0;
+            }
+        }
+    }
+
+    /// Time left until a paused host may be requested again
+    pub fn pause_remaining(&self, host: &str) -> Option<Duration> {
+        let hosts = self.hosts.lock().expect("health tracker lock poisoned");
+        let until = hosts.get(&host.to_lowercase())?.paused_until?;
+        let remaining = until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Current health of a host (fully healthy if never seen)
+    pub fn health(&self, host: &str) -> HostHealth {
+        let hosts = self.hosts.lock().expect("health tracker lock poisoned");
+        hosts.get(&host.to_lowercase()).map(|state| self.snapshot(state)).unwrap_or_default()
+    }
+
+    /// Whether a host is paused or scores below the unhealthy threshold
+    pub fn is_unhealthy(&self, host: &str) -> bool {
+        let health = self.health(host);
+        health.paused_until.is_some() || health.score < self.policy.unhealthy_below
+    }
+
+    /// Health of every host seen so far
+    pub fn all(&self) -> HashMap<String, HostHealth> {
+        let hosts = self.hosts.lock().expect("health tracker lock poisoned");
+        hosts.iter().map(|(host, state)| (host.clone(), self.snapshot(state))).collect()
+    }
+
+    /// Forget all recorded health
+    pub fn reset(&self) {
+        self.hosts.lock().expect("health tracker lock poisoned").clear();
+    }
+
+    fn snapshot(&self, state: &HostState) -> HostHealth {
+        let target_ms = self.policy.latency_target.as_secs_f64() * 1000.0;
+        let latency_factor = if state.avg_latency_ms <= target_ms { 1.0 } else { target_ms / state.avg_latency_ms };
+        let now = Instant::now();
+
+        HostHealth {
+            score: (1.0 - state.error_rate) * latency_factor,
+            error_rate: state.error_rate,
+            avg_latency_ms: state.avg_latency_ms,
+            requests: state.requests,
+            consecutive_failures: state.consecutive_failures,
+            paused_until: state
+                .paused_until
+                .filter(|until| *until > now)
+                .and_then(|until| chrono::Duration::from_std(until - now).ok())
+                .map(|remaining| Utc::now() + remaining),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_tracker() {
+        let tracker = HealthTracker::new(HealthPolicy::default().with_pause(3, Duration::from_secs(60)));
+        let fast = Duration::from_millis(100);
+
+        for _ in 0..5 {
+            tracker.record("good.example", true, fast);
+        }
+        tracker.record("slow.example", true, Duration::from_secs(8));
+        tracker.record("bad.example", true, fast);
+        tracker.record("bad.example", false, fast);
+        tracker.record("bad.example", false, fast);
+
+        assert_eq!(tracker.health("GOOD.example").score, 1.0);
+        assert_eq!(tracker.health("slow.example").score, 0.25);
+        assert!(tracker.is_unhealthy("slow.example"));
+        assert_eq!(tracker.health("unknown.example"), HostHealth::default());
+
+        let bad = tracker.health("bad.example");
+        assert!((bad.error_rate - 0.36).abs() < 1e-9);
+        assert_eq!(bad.consecutive_failures, 2);
+        assert!(tracker.pause_remaining("bad.example").is_none());
+
+        tracker.record("bad.example", false, fast);
+        assert!(tracker.pause_remaining("bad.example").unwrap() > Duration::from_secs(59));
+        assert!(tracker.health("bad.example").paused_until.is_some());
+        assert!(tracker.is_unhealthy("bad.example"));
+        assert_eq!(tracker.all().len(), 3);
+    }
+}
@@ -1,13 +1,79 @@
 //! HTML parsing module with CSS selector support
 
 use crate::error::{FerrisFetcherError, Result};
-use scraper::{Html, ElementRef, Selector};
+use regex::Regex;
+use ego_tree::NodeId;
+use scraper::{Html, ElementRef, Node, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeBounds;
+use url::Url;
+
+/// Pre-parsed CSS selector, usually created with [`crate::selector!`]
+///
+/// Rules built with [`crate::ExtractionRuleBuilder::new_compiled`] keep the
+/// parsed selector. It also dereferences to the selector source, so it can be
+/// passed wherever a selector string is expected.
+///
+/// ```
+/// use ferrisfetcher::{selector, HtmlParser};
+///
+/// let parser = HtmlParser::new(r#"<div class="price"><span>9.99</span></div>"#).unwrap();
+/// let price = parser.select_first_compiled(selector!("div.price > span")).unwrap();
+/// assert_eq!(price.inner_html(), "9.99");
+/// ```
+///
+/// Invalid selectors do not compile:
+///
+/// ```compile_fail
+/// let price = ferrisfetcher::selector!("div.price >");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CssSelector {
+    source: String,
+    selector: Selector,
+}
+
+impl CssSelector {
+    /// Parse a selector at runtime
+    pub fn parse(source: &str) -> Result<Self> {
+        let selector = Selector::parse(source)
+            .map_err(|e| FerrisFetcherError::ParseError(format!("Invalid CSS selector '{}': {}", source, e)))?;
+        Ok(Self { source: source.to_string(), selector })
+    }
+
+    /// Selector source text
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Parsed selector for use with `scraper` directly
+    pub fn selector(&self) -> &Selector {
+        &self.selector
+    }
+}
+
+impl std::ops::Deref for CssSelector {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.source
+    }
+}
+
+impl std::fmt::Display for CssSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
 
 /// HTML parser with CSS selector capabilities
 #[derive(Debug, Clone)]
 pub struct HtmlParser {
     /// Parsed HTML document
     document: Html,
+    /// URL the document was fetched from, if known
+    base_url: Option<Url>,
 }
 
 impl HtmlParser {
@@ -17,9 +83,56 @@ impl HtmlParser {
         
         Ok(Self {
             document,
+            base_url: None,
         })
     }
 
+    /// Create a new HTML parser for a document fetched from `url`
+    pub fn with_base_url(html: &str, url: &str) -> Result<Self> {
+        let mut parser = Self::new(html)?;
+        parser.base_url = Some(Url::parse(url)?);
+        Ok(parser)
+    }
+
+    /// Move the contents of `<template>` elements into the live document
+    ///
+    /// html5ever keeps template contents in a detached fragment, so selectors
+    /// cannot cross into them. This covers declarative shadow DOM
+    /// (`<template shadowrootmode="open">`) emitted by web components as well
+    /// as plain templates: each template is replaced by its contents, placing
+    /// shadow roots inside their host element.
+    pub fn inline_templates(&mut self) {
+        let template_ids: Vec<NodeId> = self.document
+            .tree
+            .nodes()
+            .filter(|node| matches!(node.value(), Node::Element(element) if element.name() == "template"))
+            .map(|node| node.id())
+            .collect();
+
+        for template_id in template_ids {
+            let content_ids: Vec<NodeId> = match self.document.tree.get(template_id) {
+                Some(template) => template
+                    .children()
+                    .filter(|child| child.value().is_fragment())
+                    .flat_map(|fragment| fragment.children().map(|node| node.id()))
+                    .collect(),
+                None => continue,
+            };
+
+            if let Some(mut template) = self.document.tree.get_mut(template_id) {
+                for content_id in content_ids {
+                    template.insert_id_before(content_id);
+                }
+                template.detach();
+            }
+        }
+    }
+
+    /// Get the URL the document was fetched from
+    pub fn base_url(&self) -> Option<&Url> {
+        self.base_url.as_ref()
+    }
+
     /// Parse HTML from a string
     pub fn parse(html: &str) -> Result<Self> {
         Self::new(html)
@@ -37,27 +150,41 @@ impl HtmlParser {
     }
 
     /// Get meta tags by name
+    ///
+    /// Names are matched case-insensitively and ignoring surrounding whitespace.
     pub fn meta_tag(&self, name: &str) -> Option<String> {
-        let selector_str = &format!("meta[name='{}']", name);
-        let selector = Selector::parse(selector_str).ok()?;
-
-        self.document
-            .select(&selector)
-            .next()
-            .and_then(|element| element.value().attr("content"))
-            .map(|content| content.to_string())
+        self.select_by_attr_value("meta", "name", name)
+            .into_iter()
+            .find_map(|element| element.value().attr("content"))
+            .map(|content| content.trim().to_string())
     }
 
     /// Get meta tags by property (for Open Graph, Twitter Cards, etc.)
+    ///
+    /// Properties are matched case-insensitively (`og:title` matches
+    /// `OG:Title`), and tags that misuse `name` instead of `property` are
+    /// accepted as a fallback.
     pub fn meta_property(&self, property: &str) -> Option<String> {
-        let selector_str = &format!("meta[property='{}']", property);
-        let selector = Selector::parse(selector_str).ok()?;
+        self.select_by_attr_value("meta", "property", property)
+            .into_iter()
+            .chain(self.select_by_attr_value("meta", "name", property))
+            .find_map(|element| element.value().attr("content"))
+            .map(|content| content.trim().to_string())
+    }
 
-        self.document
-            .select(&selector)
-            .next()
-            .and_then(|element| element.value().attr("content"))
-            .map(|content| content.to_string())
+    /// Select elements matching a selector whose `attr` equals `value`,
+    /// compared case-insensitively and ignoring surrounding whitespace
+    pub fn select_by_attr_value(&self, selector: &str, attr: &str, value: &str) -> Vec<ElementRef<'_>> {
+        let expected = value.trim();
+        self.select(selector)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|element| {
+                element.value()
+                    .attr(attr)
+                    .is_some_and(|actual| actual.trim().eq_ignore_ascii_case(expected))
+            })
+            .collect()
     }
 
     /// Select elements using a CSS selector
@@ -67,6 +194,16 @@ impl HtmlParser {
         Ok(self.document.select(&selector_obj).collect())
     }
 
+    /// Select elements using a pre-parsed selector
+    pub fn select_compiled(&self, selector: &CssSelector) -> Vec<ElementRef<'_>> {
+        self.document.select(selector.selector()).collect()
+    }
+
+    /// Select the first element matching a pre-parsed selector
+    pub fn select_first_compiled(&self, selector: &CssSelector) -> Option<ElementRef<'_>> {
+        self.document.select(selector.selector()).next()
+    }
+
     /// Select the first element matching a CSS selector
     pub fn select_first(&self, selector: &str) -> Option<ElementRef<'_>> {
         if let Ok(selector_obj) = Selector::parse(selector) {
@@ -76,6 +213,55 @@ impl HtmlParser {
         }
     }
 
+    /// Select the `n`th (zero-based) element matching a selector, document-wide
+    pub fn select_nth(&self, selector: &str, n: usize) -> Option<ElementRef<'_>> {
+        let selector_obj = Selector::parse(selector).ok()?;
+        self.document.select(&selector_obj).nth(n)
+    }
+
+    /// Select the last element matching a selector
+    pub fn select_last(&self, selector: &str) -> Option<ElementRef<'_>> {
+        let selector_obj = Selector::parse(selector).ok()?;
+        self.document.select(&selector_obj).next_back()
+    }
+
+    /// Select the elements matching a selector whose match index falls within `range`
+    pub fn select_range<R>(&self, selector: &str, range: R) -> Result<Vec<ElementRef<'_>>>
+    where
+        R: RangeBounds<usize>,
+    {
+        Ok(self.select(selector)?
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| range.contains(index))
+            .map(|(_, element)| element)
+            .collect())
+    }
+
+    /// Select elements matching a selector whose whitespace-normalised text satisfies `predicate`
+    pub fn select_where_text<F>(&self, selector: &str, predicate: F) -> Result<Vec<ElementRef<'_>>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        Ok(self.select(selector)?
+            .into_iter()
+            .filter(|element| predicate(&self.element_text(*element)))
+            .collect())
+    }
+
+    /// Select elements matching a selector whose text contains `needle` (case-insensitive)
+    pub fn select_containing_text(&self, selector: &str, needle: &str) -> Result<Vec<ElementRef<'_>>> {
+        let needle = needle.to_lowercase();
+        self.select_where_text(selector, |text| text.to_lowercase().contains(&needle))
+    }
+
+    /// Select elements matching a selector whose text matches a regular expression
+    pub fn select_matching_text(&self, selector: &str, pattern: &str) -> Result<Vec<ElementRef<'_>>> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| FerrisFetcherError::ParseError(format!("Invalid text pattern '{}': {}", pattern, e)))?;
+        self.select_where_text(selector, |text| regex.is_match(text))
+    }
+
     /// Extract text content from elements matching a selector
     pub fn select_text(&self, selector: &str) -> Result<Vec<String>> {
         let elements = self.select(selector)?;
@@ -110,6 +296,54 @@ impl HtmlParser {
             .map(|value| value.to_string())
     }
 
+    /// Extract the full attribute map of each element matching a selector
+    pub fn select_attributes(&self, selector: &str) -> Result<Vec<BTreeMap<String, String>>> {
+        let elements = self.select(selector)?;
+        Ok(elements
+            .into_iter()
+            .map(Self::element_attributes)
+            .collect())
+    }
+
+    /// Get the full attribute map of an element
+    pub fn element_attributes(element: ElementRef) -> BTreeMap<String, String> {
+        element.value()
+            .attrs()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Collect the `data-*` attributes of each element matching a selector
+    ///
+    /// Keys have the `data-` prefix stripped. Values holding a JSON object or
+    /// array are decoded; everything else is kept as a string.
+    pub fn data_attrs(&self, selector: &str) -> Result<Vec<BTreeMap<String, serde_json::Value>>> {
+        let elements = self.select(selector)?;
+        Ok(elements
+            .iter()
+            .map(|element| {
+                element.value()
+                    .attrs()
+                    .filter_map(|(name, value)| {
+                        let key = name.strip_prefix("data-")?;
+                        Some((key.to_string(), Self::decode_data_value(value)))
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Decode a `data-*` attribute value, parsing embedded JSON structures
+    fn decode_data_value(value: &str) -> serde_json::Value {
+        let trimmed = value.trim();
+        if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            if let Ok(json) = serde_json::from_str(trimmed) {
+                return json;
+            }
+        }
+        serde_json::Value::String(value.to_string())
+    }
+
     /// Extract HTML content from elements matching a selector
     pub fn select_html(&self, selector: &str) -> Result<Vec<String>> {
         let elements = self.select(selector)?;
@@ -140,12 +374,212 @@ impl HtmlParser {
             .unwrap_or_default()
     }
 
+    /// Resolve a possibly relative URL against the document's base URL
+    ///
+    /// A `<base href>` element takes precedence over the URL the document was
+    /// fetched from. Returns `None` if the URL cannot be resolved.
+    pub fn resolve_url(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if let Ok(url) = Url::parse(href) {
+            return Some(url.to_string());
+        }
+
+        let base = match self.select_first_attr("base[href]", "href") {
+            Some(base_href) => match &self.base_url {
+                Some(base_url) => base_url.join(base_href.trim()).ok(),
+                None => Url::parse(base_href.trim()).ok(),
+            },
+            None => self.base_url.clone(),
+        }?;
+
+        base.join(href).ok().map(|url| url.to_string())
+    }
+
+    /// Get the absolute URLs of all iframes on the page
+    pub fn iframes(&self) -> Vec<String> {
+        self.select_attr("iframe[src]", "src")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|src| self.resolve_url(src))
+            .collect()
+    }
+
+    /// Get the absolute URLs of the RSS and Atom feeds advertised with `<link rel="alternate">`
+    pub fn feed_links(&self) -> Vec<String> {
+        let mut feeds: Vec<String> = Vec::new();
+        for link in self.select("link[rel][type][href]").unwrap_or_default() {
+            let element = link.value();
+            let kind = element.attr("type").unwrap_or_default().trim().to_ascii_lowercase();
+            let is_alternate = Self::has_rel(link, "alternate");
+            let is_feed = matches!(kind.as_str(), "application/rss+xml" | "application/atom+xml");
+            if !(is_alternate && is_feed) {
+                continue;
+            }
+            if let Some(url) = element.attr("href").and_then(|href| self.resolve_url(href)) {
+                if !feeds.contains(&url) {
+                    feeds.push(url);
+                }
+            }
+        }
+        feeds
+    }
+
+    /// Get all links with their anchor text, resolved to absolute URLs where possible
+    pub fn link_infos(&self) -> Vec<LinkInfo> {
+        self.select("a[href]")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|element| {
+                let href = element.value().attr("href")?;
+                Some(LinkInfo {
+                    url: self.absolute_url(href),
+                    text: self.element_text(element),
+                    rel: element.value().attr("rel").map(|rel| rel.to_string()),
+                })
+            })
+            .collect()
+    }
+
     /// Get all images (src attributes) from the page
     pub fn images(&self) -> Vec<String> {
         self.select_attr("img[src]", "src")
             .unwrap_or_default()
     }
 
+    /// Get all images with their `srcset` and `<picture>` source candidates
+    ///
+    /// Candidate URLs are resolved to absolute URLs when the base URL is known.
+    pub fn image_infos(&self) -> Vec<ImageInfo> {
+        let img_selector = Self::static_selector("img");
+        let source_selector = Self::static_selector("source[srcset]");
+
+        self.document
+            .select(&img_selector)
+            .map(|img| {
+                let mut candidates = Vec::new();
+
+                if let Some(picture) = img.parent().and_then(ElementRef::wrap)
+                    .filter(|parent| parent.value().name() == "picture")
+                {
+                    for source in picture.select(&source_selector) {
+                        if let Some(srcset) = source.value().attr("srcset") {
+                            candidates.extend(self.parse_srcset(srcset));
+                        }
+                    }
+                }
+
+                if let Some(srcset) = img.value().attr("srcset") {
+                    candidates.extend(self.parse_srcset(srcset));
+                }
+
+                ImageInfo {
+                    src: img.value().attr("src").map(|src| self.absolute_url(src)),
+                    alt: img.value().attr("alt").map(|alt| alt.to_string()),
+                    candidates,
+                }
+            })
+            .collect()
+    }
+
+    /// Parse a `srcset` attribute into image candidates
+    fn parse_srcset(&self, srcset: &str) -> Vec<ImageCandidate> {
+        srcset
+            .split(',')
+            .filter_map(|candidate| {
+                let mut parts = candidate.split_whitespace();
+                let url = parts.next()?;
+                let descriptor = match parts.next() {
+                    Some(d) if d.ends_with('w') => ImageDescriptor::Width(d.trim_end_matches('w').parse().ok()?),
+                    Some(d) if d.ends_with('x') => ImageDescriptor::Density(d.trim_end_matches('x').parse().ok()?),
+                    Some(_) => return None,
+                    None => ImageDescriptor::Density(1.0),
+                };
+
+                Some(ImageCandidate {
+                    url: self.absolute_url(url),
+                    descriptor,
+                })
+            })
+            .collect()
+    }
+
+    /// Resolve a URL if possible, otherwise return it unchanged
+    fn absolute_url(&self, url: &str) -> String {
+        self.resolve_url(url).unwrap_or_else(|| url.trim().to_string())
+    }
+
+    /// Get normalised social profile URLs linked from the page, per platform
+    ///
+    /// Share buttons, posts and other non-profile links are ignored.
+    pub fn social_profiles(&self) -> HashMap<SocialPlatform, Vec<String>> {
+        let mut profiles: HashMap<SocialPlatform, Vec<String>> = HashMap::new();
+
+        for link in self.links() {
+            let Some(url) = self.resolve_url(&link) else { continue };
+            if let Some((platform, profile)) = SocialPlatform::profile_url(&url) {
+                let urls = profiles.entry(platform).or_default();
+                if !urls.contains(&profile) {
+                    urls.push(profile);
+                }
+            }
+        }
+
+        profiles
+    }
+
+    /// Get `<video>`/`<audio>` elements and embedded YouTube/Vimeo players
+    pub fn media(&self) -> Vec<MediaInfo> {
+        let media_selector = Self::static_selector("video, audio, iframe[src]");
+        let source_selector = Self::static_selector("source[src]");
+        let track_selector = Self::static_selector("track[src]");
+
+        self.document
+            .select(&media_selector)
+            .filter_map(|element| {
+                let attr = |name: &str| element.value().attr(name).map(|value| self.absolute_url(value));
+
+                let kind = match element.value().name() {
+                    "video" => MediaKind::Video,
+                    "audio" => MediaKind::Audio,
+                    _ => {
+                        let embed = attr("src").and_then(|src| EmbeddedVideo::from_url(&src))?;
+                        return Some(MediaInfo {
+                            kind: MediaKind::Embed,
+                            sources: vec![embed.canonical_url.clone()],
+                            poster: None,
+                            tracks: Vec::new(),
+                            embed: Some(embed),
+                        });
+                    }
+                };
+
+                let sources = attr("src")
+                    .into_iter()
+                    .chain(element.select(&source_selector)
+                        .filter_map(|source| source.value().attr("src"))
+                        .map(|src| self.absolute_url(src)))
+                    .collect();
+
+                let tracks = element.select(&track_selector)
+                    .map(|track| MediaTrack {
+                        src: self.absolute_url(track.value().attr("src").unwrap_or_default()),
+                        kind: track.value().attr("kind").unwrap_or("subtitles").to_string(),
+                        srclang: track.value().attr("srclang").map(|lang| lang.to_string()),
+                        label: track.value().attr("label").map(|label| label.to_string()),
+                    })
+                    .collect();
+
+                Some(MediaInfo {
+                    kind,
+                    sources,
+                    poster: attr("poster"),
+                    tracks,
+                    embed: None,
+                })
+            })
+            .collect()
+    }
+
     /// Get all forms from the page
     pub fn forms(&self) -> Vec<FormInfo> {
         let selector = "form";
@@ -164,9 +598,9 @@ impl HtmlParser {
         let action = form_element.value().attr("action").map(|s| s.to_string());
         let method = form_element.value().attr("method").map(|s| s.to_string()).unwrap_or_else(|| "GET".to_string());
         
-        let inputs = self.select("input, textarea, select")
-            .unwrap_or_default()
-            .into_iter()
+        let input_selector = Selector::parse("input, textarea, select").ok()?;
+        let inputs = form_element
+            .select(&input_selector)
             .filter_map(|element| self.extract_input_info(element))
             .collect();
 
@@ -179,7 +613,7 @@ impl HtmlParser {
 
     /// Extract input field information
     fn extract_input_info(&self, input_element: ElementRef) -> Option<InputInfo> {
-        let name = input_element.value().attr("name").map(|s| s.to_string())?;
+        let name = input_element.value().attr("name").map(|s| s.to_string()).unwrap_or_default();
         let input_type = input_element.value().attr("type").map(|s| s.to_string()).unwrap_or_else(|| "text".to_string());
         let value = input_element.value().attr("value").map(|s| s.to_string());
         let required = input_element.value().attr("required").is_some();
@@ -208,6 +642,76 @@ impl HtmlParser {
             .join(" ")
     }
 
+    /// Extract key/value pairs from spec tables and definition lists
+    ///
+    /// Within each element matching `container`, pairs are collected from
+    /// `dt`/`dd` siblings, table rows with a `th` and a `td`, and adjacent
+    /// elements where the first one's text ends with a colon (`Weight:` followed
+    /// by `<span>2 kg</span>`). Keys are whitespace-normalised with the trailing
+    /// colon removed; the first occurrence of a key wins.
+    pub fn key_value_pairs(&self, container: &str) -> Result<HashMap<String, String>> {
+        let containers = self.select(container)?;
+        let dt_selector = Self::static_selector("dt");
+        let row_selector = Self::static_selector("tr");
+        let th_selector = Self::static_selector("th");
+        let td_selector = Self::static_selector("td");
+        let any_selector = Self::static_selector("*");
+
+        let mut pairs = HashMap::new();
+        let mut insert = |key: String, value: String| {
+            let key = key.trim_end_matches(':').trim().to_string();
+            if !key.is_empty() && !value.is_empty() {
+                pairs.entry(key).or_insert(value);
+            }
+        };
+
+        for container in containers {
+            for dt in container.select(&dt_selector) {
+                if let Some(dd) = Self::next_element(dt).filter(|el| el.value().name() == "dd") {
+                    insert(self.element_text(dt), self.element_text(dd));
+                }
+            }
+
+            for row in container.select(&row_selector) {
+                if let (Some(th), Some(td)) = (row.select(&th_selector).next(), row.select(&td_selector).next()) {
+                    insert(self.element_text(th), self.element_text(td));
+                }
+            }
+
+            for label in container.select(&any_selector) {
+                if matches!(label.value().name(), "dt" | "dd" | "th" | "td" | "tr")
+                    || label.children().any(|child| child.value().is_element())
+                {
+                    continue;
+                }
+
+                let text = self.element_text(label);
+                if text.len() > 1 && text.ends_with(':') {
+                    if let Some(value) = Self::next_element(label) {
+                        insert(text, self.element_text(value));
+                    }
+                }
+            }
+        }
+
+        Ok(pairs)
+    }
+
+    /// Get the next sibling that is an element
+    fn next_element(element: ElementRef<'_>) -> Option<ElementRef<'_>> {
+        element.next_siblings().find_map(ElementRef::wrap)
+    }
+
+    /// Whitespace-normalised text content of an element
+    fn element_text(&self, element: ElementRef) -> String {
+        self.clean_whitespace(&element.text().collect::<String>())
+    }
+
+    /// Parse a selector known to be valid
+    fn static_selector(selector: &str) -> Selector {
+        Selector::parse(selector).expect("static selector is valid")
+    }
+
     /// Check if a selector exists in the document
     pub fn has_selector(&self, selector: &str) -> bool {
         Selector::parse(selector)
@@ -251,8 +755,102 @@ impl HtmlParser {
     }
 
     /// Get canonical URL
+    ///
+    /// The `rel` token is matched case-insensitively, so `rel="Canonical"` counts.
     pub fn canonical_url(&self) -> Option<String> {
-        self.select_first_attr("link[rel='canonical']", "href")
+        self.select("link[rel][href]")
+            .unwrap_or_default()
+            .into_iter()
+            .find(|link| Self::has_rel(*link, "canonical"))
+            .and_then(|link| link.value().attr("href"))
+            .map(|href| href.to_string())
+    }
+
+    /// Whether an element's space-separated `rel` tokens include `rel`, ignoring case
+    fn has_rel(element: ElementRef<'_>, rel: &str) -> bool {
+        element.value().attr("rel").unwrap_or_default().split_whitespace().any(|token| token.eq_ignore_ascii_case(rel))
+    }
+
+    /// Get the readable text of the document body
+    ///
+    /// Text inside `script`, `style`, `noscript` and `template` elements is
+    /// skipped and whitespace is normalised.
+    pub fn visible_text(&self) -> String {
+        let root = self.select_first("body").unwrap_or_else(|| self.document.root_element());
+        let mut text = String::new();
+
+        for node in root.descendants() {
+            if let Node::Text(fragment) = node.value() {
+                let hidden = node.ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .any(|el| matches!(el.value().name(), "script" | "style" | "noscript" | "template"));
+                if !hidden {
+                    text.push_str(fragment);
+                    text.push(' ');
+                }
+            }
+        }
+
+        self.clean_whitespace(&text)
+    }
+
+    /// Get the document language declared on the `<html>` element
+    pub fn language(&self) -> Option<String> {
+        self.select_first_attr("html[lang]", "lang")
+            .map(|lang| lang.trim().to_string())
+            .filter(|lang| !lang.is_empty())
+    }
+
+    /// Compute word count, reading time and structural metrics for the document
+    pub fn content_metrics(&self) -> ContentMetrics {
+        let text = self.visible_text();
+        let word_count = text.split_whitespace().count();
+        let html_length = self.document.root_element().html().len();
+
+        let mut heading_counts = BTreeMap::new();
+        for level in 1..=6 {
+            let tag = format!("h{}", level);
+            let count = self.count(&tag);
+            if count > 0 {
+                heading_counts.insert(tag, count);
+            }
+        }
+
+        let site_host = self.base_url
+            .as_ref()
+            .and_then(|url| url.host_str())
+            .map(|host| host.trim_start_matches("www.").to_string());
+        let (mut internal_links, mut outbound_links) = (0, 0);
+        for link in self.links() {
+            match Url::parse(link.trim()) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                    let host = url.host_str().map(|host| host.trim_start_matches("www.").to_string());
+                    if site_host.is_some() && host == site_host {
+                        internal_links += 1;
+                    } else {
+                        outbound_links += 1;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => internal_links += 1,
+            }
+        }
+
+        ContentMetrics {
+            word_count,
+            reading_time_minutes: word_count as f64 / WORDS_PER_MINUTE,
+            text_length: text.len(),
+            html_length,
+            text_to_html_ratio: if html_length == 0 { 0.0 } else { text.len() as f64 / html_length as f64 },
+            heading_counts,
+            internal_links,
+            outbound_links,
+        }
+    }
+
+    /// Run a basic accessibility audit of the document
+    pub fn accessibility_report(&self) -> crate::accessibility::AccessibilityReport {
+        crate::accessibility::audit(self)
     }
 
     /// Get the original HTML document
@@ -261,6 +859,274 @@ impl HtmlParser {
     }
 }
 
+/// Average adult reading speed used for reading time estimates
+const WORDS_PER_MINUTE: f64 = 238.0;
+
+/// Content size and structure metrics of a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentMetrics {
+    /// Number of words in the readable text
+    pub word_count: usize,
+    /// Estimated reading time in minutes
+    pub reading_time_minutes: f64,
+    /// Length of the readable text in bytes
+    pub text_length: usize,
+    /// Length of the serialised HTML in bytes
+    pub html_length: usize,
+    /// Readable text length divided by HTML length
+    pub text_to_html_ratio: f64,
+    /// Number of headings per level ("h1" through "h6"), omitting absent levels
+    pub heading_counts: BTreeMap<String, usize>,
+    /// Links to the same site (including relative links)
+    pub internal_links: usize,
+    /// Links to other sites
+    pub outbound_links: usize,
+}
+
+/// Link information extracted from HTML
+///
+/// `url` holds the ASCII form used in requests (punycode host, percent-encoded
+/// path); [`LinkInfo::display_url`] gives the form shown to people.
+#[derive(Debug, Clone)]
+pub struct LinkInfo {
+    pub url: String,
+    pub text: String,
+    pub rel: Option<String>,
+}
+
+impl LinkInfo {
+    /// The link URL with a Unicode host and decoded non-ASCII characters
+    pub fn display_url(&self) -> String {
+        crate::idn::url_to_display(&self.url)
+    }
+}
+
+/// Image information extracted from HTML, including responsive candidates
+#[derive(Debug, Clone)]
+pub struct ImageInfo {
+    pub src: Option<String>,
+    pub alt: Option<String>,
+    pub candidates: Vec<ImageCandidate>,
+}
+
+/// A single `srcset` candidate
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageCandidate {
+    pub url: String,
+    pub descriptor: ImageDescriptor,
+}
+
+/// Size descriptor of a `srcset` candidate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageDescriptor {
+    /// Intrinsic width in pixels (`480w`)
+    Width(u32),
+    /// Pixel density (`2x`)
+    Density(f32),
+}
+
+impl ImageInfo {
+    /// Pick the best image URL for a display width (in CSS pixels) and pixel density
+    ///
+    /// Width candidates are preferred: the smallest one at least
+    /// `target_width * density` wide is chosen, falling back to the largest.
+    /// Density candidates are chosen the same way against `density`. Falls
+    /// back to `src` when there are no candidates.
+    pub fn best_url(&self, target_width: u32, density: f32) -> Option<String> {
+        let needed_width = (target_width as f32 * density).ceil() as u32;
+        let widths: Vec<(u32, &str)> = self.candidates
+            .iter()
+            .filter_map(|c| match c.descriptor {
+                ImageDescriptor::Width(w) => Some((w, c.url.as_str())),
+                ImageDescriptor::Density(_) => None,
+            })
+            .collect();
+
+        if !widths.is_empty() {
+            return Self::pick(widths, needed_width);
+        }
+
+        let densities: Vec<(f32, &str)> = self.candidates
+            .iter()
+            .filter_map(|c| match c.descriptor {
+                ImageDescriptor::Density(x) => Some((x, c.url.as_str())),
+                ImageDescriptor::Width(_) => None,
+            })
+            .collect();
+
+        if !densities.is_empty() {
+            return Self::pick(densities, density);
+        }
+
+        self.src.clone()
+    }
+
+    /// Smallest candidate at least `needed`, otherwise the largest
+    fn pick<T: PartialOrd + Copy>(mut candidates: Vec<(T, &str)>, needed: T) -> Option<String> {
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        candidates
+            .iter()
+            .find(|(size, _)| *size >= needed)
+            .or_else(|| candidates.last())
+            .map(|(_, url)| url.to_string())
+    }
+}
+
+/// Audio/video information extracted from HTML
+#[derive(Debug, Clone)]
+pub struct MediaInfo {
+    pub kind: MediaKind,
+    pub sources: Vec<String>,
+    pub poster: Option<String>,
+    pub tracks: Vec<MediaTrack>,
+    pub embed: Option<EmbeddedVideo>,
+}
+
+/// Kind of media element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Video,
+    Audio,
+    /// An embedded third-party player
+    Embed,
+}
+
+/// Caption, subtitle or chapter track of a media element
+#[derive(Debug, Clone)]
+pub struct MediaTrack {
+    pub src: String,
+    pub kind: String,
+    pub srclang: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Known video hosting providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoProvider {
+    YouTube,
+    Vimeo,
+}
+
+/// A video embedded from a hosting provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedVideo {
+    pub provider: VideoProvider,
+    pub id: String,
+    pub canonical_url: String,
+}
+
+impl EmbeddedVideo {
+    /// Recognise a YouTube or Vimeo player/watch URL
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?.trim_start_matches("www.").trim_start_matches("m.");
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+
+        let (provider, id) = match host {
+            "youtube.com" | "youtube-nocookie.com" => {
+                let id = match segments.as_slice() {
+                    ["embed", id, ..] | ["shorts", id, ..] | ["v", id, ..] => id.to_string(),
+                    ["watch"] => url.query_pairs().find(|(key, _)| key == "v")?.1.into_owned(),
+                    _ => return None,
+                };
+                (VideoProvider::YouTube, id)
+            }
+            "youtu.be" => (VideoProvider::YouTube, segments.first()?.to_string()),
+            "player.vimeo.com" => match segments.as_slice() {
+                ["video", id, ..] => (VideoProvider::Vimeo, id.to_string()),
+                _ => return None,
+            },
+            "vimeo.com" => {
+                let id = segments.iter().rev().find(|s| s.chars().all(|c| c.is_ascii_digit()))?;
+                (VideoProvider::Vimeo, id.to_string())
+            }
+            _ => return None,
+        };
+
+        if id.is_empty() {
+            return None;
+        }
+
+        let canonical_url = match provider {
+            VideoProvider::YouTube => format!("https://www.youtube.com/watch?v={}", id),
+            VideoProvider::Vimeo => format!("https://vimeo.com/{}", id),
+        };
+
+        Some(Self {
+            provider,
+            id,
+            canonical_url,
+        })
+    }
+}
+
+/// Social platforms recognised by [`HtmlParser::social_profiles`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SocialPlatform {
+    Twitter,
+    LinkedIn,
+    Facebook,
+    Instagram,
+    GitHub,
+    YouTube,
+}
+
+impl SocialPlatform {
+    /// Normalise a link to a social profile URL, if it points at one
+    pub fn profile_url(url: &str) -> Option<(SocialPlatform, String)> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?.to_lowercase();
+        let host = host.trim_start_matches("www.").trim_start_matches("m.").trim_start_matches("mobile.");
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        let first = *segments.first()?;
+
+        let (platform, path) = match host {
+            "twitter.com" | "x.com" => {
+                if matches!(first, "intent" | "share" | "home" | "search" | "hashtag" | "i") {
+                    return None;
+                }
+                (SocialPlatform::Twitter, format!("https://x.com/{}", first.trim_start_matches('@')))
+            }
+            "linkedin.com" => match segments.as_slice() {
+                [kind @ ("in" | "company" | "school"), name, ..] => {
+                    (SocialPlatform::LinkedIn, format!("https://www.linkedin.com/{}/{}", kind, name))
+                }
+                _ => return None,
+            },
+            "facebook.com" | "fb.com" => {
+                if matches!(first, "sharer" | "sharer.php" | "share" | "dialog" | "plugins" | "tr") {
+                    return None;
+                }
+                (SocialPlatform::Facebook, format!("https://www.facebook.com/{}", first))
+            }
+            "instagram.com" => {
+                if matches!(first, "p" | "reel" | "explore" | "stories") {
+                    return None;
+                }
+                (SocialPlatform::Instagram, format!("https://www.instagram.com/{}", first))
+            }
+            "github.com" => {
+                if matches!(first, "sponsors" | "orgs" | "topics" | "features" | "about" | "login") {
+                    return None;
+                }
+                (SocialPlatform::GitHub, format!("https://github.com/{}", first))
+            }
+            "youtube.com" => match segments.as_slice() {
+                [kind @ ("channel" | "c" | "user"), name, ..] => {
+                    (SocialPlatform::YouTube, format!("https://www.youtube.com/{}/{}", kind, name))
+                }
+                [handle, ..] if handle.starts_with('@') => {
+                    (SocialPlatform::YouTube, format!("https://www.youtube.com/{}", handle))
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        Some((platform, path))
+    }
+}
+
 /// Form information extracted from HTML
 #[derive(Debug, Clone)]
 pub struct FormInfo {
@@ -324,6 +1190,32 @@ mod tests {
         assert_eq!(text, Some("First paragraph".to_string()));
     }
 
+    #[test]
+    fn test_compiled_selector() {
+        let parser = HtmlParser::new("<ul><li>a</li><li>b</li></ul>").unwrap();
+        let items = crate::selector!("ul > li");
+        assert_eq!(parser.select_compiled(items).len(), 2);
+        assert_eq!(parser.select_text(items).unwrap(), vec!["a", "b"]);
+
+        let rule = crate::ExtractionRuleBuilder::new("items", crate::selector!("li")).multiple(true).build();
+        assert_eq!(rule.selector, "li");
+        assert!(rule.compiled_selector.is_none());
+        assert!(CssSelector::parse("li >").is_err());
+
+        // Rules keep their parsed selector, and extractors parse the others once
+        let rule = crate::ExtractionRuleBuilder::new_compiled("items", items).multiple(true).build();
+        assert_eq!(rule.compiled_selector.as_ref().map(CssSelector::as_str), Some("ul > li"));
+        let mut extractor = crate::DataExtractor::with_rules(vec![rule]);
+        extractor.add_rule(crate::ExtractionRuleBuilder::new("first", "li").build());
+        assert!(extractor.get_rule("first").unwrap().compiled_selector.is_some());
+        assert_eq!(extractor.extract_all(&parser).unwrap()["items"], ["a", "b"]);
+
+        // A selector edited after compiling is parsed again
+        let mut rule = extractor.get_rule("first").unwrap().clone();
+        rule.selector = "li:last-child".to_string();
+        assert_eq!(extractor.extract_positioned(&parser, &rule).unwrap(), [(0, "b".to_string())]);
+    }
+
     #[test]
     fn test_select_attr() {
         let html = r#"
@@ -393,7 +1285,7 @@ mod tests {
         let html = "<div>Test</div>";
         let parser = HtmlParser::new(html).unwrap();
         
-        let result = parser.select("invalid[selector");
+        let result = parser.select("invalid[[selector");
         assert!(result.is_err());
     }
 
@@ -409,4 +1301,354 @@ mod tests {
         let cleaned = parser.clean_text("p").unwrap();
         assert_eq!(cleaned, vec!["Text with extra spaces"]);
     }
+
+    #[test]
+    fn test_select_attributes() {
+        let html = r#"
+        <div class="item" data-id="42" data-sku="A-1">One</div>
+        <div class="item" data-id="43">Two</div>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let attributes = parser.select_attributes(".item").unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[0].get("data-sku"), Some(&"A-1".to_string()));
+        assert_eq!(attributes[1].get("data-id"), Some(&"43".to_string()));
+        assert_eq!(attributes[1].get("class"), Some(&"item".to_string()));
+    }
+
+    #[test]
+    fn test_data_attrs() {
+        let html = r#"
+        <div id="app" class="root" data-page="home" data-state='{"user":{"id":5},"items":[1,2]}' data-count="007"></div>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let attrs = parser.data_attrs("#app").unwrap();
+        assert_eq!(attrs.len(), 1);
+
+        let attrs = &attrs[0];
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs["page"], "home");
+        assert_eq!(attrs["count"], "007");
+        assert_eq!(attrs["state"]["user"]["id"], 5);
+        assert_eq!(attrs["state"]["items"][1], 2);
+    }
+
+    #[test]
+    fn test_key_value_pairs() {
+        let html = r#"
+        <div class="specs">
+            <dl>
+                <dt>Color</dt><dd>Red</dd>
+                <dt>Size:</dt><dd> Large </dd>
+            </dl>
+            <table>
+                <tr><th>Weight</th><td>2 kg</td></tr>
+                <tr><td>no header</td><td>ignored</td></tr>
+            </table>
+            <p><span class="label">Material:</span> <span>Steel</span></p>
+        </div>
+        <dl><dt>Outside</dt><dd>container</dd></dl>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let pairs = parser.key_value_pairs(".specs").unwrap();
+        assert_eq!(pairs.len(), 4);
+        assert_eq!(pairs.get("Color"), Some(&"Red".to_string()));
+        assert_eq!(pairs.get("Size"), Some(&"Large".to_string()));
+        assert_eq!(pairs.get("Weight"), Some(&"2 kg".to_string()));
+        assert_eq!(pairs.get("Material"), Some(&"Steel".to_string()));
+    }
+
+    #[test]
+    fn test_positional_selection() {
+        let html = r#"
+        <div><p>One</p></div>
+        <div><p>Two</p><p>Three</p></div>
+        <p>Four</p>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        let text = |element: Option<ElementRef>| element.map(|e| e.text().collect::<String>());
+
+        assert_eq!(text(parser.select_nth("p", 2)), Some("Three".to_string()));
+        assert_eq!(text(parser.select_last("p")), Some("Four".to_string()));
+        assert!(parser.select_nth("p", 10).is_none());
+
+        let range: Vec<String> = parser.select_range("p", 1..3)
+            .unwrap()
+            .iter()
+            .map(|e| e.text().collect())
+            .collect();
+        assert_eq!(range, vec!["Two", "Three"]);
+        assert_eq!(parser.select_range("p", 2..).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_select_where_text() {
+        let html = r#"
+        <nav class="pagination">
+            <a href="/page/1">Previous</a>
+            <a href="/page/3">  Next  </a>
+            <a href="/page/3">Page 3</a>
+        </nav>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+
+        let next = parser.select_where_text("a", |text| text == "Next").unwrap();
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].value().attr("href"), Some("/page/3"));
+
+        assert_eq!(parser.select_containing_text("a", "PREV").unwrap().len(), 1);
+        assert_eq!(parser.select_matching_text("a", r"^Page \d+$").unwrap().len(), 1);
+        assert!(parser.select_matching_text("a", "(").is_err());
+    }
+
+    #[test]
+    fn test_meta_tags_tolerant_matching() {
+        let html = r#"
+        <head>
+            <meta name=" Description " content=" Messy description ">
+            <meta property="OG:Title" content="Shouting title">
+            <meta name="twitter:card" content="summary">
+        </head>
+        "#;
+
+        let parser = HtmlParser::new(html).unwrap();
+        assert_eq!(parser.meta_tag("description"), Some("Messy description".to_string()));
+        assert_eq!(parser.meta_property("og:title"), Some("Shouting title".to_string()));
+        assert_eq!(parser.meta_property("twitter:card"), Some("summary".to_string()));
+        assert_eq!(parser.select_by_attr_value("meta", "property", "og:TITLE").len(), 1);
+
+        let parser = HtmlParser::new(r#"<link rel="stylesheet" href="/s.css"><link rel="Canonical" href="/page">"#).unwrap();
+        assert_eq!(parser.canonical_url(), Some("/page".to_string()));
+        let parser = HtmlParser::new(r#"<link rel="CANONICAL alternate" href="/other">"#).unwrap();
+        assert_eq!(parser.canonical_url(), Some("/other".to_string()));
+        assert_eq!(HtmlParser::new(r#"<link rel="canonicals" href="/x">"#).unwrap().canonical_url(), None);
+    }
+
+    #[test]
+    fn test_inline_templates() {
+        let html = r#"
+        <product-card>
+            <template shadowrootmode="open">
+                <div class="price">$9.99</div>
+            </template>
+            <span slot="name">Widget</span>
+        </product-card>
+        <template id="row"><p class="row">Row</p></template>
+        "#;
+
+        let mut parser = HtmlParser::new(html).unwrap();
+        assert!(!parser.has_selector("product-card .price"));
+
+        parser.inline_templates();
+        assert_eq!(parser.select_first_text("product-card .price"), Some("$9.99".to_string()));
+        assert_eq!(parser.select_first_text("body > p.row"), Some("Row".to_string()));
+        assert_eq!(parser.count("template"), 0);
+    }
+
+    #[test]
+    fn test_resolve_url_and_iframes() {
+        let html = r#"
+        <iframe src="/widgets/reviews"></iframe>
+        <iframe src="https://other.example.org/embed"></iframe>
+        "#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/products/1").unwrap();
+        assert_eq!(parser.resolve_url("details"), Some("https://example.com/products/details".to_string()));
+        assert_eq!(
+            parser.iframes(),
+            vec!["https://example.com/widgets/reviews", "https://other.example.org/embed"]
+        );
+
+        let html = r#"<head><base href="https://cdn.example.com/assets/"></head>"#;
+        let parser = HtmlParser::with_base_url(html, "https://example.com/").unwrap();
+        assert_eq!(parser.resolve_url("logo.png"), Some("https://cdn.example.com/assets/logo.png".to_string()));
+
+        let parser = HtmlParser::new("<p>No base</p>").unwrap();
+        assert_eq!(parser.resolve_url("/relative"), None);
+    }
+
+    #[test]
+    fn test_feed_links() {
+        let html = r#"<head>
+        <link rel="alternate" type="application/rss+xml" href="/feed.xml">
+        <link rel="Alternate" type="application/atom+xml" href="https://example.com/atom">
+        <link rel="alternate" type="application/rss+xml" href="/feed.xml">
+        <link rel="alternate" type="text/html" hreflang="de" href="/de/">
+        <link rel="stylesheet" type="text/css" href="/style.css">
+        </head>"#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/blog/").unwrap();
+        assert_eq!(parser.feed_links(), vec!["https://example.com/feed.xml", "https://example.com/atom"]);
+    }
+
+    #[test]
+    fn test_image_infos_best_url() {
+        let html = r#"
+        <picture>
+            <source srcset="/img/hero-800.webp 800w, /img/hero-1600.webp 1600w" type="image/webp">
+            <img src="/img/hero.jpg" alt="Hero" srcset="/img/hero-400.jpg 400w">
+        </picture>
+        <img src="logo.png" srcset="logo.png, logo@2x.png 2x, logo@3x.png 3x">
+        <img src="plain.png">
+        "#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/shop/").unwrap();
+        let images = parser.image_infos();
+        assert_eq!(images.len(), 3);
+
+        let hero = &images[0];
+        assert_eq!(hero.alt, Some("Hero".to_string()));
+        assert_eq!(hero.candidates.len(), 3);
+        assert_eq!(hero.best_url(400, 1.0), Some("https://example.com/img/hero-400.jpg".to_string()));
+        assert_eq!(hero.best_url(400, 2.0), Some("https://example.com/img/hero-800.webp".to_string()));
+        assert_eq!(hero.best_url(2000, 1.0), Some("https://example.com/img/hero-1600.webp".to_string()));
+
+        let logo = &images[1];
+        assert_eq!(logo.best_url(100, 2.0), Some("https://example.com/shop/logo@2x.png".to_string()));
+        assert_eq!(logo.best_url(100, 1.0), Some("https://example.com/shop/logo.png".to_string()));
+
+        assert_eq!(images[2].best_url(100, 1.0), Some("https://example.com/shop/plain.png".to_string()));
+    }
+
+    #[test]
+    fn test_media() {
+        let html = r#"
+        <video src="/media/intro.mp4" poster="/media/intro.jpg">
+            <source src="/media/intro.webm" type="video/webm">
+            <track src="/media/intro.en.vtt" kind="captions" srclang="en" label="English">
+        </video>
+        <audio><source src="https://cdn.example.com/podcast.mp3"></audio>
+        <iframe src="https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?autoplay=1"></iframe>
+        <iframe src="https://player.vimeo.com/video/76979871"></iframe>
+        <iframe src="https://maps.example.com/embed"></iframe>
+        "#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/").unwrap();
+        let media = parser.media();
+        assert_eq!(media.len(), 4);
+
+        let video = &media[0];
+        assert_eq!(video.kind, MediaKind::Video);
+        assert_eq!(video.sources, vec!["https://example.com/media/intro.mp4", "https://example.com/media/intro.webm"]);
+        assert_eq!(video.poster, Some("https://example.com/media/intro.jpg".to_string()));
+        assert_eq!(video.tracks.len(), 1);
+        assert_eq!(video.tracks[0].kind, "captions");
+        assert_eq!(video.tracks[0].srclang, Some("en".to_string()));
+
+        assert_eq!(media[1].kind, MediaKind::Audio);
+        assert_eq!(media[1].sources, vec!["https://cdn.example.com/podcast.mp3"]);
+
+        let youtube = media[2].embed.as_ref().unwrap();
+        assert_eq!(youtube.provider, VideoProvider::YouTube);
+        assert_eq!(youtube.canonical_url, "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+
+        let vimeo = media[3].embed.as_ref().unwrap();
+        assert_eq!(vimeo.provider, VideoProvider::Vimeo);
+        assert_eq!(vimeo.id, "76979871");
+    }
+
+    #[test]
+    fn test_embedded_video_from_url() {
+        let video = EmbeddedVideo::from_url("https://youtu.be/abc123").unwrap();
+        assert_eq!(video.canonical_url, "https://www.youtube.com/watch?v=abc123");
+
+        let video = EmbeddedVideo::from_url("https://m.youtube.com/watch?v=xyz&t=10").unwrap();
+        assert_eq!(video.id, "xyz");
+
+        let video = EmbeddedVideo::from_url("https://vimeo.com/channels/staffpicks/123456").unwrap();
+        assert_eq!(video.id, "123456");
+
+        assert!(EmbeddedVideo::from_url("https://example.com/embed/abc").is_none());
+    }
+
+    #[test]
+    fn test_social_profiles() {
+        let html = r#"
+        <footer>
+            <a href="https://twitter.com/ferris?ref=footer">Twitter</a>
+            <a href="https://x.com/ferris/">X</a>
+            <a href="https://twitter.com/intent/tweet?text=hi">Share</a>
+            <a href="https://www.linkedin.com/company/ferrisfetcher/about/">LinkedIn</a>
+            <a href="https://www.facebook.com/sharer/sharer.php?u=x">Share</a>
+            <a href="https://instagram.com/ferris.rs">Instagram</a>
+            <a href="https://www.instagram.com/p/abc123/">Post</a>
+            <a href="https://github.com/M1tsumi/FerrisFetcher">Repo</a>
+            <a href="https://www.youtube.com/@ferris">YouTube</a>
+            <a href="/contact">Contact</a>
+        </footer>
+        "#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/").unwrap();
+        let profiles = parser.social_profiles();
+        assert_eq!(profiles.len(), 5);
+        assert_eq!(profiles[&SocialPlatform::Twitter], vec!["https://x.com/ferris"]);
+        assert_eq!(profiles[&SocialPlatform::LinkedIn], vec!["https://www.linkedin.com/company/ferrisfetcher"]);
+        assert_eq!(profiles[&SocialPlatform::Instagram], vec!["https://www.instagram.com/ferris.rs"]);
+        assert_eq!(profiles[&SocialPlatform::GitHub], vec!["https://github.com/M1tsumi"]);
+        assert_eq!(profiles[&SocialPlatform::YouTube], vec!["https://www.youtube.com/@ferris"]);
+        assert!(!profiles.contains_key(&SocialPlatform::Facebook));
+    }
+
+    #[test]
+    fn test_link_infos() {
+        let html = r#"<a href="/about" rel="nofollow">  About
+            us </a><a href="mailto:hi@example.com">Mail</a>"#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/").unwrap();
+        let links = parser.link_infos();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].url, "https://example.com/about");
+        assert_eq!(links[0].text, "About us");
+        assert_eq!(links[0].rel, Some("nofollow".to_string()));
+        assert_eq!(links[1].url, "mailto:hi@example.com");
+
+        let html = r#"<a href="https://Bücher.de/straße">Books</a><a href="/café">Café</a>"#;
+        let parser = HtmlParser::with_base_url(html, "https://xn--bcher-kva.de/").unwrap();
+        let links = parser.link_infos();
+        assert_eq!(links[0].url, "https://xn--bcher-kva.de/stra%C3%9Fe");
+        assert_eq!(links[0].display_url(), "https://bücher.de/straße");
+        assert_eq!(links[1].url, "https://xn--bcher-kva.de/caf%C3%A9");
+        assert_eq!(links[1].display_url(), "https://bücher.de/café");
+    }
+
+    #[test]
+    fn test_content_metrics() {
+        let html = r#"
+        <html>
+        <head><title>Ignored title</title><style>body { color: red; }</style></head>
+        <body>
+            <h1>Heading one</h1>
+            <h2>Sub heading</h2>
+            <h2>Another sub</h2>
+            <p>Some readable words here.</p>
+            <script>var hidden = "not counted";</script>
+            <a href="/about">About</a>
+            <a href="https://www.example.com/blog">Blog</a>
+            <a href="https://other.org/">Other</a>
+            <a href="mailto:a@example.com">Mail</a>
+        </body>
+        </html>
+        "#;
+
+        let parser = HtmlParser::with_base_url(html, "https://example.com/").unwrap();
+        assert_eq!(
+            parser.visible_text(),
+            "Heading one Sub heading Another sub Some readable words here. About Blog Other Mail"
+        );
+
+        let metrics = parser.content_metrics();
+        assert_eq!(metrics.word_count, 14);
+        assert!(metrics.reading_time_minutes > 0.0 && metrics.reading_time_minutes < 1.0);
+        assert!(metrics.text_to_html_ratio > 0.0 && metrics.text_to_html_ratio < 1.0);
+        assert_eq!(metrics.heading_counts.get("h1"), Some(&1));
+        assert_eq!(metrics.heading_counts.get("h2"), Some(&2));
+        assert_eq!(metrics.heading_counts.get("h3"), None);
+        assert_eq!(metrics.internal_links, 2);
+        assert_eq!(metrics.outbound_links, 1);
+    }
 }
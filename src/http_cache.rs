@@ -0,0 +1,378 @@
+//! HTTP response cache with conditional revalidation
+//!
+//! [`HttpCache`] stores successful GET responses keyed by URL, in a bounded
+//! in-memory LRU and optionally on disk. Responses are fresh for their
+//! `Cache-Control: max-age` (or until `Expires`); stale responses carrying an
+//! `ETag` or `Last-Modified` validator are revalidated with `If-None-Match` /
+//! `If-Modified-Since`, and a `304 Not Modified` answer reuses the stored body.
+//! Responses marked `no-store` are never cached.
+
+use crate::error::{FerrisFetcherError, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Response, ResponseBuilderExt, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Headers describing the transfer rather than the content, dropped when storing
+const HOP_HEADERS: &[&str] = &["content-encoding", "content-length", "transfer-encoding", "connection"];
+
+/// Freshness directives of a response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheDirectives {
+    /// Must not be stored
+    pub no_store: bool,
+    /// Must be revalidated before every use
+    pub no_cache: bool,
+    /// How long the response stays fresh
+    pub max_age: Option<Duration>,
+}
+
+impl CacheDirectives {
+    /// Read `Cache-Control`, falling back to `Expires` for the lifetime
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut directives = Self::default();
+        for value in headers.get_all("cache-control").iter().filter_map(|value| value.to_str().ok()) {
+            for directive in value.split(',').map(|directive| directive.trim().to_ascii_lowercase()) {
+                match directive.split_once('=') {
+                    Some(("max-age", seconds)) => {
+                        directives.max_age = seconds.trim_matches('"').parse().ok().map(Duration::from_secs);
+                    }
+                    None if directive == "no-store" => directives.no_store = true,
+                    None if directive == "no-cache" => directives.no_cache = true,
+                    _ => {}
+                }
+            }
+        }
+
+        if directives.max_age.is_none() {
+            let date = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).and_then(parse_http_date);
+            if let Some(expires) = date("expires") {
+                let now = date("date").unwrap_or_else(Utc::now);
+                directives.max_age = Some((expires - now).to_std().unwrap_or_default());
+            }
+        }
+        directives
+    }
+}
+
+/// A stored response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// Final URL of the response
+    pub url: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Response headers, without transfer headers
+    pub headers: Vec<(String, String)>,
+    /// Decoded response body
+    #[serde(skip)]
+    pub body: Vec<u8>,
+    /// When the response was stored or last revalidated
+    pub stored_at: DateTime<Utc>,
+}
+
+impl CachedResponse {
+    /// Capture a response whose body has already been read
+    pub fn new(url: &Url, status: u16, headers: &HeaderMap, body: Vec<u8>) -> Self {
+        let headers = headers
+            .iter()
+            .filter(|(name, _)| !HOP_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        Self { url: url.to_string(), status, headers, body, stored_at: Utc::now() }
+    }
+
+    /// Stored headers as a header map
+    pub fn header_map(&self) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+                map.append(name, value);
+            }
+        }
+        map
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    /// `ETag` validator, if any
+    pub fn etag(&self) -> Option<&str> {
+        self.header("etag")
+    }
+
+    /// `Last-Modified` validator, if any
+    pub fn last_modified(&self) -> Option<&str> {
+        self.header("last-modified")
+    }
+
+    /// Whether the response can be revalidated with a conditional request
+    pub fn has_validators(&self) -> bool {
+        self.etag().is_some() || self.last_modified().is_some()
+    }
+
+    /// Whether the response may be used without revalidation
+    pub fn is_fresh(&self) -> bool {
+        let directives = CacheDirectives::from_headers(&self.header_map());
+        if directives.no_cache {
+            return false;
+        }
+        let age = (Utc::now() - self.stored_at).to_std().unwrap_or_default();
+        directives.max_age.is_some_and(|max_age| age < max_age)
+    }
+
+    /// Headers turning a request into a conditional revalidation
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = self.etag().and_then(|etag| HeaderValue::from_str(etag).ok()) {
+            headers.insert(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = self.last_modified().and_then(|modified| HeaderValue::from_str(modified).ok()) {
+            headers.insert(reqwest::header::IF_MODIFIED_SINCE, modified);
+        }
+        headers
+    }
+
+    /// Merge the headers of a `304 Not Modified` answer and restart the freshness window
+    pub fn refresh(&mut self, headers: &HeaderMap) {
+        for name in headers.keys().filter(|name| !HOP_HEADERS.contains(&name.as_str())) {
+            self.headers.retain(|(key, _)| !key.eq_ignore_ascii_case(name.as_str()));
+            for value in headers.get_all(name).iter().filter_map(|value| value.to_str().ok()) {
+                self.headers.push((name.to_string(), value.to_string()));
+            }
+        }
+        self.stored_at = Utc::now();
+    }
+
+    /// Build a response serving the stored body
+    pub fn to_response(&self) -> Result<Response> {
+        let mut builder = http::Response::builder().status(self.status);
+        if let Ok(url) = Url::parse(&self.url) {
+            builder = builder.url(url);
+        }
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(self.header_map());
+            headers.insert(reqwest::header::CONTENT_LENGTH, HeaderValue::from(self.body.len()));
+        }
+        let response = builder
+            .body(self.body.clone())
+            .map_err(|e| FerrisFetcherError::NetworkError(format!("Invalid cached response: {}", e)))?;
+        Ok(Response::from(response))
+    }
+}
+
+/// A response stored on disk, next to its body file
+#[derive(Debug, Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    response: CachedResponse,
+}
+
+/// A response held in memory
+#[derive(Debug)]
+struct MemoryEntry {
+    response: CachedResponse,
+    last_used: Instant,
+}
+
+/// Bounded in-memory and optional on-disk HTTP response cache
+pub struct HttpCache {
+    entries: Mutex<HashMap<String, MemoryEntry>>,
+    max_entries: usize,
+    disk_dir: Option<PathBuf>,
+}
+
+impl fmt::Debug for HttpCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpCache")
+            .field("max_entries", &self.max_entries)
+            .field("disk_dir", &self.disk_dir)
+            .finish()
+    }
+}
+
+impl HttpCache {
+    /// Keep up to `max_entries` responses in memory
+    pub fn new(max_entries: usize) -> Self {
+        Self { entries: Mutex::new(HashMap::new()), max_entries: max_entries.max(1), disk_dir: None }
+    }
+
+    /// Also store responses in `dir`, surviving restarts
+    pub fn with_disk(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        self.disk_dir = Some(dir.as_ref().to_path_buf());
+        Ok(self)
+    }
+
+    /// Get the stored response for a URL, fresh or not
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        {
+            let mut entries = self.entries.lock().expect("http cache lock poisoned");
+            if let Some(entry) = entries.get_mut(url) {
+                entry.last_used = Instant::now();
+                return Some(entry.response.clone());
+            }
+        }
+
+        let (meta, body) = self.disk_paths(url)?;
+        let entry: DiskEntry = serde_json::from_slice(&std::fs::read(meta).ok()?).ok()?;
+        if entry.key != url {
+            return None;
+        }
+        let mut response = entry.response;
+        response.body = std::fs::read(body).ok()?;
+        debug!("Loaded cached response for {} from disk", url);
+        self.insert_memory(url, response.clone());
+        Some(response)
+    }
+
+    /// Store a response under a URL
+    pub fn insert(&self, url: &str, response: CachedResponse) -> Result<()> {
+        if let Some((meta, body)) = self.disk_paths(url) {
+            std::fs::write(body, &response.body)?;
+            let entry = DiskEntry { key: url.to_string(), response: response.clone() };
+            std::fs::write(meta, serde_json::to_vec(&entry)?)?;
+        }
+        self.insert_memory(url, response);
+        Ok(())
+    }
+
+    fn insert_memory(&self, url: &str, response: CachedResponse) {
+        let mut entries = self.entries.lock().expect("http cache lock poisoned");
+        if !entries.contains_key(url) && entries.len() >= self.max_entries {
+            let oldest = entries.iter().min_by_key(|(_, entry)| entry.last_used).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(url.to_string(), MemoryEntry { response, last_used: Instant::now() });
+    }
+
+    /// Drop the stored response for a URL
+    pub fn invalidate(&self, url: &str) {
+        self.entries.lock().expect("http cache lock poisoned").remove(url);
+        if let Some((meta, body)) = self.disk_paths(url) {
+            for path in [meta, body] {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        warn!("Failed to remove cached response {}: {}", path.display(), e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drop every stored response
+    pub fn clear(&self) -> Result<()> {
+        self.entries.lock().expect("http cache lock poisoned").clear();
+        if let Some(dir) = &self.disk_dir {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "json" || ext == "body") {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of responses held in memory
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("http cache lock poisoned").len()
+    }
+
+    /// Whether no responses are held in memory
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn disk_paths(&self, url: &str) -> Option<(PathBuf, PathBuf)> {
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        let name = format!("{:016x}", hasher.finish());
+        self.disk_dir.as_ref().map(|dir| (dir.join(format!("{}.json", name)), dir.join(format!("{}.body", name))))
+    }
+}
+
+/// Parse an HTTP date such as `Sun, 06 Nov 1994 08:49:37 GMT`
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim()).ok().map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs.iter().map(|(name, value)| (HeaderName::from_static(name), HeaderValue::from_static(value))).collect()
+    }
+
+    #[test]
+    fn test_cache_directives() {
+        let directives = CacheDirectives::from_headers(&headers(&[("cache-control", "public, max-age=60, no-cache")]));
+        assert_eq!(directives.max_age, Some(Duration::from_secs(60)));
+        assert!(directives.no_cache && !directives.no_store);
+        assert!(CacheDirectives::from_headers(&headers(&[("cache-control", "no-store")])).no_store);
+
+        let expires = CacheDirectives::from_headers(&headers(&[
+            ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+            ("expires", "Sun, 06 Nov 1994 08:50:37 GMT"),
+        ]));
+        assert_eq!(expires.max_age, Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_cached_response() {
+        let url = Url::parse("https://example.com/page").unwrap();
+        let mut cached = CachedResponse::new(
+            &url,
+            200,
+            &headers(&[("etag", "\"v1\""), ("content-encoding", "gzip"), ("cache-control", "max-age=0")]),
+            b"<html></html>".to_vec(),
+        );
+        assert!(cached.has_validators());
+        assert!(!cached.is_fresh());
+        assert_eq!(cached.conditional_headers()["if-none-match"], "\"v1\"");
+        assert!(cached.header("content-encoding").is_none());
+
+        cached.refresh(&headers(&[("cache-control", "max-age=600")]));
+        assert!(cached.is_fresh());
+        assert_eq!(cached.etag(), Some("\"v1\""));
+    }
+
+    #[tokio::test]
+    async fn test_cached_response_to_response() {
+        let url = Url::parse("https://example.com/page").unwrap();
+        let cached = CachedResponse::new(&url, 200, &headers(&[("content-type", "text/html")]), b"hello".to_vec());
+        let response = cached.to_response().unwrap();
+        assert_eq!(response.url().as_str(), "https://example.com/page");
+        assert_eq!(response.headers()["content-type"], "text/html");
+        assert_eq!(response.content_length(), Some(5));
+        assert_eq!(response.bytes().await.unwrap().as_ref(), b"hello");
+    }
+
+    #[test]
+    fn test_http_cache_disk() {
+        let dir = std::env::temp_dir().join(format!("ferrisfetcher-http-cache-{}", std::process::id()));
+        let url = Url::parse("https://example.com/a").unwrap();
+        let cache = HttpCache::new(1).with_disk(&dir).unwrap();
+        cache.insert(url.as_str(), CachedResponse::new(&url, 200, &HeaderMap::new(), b"body".to_vec())).unwrap();
+
+        let reopened = HttpCache::new(1).with_disk(&dir).unwrap();
+        assert_eq!(reopened.get(url.as_str()).unwrap().body, b"body");
+        assert_eq!(reopened.len(), 1);
+
+        reopened.invalidate(url.as_str());
+        assert!(reopened.get(url.as_str()).is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,112 @@
+//! Internationalized domain names and non-ASCII URLs
+//!
+//! URLs are requested, compared and stored in their ASCII form: hosts in
+//! punycode (`xn--bcher-kva.de`) and paths percent-encoded as UTF-8. The
+//! display form restores the Unicode host and decodes percent-encoded
+//! non-ASCII characters, as browsers show them.
+
+use percent_encoding::percent_decode_str;
+use url::{Host, Url};
+
+/// ASCII (punycode) form of a host name, lowercased; `None` if it is not a valid host
+pub fn host_to_ascii(host: &str) -> Option<String> {
+    match Host::parse(host.trim()).ok()? {
+        Host::Domain(domain) => Some(domain.to_lowercase()),
+        Host::Ipv4(address) => Some(address.to_string()),
+        Host::Ipv6(address) => Some(format!("[{}]", address)),
+    }
+}
+
+/// Unicode form of a host name, leaving labels that are not valid punycode as they are
+pub fn host_to_unicode(host: &str) -> String {
+    let (unicode, result) = idna::domain_to_unicode(host);
+    match result {
+        Ok(()) => unicode,
+        Err(_) => host.to_string(),
+    }
+}
+
+/// ASCII form of a URL, as sent in requests; `None` if it does not parse
+pub fn url_to_ascii(url: &str) -> Option<String> {
+    Url::parse(url.trim()).ok().map(String::from)
+}
+
+/// Human-readable form of a URL with a Unicode host and decoded non-ASCII characters
+///
+/// Percent-encoded ASCII such as `%20` or `%2F` stays encoded so the result
+/// still denotes the same URL. Text that is not an absolute URL is returned unchanged.
+pub fn url_to_display(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url.trim()) else { return url.to_string() };
+    let mut display = parsed.to_string();
+    if let Some(Host::Domain(ascii)) = parsed.host() {
+        let unicode = host_to_unicode(ascii);
+        // The host follows the scheme and any credentials
+        let host_start = display.find("://").map_or(0, |index| index + 3);
+        let host_start = display[host_start..].find('@').map_or(host_start, |index| host_start + index + 1);
+        if unicode != ascii && display[host_start..].starts_with(ascii) {
+            display.replace_range(host_start..host_start + ascii.len(), &unicode);
+        }
+    }
+    decode_non_ascii(&display)
+}
+
+/// Decode percent-encoded sequences that form non-ASCII UTF-8 characters
+fn decode_non_ascii(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('%') {
+        decoded.push_str(&rest[..start]);
+        let encoded_len = rest.as_bytes()[start..]
+            .chunks(3)
+            .take_while(|chunk| chunk.len() == 3 && chunk[0] == b'%' && chunk[1..].iter().all(u8::is_ascii_hexdigit))
+            .count()
+            * 3;
+        if encoded_len == 0 {
+            decoded.push('%');
+            rest = &rest[start + 1..];
+            continue;
+        }
+
+        let bytes: Vec<u8> = percent_decode_str(&rest[start..start + encoded_len]).collect();
+        for chunk in bytes.utf8_chunks() {
+            for c in chunk.valid().chars() {
+                if c.is_ascii() {
+                    decoded.push_str(&format!("%{:02X}", c as u8));
+                } else {
+                    decoded.push(c);
+                }
+            }
+            for byte in chunk.invalid() {
+                decoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        rest = &rest[start + encoded_len..];
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_forms() {
+        assert_eq!(host_to_ascii("Bücher.de").as_deref(), Some("xn--bcher-kva.de"));
+        assert_eq!(host_to_ascii("EXAMPLE.com").as_deref(), Some("example.com"));
+        assert_eq!(host_to_ascii("bad host"), None);
+        assert_eq!(host_to_unicode("xn--bcher-kva.de"), "bücher.de");
+        assert_eq!(host_to_unicode("example.com"), "example.com");
+    }
+
+    #[test]
+    fn test_url_forms() {
+        let ascii = url_to_ascii("https://bücher.de/straße?q=ü#teil").unwrap();
+        assert_eq!(ascii, "https://xn--bcher-kva.de/stra%C3%9Fe?q=%C3%BC#teil");
+        assert_eq!(url_to_display(&ascii), "https://bücher.de/straße?q=ü#teil");
+
+        // Encoded ASCII and invalid UTF-8 stay encoded
+        assert_eq!(url_to_display("https://example.com/a%20b%2Fc%FF%e2%82%ac"), "https://example.com/a%20b%2Fc%FF€");
+        assert_eq!(url_to_display("/relative/%C3%BC"), "/relative/%C3%BC");
+    }
+}
@@ -0,0 +1,543 @@
+//! Extraction from JSON API responses with JSONPath
+//!
+//! Rules of type [`ExtractionType::JsonPath`](crate::ExtractionType::JsonPath)
+//! hold a JSONPath expression in their `selector` and run on responses whose
+//! content type is JSON instead of the HTML rules. The supported syntax:
+//!
+//! | Expression             | Selects                                        |
+//! |------------------------|------------------------------------------------|
+//! | `$`                    | the document root (optional at the start)      |
+//! | `.name`, `['name']`    | an object member                               |
+//! | `[0]`, `[-1]`          | an array element, negative from the end        |
+//! | `.*`, `[*]`            | all members or elements                        |
+//! | `[1:3]`, `[::2]`       | an array slice                                 |
+//! | `['a','b']`, `[0,2]`   | several members or elements                    |
+//! | `..name`, `..*`        | matches at any depth                           |
+//! | `[?(@.price < 10)]`    | elements whose relative path compares true     |
+//! | `[?(@.sku)]`           | elements where the relative path exists        |
+//!
+//! Strings are extracted as they are, numbers and booleans in their JSON
+//! form, objects and arrays as compact JSON; `null` yields no value.
+
+use crate::error::{FerrisFetcherError, Result};
+use serde_json::Value;
+use std::cmp::Ordering;
+use url::Url;
+
+/// A compiled JSONPath expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    expression: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Child(Selector),
+    Descendant(Selector),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Wildcard,
+    Slice { start: Option<i64>, end: Option<i64>, step: i64 },
+    Union(Vec<Selector>),
+    Filter(Filter),
+}
+
+/// `[?(@.path op literal)]`, or an existence test without the comparison
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    path: Vec<Selector>,
+    comparison: Option<(Comparison, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl JsonPath {
+    /// Compile an expression
+    pub fn parse(expression: &str) -> Result<Self> {
+        let segments = PathParser::new(expression).parse().map_err(|message| {
+            FerrisFetcherError::ExtractionError(format!("Invalid JSONPath '{}': {}", expression, message))
+        })?;
+        Ok(Self { expression: expression.to_string(), segments })
+    }
+
+    /// The expression as written
+    pub fn as_str(&self) -> &str {
+        &self.expression
+    }
+
+    /// Values matched in `root`, in document order
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![root];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for value in current {
+                match segment {
+                    Segment::Child(selector) => apply(selector, value, &mut next),
+                    Segment::Descendant(selector) => {
+                        for descendant in descendants(value) {
+                            apply(selector, descendant, &mut next);
+                        }
+                    }
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+/// A JSON document with the URL it came from
+#[derive(Debug, Clone)]
+pub struct JsonExtractor {
+    document: Value,
+    base_url: Option<Url>,
+}
+
+impl JsonExtractor {
+    /// Wrap an already parsed document
+    pub fn new(document: Value) -> Self {
+        Self { document, base_url: None }
+    }
+
+    /// Parse a JSON document
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+
+    /// Parse a JSON document fetched from `url`
+    pub fn with_base_url(json: &str, url: &str) -> Result<Self> {
+        let mut extractor = Self::parse(json)?;
+        extractor.base_url = Some(Url::parse(url)?);
+        Ok(extractor)
+    }
+
+    /// Whether a response with this `Content-Type` holds JSON
+    pub fn is_json_content_type(content_type: &str) -> bool {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        mime == "application/json" || mime == "text/json" || mime.ends_with("+json")
+    }
+
+    /// The parsed document
+    pub fn document(&self) -> &Value {
+        &self.document
+    }
+
+    /// The URL the document was fetched from
+    pub fn base_url(&self) -> Option<&Url> {
+        self.base_url.as_ref()
+    }
+
+    /// Values matched by an expression
+    pub fn select(&self, expression: &str) -> Result<Vec<&Value>> {
+        Ok(JsonPath::parse(expression)?.select(&self.document))
+    }
+
+    /// Matched values as strings, dropping `null`s
+    pub fn select_strings(&self, expression: &str) -> Result<Vec<String>> {
+        Ok(self.select(expression)?.into_iter().filter_map(value_to_string).collect())
+    }
+}
+
+/// String form of an extracted value; `None` for `null`
+pub fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn apply<'a>(selector: &Selector, value: &'a Value, out: &mut Vec<&'a Value>) {
+    match selector {
+        Selector::Name(name) => out.extend(value.as_object().and_then(|object| object.get(name))),
+        Selector::Index(index) => {
+            if let Some(array) = value.as_array() {
+                let index = if *index < 0 { array.len() as i64 + index } else { *index };
+                out.extend(usize::try_from(index).ok().and_then(|index| array.get(index)));
+            }
+        }
+        Selector::Wildcard => out.extend(children(value)),
+        Selector::Slice { start, end, step } => {
+            if let Some(array) = value.as_array() {
+                out.extend(slice_indices(array.len(), *start, *end, *step).into_iter().map(|index| &array[index]));
+            }
+        }
+        Selector::Union(selectors) => {
+            for selector in selectors {
+                apply(selector, value, out);
+            }
+        }
+        Selector::Filter(filter) => out.extend(children(value).filter(|child| filter.matches(child))),
+    }
+}
+
+fn children(value: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match value {
+        Value::Array(array) => Box::new(array.iter()),
+        Value::Object(object) => Box::new(object.values()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+/// The value and everything nested in it, depth first
+fn descendants(value: &Value) -> Vec<&Value> {
+    fn collect<'a>(value: &'a Value, found: &mut Vec<&'a Value>) {
+        found.push(value);
+        for child in children(value) {
+            collect(child, found);
+        }
+    }
+    let mut found = Vec::new();
+    collect(value, &mut found);
+    found
+}
+
+fn slice_indices(len: usize, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let len = len as i64;
+    let normalize = |bound: i64| if bound < 0 { (len + bound).max(0) } else { bound.min(len) };
+    let mut indices = Vec::new();
+    if step > 0 {
+        let (mut index, end) = (start.map_or(0, normalize), end.map_or(len, normalize));
+        while index < end {
+            indices.push(index as usize);
+            index += step;
+        }
+    } else if step < 0 {
+        let mut index = start.map_or(len - 1, |start| normalize(start).min(len - 1));
+        let end = end.map_or(-1, normalize);
+        while index > end && index >= 0 {
+            indices.push(index as usize);
+            index += step;
+        }
+    }
+    indices
+}
+
+impl Filter {
+    fn matches(&self, value: &Value) -> bool {
+        let mut current = vec![value];
+        for selector in &self.path {
+            let mut next = Vec::new();
+            for value in current {
+                apply(selector, value, &mut next);
+            }
+            current = next;
+        }
+        match &self.comparison {
+            None => !current.is_empty(),
+            Some((comparison, literal)) => current.into_iter().any(|value| comparison.holds(value, literal)),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, value: &Value, literal: &Value) -> bool {
+        let ordering = match (value, literal) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (a, b) if a == b => Some(Ordering::Equal),
+            _ => None,
+        };
+        match self {
+            Comparison::Eq => ordering == Some(Ordering::Equal),
+            Comparison::Ne => ordering != Some(Ordering::Equal),
+            Comparison::Lt => ordering == Some(Ordering::Less),
+            Comparison::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Comparison::Gt => ordering == Some(Ordering::Greater),
+            Comparison::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+/// Recursive-descent parser over the characters of an expression
+struct PathParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+type ParseResult<T> = std::result::Result<T, String>;
+
+impl PathParser {
+    fn new(expression: &str) -> Self {
+        Self { chars: expression.trim().chars().collect(), pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> ParseResult<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at position {}", c, self.pos))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse(mut self) -> ParseResult<Vec<Segment>> {
+        if self.chars.is_empty() {
+            return Err("expression is empty".to_string());
+        }
+        // A leading name without `$.` is relative to the root
+        if !self.eat('$') && !matches!(self.peek(), Some('.' | '[')) {
+            let name = self.name()?;
+            let mut segments = vec![Segment::Child(Selector::Name(name))];
+            segments.extend(self.segments(true)?);
+            return Ok(segments);
+        }
+        self.segments(true)
+    }
+
+    /// Segments up to the end of the expression, or up to a filter's end if `!top_level`
+    fn segments(&mut self, top_level: bool) -> ParseResult<Vec<Segment>> {
+        let mut segments = Vec::new();
+        loop {
+            match self.peek() {
+                None => break,
+                Some('.') if self.chars.get(self.pos + 1) == Some(&'.') => {
+                    self.pos += 2;
+                    let selector = match self.peek() {
+                        Some('[') => self.bracket()?,
+                        Some('*') => {
+                            self.pos += 1;
+                            Selector::Wildcard
+                        }
+                        _ => Selector::Name(self.name()?),
+                    };
+                    segments.push(Segment::Descendant(selector));
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    if self.eat('*') {
+                        segments.push(Segment::Child(Selector::Wildcard));
+                    } else {
+                        segments.push(Segment::Child(Selector::Name(self.name()?)));
+                    }
+                }
+                Some('[') => segments.push(Segment::Child(self.bracket()?)),
+                Some(_) if !top_level => break,
+                Some(c) => return Err(format!("unexpected '{}' at position {}", c, self.pos)),
+            }
+        }
+        Ok(segments)
+    }
+
+    fn name(&mut self) -> ParseResult<String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '$') {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(format!("expected a member name at position {}", start));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn bracket(&mut self) -> ParseResult<Selector> {
+        self.expect('[')?;
+        self.skip_whitespace();
+        let selector = if self.eat('?') {
+            self.filter()?
+        } else if self.eat('*') {
+            Selector::Wildcard
+        } else {
+            let mut selectors = vec![self.bracket_item()?];
+            self.skip_whitespace();
+            while self.eat(',') {
+                self.skip_whitespace();
+                selectors.push(self.bracket_item()?);
+                self.skip_whitespace();
+            }
+            if selectors.len() == 1 { selectors.remove(0) } else { Selector::Union(selectors) }
+        };
+        self.skip_whitespace();
+        self.expect(']')?;
+        Ok(selector)
+    }
+
+    fn bracket_item(&mut self) -> ParseResult<Selector> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.quoted()?)),
+            _ => {
+                let start = self.integer()?;
+                if !self.eat(':') {
+                    return start.map(Selector::Index).ok_or_else(|| format!("expected an index at position {}", self.pos));
+                }
+                let end = self.integer()?;
+                let step = if self.eat(':') { self.integer()?.unwrap_or(1) } else { 1 };
+                if step == 0 {
+                    return Err("slice step cannot be 0".to_string());
+                }
+                Ok(Selector::Slice { start, end, step })
+            }
+        }
+    }
+
+    fn integer(&mut self) -> ParseResult<Option<i64>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        self.skip_whitespace();
+        if digits.is_empty() {
+            return Ok(None);
+        }
+        digits.parse().map(Some).map_err(|_| format!("invalid number '{}'", digits))
+    }
+
+    fn quoted(&mut self) -> ParseResult<String> {
+        let quote = self.peek().ok_or("expected a quoted string")?;
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None => return Err("unterminated string".to_string()),
+                Some('\\') => {
+                    self.pos += 1;
+                    text.extend(self.peek());
+                }
+                Some(c) if c == quote => break,
+                Some(c) => text.push(c),
+            }
+            self.pos += 1;
+        }
+        self.pos += 1;
+        Ok(text)
+    }
+
+    fn filter(&mut self) -> ParseResult<Selector> {
+        self.expect('(')?;
+        self.skip_whitespace();
+        self.expect('@')?;
+        let path = self.segments(false)?
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Child(selector) => Ok(selector),
+                Segment::Descendant(_) => Err("'..' is not supported in filters".to_string()),
+            })
+            .collect::<ParseResult<Vec<_>>>()?;
+        self.skip_whitespace();
+
+        let comparison = match self.comparison() {
+            Some(comparison) => {
+                self.skip_whitespace();
+                Some((comparison, self.literal()?))
+            }
+            None => None,
+        };
+        self.skip_whitespace();
+        self.expect(')')?;
+        Ok(Selector::Filter(Filter { path, comparison }))
+    }
+
+    fn comparison(&mut self) -> Option<Comparison> {
+        let two: String = self.chars.iter().skip(self.pos).take(2).collect();
+        let (comparison, len) = match two.as_str() {
+            "==" => (Comparison::Eq, 2),
+            "!=" => (Comparison::Ne, 2),
+            "<=" => (Comparison::Le, 2),
+            ">=" => (Comparison::Ge, 2),
+            _ if two.starts_with('<') => (Comparison::Lt, 1),
+            _ if two.starts_with('>') => (Comparison::Gt, 1),
+            _ => return None,
+        };
+        self.pos += len;
+        Some(comparison)
+    }
+
+    fn literal(&mut self) -> ParseResult<Value> {
+        if matches!(self.peek(), Some('\'' | '"')) {
+            return Ok(Value::String(self.quoted()?));
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace() && c != ')') {
+            self.pos += 1;
+        }
+        let token: String = self.chars[start..self.pos].iter().collect();
+        serde_json::from_str(&token)
+            .ok()
+            .filter(|value: &Value| !value.is_array() && !value.is_object())
+            .ok_or_else(|| format!("invalid literal '{}'", token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn strings(document: &Value, expression: &str) -> Vec<String> {
+        JsonPath::parse(expression).unwrap().select(document).into_iter().filter_map(value_to_string).collect()
+    }
+
+    #[test]
+    fn test_json_path_selection() {
+        let document = json!({
+            "store": {
+                "name": "Corner Shop",
+                "products": [
+                    {"sku": "a1", "title": "Tea", "price": 4.5, "tags": ["hot"]},
+                    {"sku": "b2", "title": "Cake", "price": 12, "discount": null},
+                    {"title": "Gift card", "price": 25},
+                ],
+            },
+            "total": 3,
+        });
+
+        assert_eq!(strings(&document, "$.store.name"), ["Corner Shop"]);
+        assert_eq!(strings(&document, "store['name']"), ["Corner Shop"]);
+        assert_eq!(strings(&document, "$.store.products[*].title"), ["Tea", "Cake", "Gift card"]);
+        assert_eq!(strings(&document, "$.store.products[-1].title"), ["Gift card"]);
+        assert_eq!(strings(&document, "$.store.products[0:2].sku"), ["a1", "b2"]);
+        assert_eq!(strings(&document, "$.store.products[::-1].price"), ["25", "12", "4.5"]);
+        assert_eq!(strings(&document, "$.store.products[0]['sku','price']"), ["a1", "4.5"]);
+        assert_eq!(strings(&document, "$..sku"), ["a1", "b2"]);
+        assert_eq!(strings(&document, "$.store.products[?(@.price < 20)].title"), ["Tea", "Cake"]);
+        assert_eq!(strings(&document, "$.store.products[?(@.sku)].title"), ["Tea", "Cake"]);
+        assert_eq!(strings(&document, "$.store.products[?(@.sku == 'b2')].price"), ["12"]);
+        assert_eq!(strings(&document, "$.store.products[0].tags"), [r#"["hot"]"#]);
+        assert!(strings(&document, "$.store.products[1].discount").is_empty());
+        assert_eq!(strings(&document, "$.total"), ["3"]);
+    }
+
+    #[test]
+    fn test_invalid_json_path() {
+        for expression in ["", "$.", "$[", "$.a[1:2:0]", "$[?(@.a == )]", "$.a b"] {
+            assert!(JsonPath::parse(expression).is_err(), "{} should not parse", expression);
+        }
+        assert!(JsonExtractor::is_json_content_type("application/json; charset=utf-8"));
+        assert!(JsonExtractor::is_json_content_type("application/ld+json"));
+        assert!(!JsonExtractor::is_json_content_type("text/html"));
+    }
+}
@@ -9,33 +9,152 @@
 //! 
 //! # Quick Start
 //! 
-//! ```rust
-//! use ferrisfetcher::{FerrisFetcher, Config};
+//! ```rust,no_run
+//! use ferrisfetcher::FerrisFetcher;
 //! 
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let fetcher = FerrisFetcher::new();
+//!     let fetcher = FerrisFetcher::new()?;
 //!     let result = fetcher.scrape("https://example.com").await?;
 //!     println!("Title: {}", result.title.unwrap_or_default());
 //!     Ok(())
 //! }
 //! ```
 
+// Lets the `ferrisfetcher::` paths generated by macros resolve inside this crate
+extern crate self as ferrisfetcher;
+
+pub mod accessibility;
+pub mod chunking;
+pub mod classify;
 pub mod client;
+pub mod clock;
 pub mod config;
+pub mod cookies;
+pub mod cost;
+pub mod crawl_plan;
+pub mod crawl_report;
+pub mod crawler;
 pub mod error;
+pub mod export;
 pub mod extractor;
+pub mod feeds;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fingerprint;
+pub mod health;
 pub mod html_parser;
+pub mod http_cache;
+pub mod idn;
+pub mod json_extractor;
+#[cfg(feature = "llm")]
+pub mod llm;
+pub mod memory;
+pub mod metadata;
+pub mod monitor;
+#[cfg(feature = "node")]
+pub mod node;
+pub mod notify;
+#[cfg(feature = "object-storage")]
+pub mod object_store;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod pool;
+#[cfg(feature = "database")]
+pub mod postgres;
+pub mod progress;
+pub mod projection;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod query;
+pub mod quota;
+pub mod rate_limiter;
+pub mod record_filter;
+#[cfg(feature = "hot-reload")]
+pub mod reload;
+pub mod repair;
+pub mod result_cache;
+pub mod robots;
+pub mod rule_dsl;
+pub mod schema;
 pub mod scraper;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod service;
+#[cfg(feature = "google-sheets")]
+pub mod sheets;
+pub mod template;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod text_analysis;
 pub mod types;
+pub mod url_source;
+pub mod url_template;
+pub mod validation;
+pub mod workspace;
+pub mod xlsx;
 
+pub use accessibility::AccessibilityReport;
+pub use chunking::TextChunk;
+pub use classify::{HeuristicClassifier, PageClassifier};
 pub use client::HttpClient;
+pub use clock::{Clock, ManualClock, RandomSource, SystemClock};
 pub use config::Config;
+pub use cookies::{CookieJar, CookieRecord};
+pub use cost::{BandwidthCost, CostModel, CostReport};
+pub use crawl_plan::{CrawlPlan, PlanProgress, PlannedPage};
+pub use crawl_report::CrawlReport;
+pub use crawler::{CrawlOptions, CrawledPage, Crawler, PageKind, TrailingSlash, WwwHandling};
 pub use error::{FerrisFetcherError, Result};
-pub use extractor::{DataExtractor, ExtractionRuleBuilder, presets};
-pub use html_parser::HtmlParser;
+pub use export::{export_stream, CsvExporter, Exporter, FileExporter, JsonLinesExporter, JsonlFormat, RecordFormat, RecordSink, ShardStrategy, ShardedExporter, WarcFormat, XmlFormat};
+pub use extractor::{DataExtractor, ExtractionOutcome, ExtractionRuleBuilder, RuleMigration, RuleSet, presets};
+pub use extractor::presets::Preset;
+pub use feeds::{Feed, FeedEntry, FeedFormat};
+pub use ferrisfetcher_macros::selector;
+pub use fingerprint::{fingerprint, TechStack};
+pub use health::{HealthPolicy, HealthTracker, HostHealth};
+pub use html_parser::{ContentMetrics, CssSelector, HtmlParser, ImageInfo, LinkInfo, MediaInfo, SocialPlatform};
+pub use http_cache::{CacheDirectives, CachedResponse, HttpCache};
+pub use json_extractor::{JsonExtractor, JsonPath};
+#[cfg(feature = "llm")]
+pub use llm::LlmExtractor;
+pub use memory::{Admission, MemoryBudget, MemoryKind, MemoryReservation};
+pub use metadata::{MetadataExtractor, MetaTagExtractor};
+pub use monitor::{DriftAlert, DriftMonitor, HitRates};
+pub use notify::{EmailNotifier, EventKind, MessageTemplate, NotificationEvent, Notifications, Notifier, SlackNotifier};
+#[cfg(feature = "object-storage")]
+pub use object_store::{ObjectStorageSink, StorageCredentials};
+#[cfg(feature = "parquet")]
+pub use parquet::ParquetExporter;
+pub use pool::FetcherPool;
+#[cfg(feature = "database")]
+pub use postgres::{PgExecutor, PostgresSink};
+pub use progress::{ProgressReport, ProgressReporter};
+pub use projection::{ProjectedJsonl, Projection};
+pub use query::{BatchProfile, FieldProfile, FieldType, GroupedResults, ResultSet};
+pub use quota::{MemoryQuotaStore, Quota, QuotaManager, QuotaStatus, QuotaStore, QuotaUsage};
+pub use rate_limiter::RateLimiter;
+pub use record_filter::{FilterChain, Filtered, RecordFilter};
+#[cfg(feature = "hot-reload")]
+pub use reload::RuleWatcher;
+pub use repair::{RuleAnchor, SelectorSuggestion};
+pub use result_cache::ResultCache;
+pub use robots::{RobotsCache, RobotsPolicy, RobotsRules};
+pub use schema::{ScrapedDataV1, SCHEMA_VERSION};
 pub use scraper::{FerrisFetcher, FerrisFetcherBuilder};
-pub use types::{ScrapedData, ExtractionRule, ExtractionType, RetryPolicy, HttpMethod, RequestStats, RateLimit};
+#[cfg(feature = "server")]
+pub use server::{JobFailure, JobState, JobSummary, ScrapeServer};
+pub use service::{ScraperService, ServiceCommand, ServiceEvent, ServiceHandle};
+#[cfg(feature = "google-sheets")]
+pub use sheets::SheetsSink;
+pub use template::{Template, TemplateFormat};
+pub use text_analysis::{KeywordAnalyzer, KeywordReport};
+pub use types::{AdaptiveRate, Aggregate, Discovery, ItemRule, ScrapedData, ScrapeOutcome, ResponseStatus, ScrapeWarning, WarningKind, ErrorHook, RetryDecision, RetryPredicate, RetryBudget, Jitter, PageType, PhaseTotals, StatsBucket, DomainStats, HostStats, ExtractionRule, ExtractionType, RetryPolicy, BatchOptions, BatchResults, HttpMethod, MetadataLevel, RequestStats, RequestTimings, RateLimit, RedirectHop, RuleCondition, RuleSetVersion, Transform};
+pub use url_source::{CsvColumn, RejectedUrl, UrlFormat, UrlSource};
+pub use url_template::UrlTemplate;
+pub use validation::{IssueSource, ValidationIssue, ValidationReport};
+pub use workspace::{CleanupPolicy, JobWorkspace, WorkDir};
+pub use xlsx::{SheetLayout, XlsxExporter};
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
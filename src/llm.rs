@@ -0,0 +1,262 @@
+//! LLM-assisted extraction for pages where selectors are too brittle
+//!
+//! [`LlmExtractor`] sends the cleaned page text and a field schema to an
+//! OpenAI-compatible chat completions endpoint and maps the JSON object it
+//! answers with onto extracted fields, in the same shape as
+//! [`DataExtractor::extract_all`](crate::extractor::DataExtractor::extract_all).
+
+use crate::error::{FerrisFetcherError, Result};
+use crate::html_parser::HtmlParser;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// A field the LLM is asked to extract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmField {
+    /// Name of the field in the extracted data
+    pub name: String,
+    /// What the field contains, passed to the model
+    pub description: String,
+    /// Whether the field holds a list of values
+    pub multiple: bool,
+}
+
+/// Extractor delegating to a user-configured LLM endpoint
+#[derive(Debug, Clone)]
+pub struct LlmExtractor {
+    client: reqwest::Client,
+    /// Chat completions URL (e.g. `https://api.openai.com/v1/chat/completions`)
+    endpoint: String,
+    /// Model name sent with each request
+    model: String,
+    /// Bearer token, if the endpoint requires one
+    api_key: Option<String>,
+    /// Fields to extract
+    fields: Vec<LlmField>,
+    /// Maximum number of page text characters sent to the model
+    max_input_chars: usize,
+    /// Request timeout
+    timeout: Duration,
+}
+
+impl LlmExtractor {
+    /// Create an extractor for a chat completions endpoint and model
+    pub fn new(endpoint: &str, model: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.to_string(),
+            model: model.to_string(),
+            api_key: None,
+            fields: Vec::new(),
+            max_input_chars: 12_000,
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Set the bearer token sent in the `Authorization` header
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Add a single-valued field to the schema
+    pub fn field(mut self, name: &str, description: &str) -> Self {
+        self.fields.push(LlmField {
+            name: name.to_string(),
+            description: description.to_string(),
+            multiple: false,
+        });
+        self
+    }
+
+    /// Add a list-valued field to the schema
+    pub fn list_field(mut self, name: &str, description: &str) -> Self {
+        self.fields.push(LlmField {
+            name: name.to_string(),
+            description: description.to_string(),
+            multiple: true,
+        });
+        self
+    }
+
+    /// Set the maximum number of page text characters sent to the model
+    pub fn max_input_chars(mut self, max: usize) -> Self {
+        self.max_input_chars = max;
+        self
+    }
+
+    /// Set the request timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get the configured fields
+    pub fn fields(&self) -> &[LlmField] {
+        &self.fields
+    }
+
+    /// Extract all configured fields from a parsed page
+    pub async fn extract_all(&self, parser: &HtmlParser) -> Result<HashMap<String, Vec<String>>> {
+        let text = parser.visible_text();
+        self.extract_text(&text).await
+    }
+
+    /// Extract all configured fields from already cleaned page text
+    pub async fn extract_text(&self, text: &str) -> Result<HashMap<String, Vec<String>>> {
+        if self.fields.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        debug!("Requesting {} fields from LLM endpoint {}", self.fields.len(), self.endpoint);
+
+        let mut request = self.client
+            .post(&self.endpoint)
+            .timeout(self.timeout)
+            .json(&self.request_body(text));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FerrisFetcherError::ExtractionError(
+                format!("LLM endpoint returned status {}", status)
+            ));
+        }
+
+        let body: Value = response.json().await?;
+        let results = self.parse_response(&body)?;
+        info!("LLM extracted data for {} fields", results.len());
+        Ok(results)
+    }
+
+    /// Build the chat completions request for the given page text
+    fn request_body(&self, text: &str) -> Value {
+        let schema: serde_json::Map<String, Value> = self.fields
+            .iter()
+            .map(|field| {
+                let kind = if field.multiple { "array of strings" } else { "string" };
+                (field.name.clone(), json!(format!("{} ({}, null if absent)", field.description, kind)))
+            })
+            .collect();
+
+        let text = match text.char_indices().nth(self.max_input_chars) {
+            Some((end, _)) => &text[..end],
+            None => text,
+        };
+
+        json!({
+            "model": self.model,
+            "temperature": 0,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": "You extract structured data from web pages. Answer with a single JSON object \
+                                containing exactly the requested keys and nothing else. Only use information \
+                                present in the page text.",
+                },
+                {
+                    "role": "user",
+                    "content": format!("Fields:\n{}\n\nPage text:\n{}", Value::Object(schema), text),
+                },
+            ],
+        })
+    }
+
+    /// Map a chat completions response onto the configured fields
+    fn parse_response(&self, body: &Value) -> Result<HashMap<String, Vec<String>>> {
+        let content = body["choices"][0]["message"]["content"]
+            .as_str()
+            .ok_or_else(|| FerrisFetcherError::ExtractionError(
+                "LLM response contains no message content".to_string()
+            ))?;
+
+        // Models often wrap JSON answers in a Markdown code fence
+        let content = content.trim();
+        let content = content
+            .strip_prefix("```json")
+            .or_else(|| content.strip_prefix("```"))
+            .and_then(|inner| inner.strip_suffix("```"))
+            .unwrap_or(content);
+
+        let answer: Value = serde_json::from_str(content.trim())?;
+        let answer = answer.as_object().ok_or_else(|| FerrisFetcherError::ExtractionError(
+            "LLM response is not a JSON object".to_string()
+        ))?;
+
+        let mut results = HashMap::new();
+        for field in &self.fields {
+            let values: Vec<String> = match answer.get(&field.name) {
+                Some(Value::Array(items)) => items.iter().filter_map(Self::value_text).collect(),
+                Some(value) => Self::value_text(value).into_iter().collect(),
+                None => Vec::new(),
+            };
+
+            let values = if field.multiple { values } else { values.into_iter().take(1).collect() };
+            if !values.is_empty() {
+                results.insert(field.name.clone(), values);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Text of a scalar JSON value, skipping nulls and empty strings
+    fn value_text(value: &Value) -> Option<String> {
+        match value {
+            Value::String(text) => Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+            Value::Number(number) => Some(number.to_string()),
+            Value::Bool(flag) => Some(flag.to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extractor() -> LlmExtractor {
+        LlmExtractor::new("http://localhost/v1/chat/completions", "test-model")
+            .field("price", "Product price including currency")
+            .list_field("features", "Bullet-point product features")
+            .max_input_chars(10)
+    }
+
+    #[test]
+    fn test_request_body() {
+        let body = extractor().request_body("Widget costs $10 and ships free");
+        assert_eq!(body["model"], "test-model");
+
+        let prompt = body["messages"][1]["content"].as_str().unwrap();
+        assert!(prompt.contains("\"price\""));
+        assert!(prompt.contains("array of strings"));
+        assert!(prompt.ends_with("Widget cos"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = json!({
+            "choices": [{
+                "message": {
+                    "content": "```json\n{\"price\": \"$10\", \"features\": [\"Free shipping\", null, 2]}\n```"
+                }
+            }]
+        });
+
+        let results = extractor().parse_response(&body).unwrap();
+        assert_eq!(results.get("price").unwrap(), &vec!["$10".to_string()]);
+        assert_eq!(results.get("features").unwrap(), &vec!["Free shipping".to_string(), "2".to_string()]);
+
+        let body = json!({ "choices": [{ "message": { "content": "{\"price\": null}" } }] });
+        assert!(extractor().parse_response(&body).unwrap().is_empty());
+
+        let body = json!({ "choices": [] });
+        assert!(extractor().parse_response(&body).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Global memory budget for in-flight and retained scrape data
+//!
+//! [`MemoryBudget`] keeps an approximate count of bytes held in response
+//! bodies being processed, batch results waiting to be returned and cached
+//! results. While the count exceeds the limit, new scrapes are only admitted
+//! once running ones finish, so bursts of heavy pages degrade to serial
+//! scraping instead of exhausting memory.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tracing::debug;
+
+/// What a reservation holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryKind {
+    /// Response bodies of scrapes in progress
+    Body,
+    /// Finished results queued until a batch returns
+    Result,
+    /// Results kept in a [`crate::ResultCache`]
+    Cache,
+}
+
+impl MemoryKind {
+    fn slot(self) -> usize {
+        match self {
+            MemoryKind::Body => 0,
+            MemoryKind::Result => 1,
+            MemoryKind::Cache => 2,
+        }
+    }
+}
+
+/// Shared, approximate memory budget
+#[derive(Debug)]
+pub struct MemoryBudget {
+    limit: usize,
+    used: [AtomicUsize; 3],
+    active: AtomicUsize,
+    released: Notify,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl MemoryBudget {
+    /// Pause intake while more than `limit` bytes are held
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: Default::default(),
+            active: AtomicUsize::new(0),
+            released: Notify::new(),
+        }
+    }
+
+    /// Track usage without ever pausing intake
+    pub fn unlimited() -> Self {
+        Self::new(usize::MAX)
+    }
+
+    /// Configured limit in bytes
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Bytes currently held
+    pub fn used(&self) -> usize {
+        self.used.iter().map(|used| used.load(Ordering::Acquire)).sum()
+    }
+
+    /// Bytes currently held of one kind
+    pub fn used_by(&self, kind: MemoryKind) -> usize {
+        self.used[kind.slot()].load(Ordering::Acquire)
+    }
+
+    /// Whether more than the limit is held
+    pub fn is_exceeded(&self) -> bool {
+        self.used() > self.limit
+    }
+
+    /// Number of admitted scrapes still running
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Account for `bytes` until the returned reservation is dropped
+    ///
+    /// Never waits: data that already exists is always accounted for.
+    pub fn reserve(self: &Arc<Self>, kind: MemoryKind, bytes: usize) -> MemoryReservation {
+        self.used[kind.slot()].fetch_add(bytes, Ordering::AcqRel);
+        MemoryReservation { budget: Arc::clone(self), kind, bytes }
+    }
+
+    /// Wait until a new scrape may start
+    ///
+    /// While the budget is exceeded this waits for running scrapes to finish;
+    /// once none is running a scrape is admitted regardless, so retained
+    /// results and caches cannot stall intake forever.
+    pub async fn admit(self: &Arc<Self>) -> Admission {
+        let mut paused = false;
+        loop {
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+
+            if !self.is_exceeded() || self.active() == 0 {
+                break;
+            }
+            if !paused {
+                debug!("Memory budget exceeded ({} of {} bytes), pausing intake", self.used(), self.limit);
+                paused = true;
+            }
+            released.await;
+        }
+
+        self.active.fetch_add(1, Ordering::AcqRel);
+        Admission { budget: Arc::clone(self) }
+    }
+}
+
+/// Bytes accounted for in a [`MemoryBudget`], released on drop
+#[derive(Debug)]
+pub struct MemoryReservation {
+    budget: Arc<MemoryBudget>,
+    kind: MemoryKind,
+    bytes: usize,
+}
+
+impl MemoryReservation {
+    /// Bytes held by this reservation
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.budget.used[self.kind.slot()].fetch_sub(self.bytes, Ordering::AcqRel);
+        self.budget.released.notify_waiters();
+    }
+}
+
+/// A running scrape admitted by [`MemoryBudget::admit`], finished on drop
+#[derive(Debug)]
+pub struct Admission {
+    budget: Arc<MemoryBudget>,
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        self.budget.active.fetch_sub(1, Ordering::AcqRel);
+        self.budget.released.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_memory_budget_pauses_intake() {
+        let budget = Arc::new(MemoryBudget::new(100));
+        let first = budget.admit().await;
+        let body = budget.reserve(MemoryKind::Body, 150);
+        let result = budget.reserve(MemoryKind::Result, 20);
+        assert_eq!(budget.used(), 170);
+        assert_eq!(budget.used_by(MemoryKind::Body), 150);
+        assert!(budget.is_exceeded());
+
+        let waiting = tokio::spawn({
+            let budget = Arc::clone(&budget);
+            async move { budget.admit().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        drop(body);
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+        assert_eq!(budget.active(), 1);
+
+        // Retained results alone never block the only scrape
+        drop(second);
+        let _cache = budget.reserve(MemoryKind::Cache, 500);
+        let _third = tokio::time::timeout(Duration::from_secs(1), budget.admit()).await.unwrap();
+        drop(result);
+        assert_eq!(budget.used(), 500);
+    }
+}
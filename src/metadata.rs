@@ -0,0 +1,92 @@
+//! Custom metadata extraction
+//!
+//! [`MetadataExtractor`]s registered with
+//! [`Config::with_metadata_extractor`](crate::Config::with_metadata_extractor)
+//! run after the built-in metadata extraction on every scrape, whatever the
+//! [`MetadataLevel`](crate::MetadataLevel), and add their entries to
+//! [`ScrapedData::metadata`], replacing built-in entries of the same name.
+
+use crate::error::Result;
+use crate::html_parser::HtmlParser;
+use crate::types::ScrapedData;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Contributes extra metadata entries for a scraped page
+pub trait MetadataExtractor: Debug + Send + Sync {
+    /// Name reported when the extractor fails
+    fn name(&self) -> &str;
+
+    /// Compute metadata entries from the parsed document and the data scraped so far
+    fn extract(&self, parser: &HtmlParser, data: &ScrapedData) -> Result<HashMap<String, Value>>;
+}
+
+/// Records the content of selected `<meta>` tags, matched by `name` or `property`
+///
+/// Names ending in `*` match every tag with that prefix, e.g. `"acme:*"` for
+/// company-specific tags.
+#[derive(Debug, Clone, Default)]
+pub struct MetaTagExtractor {
+    names: Vec<String>,
+}
+
+impl MetaTagExtractor {
+    /// Record the tags with the given names
+    pub fn new<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { names: names.into_iter().map(Into::into).collect() }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.names.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        })
+    }
+}
+
+impl MetadataExtractor for MetaTagExtractor {
+    fn name(&self) -> &str {
+        "meta_tags"
+    }
+
+    fn extract(&self, parser: &HtmlParser, _data: &ScrapedData) -> Result<HashMap<String, Value>> {
+        let mut entries = HashMap::new();
+        for element in parser.select("meta[content]")? {
+            let tag = element.value();
+            let Some(name) = tag.attr("name").or_else(|| tag.attr("property")) else { continue };
+            if self.matches(name) {
+                let content = tag.attr("content").unwrap_or_default();
+                entries.entry(name.to_string()).or_insert_with(|| Value::from(content));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meta_tag_extractor() {
+        let html = r#"<head>
+            <meta name="author" content="Ada">
+            <meta property="acme:team" content="search">
+            <meta property="acme:tier" content="gold">
+            <meta name="robots" content="noindex">
+        </head>"#;
+        let parser = HtmlParser::new(html).unwrap();
+        let data = ScrapedData::new("https://example.com/".to_string());
+
+        let entries = MetaTagExtractor::new(["author", "acme:*"]).extract(&parser, &data).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries["author"], "Ada");
+        assert_eq!(entries["acme:tier"], "gold");
+        assert!(!entries.contains_key("robots"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Selector drift monitoring
+//!
+//! A [`DriftMonitor`] periodically scrapes a small sample of known URLs for
+//! each watched rule set and compares every rule's hit rate (the share of
+//! sampled pages where it extracted a value) against a baseline. When a rule's
+//! hit rate drops by more than the tolerance, a [`DriftAlert`] is returned and
+//! a [`NotificationEvent::SelectorDrift`] is dispatched, so site redesigns are
+//! caught before a full crawl comes back empty.
+//!
+//! The baseline of a rule set is taken from its first successful check unless
+//! one is supplied with [`DriftMonitor::with_baseline`].
+
+use crate::notify::{NotificationEvent, Notifications};
+use crate::query::ResultSet;
+use crate::scraper::FerrisFetcher;
+use crate::types::BatchOptions;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Default drop in hit rate that raises an alert
+const DEFAULT_TOLERANCE: f64 = 0.2;
+
+/// Hit rate (0.0-1.0) per rule name
+pub type HitRates = BTreeMap<String, f64>;
+
+/// A rule whose hit rate fell below its baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftAlert {
+    /// Name of the watched rule set
+    pub rule_set: String,
+    /// Rule that drifted
+    pub rule: String,
+    /// Hit rate in the baseline
+    pub baseline: f64,
+    /// Hit rate in the latest sample
+    pub current: f64,
+    /// Sampled pages the hit rate is based on
+    pub sampled: usize,
+}
+
+/// A rule set, the fetcher applying it and its sample URLs
+#[derive(Debug)]
+struct Watched {
+    name: String,
+    fetcher: FerrisFetcher,
+    urls: Vec<String>,
+    baseline: Option<HitRates>,
+}
+
+/// Scrapes sample URLs per rule set and alerts when rules stop matching
+#[derive(Debug)]
+pub struct DriftMonitor {
+    watched: Vec<Watched>,
+    notifications: Notifications,
+    tolerance: f64,
+}
+
+impl DriftMonitor {
+    /// Create a monitor dispatching alerts through `notifications`
+    pub fn new(notifications: Notifications) -> Self {
+        Self { watched: Vec::new(), notifications, tolerance: DEFAULT_TOLERANCE }
+    }
+
+    /// Watch the rules of `fetcher` on a sample of URLs, under a name used in alerts
+    pub fn watch(mut self, name: &str, fetcher: FerrisFetcher, urls: &[&str]) -> Self {
+        self.watched.push(Watched {
+            name: name.to_string(),
+            fetcher,
+            urls: urls.iter().map(|url| url.to_string()).collect(),
+            baseline: None,
+        });
+        self
+    }
+
+    /// Use known hit rates as the baseline of a watched rule set
+    pub fn with_baseline(mut self, name: &str, baseline: HitRates) -> Self {
+        if let Some(watched) = self.watched.iter_mut().find(|watched| watched.name == name) {
+            watched.baseline = Some(baseline);
+        }
+        self
+    }
+
+    /// Alert when a hit rate drops by more than `tolerance` (0.0-1.0) from the baseline
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Baseline hit rates of a watched rule set, once known
+    pub fn baseline(&self, name: &str) -> Option<&HitRates> {
+        self.watched.iter().find(|watched| watched.name == name)?.baseline.as_ref()
+    }
+
+    /// Take the next check of a rule set as its new baseline, e.g. after updating its rules
+    pub fn reset_baseline(&mut self, name: &str) {
+        if let Some(watched) = self.watched.iter_mut().find(|watched| watched.name == name) {
+            watched.baseline = None;
+        }
+    }
+
+    /// Sample every watched rule set once, dispatching and returning the alerts raised
+    pub async fn check(&mut self) -> Vec<DriftAlert> {
+        let mut alerts = Vec::new();
+        for watched in &mut self.watched {
+            let Some((rates, sampled)) = sample(watched).await else { continue };
+            let Some(baseline) = &watched.baseline else {
+                info!("Recorded drift baseline for {} from {} pages", watched.name, sampled);
+                watched.baseline = Some(rates);
+                continue;
+            };
+
+            for (rule, &current) in &rates {
+                let expected = baseline.get(rule).copied().unwrap_or(current);
+                if expected - current > self.tolerance {
+                    warn!("Rule {} of {} hit {:.0}% of sampled pages, baseline {:.0}%",
+                          rule, watched.name, current * 100.0, expected * 100.0);
+                    alerts.push(DriftAlert {
+                        rule_set: watched.name.clone(),
+                        rule: rule.clone(),
+                        baseline: expected,
+                        current,
+                        sampled,
+                    });
+                }
+            }
+        }
+
+        for alert in &alerts {
+            self.notifications.dispatch(&NotificationEvent::SelectorDrift(alert.clone())).await;
+        }
+        alerts
+    }
+
+    /// Check every `interval` until the task is dropped or aborted
+    pub async fn run(&mut self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let alerts = self.check().await;
+            debug!("Drift check raised {} alerts", alerts.len());
+        }
+    }
+}
+
+/// Scrape a rule set's sample, returning each rule's hit rate and the pages
+/// they are based on; pages that failed to load are left out
+async fn sample(watched: &Watched) -> Option<(HitRates, usize)> {
+    let urls: Vec<&str> = watched.urls.iter().map(String::as_str).collect();
+    let pages: ResultSet = watched.fetcher
+        .scrape_outcomes(&urls, &BatchOptions::default())
+        .await
+        .into_iter()
+        .filter_map(|outcome| outcome.into_data())
+        .filter(|data| data.status().is_success())
+        .collect();
+    if pages.is_empty() {
+        warn!("No sample page of {} could be scraped, skipping the drift check", watched.name);
+        return None;
+    }
+
+    let rules: Vec<String> = watched.fetcher.extraction_rules().keys().cloned().collect();
+    let profile = pages.profile_fields(rules.iter().map(String::as_str));
+    let rates = profile.fields.into_iter().map(|field| (field.field, field.fill_rate)).collect();
+    Some((rates, pages.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::error::Result;
+    use crate::extractor::ExtractionRuleBuilder;
+    use crate::notify::{EventKind, Notifier};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use crate::test_support::{serve, StubResponse};
+
+    #[derive(Debug, Default)]
+    struct RecordingNotifier {
+        subjects: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, subject: &str, _body: &str) -> Result<()> {
+            self.subjects.lock().unwrap().push(subject.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drift_monitor() {
+        let redesigned = Arc::new(AtomicBool::new(false));
+        let serving = Arc::clone(&redesigned);
+        let base = serve(move |_| {
+            StubResponse::ok(if serving.load(Ordering::SeqCst) {
+                r#"<html><h1>Mug</h1><span class="cost">10</span></html>"#
+            } else {
+                r#"<html><h1>Mug</h1><span class="price">10</span></html>"#
+            })
+        });
+
+        let rules = vec![
+            ExtractionRuleBuilder::new("name", "h1").build(),
+            ExtractionRuleBuilder::new("price", ".price").build(),
+        ];
+        let fetcher = FerrisFetcher::with_config_and_rules(Config::default().without_rate_limit(), rules).unwrap();
+        let recorder = Arc::new(RecordingNotifier::default());
+        let notifications = Notifications::new().on(EventKind::SelectorDrift, recorder.clone());
+        let urls = [format!("{}/1", base), format!("{}/2", base)];
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let mut monitor = DriftMonitor::new(notifications).watch("shop", fetcher, &urls);
+
+        // The first check records the baseline
+        assert!(monitor.check().await.is_empty());
+        assert_eq!(monitor.baseline("shop").unwrap()["price"], 1.0);
+        assert!(monitor.check().await.is_empty());
+
+        redesigned.store(true, Ordering::SeqCst);
+        let alerts = monitor.check().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!((alerts[0].rule.as_str(), alerts[0].baseline, alerts[0].current, alerts[0].sampled), ("price", 1.0, 0.0, 2));
+        assert_eq!(recorder.subjects.lock().unwrap().as_slice(), ["Selector drift in shop: price"]);
+
+        monitor.reset_baseline("shop");
+        assert!(monitor.check().await.is_empty());
+        assert_eq!(monitor.baseline("shop").unwrap()["price"], 0.0);
+    }
+}
@@ -0,0 +1,302 @@
+//! Node.js bindings (`node` feature)
+//!
+//! Exposes [`FerrisFetcher`] to JavaScript through napi-rs, so Node backends
+//! can scrape static pages without driving a headless browser. Build the
+//! addon with `cargo rustc --release --lib --features node --crate-type cdylib`
+//! and load the resulting library renamed to `ferrisfetcher.node`.
+//!
+//! ```js
+//! const { FerrisFetcher } = require('./ferrisfetcher.node');
+//!
+//! const fetcher = new FerrisFetcher({ maxConcurrent: 8, rules: [{ name: 'price', selector: '.price', multiple: true }] });
+//! fetcher.addRules('links: a[href] @attr(href) all');
+//! const page = await fetcher.scrape('https://example.com');
+//! await fetcher.scrapeStream(urls, (err, page) => {
+//!   if (err) console.error(err.message); else console.log(page.url, page.extracted_data.price);
+//! });
+//! ```
+//!
+//! Methods are camelCased; pages and statistics are plain objects with the
+//! same fields as their JSON serialization. Scrapes run on the addon's Tokio
+//! runtime and return promises. Invalid options and rules throw with status
+//! `InvalidArg`; failed scrapes reject with the error message.
+
+use crate::config::Config;
+use crate::error::FerrisFetcherError;
+use crate::extractor::ExtractionRuleBuilder;
+use crate::rule_dsl;
+use crate::scraper::FerrisFetcher;
+use crate::types::{ExtractionRule, ExtractionType};
+use futures::StreamExt;
+use napi::bindgen_prelude::within_runtime_if_available;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Status};
+use napi_derive::napi;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Throw configuration errors as `InvalidArg` and the rest as generic failures
+fn to_napi_err(error: FerrisFetcherError) -> Error {
+    match error {
+        FerrisFetcherError::ConfigError(_) => Error::new(Status::InvalidArg, error.to_string()),
+        error => Error::from_reason(error.to_string()),
+    }
+}
+
+/// Convert a value to a JavaScript object through its JSON serialization
+fn to_js<T: Serialize>(value: &T) -> napi::Result<Value> {
+    serde_json::to_value(value).map_err(|error| Error::from_reason(error.to_string()))
+}
+
+/// Options of the `FerrisFetcher` constructor
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct FetcherOptions {
+    /// User agent sent with every request
+    pub user_agent: Option<String>,
+    /// Request timeout in milliseconds
+    pub timeout_ms: Option<u32>,
+    /// Maximum number of requests in flight
+    pub max_concurrent: Option<u32>,
+    /// Whether to rate limit requests, on by default
+    pub rate_limit: Option<bool>,
+    /// Headers sent with every request
+    pub headers: Option<HashMap<String, String>>,
+    /// Seed for reproducible jitter and scheduling
+    pub seed: Option<u32>,
+    /// Initial extraction rules
+    pub rules: Option<Vec<Rule>>,
+}
+
+impl FetcherOptions {
+    fn into_config(self) -> crate::error::Result<Config> {
+        let mut config = Config::default();
+        if let Some(user_agent) = self.user_agent {
+            config = config.with_user_agent(user_agent);
+        }
+        if let Some(timeout) = self.timeout_ms {
+            config = config.with_timeout(Duration::from_millis(timeout.into()));
+        }
+        if let Some(max) = self.max_concurrent {
+            config = config.with_max_concurrent_requests(max as usize);
+        }
+        if self.rate_limit == Some(false) {
+            config = config.without_rate_limit();
+        }
+        for (name, value) in self.headers.unwrap_or_default() {
+            config = config.with_header(&name, &value)?;
+        }
+        if let Some(seed) = self.seed {
+            config = config.with_seed(seed.into());
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Extraction rule given as `{ name, selector, attribute?, multiple?, html?, group? }`
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// Name the extracted values are stored under
+    pub name: String,
+    /// CSS selector
+    pub selector: String,
+    /// Attribute to extract instead of the text
+    pub attribute: Option<String>,
+    /// Whether to extract every match instead of the first
+    pub multiple: Option<bool>,
+    /// Whether to extract the inner HTML instead of the text
+    pub html: Option<bool>,
+    /// Group to add the rule to
+    pub group: Option<String>,
+}
+
+impl From<Rule> for ExtractionRule {
+    fn from(rule: Rule) -> Self {
+        let mut builder = ExtractionRuleBuilder::new(&rule.name, &rule.selector).multiple(rule.multiple.unwrap_or(false));
+        if let Some(attribute) = &rule.attribute {
+            builder = builder.extraction_type(ExtractionType::Attribute).attribute(attribute);
+        } else if rule.html == Some(true) {
+            builder = builder.extraction_type(ExtractionType::Html);
+        }
+        if let Some(group) = &rule.group {
+            builder = builder.group(group);
+        }
+        builder.build()
+    }
+}
+
+/// Scrape `urls` concurrently, passing each page or error to `emit` as it
+/// finishes, and return the number of pages scraped
+async fn stream_pages(fetcher: FerrisFetcher, urls: Vec<String>, mut emit: impl FnMut(napi::Result<Value>)) -> u32 {
+    let mut results = std::pin::pin!(fetcher.scrape_stream(futures::stream::iter(urls)));
+    let mut scraped = 0;
+    while let Some(result) = results.next().await {
+        emit(match result {
+            Ok(data) => {
+                scraped += 1;
+                to_js(&data)
+            }
+            Err(error) => Err(to_napi_err(error)),
+        });
+    }
+    scraped
+}
+
+/// Scraper exported to JavaScript as `FerrisFetcher`
+///
+/// Rule changes apply to scrapes started after them.
+#[napi(js_name = "FerrisFetcher")]
+pub struct JsFetcher {
+    fetcher: Mutex<FerrisFetcher>,
+}
+
+impl JsFetcher {
+    /// A handle to the fetcher for one call
+    fn handle(&self) -> FerrisFetcher {
+        self.fetcher.lock().expect("fetcher lock poisoned").handle()
+    }
+}
+
+#[napi]
+impl JsFetcher {
+    /// Create a fetcher, `new FerrisFetcher(options?)`
+    #[napi(constructor)]
+    pub fn new(options: Option<FetcherOptions>) -> napi::Result<Self> {
+        let mut options = options.unwrap_or_default();
+        let rules = options.rules.take().unwrap_or_default().into_iter().map(ExtractionRule::from).collect();
+        let config = options.into_config().map_err(to_napi_err)?;
+        let fetcher = within_runtime_if_available(|| FerrisFetcher::with_config_and_rules(config, rules)).map_err(to_napi_err)?;
+        Ok(Self { fetcher: Mutex::new(fetcher) })
+    }
+
+    /// Add a rule, replacing any rule of the same name
+    #[napi]
+    pub fn add_rule(&self, rule: Rule) {
+        self.fetcher.lock().expect("fetcher lock poisoned").add_extraction_rule(rule.into());
+    }
+
+    /// Add rules written in the rule DSL, one per line
+    #[napi]
+    pub fn add_rules(&self, text: String) -> napi::Result<()> {
+        let rules = rule_dsl::parse_rules(&text).map_err(to_napi_err)?;
+        let mut fetcher = self.fetcher.lock().expect("fetcher lock poisoned");
+        for rule in rules {
+            fetcher.add_extraction_rule(rule);
+        }
+        Ok(())
+    }
+
+    /// Remove a rule by name, returning whether it existed
+    #[napi]
+    pub fn remove_rule(&self, name: String) -> bool {
+        self.fetcher.lock().expect("fetcher lock poisoned").remove_extraction_rule(&name).is_some()
+    }
+
+    /// Names of the current rules, sorted
+    #[napi]
+    pub fn rule_names(&self) -> Vec<String> {
+        let fetcher = self.fetcher.lock().expect("fetcher lock poisoned");
+        let mut names: Vec<String> = fetcher.extraction_rules().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Scrape a page
+    #[napi]
+    pub async fn scrape(&self, url: String) -> napi::Result<Value> {
+        let data = self.handle().scrape(&url).await.map_err(to_napi_err)?;
+        to_js(&data)
+    }
+
+    /// Scrape pages concurrently, resolving with those scraped in completion order
+    #[napi]
+    pub async fn scrape_multiple(&self, urls: Vec<String>) -> napi::Result<Value> {
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let pages = self.handle().scrape_multiple(&urls).await.map_err(to_napi_err)?;
+        to_js(&pages)
+    }
+
+    /// Scrape pages concurrently, calling `callback(err, page)` as each finishes
+    ///
+    /// Resolves with the number of pages scraped once every URL is done.
+    /// Errors passed to the callback name the URL that failed.
+    #[napi(ts_args_type = "urls: string[], callback: (err: Error | null, page: any) => void")]
+    pub async fn scrape_stream(&self, urls: Vec<String>, callback: ThreadsafeFunction<Value>) -> napi::Result<u32> {
+        Ok(stream_pages(self.handle(), urls, |page| {
+            callback.call(page, ThreadsafeFunctionCallMode::NonBlocking);
+        })
+        .await)
+    }
+
+    /// Request statistics
+    #[napi]
+    pub async fn stats(&self) -> napi::Result<Value> {
+        to_js(&self.handle().get_stats().await)
+    }
+
+    /// Requests, failures, bytes and latency percentiles per host
+    #[napi]
+    pub async fn stats_by_domain(&self) -> napi::Result<Value> {
+        to_js(&self.handle().get_stats_by_domain().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::serve_page;
+
+    #[test]
+    fn test_node_options() {
+        let rule = Rule { name: "links".into(), selector: "a".into(), attribute: Some("href".into()), multiple: Some(true), html: None, group: None };
+        let rule = ExtractionRule::from(rule);
+        assert!(matches!(rule.extraction_type, ExtractionType::Attribute));
+        assert!(rule.multiple);
+
+        let options = FetcherOptions { user_agent: Some("node-test".into()), timeout_ms: Some(1500), rate_limit: Some(false), ..FetcherOptions::default() };
+        let config = options.into_config().unwrap();
+        assert_eq!(config.user_agent, "node-test");
+        assert_eq!(config.timeout, Duration::from_millis(1500));
+        assert!(config.rate_limit.is_none());
+
+        let options = FetcherOptions { max_concurrent: Some(0), ..FetcherOptions::default() };
+        assert!(matches!(options.into_config(), Err(FerrisFetcherError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_node_scrape() {
+        let base = serve_page(r#"<html><title>Node</title><span class="price">7</span></html>"#);
+        let rule = Rule { name: "price".into(), selector: ".price".into(), attribute: None, multiple: Some(true), html: None, group: None };
+        let options = FetcherOptions { rate_limit: Some(false), rules: Some(vec![rule]), ..FetcherOptions::default() };
+        let fetcher = JsFetcher::new(Some(options)).unwrap();
+        assert!(fetcher.add_rules("broken".into()).is_err_and(|error| error.status == Status::InvalidArg));
+
+        let page = fetcher.scrape(format!("{}/a", base)).await.unwrap();
+        assert_eq!(page["title"], "Node");
+        assert_eq!(page["extracted_data"]["price"], serde_json::json!(["7"]));
+        assert!(fetcher.scrape("not a url".into()).await.is_err());
+
+        let pages = fetcher.scrape_multiple(vec![format!("{}/b", base), format!("{}/c", base)]).await.unwrap();
+        assert_eq!(pages.as_array().map(Vec::len), Some(2));
+        assert_eq!(fetcher.stats().await.unwrap()["successful_requests"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_node_stream_pages() {
+        let base = serve_page("<html><title>Streamed</title></html>");
+        let fetcher = JsFetcher::new(Some(FetcherOptions { rate_limit: Some(false), ..FetcherOptions::default() })).unwrap();
+
+        let mut pages = Vec::new();
+        let urls = vec![format!("{}/1", base), "http://127.0.0.1:1/".to_string(), format!("{}/2", base)];
+        let scraped = stream_pages(fetcher.handle(), urls, |page| pages.push(page)).await;
+        assert_eq!((scraped, pages.len()), (2, 3));
+        assert!(pages.iter().filter_map(|page| page.as_ref().ok()).all(|page| page["title"] == "Streamed"));
+        let error = pages.iter().find_map(|page| page.as_ref().err()).unwrap();
+        assert!(error.reason.contains("http://127.0.0.1:1/"));
+    }
+}
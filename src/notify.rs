@@ -0,0 +1,586 @@
+//! Notifications on job completion and anomalies
+//!
+//! [`Notifications`] routes [`NotificationEvent`]s to Slack or email
+//! [`Notifier`]s, rendering each event through a [`MessageTemplate`]. Events
+//! are raised when a job finishes, when its error rate crosses a threshold,
+//! when a page's extracted content changes and when a rule stops matching
+//! (see [`DriftMonitor`](crate::monitor::DriftMonitor)).
+
+use crate::crawl_report::CrawlReport;
+use crate::error::{FerrisFetcherError, Result};
+use crate::monitor::DriftAlert;
+use crate::types::ScrapedData;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::{debug, warn};
+
+/// Kind of event a notifier subscribes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// A job finished
+    JobCompleted,
+    /// A job's error rate exceeded the configured threshold
+    ErrorRate,
+    /// A page's extracted data differs from a previous scrape
+    ContentChanged,
+    /// A rule's hit rate on sampled pages dropped below its baseline
+    SelectorDrift,
+}
+
+/// Something worth telling a human about
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    /// A job finished
+    JobCompleted { job: String, report: CrawlReport },
+    /// A job's share of failed fetches exceeded the threshold
+    ErrorRateExceeded {
+        job: String,
+        error_rate: f64,
+        threshold: f64,
+        report: CrawlReport,
+    },
+    /// Extracted fields of a page changed
+    ContentChanged { url: String, fields: Vec<String> },
+    /// A rule matched far fewer sampled pages than in its baseline
+    SelectorDrift(DriftAlert),
+}
+
+impl NotificationEvent {
+    /// Compare two scrapes of a page, returning an event if any extracted field changed
+    pub fn content_changed(previous: &ScrapedData, current: &ScrapedData) -> Option<Self> {
+        let mut fields: Vec<String> = current.extracted_data
+            .iter()
+            .filter(|(name, value)| previous.extracted_data.get(*name) != Some(*value))
+            .map(|(name, _)| name.clone())
+            .chain(
+                previous.extracted_data
+                    .keys()
+                    .filter(|name| !current.extracted_data.contains_key(*name))
+                    .cloned(),
+            )
+            .collect();
+        fields.sort();
+
+        (!fields.is_empty()).then(|| NotificationEvent::ContentChanged {
+            url: current.url.clone(),
+            fields,
+        })
+    }
+
+    /// Kind of the event
+    pub fn kind(&self) -> EventKind {
+        match self {
+            NotificationEvent::JobCompleted { .. } => EventKind::JobCompleted,
+            NotificationEvent::ErrorRateExceeded { .. } => EventKind::ErrorRate,
+            NotificationEvent::ContentChanged { .. } => EventKind::ContentChanged,
+            NotificationEvent::SelectorDrift(_) => EventKind::SelectorDrift,
+        }
+    }
+
+    /// Values available to message templates
+    pub fn variables(&self) -> HashMap<&'static str, String> {
+        let mut vars = HashMap::new();
+        match self {
+            NotificationEvent::JobCompleted { job, report } => {
+                vars.insert("job", job.clone());
+                Self::report_variables(report, &mut vars);
+            }
+            NotificationEvent::ErrorRateExceeded { job, error_rate, threshold, report } => {
+                vars.insert("job", job.clone());
+                vars.insert("error_rate", format!("{:.1}%", error_rate * 100.0));
+                vars.insert("threshold", format!("{:.1}%", threshold * 100.0));
+                Self::report_variables(report, &mut vars);
+            }
+            NotificationEvent::ContentChanged { url, fields } => {
+                vars.insert("url", url.clone());
+                vars.insert("fields", fields.join(", "));
+            }
+            NotificationEvent::SelectorDrift(alert) => {
+                vars.insert("rule_set", alert.rule_set.clone());
+                vars.insert("rule", alert.rule.clone());
+                vars.insert("baseline", format!("{:.1}%", alert.baseline * 100.0));
+                vars.insert("hit_rate", format!("{:.1}%", alert.current * 100.0));
+                vars.insert("sampled", alert.sampled.to_string());
+            }
+        }
+        vars
+    }
+
+    fn report_variables(report: &CrawlReport, vars: &mut HashMap<&'static str, String>) {
+        vars.insert("pages", report.pages_fetched.to_string());
+        vars.insert("errors", report.error_count().to_string());
+        vars.insert("duration", format!("{:.1}s", report.duration_ms as f64 / 1000.0));
+        vars.insert("finished_at", report.finished_at.to_rfc3339());
+    }
+}
+
+/// Subject and body of a message with `{variable}` placeholders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageTemplate {
+    /// Subject line (used by email, bolded on Slack)
+    pub subject: String,
+    /// Message body
+    pub body: String,
+}
+
+impl MessageTemplate {
+    /// Create a template
+    pub fn new(subject: &str, body: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Default template for an event kind
+    pub fn default_for(kind: EventKind) -> Self {
+        match kind {
+            EventKind::JobCompleted => Self::new(
+                "Job {job} completed",
+                "Job {job} finished in {duration}: {pages} pages fetched, {errors} errors.",
+            ),
+            EventKind::ErrorRate => Self::new(
+                "Job {job} error rate {error_rate}",
+                "Job {job} failed {errors} of its fetches ({error_rate}, threshold {threshold}).",
+            ),
+            EventKind::ContentChanged => Self::new(
+                "Content changed on {url}",
+                "Fields changed on {url}: {fields}",
+            ),
+            EventKind::SelectorDrift => Self::new(
+                "Selector drift in {rule_set}: {rule}",
+                "Rule {rule} of {rule_set} matched {hit_rate} of {sampled} sampled pages, down from {baseline}.",
+            ),
+        }
+    }
+
+    /// Render the subject and body; unknown placeholders are left as is
+    pub fn render(&self, vars: &HashMap<&'static str, String>) -> (String, String) {
+        (render(&self.subject, vars), render(&self.body, vars))
+    }
+}
+
+fn render(template: &str, vars: &HashMap<&'static str, String>) -> String {
+    vars.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Delivers rendered messages to people
+#[async_trait]
+pub trait Notifier: fmt::Debug + Send + Sync {
+    /// Send a message
+    async fn notify(&self, subject: &str, body: &str) -> Result<()>;
+}
+
+/// Notifier posting to a Slack incoming webhook
+#[derive(Debug, Clone)]
+pub struct SlackNotifier {
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+impl SlackNotifier {
+    /// Post to the given incoming webhook URL
+    pub fn new(webhook_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+
+    fn payload(subject: &str, body: &str) -> serde_json::Value {
+        json!({ "text": format!("*{}*\n{}", subject, body) })
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    async fn notify(&self, subject: &str, body: &str) -> Result<()> {
+        let response = self.client
+            .post(&self.webhook_url)
+            .json(&Self::payload(subject, body))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(FerrisFetcherError::NetworkError(format!(
+                "Slack webhook returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Notifier sending email through an SMTP relay
+///
+/// Speaks plain SMTP, optionally with `AUTH PLAIN`; point it at a local MTA
+/// or a relay that does not require TLS. Connecting and the whole SMTP
+/// conversation must finish within the timeout, 30 seconds by default.
+#[derive(Debug, Clone)]
+pub struct EmailNotifier {
+    server: String,
+    from: String,
+    to: Vec<String>,
+    credentials: Option<(String, String)>,
+    timeout: Duration,
+}
+
+impl EmailNotifier {
+    /// Send from `from` to `to` via the relay at `server` (`host:port`)
+    pub fn new(server: &str, from: &str, to: &[&str]) -> Self {
+        Self {
+            server: server.to_string(),
+            from: from.to_string(),
+            to: to.iter().map(|address| address.to_string()).collect(),
+            credentials: None,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Authenticate with `AUTH PLAIN`
+    pub fn with_credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Give up on a message not sent within `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Message in RFC 5322 format, dot-stuffed for the DATA command
+    fn message(&self, subject: &str, body: &str) -> String {
+        let mut message = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from,
+            self.to.join(", "),
+            encode_header(&subject.replace(['\r', '\n'], " ")),
+            chrono::Utc::now().to_rfc2822()
+        );
+        for line in body.lines() {
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        message.push_str(".\r\n");
+        message
+    }
+
+    /// Connect and hold the SMTP conversation delivering one message
+    async fn send(&self, subject: &str, body: &str) -> Result<()> {
+        let stream = TcpStream::connect(&self.server).await?;
+        let mut smtp = SmtpConnection { stream: BufReader::new(stream) };
+
+        smtp.expect(220).await?;
+        smtp.command("EHLO ferrisfetcher", 250).await?;
+        if let Some((username, password)) = &self.credentials {
+            let token = base64_encode(format!("\0{}\0{}", username, password).as_bytes());
+            smtp.command(&format!("AUTH PLAIN {}", token), 235).await?;
+        }
+        smtp.command(&format!("MAIL FROM:<{}>", self.from), 250).await?;
+        for recipient in &self.to {
+            smtp.command(&format!("RCPT TO:<{}>", recipient), 250).await?;
+        }
+        smtp.command("DATA", 354).await?;
+        smtp.stream.write_all(self.message(subject, body).as_bytes()).await?;
+        smtp.expect(250).await?;
+        smtp.command("QUIT", 221).await
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    async fn notify(&self, subject: &str, body: &str) -> Result<()> {
+        tokio::time::timeout(self.timeout, self.send(subject, body))
+            .await
+            .map_err(|_| FerrisFetcherError::TimeoutError(self.timeout))??;
+
+        debug!("Sent email '{}' to {}", subject, self.to.join(", "));
+        Ok(())
+    }
+}
+
+/// Line-oriented SMTP conversation
+struct SmtpConnection {
+    stream: BufReader<TcpStream>,
+}
+
+impl SmtpConnection {
+    async fn command(&mut self, command: &str, code: u16) -> Result<()> {
+        self.stream.write_all(format!("{}\r\n", command).as_bytes()).await?;
+        self.expect(code).await
+    }
+
+    /// Read a (possibly multi-line) reply and check its code
+    async fn expect(&mut self, code: u16) -> Result<()> {
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).await? == 0 {
+                return Err(FerrisFetcherError::NetworkError("SMTP server closed the connection".to_string()));
+            }
+            if !line.starts_with(&code.to_string()) {
+                return Err(FerrisFetcherError::NetworkError(format!("Unexpected SMTP reply: {}", line.trim_end())));
+            }
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Routes events to notifiers
+#[derive(Debug, Clone, Default)]
+pub struct Notifications {
+    subscriptions: Vec<(EventKind, Arc<dyn Notifier>)>,
+    templates: HashMap<EventKind, MessageTemplate>,
+    error_rate_threshold: Option<f64>,
+}
+
+impl Notifications {
+    /// Create an empty router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send events of a kind to a notifier
+    pub fn on(mut self, kind: EventKind, notifier: Arc<dyn Notifier>) -> Self {
+        self.subscriptions.push((kind, notifier));
+        self
+    }
+
+    /// Use a custom template for an event kind
+    pub fn with_template(mut self, kind: EventKind, template: MessageTemplate) -> Self {
+        self.templates.insert(kind, template);
+        self
+    }
+
+    /// Raise [`EventKind::ErrorRate`] when more than `threshold` (0.0-1.0) of a job's fetches fail
+    pub fn with_error_rate_threshold(mut self, threshold: f64) -> Self {
+        self.error_rate_threshold = Some(threshold.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Deliver an event to its subscribers, returning how many were notified
+    ///
+    /// Delivery failures are logged rather than returned so that alerting
+    /// never fails the job itself.
+    pub async fn dispatch(&self, event: &NotificationEvent) -> usize {
+        let kind = event.kind();
+        let template = self.templates.get(&kind).cloned().unwrap_or_else(|| MessageTemplate::default_for(kind));
+        let (subject, body) = template.render(&event.variables());
+
+        let mut delivered = 0;
+        for (_, notifier) in self.subscriptions.iter().filter(|(subscribed, _)| *subscribed == kind) {
+            match notifier.notify(&subject, &body).await {
+                Ok(()) => delivered += 1,
+                Err(e) => warn!("Failed to send {:?} notification via {:?}: {}", kind, notifier, e),
+            }
+        }
+        delivered
+    }
+
+    /// Raise the completion event for a job, plus an error-rate event if the threshold was exceeded
+    pub async fn job_finished(&self, job: &str, report: &CrawlReport) -> usize {
+        let mut delivered = self.dispatch(&NotificationEvent::JobCompleted {
+            job: job.to_string(),
+            report: report.clone(),
+        }).await;
+
+        let errors = report.error_count();
+        let attempts = report.pages_fetched + errors;
+        if let Some(threshold) = self.error_rate_threshold {
+            let error_rate = if attempts == 0 { 0.0 } else { errors as f64 / attempts as f64 };
+            if error_rate > threshold {
+                delivered += self.dispatch(&NotificationEvent::ErrorRateExceeded {
+                    job: job.to_string(),
+                    error_rate,
+                    threshold,
+                    report: report.clone(),
+                }).await;
+            }
+        }
+
+        delivered
+    }
+}
+
+/// Header text as RFC 2047 encoded words when it is not plain ASCII
+///
+/// Each word carries at most 45 bytes of UTF-8, whole characters only, to
+/// stay within the 75 character limit; words are folded onto separate lines.
+fn encode_header(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    let mut words = Vec::new();
+    let mut start = 0;
+    for (index, character) in text.char_indices() {
+        if index + character.len_utf8() - start > 45 {
+            words.push(&text[start..index]);
+            start = index;
+        }
+    }
+    words.push(&text[start..]);
+    words
+        .iter()
+        .map(|word| format!("=?UTF-8?B?{}?=", base64_encode(word.as_bytes())))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+/// Standard base64 encoding
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+            | chunk.get(2).copied().unwrap_or(0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawl_report::CrawlLog;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    #[derive(Debug, Default)]
+    struct RecordingNotifier {
+        messages: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, subject: &str, body: &str) -> Result<()> {
+            self.messages.lock().unwrap().push((subject.to_string(), body.to_string()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifications_dispatch() {
+        let mut log = CrawlLog::new();
+        log.record_page("https://example.com/", 0, Duration::from_millis(10));
+        log.record_error(&FerrisFetcherError::NetworkError("reset".to_string()));
+        let report = log.report();
+
+        let recorder = Arc::new(RecordingNotifier::default());
+        let notifications = Notifications::new()
+            .on(EventKind::JobCompleted, recorder.clone())
+            .on(EventKind::ErrorRate, recorder.clone())
+            .with_template(EventKind::JobCompleted, MessageTemplate::new("{job} done", "{pages} pages, {errors} errors, {missing}"))
+            .with_error_rate_threshold(0.25);
+
+        assert_eq!(notifications.job_finished("nightly", &report).await, 2);
+        let messages = recorder.messages.lock().unwrap().clone();
+        assert_eq!(messages[0], ("nightly done".to_string(), "1 pages, 1 errors, {missing}".to_string()));
+        assert_eq!(messages[1].0, "Job nightly error rate 50.0%");
+
+        let mut previous = ScrapedData::new("https://shop.example/1".to_string());
+        previous.add_extracted_data("price", vec!["10".to_string()]);
+        previous.add_extracted_data("name", vec!["Mug".to_string()]);
+        let mut current = previous.clone();
+        assert!(NotificationEvent::content_changed(&previous, &current).is_none());
+        current.add_extracted_data("price", vec!["12".to_string()]);
+        let event = NotificationEvent::content_changed(&previous, &current).unwrap();
+        assert_eq!(notifications.dispatch(&event).await, 0);
+        assert_eq!(event.variables()["fields"], "price");
+
+        assert_eq!(SlackNotifier::payload("Hi", "there")["text"], "*Hi*\nthere");
+        assert_eq!(base64_encode(b"\0user\0pass"), "AHVzZXIAcGFzcw==");
+    }
+
+    #[tokio::test]
+    async fn test_email_notifier() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            let mut transcript = String::new();
+            stream.write_all(b"220 relay ready\r\n").await.unwrap();
+            loop {
+                let mut line = String::new();
+                stream.read_line(&mut line).await.unwrap();
+                transcript.push_str(&line);
+                let reply: &[u8] = match line.trim_end() {
+                    "EHLO ferrisfetcher" => b"250-relay\r\n250 AUTH PLAIN\r\n",
+                    "DATA" => b"354 go ahead\r\n",
+                    "." => b"250 queued\r\n",
+                    "QUIT" => b"221 bye\r\n",
+                    command if command.starts_with("AUTH") => b"235 ok\r\n",
+                    command if command.starts_with("MAIL") || command.starts_with("RCPT") => b"250 ok\r\n",
+                    _ => continue,
+                };
+                stream.write_all(reply).await.unwrap();
+                if line.starts_with("QUIT") {
+                    return transcript;
+                }
+            }
+        });
+
+        EmailNotifier::new(&address, "bot@example.com", &["ops@example.com"])
+            .with_credentials("user", "pass")
+            .notify("Job done", "All good\n.hidden line")
+            .await
+            .unwrap();
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.contains("AUTH PLAIN AHVzZXIAcGFzcw==\r\n"));
+        assert!(transcript.contains("RCPT TO:<ops@example.com>\r\n"));
+        assert!(transcript.contains("Subject: Job done\r\n"));
+        assert!(transcript.contains("\r\n..hidden line\r\n"));
+    }
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("Job done"), "Job done");
+        assert_eq!(encode_header("Prix modifié"), "=?UTF-8?B?UHJpeCBtb2RpZmnDqQ==?=");
+
+        let encoded = encode_header(&"é".repeat(30));
+        let words: Vec<&str> = encoded.split("\r\n ").collect();
+        assert_eq!(words.len(), 2);
+        assert!(words.iter().all(|word| word.len() <= 75 && word.starts_with("=?UTF-8?B?")));
+    }
+
+    #[tokio::test]
+    async fn test_email_notifier_timeout() {
+        // A relay that accepts the connection but never greets
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let _server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            drop(stream);
+        });
+
+        let error = EmailNotifier::new(&address, "bot@example.com", &["ops@example.com"])
+            .with_timeout(Duration::from_millis(100))
+            .notify("Job done", "All good")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, FerrisFetcherError::TimeoutError(timeout) if timeout == Duration::from_millis(100)));
+    }
+}
@@ -0,0 +1,518 @@
+//! Apache Parquet export
+//!
+//! Records are written in row groups of uncompressed, PLAIN-encoded column
+//! chunks. `url`, `status_code` and `depth` (INT32) and `timestamp` (INT64
+//! milliseconds) are required columns; `title`, `parent_url`, `discovered_via`
+//! and the extracted fields are optional UTF-8 columns, with multiple values
+//! joined by newlines.
+
+use crate::error::Result;
+use crate::export::{table_columns, Exporter, FIXED_COLUMNS};
+use crate::types::ScrapedData;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Magic bytes at the start and end of every Parquet file
+const MAGIC: &[u8; 4] = b"PAR1";
+
+/// Rows buffered before a row group is written
+const DEFAULT_ROW_GROUP_SIZE: usize = 10_000;
+
+/// Parquet physical types
+const TYPE_INT32: i32 = 1;
+const TYPE_INT64: i32 = 2;
+const TYPE_BYTE_ARRAY: i32 = 6;
+
+/// Parquet converted types
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+
+/// Parquet repetition types
+const REQUIRED: i32 = 0;
+const OPTIONAL: i32 = 1;
+
+/// Parquet encodings
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+
+/// Thrift compact protocol field types
+const THRIFT_I32: u8 = 5;
+const THRIFT_I64: u8 = 6;
+const THRIFT_BINARY: u8 = 8;
+const THRIFT_LIST: u8 = 9;
+const THRIFT_STRUCT: u8 = 12;
+
+/// Type of an output column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    /// Required UTF-8 string
+    Text,
+    /// Optional UTF-8 string
+    OptionalText,
+    /// Required 32-bit integer
+    Int32,
+    /// Required timestamp in milliseconds since the epoch
+    Timestamp,
+}
+
+impl ColumnKind {
+    fn of(column: &str) -> Self {
+        match column {
+            "url" => Self::Text,
+            "status_code" | "depth" => Self::Int32,
+            "timestamp" => Self::Timestamp,
+            _ => Self::OptionalText,
+        }
+    }
+
+    fn physical_type(self) -> i32 {
+        match self {
+            Self::Text | Self::OptionalText => TYPE_BYTE_ARRAY,
+            Self::Int32 => TYPE_INT32,
+            Self::Timestamp => TYPE_INT64,
+        }
+    }
+
+    fn converted_type(self) -> Option<i32> {
+        match self {
+            Self::Text | Self::OptionalText => Some(CONVERTED_UTF8),
+            Self::Int32 => None,
+            Self::Timestamp => Some(CONVERTED_TIMESTAMP_MILLIS),
+        }
+    }
+
+    fn is_optional(self) -> bool {
+        self == Self::OptionalText
+    }
+}
+
+/// Location of a written column chunk
+#[derive(Debug, Clone)]
+struct ChunkInfo {
+    offset: u64,
+    size: u64,
+    num_values: usize,
+}
+
+/// A written row group
+#[derive(Debug, Clone)]
+struct RowGroupInfo {
+    num_rows: usize,
+    chunks: Vec<ChunkInfo>,
+}
+
+/// Parquet exporter with one column per extracted field
+///
+/// Columns are `url`, `title`, `status_code` and `timestamp`, then the crawl
+/// topology columns if any record was crawled, followed by the extracted
+/// fields in name order. Unless set with
+/// [`ParquetExporter::with_columns`], they are taken from all records passed
+/// to [`Exporter::export_all`], or else from the first buffered row group.
+/// The file is only valid once [`Exporter::finish`] has written its footer.
+#[derive(Debug)]
+pub struct ParquetExporter<W: Write> {
+    writer: W,
+    columns: Option<Vec<String>>,
+    row_group_size: usize,
+    pending: Vec<ScrapedData>,
+    row_groups: Vec<RowGroupInfo>,
+    offset: u64,
+}
+
+impl ParquetExporter<BufWriter<File>> {
+    /// Create (or truncate) a file and export to it
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> ParquetExporter<W> {
+    /// Export to an existing writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            columns: None,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            pending: Vec::new(),
+            row_groups: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Write exactly these columns
+    pub fn with_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Write a row group every `rows` records
+    pub fn with_row_group_size(mut self, rows: usize) -> Self {
+        self.row_group_size = rows.max(1);
+        self
+    }
+
+    /// Get the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Write the buffered records as a row group
+    fn flush_row_group(&mut self) -> Result<()> {
+        if self.offset == 0 {
+            self.write(MAGIC)?;
+        }
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let rows = std::mem::take(&mut self.pending);
+        let columns = self.columns.get_or_insert_with(|| table_columns(&rows)).clone();
+        let mut chunks = Vec::with_capacity(columns.len());
+        for column in &columns {
+            let kind = ColumnKind::of(column);
+            let data = encode_column(&rows, column, kind);
+            let header = page_header(rows.len(), data.len());
+            let offset = self.offset;
+            self.write(&header)?;
+            self.write(&data)?;
+            chunks.push(ChunkInfo { offset, size: self.offset - offset, num_values: rows.len() });
+        }
+
+        self.row_groups.push(RowGroupInfo { num_rows: rows.len(), chunks });
+        Ok(())
+    }
+}
+
+impl<W: Write> Exporter for ParquetExporter<W> {
+    fn export(&mut self, data: &ScrapedData) -> Result<()> {
+        self.pending.push(data.clone());
+        if self.pending.len() >= self.row_group_size {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush_row_group()?;
+        let columns = self.columns.get_or_insert_with(|| FIXED_COLUMNS.iter().map(|column| column.to_string()).collect());
+        let metadata = file_metadata(columns, &self.row_groups);
+        self.row_groups.clear();
+
+        self.write(&metadata)?;
+        self.write(&(metadata.len() as u32).to_le_bytes())?;
+        self.write(MAGIC)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn export_all(&mut self, records: &[ScrapedData]) -> Result<()> {
+        if self.columns.is_none() {
+            let mut all = std::mem::take(&mut self.pending);
+            all.extend_from_slice(records);
+            self.columns = Some(table_columns(&all));
+            self.pending = all;
+        } else {
+            self.pending.extend_from_slice(records);
+        }
+
+        while self.pending.len() >= self.row_group_size {
+            let rest = self.pending.split_off(self.row_group_size);
+            self.flush_row_group()?;
+            self.pending = rest;
+        }
+        Ok(())
+    }
+}
+
+/// Value of a column for a record, `None` for missing optional values
+fn text_value(data: &ScrapedData, column: &str) -> Option<String> {
+    if let Some(values) = data.extracted_data.get(column) {
+        return Some(values.join("\n"));
+    }
+    match column {
+        "url" => Some(data.url.clone()),
+        "title" => data.title.clone(),
+        "parent_url" => data.parent_url.clone(),
+        "discovered_via" => data.discovered_via.map(|via| via.as_str().to_string()),
+        _ => None,
+    }
+}
+
+/// Encode the data page of a column: definition levels for optional columns,
+/// then the PLAIN-encoded present values
+fn encode_column(rows: &[ScrapedData], column: &str, kind: ColumnKind) -> Vec<u8> {
+    let mut data = Vec::new();
+    match kind {
+        ColumnKind::Int32 => {
+            for row in rows {
+                let value = if column == "depth" { row.depth as i32 } else { i32::from(row.status_code) };
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        ColumnKind::Timestamp => {
+            for row in rows {
+                data.extend_from_slice(&row.timestamp.timestamp_millis().to_le_bytes());
+            }
+        }
+        ColumnKind::Text | ColumnKind::OptionalText => {
+            let values: Vec<Option<String>> = rows.iter().map(|row| text_value(row, column)).collect();
+            if kind.is_optional() {
+                let levels = definition_levels(values.iter().map(Option::is_some));
+                data.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+                data.extend_from_slice(&levels);
+            }
+            for value in values.iter() {
+                let value = match (value, kind) {
+                    (Some(value), _) => value.as_str(),
+                    (None, ColumnKind::Text) => "",
+                    (None, _) => continue,
+                };
+                data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                data.extend_from_slice(value.as_bytes());
+            }
+        }
+    }
+    data
+}
+
+/// RLE-encode definition levels of bit width 1, one run per stretch of equal levels
+fn definition_levels(present: impl Iterator<Item = bool>) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut run: Option<(bool, u64)> = None;
+    for level in present {
+        run = match run {
+            Some((value, count)) if value == level => Some((value, count + 1)),
+            Some((value, count)) => {
+                push_varint(&mut encoded, count << 1);
+                encoded.push(u8::from(value));
+                Some((level, 1))
+            }
+            None => Some((level, 1)),
+        };
+    }
+    if let Some((value, count)) = run {
+        push_varint(&mut encoded, count << 1);
+        encoded.push(u8::from(value));
+    }
+    encoded
+}
+
+/// Header of an uncompressed data page
+fn page_header(num_values: usize, size: usize) -> Vec<u8> {
+    let mut header = CompactWriter::default();
+    header.i32(1, 0); // DATA_PAGE
+    header.i32(2, size as i32);
+    header.i32(3, size as i32);
+    header.begin(5);
+    header.i32(1, num_values as i32);
+    header.i32(2, ENCODING_PLAIN);
+    header.i32(3, ENCODING_RLE);
+    header.i32(4, ENCODING_RLE);
+    header.end();
+    header.finish()
+}
+
+/// File footer describing the schema and every row group
+fn file_metadata(columns: &[String], row_groups: &[RowGroupInfo]) -> Vec<u8> {
+    let mut meta = CompactWriter::default();
+    meta.i32(1, 1);
+
+    meta.list(2, THRIFT_STRUCT, columns.len() + 1);
+    meta.begin_element();
+    meta.string(4, "schema");
+    meta.i32(5, columns.len() as i32);
+    meta.end();
+    for column in columns {
+        let kind = ColumnKind::of(column);
+        meta.begin_element();
+        meta.i32(1, kind.physical_type());
+        meta.i32(3, if kind.is_optional() { OPTIONAL } else { REQUIRED });
+        meta.string(4, column);
+        if let Some(converted) = kind.converted_type() {
+            meta.i32(6, converted);
+        }
+        meta.end();
+    }
+
+    meta.i64(3, row_groups.iter().map(|group| group.num_rows as i64).sum());
+
+    meta.list(4, THRIFT_STRUCT, row_groups.len());
+    for group in row_groups {
+        meta.begin_element();
+        meta.list(1, THRIFT_STRUCT, group.chunks.len());
+        for (column, chunk) in columns.iter().zip(&group.chunks) {
+            let kind = ColumnKind::of(column);
+            meta.begin_element();
+            meta.i64(2, chunk.offset as i64);
+            meta.begin(3);
+            meta.i32(1, kind.physical_type());
+            meta.list(2, THRIFT_I32, 2);
+            meta.list_i32(ENCODING_PLAIN);
+            meta.list_i32(ENCODING_RLE);
+            meta.list(3, THRIFT_BINARY, 1);
+            meta.list_string(column);
+            meta.i32(4, 0); // UNCOMPRESSED
+            meta.i64(5, chunk.num_values as i64);
+            meta.i64(6, chunk.size as i64);
+            meta.i64(7, chunk.size as i64);
+            meta.i64(9, chunk.offset as i64);
+            meta.end();
+            meta.end();
+        }
+        meta.i64(2, group.chunks.iter().map(|chunk| chunk.size as i64).sum());
+        meta.i64(3, group.num_rows as i64);
+        meta.end();
+    }
+
+    meta.string(6, concat!("ferrisfetcher version ", env!("CARGO_PKG_VERSION")));
+    meta.finish()
+}
+
+/// Append an unsigned LEB128 varint
+fn push_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+/// Minimal Thrift compact protocol encoder for Parquet metadata
+#[derive(Debug, Default)]
+struct CompactWriter {
+    buf: Vec<u8>,
+    last_field: i16,
+    parents: Vec<i16>,
+}
+
+impl CompactWriter {
+    fn zigzag(&mut self, value: i64) {
+        push_varint(&mut self.buf, ((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let delta = id - self.last_field;
+        if (1..=15).contains(&delta) {
+            self.buf.push(((delta as u8) << 4) | kind);
+        } else {
+            self.buf.push(kind);
+            self.zigzag(id.into());
+        }
+        self.last_field = id;
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, THRIFT_I32);
+        self.zigzag(value.into());
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, THRIFT_I64);
+        self.zigzag(value);
+    }
+
+    fn string(&mut self, id: i16, value: &str) {
+        self.field(id, THRIFT_BINARY);
+        self.list_string(value);
+    }
+
+    fn list(&mut self, id: i16, element: u8, len: usize) {
+        self.field(id, THRIFT_LIST);
+        if len < 15 {
+            self.buf.push(((len as u8) << 4) | element);
+        } else {
+            self.buf.push(0xf0 | element);
+            push_varint(&mut self.buf, len as u64);
+        }
+    }
+
+    fn list_i32(&mut self, value: i32) {
+        self.zigzag(value.into());
+    }
+
+    fn list_string(&mut self, value: &str) {
+        push_varint(&mut self.buf, value.len() as u64);
+        self.buf.extend_from_slice(value.as_bytes());
+    }
+
+    /// Start a struct-valued field
+    fn begin(&mut self, id: i16) {
+        self.field(id, THRIFT_STRUCT);
+        self.begin_element();
+    }
+
+    /// Start a struct inside a list
+    fn begin_element(&mut self) {
+        self.parents.push(self.last_field);
+        self.last_field = 0;
+    }
+
+    fn end(&mut self) {
+        self.buf.push(0);
+        self.last_field = self.parents.pop().unwrap_or(0);
+    }
+
+    /// Close the top-level struct and return its bytes
+    fn finish(mut self) -> Vec<u8> {
+        self.buf.push(0);
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(url: &str, price: Option<&str>) -> ScrapedData {
+        let mut data = ScrapedData::new(url.to_string());
+        data.title = Some("Page".to_string());
+        if let Some(price) = price {
+            data.add_extracted_data("price", vec![price.to_string()]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parquet_exporter_layout() {
+        let records = vec![
+            record("https://a.example/", Some("9.99")),
+            record("https://b.example/", None),
+            record("https://c.example/", Some("1.50")),
+        ];
+        let mut exporter = ParquetExporter::new(Vec::new()).with_row_group_size(2);
+        exporter.export_all(&records).unwrap();
+        exporter.finish().unwrap();
+        let output = exporter.into_inner();
+
+        assert_eq!(&output[..4], MAGIC);
+        assert_eq!(&output[output.len() - 4..], MAGIC);
+        let footer_len = u32::from_le_bytes(output[output.len() - 8..output.len() - 4].try_into().unwrap()) as usize;
+        let footer = &output[output.len() - 8 - footer_len..output.len() - 8];
+        let contains = |haystack: &[u8], needle: &[u8]| haystack.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(footer, b"price"));
+        assert!(contains(footer, b"status_code"));
+        assert!(contains(&output, b"https://c.example/"));
+        assert!(contains(&output, b"9.99"));
+    }
+
+    #[test]
+    fn test_definition_levels() {
+        assert_eq!(definition_levels([true, true, false, true].into_iter()), vec![4, 1, 2, 0, 2, 1]);
+        assert!(definition_levels(std::iter::empty()).is_empty());
+    }
+}
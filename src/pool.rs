@@ -0,0 +1,167 @@
+//! Cache of configured fetchers for services scraping on behalf of many tenants
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::scraper::FerrisFetcher;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// A cached fetcher and when it was last handed out
+#[derive(Debug)]
+struct PoolEntry {
+    fetcher: FerrisFetcher,
+    last_used: Instant,
+}
+
+/// Lazily created, bounded cache of [`FerrisFetcher`] instances
+///
+/// Fetchers are keyed by tenant name or configuration fingerprint and handed
+/// out as cheap handles, so each tenant reuses its HTTP client, connection
+/// pool and rate limiter across requests. When the pool is full the least
+/// recently used fetcher is evicted; fetchers idle for longer than the idle
+/// timeout are evicted on the next access.
+#[derive(Debug)]
+pub struct FetcherPool {
+    entries: Mutex<HashMap<String, PoolEntry>>,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+}
+
+impl FetcherPool {
+    /// Create a pool holding at most `max_size` fetchers
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_size: max_size.max(1),
+            idle_timeout: None,
+        }
+    }
+
+    /// Evict fetchers not used for longer than `timeout`
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Get the fetcher cached under `key`, creating it with `create` if missing
+    pub fn get_or_create<F>(&self, key: &str, create: F) -> Result<FerrisFetcher>
+    where
+        F: FnOnce() -> Result<FerrisFetcher>,
+    {
+        let mut entries = self.entries.lock().expect("fetcher pool lock poisoned");
+        let now = Instant::now();
+        self.evict_idle_entries(&mut entries, now);
+
+        if let Some(entry) = entries.get_mut(key) {
+            entry.last_used = now;
+            return Ok(entry.fetcher.handle());
+        }
+
+        let fetcher = create()?;
+        if entries.len() >= self.max_size {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                debug!("Evicting least recently used fetcher '{}'", oldest);
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(key.to_string(), PoolEntry { fetcher: fetcher.handle(), last_used: now });
+        Ok(fetcher)
+    }
+
+    /// Get the fetcher for a configuration, keyed by [`Config::fingerprint`]
+    pub fn get_for_config(&self, config: &Config) -> Result<FerrisFetcher> {
+        let key = format!("config:{:016x}", config.fingerprint());
+        self.get_or_create(&key, || FerrisFetcher::with_config(config.clone()))
+    }
+
+    /// Remove the fetcher cached under `key`
+    pub fn remove(&self, key: &str) -> Option<FerrisFetcher> {
+        self.entries
+            .lock()
+            .expect("fetcher pool lock poisoned")
+            .remove(key)
+            .map(|entry| entry.fetcher)
+    }
+
+    /// Check whether a fetcher is cached under `key`
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.lock().expect("fetcher pool lock poisoned").contains_key(key)
+    }
+
+    /// Evict fetchers past the idle timeout
+    pub fn evict_idle(&self) {
+        let mut entries = self.entries.lock().expect("fetcher pool lock poisoned");
+        self.evict_idle_entries(&mut entries, Instant::now());
+    }
+
+    /// Remove all cached fetchers
+    pub fn clear(&self) {
+        self.entries.lock().expect("fetcher pool lock poisoned").clear();
+    }
+
+    /// Number of cached fetchers
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("fetcher pool lock poisoned").len()
+    }
+
+    /// Check whether the pool is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn evict_idle_entries(&self, entries: &mut HashMap<String, PoolEntry>, now: Instant) {
+        if let Some(timeout) = self.idle_timeout {
+            entries.retain(|_, entry| now.duration_since(entry.last_used) <= timeout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn test_pool_caches_and_evicts() {
+        let pool = FetcherPool::new(2);
+        let created = Cell::new(0);
+        let create = || {
+            created.set(created.get() + 1);
+            FerrisFetcher::new()
+        };
+
+        pool.get_or_create("acme", create).unwrap();
+        pool.get_or_create("acme", create).unwrap();
+        assert_eq!(created.get(), 1);
+
+        pool.get_or_create("globex", create).unwrap();
+        pool.get_or_create("acme", create).unwrap();
+        pool.get_or_create("initech", create).unwrap();
+        assert_eq!(created.get(), 3);
+        assert_eq!(pool.len(), 2);
+        assert!(pool.contains("acme"));
+        assert!(!pool.contains("globex"));
+
+        let config = Config::new().with_max_concurrent_requests(3);
+        let fetcher = pool.get_for_config(&config).unwrap();
+        assert_eq!(fetcher.max_concurrent_requests(), 3);
+        pool.get_for_config(&config.clone()).unwrap();
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_idle_timeout() {
+        let pool = FetcherPool::new(10).with_idle_timeout(Duration::from_millis(10));
+        pool.get_or_create("acme", FerrisFetcher::new).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        pool.evict_idle();
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! PostgreSQL sink with upsert semantics (`database` feature)
+//!
+//! [`PostgresSink`] creates its table on first use and upserts records in
+//! batches, keyed by URL and the rule set version that produced them. It runs
+//! on top of any driver through the [`PgExecutor`] trait: every parameter is
+//! passed as optional text and cast in SQL, so an implementation only needs to
+//! bind strings.
+
+use crate::error::{FerrisFetcherError, Result};
+use crate::export::RecordSink;
+use crate::types::ScrapedData;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Columns written per record, in parameter order
+const COLUMNS: [&str; 8] = [
+    "url",
+    "rule_set",
+    "rule_set_version",
+    "status_code",
+    "title",
+    "extracted_data",
+    "metadata",
+    "scraped_at",
+];
+
+/// SQL type each column's text parameter is cast to
+const COLUMN_TYPES: [&str; 8] = ["text", "text", "integer", "integer", "text", "jsonb", "jsonb", "timestamptz"];
+
+/// Postgres limit on bind parameters per statement
+const MAX_PARAMETERS: usize = 65_535;
+
+/// Executes SQL on a PostgreSQL connection
+///
+/// Parameters are `$1`, `$2`, ... bound as `text` (`None` is `NULL`).
+#[async_trait]
+pub trait PgExecutor: Send + Sync {
+    /// Execute a statement and return the number of affected rows
+    async fn execute(&self, sql: &str, params: &[Option<String>]) -> Result<u64>;
+}
+
+/// Sink upserting records into a PostgreSQL table
+#[derive(Debug)]
+pub struct PostgresSink<E: PgExecutor> {
+    executor: E,
+    table: String,
+    batch_size: usize,
+    bootstrap: bool,
+    bootstrapped: bool,
+    pending: Vec<Vec<Option<String>>>,
+}
+
+impl<E: PgExecutor> PostgresSink<E> {
+    /// Upsert into `table` (optionally schema-qualified, e.g. `scraping.pages`)
+    pub fn new(executor: E, table: &str) -> Result<Self> {
+        if table.is_empty() || table.split('.').any(str::is_empty) {
+            return Err(FerrisFetcherError::ConfigError(format!("Invalid table name '{}'", table)));
+        }
+
+        Ok(Self {
+            executor,
+            table: table.split('.').map(quote_identifier).collect::<Vec<_>>().join("."),
+            batch_size: 500,
+            bootstrap: true,
+            bootstrapped: false,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Set how many records are written per statement
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.clamp(1, MAX_PARAMETERS / COLUMNS.len());
+        self
+    }
+
+    /// Do not create the table; it must already exist
+    pub fn without_bootstrap(mut self) -> Self {
+        self.bootstrap = false;
+        self
+    }
+
+    /// Get the underlying executor
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    /// Number of records waiting to be written
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Create the table if it does not exist
+    pub async fn ensure_schema(&mut self) -> Result<()> {
+        self.executor.execute(&self.schema_sql(), &[]).await?;
+        self.bootstrapped = true;
+        Ok(())
+    }
+
+    fn schema_sql(&self) -> String {
+        format!(
+            "CREATE TABLE IF NOT EXISTS {table} (\n\
+             \x20   url TEXT NOT NULL,\n\
+             \x20   rule_set TEXT NOT NULL DEFAULT '',\n\
+             \x20   rule_set_version INTEGER NOT NULL DEFAULT 0,\n\
+             \x20   status_code INTEGER NOT NULL,\n\
+             \x20   title TEXT,\n\
+             \x20   extracted_data JSONB NOT NULL,\n\
+             \x20   metadata JSONB NOT NULL,\n\
+             \x20   scraped_at TIMESTAMPTZ NOT NULL,\n\
+             \x20   PRIMARY KEY (url, rule_set, rule_set_version)\n\
+             )",
+            table = self.table
+        )
+    }
+
+    /// Build the upsert statement for `rows` records
+    fn upsert_sql(&self, rows: usize) -> String {
+        let values: Vec<String> = (0..rows)
+            .map(|row| {
+                let params: Vec<String> = COLUMN_TYPES
+                    .iter()
+                    .enumerate()
+                    .map(|(column, kind)| format!("(${}::text)::{}", row * COLUMNS.len() + column + 1, kind))
+                    .collect();
+                format!("({})", params.join(", "))
+            })
+            .collect();
+        let updates: Vec<String> = COLUMNS[3..]
+            .iter()
+            .map(|column| format!("{0} = EXCLUDED.{0}", column))
+            .collect();
+
+        format!(
+            "INSERT INTO {} ({}) VALUES {} ON CONFLICT (url, rule_set, rule_set_version) DO UPDATE SET {}",
+            self.table,
+            COLUMNS.join(", "),
+            values.join(", "),
+            updates.join(", ")
+        )
+    }
+
+    /// Text parameters of a record, in [`COLUMNS`] order
+    fn row(data: &ScrapedData) -> Result<Vec<Option<String>>> {
+        let (rule_set, version) = match &data.rule_set {
+            Some(rule_set) => (rule_set.name.clone(), rule_set.version),
+            None => (String::new(), 0),
+        };
+
+        Ok(vec![
+            Some(data.url.clone()),
+            Some(rule_set),
+            Some(version.to_string()),
+            Some(data.status_code.to_string()),
+            data.title.clone(),
+            Some(serde_json::to_string(&data.extracted_data)?),
+            Some(serde_json::to_string(&data.metadata)?),
+            Some(data.timestamp.to_rfc3339()),
+        ])
+    }
+}
+
+#[async_trait]
+impl<E: PgExecutor> RecordSink for PostgresSink<E> {
+    async fn send(&mut self, data: &ScrapedData) -> Result<()> {
+        self.pending.push(Self::row(data)?);
+        if self.pending.len() >= self.batch_size {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        if self.bootstrap && !self.bootstrapped {
+            self.ensure_schema().await?;
+        }
+
+        // A statement may not update the same row twice, so keep the last record per key
+        let mut latest: HashMap<(Option<String>, Option<String>, Option<String>), usize> = HashMap::new();
+        for (index, row) in self.pending.iter().enumerate() {
+            latest.insert((row[0].clone(), row[1].clone(), row[2].clone()), index);
+        }
+        let mut keep: Vec<usize> = latest.into_values().collect();
+        keep.sort_unstable();
+
+        let params: Vec<Option<String>> = keep
+            .iter()
+            .flat_map(|&index| self.pending[index].iter().cloned())
+            .collect();
+        let affected = self.executor.execute(&self.upsert_sql(keep.len()), &params).await?;
+        debug!("Upserted {} records into {}", affected, self.table);
+
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Quote an identifier for use in SQL
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RuleSetVersion;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct RecordingExecutor {
+        statements: Mutex<Vec<(String, Vec<Option<String>>)>>,
+    }
+
+    #[async_trait]
+    impl PgExecutor for RecordingExecutor {
+        async fn execute(&self, sql: &str, params: &[Option<String>]) -> Result<u64> {
+            self.statements.lock().unwrap().push((sql.to_string(), params.to_vec()));
+            Ok((params.len() / COLUMNS.len()) as u64)
+        }
+    }
+
+    fn record(url: &str, price: &str) -> ScrapedData {
+        let mut data = ScrapedData::new(url.to_string());
+        data.status_code = 200;
+        data.rule_set = Some(RuleSetVersion::new("products".to_string(), 3));
+        data.add_extracted_data("price", vec![price.to_string()]);
+        data
+    }
+
+    #[tokio::test]
+    async fn test_postgres_upsert_batches() {
+        let mut sink = PostgresSink::new(RecordingExecutor::default(), "scraping.pages")
+            .unwrap()
+            .batch_size(3);
+        sink.send_all(&[
+            record("https://shop.example/1", "10"),
+            record("https://shop.example/2", "20"),
+            record("https://shop.example/1", "12"),
+        ]).await.unwrap();
+        sink.send(&record("https://shop.example/3", "30")).await.unwrap();
+        sink.flush().await.unwrap();
+
+        let statements = sink.executor().statements.lock().unwrap();
+        assert_eq!(statements.len(), 3);
+        assert!(statements[0].0.starts_with("CREATE TABLE IF NOT EXISTS \"scraping\".\"pages\""));
+
+        let (sql, params) = &statements[1];
+        assert!(sql.contains("VALUES (($1::text)::text, ($2::text)::text, ($3::text)::integer"));
+        assert!(sql.contains("ON CONFLICT (url, rule_set, rule_set_version) DO UPDATE SET status_code = EXCLUDED.status_code"));
+        assert_eq!(params.len(), 2 * COLUMNS.len());
+        assert_eq!(params[COLUMNS.len()].as_deref(), Some("https://shop.example/1"));
+        assert_eq!(params[COLUMNS.len() + 5].as_deref(), Some(r#"{"price":["12"]}"#));
+
+        assert_eq!(statements[2].1.len(), COLUMNS.len());
+        assert!(PostgresSink::new(RecordingExecutor::default(), "a..b").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Progress reporting for batch scrapes
+//!
+//! A [`ProgressReporter`] passed to [`FerrisFetcher::scrape_multiple_with_progress`]
+//! receives [`ProgressReport`] snapshots: its callback runs at a fixed interval
+//! and once more when the batch ends, while [`ProgressReporter::subscribe`]
+//! gives UIs a watch channel that always holds the latest snapshot.
+//!
+//! [`FerrisFetcher::scrape_multiple_with_progress`]: crate::FerrisFetcher::scrape_multiple_with_progress
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Default interval between progress callbacks
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Snapshot of a batch scrape's progress
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgressReport {
+    /// URLs in the batch
+    pub total: usize,
+    /// URLs scraped successfully
+    pub completed: usize,
+    /// URLs that failed or panicked
+    pub failed: usize,
+    /// Scrapes currently running
+    pub in_flight: usize,
+    /// URLs not started yet
+    pub queued: usize,
+    /// Time since the batch started
+    pub elapsed: Duration,
+    /// Finished URLs per second so far
+    pub rate: f64,
+    /// Estimated time until the remaining URLs finish, once the rate is known
+    pub eta: Option<Duration>,
+}
+
+impl ProgressReport {
+    /// URLs finished, successfully or not
+    pub fn finished(&self) -> usize {
+        self.completed + self.failed
+    }
+
+    /// Fraction of the batch finished, from 0.0 to 1.0
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.finished() as f64 / self.total as f64
+        }
+    }
+
+    /// Whether no URLs are running or waiting
+    pub fn is_done(&self) -> bool {
+        self.in_flight == 0 && self.queued == 0
+    }
+}
+
+type ProgressCallback = Arc<dyn Fn(&ProgressReport) + Send + Sync>;
+
+/// Delivers [`ProgressReport`]s of a batch to a callback and a watch channel
+#[derive(Clone)]
+pub struct ProgressReporter {
+    interval: Duration,
+    callback: Option<ProgressCallback>,
+    sender: Arc<watch::Sender<ProgressReport>>,
+}
+
+impl ProgressReporter {
+    /// Create a reporter calling back every `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval: interval.max(Duration::from_millis(1)),
+            callback: None,
+            sender: Arc::new(watch::channel(ProgressReport::default()).0),
+        }
+    }
+
+    /// Call `callback` with a report every interval and when the batch ends
+    pub fn on_report<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ProgressReport) + Send + Sync + 'static,
+    {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Interval between callbacks
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Receive every change of the report, e.g. to drive a progress bar
+    pub fn subscribe(&self) -> watch::Receiver<ProgressReport> {
+        self.sender.subscribe()
+    }
+
+    /// The latest report
+    pub fn latest(&self) -> ProgressReport {
+        self.sender.borrow().clone()
+    }
+
+    /// Start tracking a batch of `total` URLs
+    pub(crate) fn start(&self, total: usize) -> BatchProgress<'_> {
+        let progress = BatchProgress { reporter: self, started: Instant::now(), total, completed: 0, failed: 0 };
+        progress.update(0, total);
+        progress
+    }
+}
+
+impl Default for ProgressReporter {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTERVAL)
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("interval", &self.interval)
+            .field("callback", &self.callback.is_some())
+            .field("latest", &*self.sender.borrow())
+            .finish()
+    }
+}
+
+/// Counters of a running batch
+pub(crate) struct BatchProgress<'a> {
+    reporter: &'a ProgressReporter,
+    started: Instant,
+    total: usize,
+    completed: usize,
+    failed: usize,
+}
+
+impl BatchProgress<'_> {
+    /// Count a finished URL
+    pub(crate) fn record(&mut self, success: bool) {
+        if success {
+            self.completed += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+
+    /// Publish the current counts to the watch channel
+    pub(crate) fn update(&self, in_flight: usize, queued: usize) {
+        let report = self.report(in_flight, queued);
+        self.reporter.sender.send_replace(report);
+    }
+
+    /// Run the callback with the latest report
+    pub(crate) fn notify(&self) {
+        if let Some(callback) = &self.reporter.callback {
+            callback(&self.reporter.latest());
+        }
+    }
+
+    fn report(&self, in_flight: usize, queued: usize) -> ProgressReport {
+        let elapsed = self.started.elapsed();
+        let finished = self.completed + self.failed;
+        let rate = if elapsed.is_zero() { 0.0 } else { finished as f64 / elapsed.as_secs_f64() };
+        let eta = (rate > 0.0).then(|| Duration::from_secs_f64((in_flight + queued) as f64 / rate));
+        ProgressReport {
+            total: self.total,
+            completed: self.completed,
+            failed: self.failed,
+            in_flight,
+            queued,
+            elapsed,
+            rate,
+            eta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_batch_progress_reports() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let reporter = ProgressReporter::default().on_report(move |_| {
+            counted.fetch_add(1, Ordering::SeqCst);
+        });
+        let receiver = reporter.subscribe();
+
+        let mut progress = reporter.start(4);
+        assert_eq!(receiver.borrow().queued, 4);
+        assert_eq!(reporter.latest().eta, None);
+
+        std::thread::sleep(Duration::from_millis(5));
+        progress.record(true);
+        progress.record(false);
+        progress.update(1, 1);
+        let report = reporter.latest();
+        assert_eq!((report.completed, report.failed, report.in_flight, report.queued), (1, 1, 1, 1));
+        assert_eq!(report.fraction(), 0.5);
+        assert!(report.rate > 0.0 && report.eta.is_some() && !report.is_done());
+
+        progress.notify();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,168 @@
+//! Field projection for exported records
+//!
+//! A [`Projection`] turns a [`ScrapedData`] into a lean JSON object: nested
+//! maps can be flattened into top-level keys, keys included or excluded
+//! (a trailing `*` matches a prefix) and renamed. [`ProjectedJsonl`] writes
+//! projected records as JSON lines for any exporter or sink.
+
+use crate::error::Result;
+use crate::export::RecordFormat;
+use crate::types::ScrapedData;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Which keys of a record are written and under what names
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Projection {
+    /// Keys to keep (all when empty)
+    pub include: Vec<String>,
+    /// Keys to drop
+    pub exclude: Vec<String>,
+    /// Keys to rename, applied last
+    pub rename: HashMap<String, String>,
+    /// Lift `metadata` entries into `metadata.<key>` top-level keys
+    pub flatten_metadata: bool,
+    /// Lift `extracted_data` fields into top-level keys
+    pub flatten_extracted: bool,
+}
+
+impl Projection {
+    /// Create a projection keeping every key
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only these keys
+    pub fn include(mut self, keys: &[&str]) -> Self {
+        self.include = keys.iter().map(|key| key.to_string()).collect();
+        self
+    }
+
+    /// Drop these keys
+    pub fn exclude(mut self, keys: &[&str]) -> Self {
+        self.exclude = keys.iter().map(|key| key.to_string()).collect();
+        self
+    }
+
+    /// Write `from` as `to`
+    pub fn rename(mut self, from: &str, to: &str) -> Self {
+        self.rename.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Lift metadata entries into `metadata.<key>` keys
+    pub fn flatten_metadata(mut self) -> Self {
+        self.flatten_metadata = true;
+        self
+    }
+
+    /// Lift extracted fields into top-level keys
+    pub fn flatten_extracted(mut self) -> Self {
+        self.flatten_extracted = true;
+        self
+    }
+
+    /// Project a record into a JSON object
+    pub fn apply(&self, data: &ScrapedData) -> Result<Map<String, Value>> {
+        let Value::Object(mut record) = serde_json::to_value(data)? else {
+            unreachable!("ScrapedData serializes to an object");
+        };
+
+        if self.flatten_metadata {
+            if let Some(Value::Object(metadata)) = record.remove("metadata") {
+                for (key, value) in metadata {
+                    record.insert(format!("metadata.{}", key), value);
+                }
+            }
+        }
+        if self.flatten_extracted {
+            if let Some(Value::Object(fields)) = record.remove("extracted_data") {
+                for (key, value) in fields {
+                    record.entry(key).or_insert(value);
+                }
+            }
+        }
+
+        Ok(record
+            .into_iter()
+            .filter(|(key, _)| self.include.is_empty() || self.include.iter().any(|pattern| key_matches(pattern, key)))
+            .filter(|(key, _)| !self.exclude.iter().any(|pattern| key_matches(pattern, key)))
+            .map(|(key, value)| (self.rename.get(&key).cloned().unwrap_or(key), value))
+            .collect())
+    }
+}
+
+/// Match a key against a name or `prefix*` pattern
+fn key_matches(pattern: &str, key: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => key.starts_with(prefix),
+        None => pattern == key,
+    }
+}
+
+/// JSON lines of projected records
+#[derive(Debug, Clone, Default)]
+pub struct ProjectedJsonl {
+    projection: Projection,
+}
+
+impl ProjectedJsonl {
+    /// Write records through a projection
+    pub fn new(projection: Projection) -> Self {
+        Self { projection }
+    }
+}
+
+impl RecordFormat for ProjectedJsonl {
+    fn extension(&self) -> &str {
+        "jsonl"
+    }
+
+    fn encode(&self, data: &ScrapedData) -> Result<Vec<u8>> {
+        let mut line = serde_json::to_vec(&self.projection.apply(data)?)?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_projection() {
+        let mut data = ScrapedData::new("https://shop.example/mug".to_string());
+        data.content = "<html>…</html>".to_string();
+        data.metadata.insert("lang".to_string(), json!("en"));
+        data.metadata.insert("og:type".to_string(), json!("product"));
+        data.add_extracted_data("price", vec!["10".to_string()]);
+
+        let projection: Projection = serde_json::from_value(json!({
+            "include": ["url", "metadata.*", "price"],
+            "exclude": ["metadata.og:type"],
+            "rename": {"url": "link"},
+            "flatten_metadata": true,
+            "flatten_extracted": true
+        })).unwrap();
+        assert_eq!(
+            projection,
+            Projection::new()
+                .include(&["url", "metadata.*", "price"])
+                .exclude(&["metadata.og:type"])
+                .rename("url", "link")
+                .flatten_metadata()
+                .flatten_extracted()
+        );
+
+        let line = ProjectedJsonl::new(projection).encode(&data).unwrap();
+        let record: Value = serde_json::from_slice(&line).unwrap();
+        assert_eq!(record, json!({"link": "https://shop.example/mug", "metadata.lang": "en", "price": ["10"]}));
+
+        let full = Projection::new().exclude(&["content"]).apply(&data).unwrap();
+        assert!(!full.contains_key("content"));
+        assert_eq!(full["metadata"]["lang"], "en");
+    }
+}
@@ -0,0 +1,329 @@
+//! Python bindings (`python` feature)
+//!
+//! Exposes [`FerrisFetcher`], extraction rules and batch scraping as the
+//! `ferrisfetcher` Python extension module, so scrapes can be driven from
+//! Python scripts and notebooks. Build the wheel with
+//! [maturin](https://www.maturin.rs) (`maturin develop --release`); the
+//! bundled `pyproject.toml` enables this feature.
+//!
+//! ```python
+//! from ferrisfetcher import FerrisFetcher, Rule
+//!
+//! fetcher = FerrisFetcher(max_concurrent=8, rules=[Rule("price", ".price", multiple=True)])
+//! fetcher.add_rules("links: a[href] @attr(href) all")
+//! pages = fetcher.scrape_many(["https://example.com/a", "https://example.com/b"])
+//! print(pages[0]["extracted_data"]["price"])
+//! ```
+//!
+//! Pages and statistics are returned as plain dicts with the same fields as
+//! their JSON serialization. Every call blocks the calling thread on a Tokio
+//! runtime owned by the fetcher, with the GIL released while it waits.
+//! Invalid configurations and rules raise `ValueError`; failed scrapes raise
+//! `ferrisfetcher.ScrapeError`.
+
+use crate::config::Config;
+use crate::error::FerrisFetcherError;
+use crate::extractor::ExtractionRuleBuilder;
+use crate::rule_dsl;
+use crate::scraper::FerrisFetcher;
+use crate::types::{BatchOptions, ExtractionRule, ExtractionType};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+create_exception!(ferrisfetcher, ScrapeError, PyException, "A scrape failed.");
+
+/// Raise configuration errors as `ValueError` and the rest as `ScrapeError`
+fn to_py_err(error: FerrisFetcherError) -> PyErr {
+    match error {
+        FerrisFetcherError::ConfigError(_) => PyValueError::new_err(error.to_string()),
+        error => ScrapeError::new_err(error.to_string()),
+    }
+}
+
+/// Convert a value to Python objects through its JSON serialization
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|error| PyValueError::new_err(error.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// Duration from a number of seconds given in Python
+fn seconds(secs: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(secs).map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+/// Extraction rule, `Rule(name, selector, attribute=None, multiple=False, html=False, group=None)`
+#[pyclass(name = "Rule", module = "ferrisfetcher", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyRule {
+    rule: ExtractionRule,
+}
+
+#[pymethods]
+impl PyRule {
+    #[new]
+    #[pyo3(signature = (name, selector, attribute=None, multiple=false, html=false, group=None))]
+    fn new(name: &str, selector: &str, attribute: Option<&str>, multiple: bool, html: bool, group: Option<&str>) -> Self {
+        let mut builder = ExtractionRuleBuilder::new(name, selector).multiple(multiple);
+        if let Some(attribute) = attribute {
+            builder = builder.extraction_type(ExtractionType::Attribute).attribute(attribute);
+        } else if html {
+            builder = builder.extraction_type(ExtractionType::Html);
+        }
+        if let Some(group) = group {
+            builder = builder.group(group);
+        }
+        Self { rule: builder.build() }
+    }
+
+    /// Parse rules written in the rule DSL, one per line
+    #[staticmethod]
+    fn parse(text: &str) -> PyResult<Vec<PyRule>> {
+        let rules = rule_dsl::parse_rules(text).map_err(to_py_err)?;
+        Ok(rules.into_iter().map(|rule| PyRule { rule }).collect())
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.rule.name
+    }
+
+    #[getter]
+    fn selector(&self) -> &str {
+        &self.rule.selector
+    }
+
+    #[getter]
+    fn attribute(&self) -> Option<&str> {
+        self.rule.attribute.as_deref()
+    }
+
+    #[getter]
+    fn multiple(&self) -> bool {
+        self.rule.multiple
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Rule({:?}, {:?})", self.rule.name, self.rule.selector)
+    }
+}
+
+/// Scraper, `FerrisFetcher(user_agent=None, timeout=None, max_concurrent=None, rate_limit=True, headers=None, seed=None, rules=None)`
+///
+/// Rule changes apply to scrapes started after them.
+#[pyclass(name = "FerrisFetcher", module = "ferrisfetcher", frozen)]
+pub struct PyFetcher {
+    fetcher: Mutex<FerrisFetcher>,
+    runtime: Runtime,
+}
+
+impl PyFetcher {
+    /// A handle to the fetcher for one call
+    fn handle(&self) -> FerrisFetcher {
+        self.fetcher.lock().expect("fetcher lock poisoned").handle()
+    }
+}
+
+#[pymethods]
+impl PyFetcher {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (user_agent=None, timeout=None, max_concurrent=None, rate_limit=true, headers=None, seed=None, rules=None))]
+    fn new(
+        user_agent: Option<String>,
+        timeout: Option<f64>,
+        max_concurrent: Option<usize>,
+        rate_limit: bool,
+        headers: Option<HashMap<String, String>>,
+        seed: Option<u64>,
+        rules: Option<Vec<PyRule>>,
+    ) -> PyResult<Self> {
+        let mut config = Config::default();
+        if let Some(user_agent) = user_agent {
+            config = config.with_user_agent(user_agent);
+        }
+        if let Some(timeout) = timeout {
+            config = config.with_timeout(seconds(timeout)?);
+        }
+        if let Some(max) = max_concurrent {
+            config = config.with_max_concurrent_requests(max);
+        }
+        if !rate_limit {
+            config = config.without_rate_limit();
+        }
+        for (name, value) in headers.unwrap_or_default() {
+            config = config.with_header(&name, &value).map_err(to_py_err)?;
+        }
+        if let Some(seed) = seed {
+            config = config.with_seed(seed);
+        }
+        config.validate().map_err(to_py_err)?;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
+        let rules = rules.unwrap_or_default().into_iter().map(|rule| rule.rule).collect();
+        let fetcher = {
+            let _guard = runtime.enter();
+            FerrisFetcher::with_config_and_rules(config, rules).map_err(to_py_err)?
+        };
+        Ok(Self { fetcher: Mutex::new(fetcher), runtime })
+    }
+
+    /// Add a rule, replacing any rule of the same name
+    fn add_rule(&self, rule: PyRule) {
+        self.fetcher.lock().expect("fetcher lock poisoned").add_extraction_rule(rule.rule);
+    }
+
+    /// Add rules written in the rule DSL, one per line
+    fn add_rules(&self, text: &str) -> PyResult<()> {
+        let rules = rule_dsl::parse_rules(text).map_err(to_py_err)?;
+        let mut fetcher = self.fetcher.lock().expect("fetcher lock poisoned");
+        for rule in rules {
+            fetcher.add_extraction_rule(rule);
+        }
+        Ok(())
+    }
+
+    /// Remove a rule by name, returning whether it existed
+    fn remove_rule(&self, name: &str) -> bool {
+        self.fetcher.lock().expect("fetcher lock poisoned").remove_extraction_rule(name).is_some()
+    }
+
+    /// The current rules, sorted by name
+    fn rules(&self) -> Vec<PyRule> {
+        let fetcher = self.fetcher.lock().expect("fetcher lock poisoned");
+        let mut rules: Vec<PyRule> = fetcher.extraction_rules().values().map(|rule| PyRule { rule: rule.clone() }).collect();
+        rules.sort_by(|a, b| a.rule.name.cmp(&b.rule.name));
+        rules
+    }
+
+    /// Scrape a page, returning it as a dict
+    fn scrape(&self, py: Python<'_>, url: &str) -> PyResult<PyObject> {
+        let fetcher = self.handle();
+        let data = py.allow_threads(|| self.runtime.block_on(fetcher.scrape(url))).map_err(to_py_err)?;
+        to_python(py, &data)
+    }
+
+    /// Scrape pages concurrently, returning those scraped in input order
+    ///
+    /// `delay` and `jitter` are seconds waited before each request; failed
+    /// URLs are left out.
+    #[pyo3(signature = (urls, delay=None, jitter=None, shuffle=false, seed=None))]
+    fn scrape_many(
+        &self,
+        py: Python<'_>,
+        urls: Vec<String>,
+        delay: Option<f64>,
+        jitter: Option<f64>,
+        shuffle: bool,
+        seed: Option<u64>,
+    ) -> PyResult<PyObject> {
+        let mut options = BatchOptions::new().preserve_order(true);
+        if let Some(delay) = delay {
+            options = options.with_delay(seconds(delay)?);
+        }
+        if let Some(jitter) = jitter {
+            options = options.with_jitter(seconds(jitter)?);
+        }
+        if shuffle {
+            options = options.shuffled();
+        }
+        if let Some(seed) = seed {
+            options = options.with_seed(seed);
+        }
+        let fetcher = self.handle();
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let pages = py.allow_threads(|| self.runtime.block_on(fetcher.scrape_batch(&urls, &options))).map_err(to_py_err)?;
+        to_python(py, &pages)
+    }
+
+    /// Scrape pages concurrently, returning `{"scraped", "failed", "not_started"}`
+    ///
+    /// Each failure is a dict with the `url`, the `error` message and its `category`.
+    fn scrape_many_detailed(&self, py: Python<'_>, urls: Vec<String>) -> PyResult<PyObject> {
+        let fetcher = self.handle();
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let results = py.allow_threads(|| self.runtime.block_on(fetcher.scrape_multiple_detailed(&urls)));
+        let failed: Vec<_> = results.failed.iter()
+            .map(|(url, error)| json!({ "url": url, "error": error.to_string(), "category": error.category() }))
+            .chain(results.panicked.iter().map(|(url, message)| json!({ "url": url, "error": message, "category": "Panic" })))
+            .collect();
+        to_python(py, &json!({ "scraped": results.scraped, "failed": failed, "not_started": results.not_started }))
+    }
+
+    /// Request statistics as a dict
+    fn stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let fetcher = self.handle();
+        let stats = py.allow_threads(|| self.runtime.block_on(fetcher.get_stats()));
+        to_python(py, &stats)
+    }
+
+    /// Requests, failures, bytes and latency percentiles per host
+    fn stats_by_domain(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let fetcher = self.handle();
+        let stats = py.allow_threads(|| self.runtime.block_on(fetcher.get_stats_by_domain()));
+        to_python(py, &stats)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FerrisFetcher(rules={})", self.fetcher.lock().expect("fetcher lock poisoned").extraction_rules().len())
+    }
+}
+
+/// The `ferrisfetcher` Python module
+#[pymodule]
+#[pyo3(name = "ferrisfetcher")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyFetcher>()?;
+    module.add_class::<PyRule>()?;
+    module.add("ScrapeError", module.py().get_type::<ScrapeError>())?;
+    module.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use crate::test_support::serve_page;
+
+    #[test]
+    fn test_python_fetcher() {
+        let base = serve_page(r#"<html><title>Snake</title><span class="price">3</span><span class="price">4</span></html>"#);
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let rule = PyRule::new("price", ".price", None, true, false, None);
+            let fetcher = PyFetcher::new(None, Some(5.0), None, false, None, None, Some(vec![rule])).unwrap();
+            assert!(fetcher.add_rules("broken").unwrap_err().is_instance_of::<PyValueError>(py));
+            fetcher.add_rules("heading: title").unwrap();
+            assert_eq!(fetcher.rules().iter().map(|rule| rule.name()).collect::<Vec<_>>(), ["heading", "price"]);
+
+            let page = fetcher.scrape(py, &format!("{}/a", base)).unwrap();
+            let page = page.downcast_bound::<PyDict>(py).unwrap();
+            assert_eq!(page.get_item("title").unwrap().unwrap().extract::<String>().unwrap(), "Snake");
+            let prices: Vec<String> = page.get_item("extracted_data").unwrap().unwrap().get_item("price").unwrap().extract().unwrap();
+            assert_eq!(prices, ["3", "4"]);
+
+            let urls = vec![format!("{}/b", base), format!("{}/c", base)];
+            let pages: Vec<Bound<'_, PyDict>> = fetcher.scrape_many(py, urls, None, None, false, None).unwrap().extract(py).unwrap();
+            assert_eq!(pages[1].get_item("url").unwrap().unwrap().extract::<String>().unwrap(), format!("{}/c", base));
+
+            let results = fetcher.scrape_many_detailed(py, vec!["http://127.0.0.1:1/".to_string()]).unwrap();
+            let failed = results.bind(py).get_item("failed").unwrap();
+            assert_eq!(failed.get_item(0).unwrap().get_item("url").unwrap().extract::<String>().unwrap(), "http://127.0.0.1:1/");
+
+            assert!(fetcher.remove_rule("heading"));
+            let stats = fetcher.stats(py).unwrap();
+            assert_eq!(stats.bind(py).get_item("total_requests").unwrap().extract::<u64>().unwrap(), 4);
+        });
+    }
+}
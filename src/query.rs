@@ -0,0 +1,465 @@
+//! In-memory queries over a batch of scrape results
+//!
+//! A [`ResultSet`] filters, groups and aggregates [`ScrapedData`] records for
+//! quick analyses without exporting them first:
+//!
+//! ```rust
+//! use ferrisfetcher::{ResultSet, ScrapedData};
+//!
+//! let results: ResultSet = ["https://a.example/1", "https://a.example/2", "https://b.example/"]
+//!     .into_iter()
+//!     .map(|url| ScrapedData::new(url.to_string()))
+//!     .collect();
+//! let per_host = results.group_by("host").count();
+//! assert_eq!(per_host["a.example"], 2);
+//! ```
+//!
+//! Fields are extracted values or record columns such as `url`, `host` and
+//! `status_code` (see [`column_value`]). Numeric aggregates parse each
+//! extracted value, skipping those that are not numbers.
+//!
+//! [`ResultSet::profile`] summarises extraction quality per field: how often
+//! it was filled, how many distinct values and what lengths it had, and which
+//! type its values look like.
+
+use crate::error::Result;
+use crate::export::column_value;
+use crate::record_filter::{FilterChain, RecordFilter};
+use crate::types::ScrapedData;
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use url::Url;
+
+/// A batch of scrape results that can be filtered, grouped and aggregated
+#[derive(Debug, Clone, Default)]
+pub struct ResultSet {
+    records: Vec<ScrapedData>,
+}
+
+impl ResultSet {
+    /// Wrap a batch of results
+    pub fn new(records: Vec<ScrapedData>) -> Self {
+        Self { records }
+    }
+
+    /// Number of records
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether there are no records
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The records in their original order
+    pub fn records(&self) -> &[ScrapedData] {
+        &self.records
+    }
+
+    /// Iterate over the records
+    pub fn iter(&self) -> std::slice::Iter<'_, ScrapedData> {
+        self.records.iter()
+    }
+
+    /// Take the records back out
+    pub fn into_inner(self) -> Vec<ScrapedData> {
+        self.records
+    }
+
+    /// Keep the records matching a predicate
+    pub fn filter<F>(self, mut predicate: F) -> Self
+    where
+        F: FnMut(&ScrapedData) -> bool,
+    {
+        Self { records: self.records.into_iter().filter(|data| predicate(data)).collect() }
+    }
+
+    /// Keep the records whose `field` equals `value`
+    pub fn where_eq(self, field: &str, value: &str) -> Self {
+        self.filter(|data| column_value(data, field) == value)
+    }
+
+    /// Keep the records accepted by declarative filters, failing on invalid patterns
+    pub fn filter_by(self, filters: Vec<RecordFilter>) -> Result<Self> {
+        let mut chain = FilterChain::new(filters)?;
+        Ok(self.filter(|data| chain.accept(data)))
+    }
+
+    /// Split the records by the value of `field`, records without it under `""`
+    pub fn group_by(&self, field: &str) -> GroupedResults {
+        let mut groups: BTreeMap<String, ResultSet> = BTreeMap::new();
+        for data in &self.records {
+            groups.entry(column_value(data, field)).or_default().records.push(data.clone());
+        }
+        GroupedResults { groups }
+    }
+
+    /// Number of records
+    pub fn count(&self) -> usize {
+        self.len()
+    }
+
+    /// Values of `field` in record order, skipping records without it
+    pub fn values(&self, field: &str) -> Vec<String> {
+        self.records.iter().map(|data| column_value(data, field)).filter(|value| !value.is_empty()).collect()
+    }
+
+    /// Distinct values of `field`, sorted
+    pub fn distinct(&self, field: &str) -> Vec<String> {
+        self.values(field).into_iter().collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    /// Sum of the numeric values of `field`
+    pub fn sum(&self, field: &str) -> f64 {
+        self.numbers(field).sum()
+    }
+
+    /// Mean of the numeric values of `field`, `None` if there are none
+    pub fn mean(&self, field: &str) -> Option<f64> {
+        let (count, sum) = self.numbers(field).fold((0usize, 0.0), |(count, sum), value| (count + 1, sum + value));
+        (count > 0).then(|| sum / count as f64)
+    }
+
+    /// Smallest numeric value of `field`
+    pub fn min(&self, field: &str) -> Option<f64> {
+        self.numbers(field).reduce(f64::min)
+    }
+
+    /// Largest numeric value of `field`
+    pub fn max(&self, field: &str) -> Option<f64> {
+        self.numbers(field).reduce(f64::max)
+    }
+
+    /// Profile every extracted field of the batch
+    pub fn profile(&self) -> BatchProfile {
+        let fields: BTreeSet<&String> = self.records.iter().flat_map(|data| data.extracted_data.keys()).collect();
+        self.profile_fields(fields.into_iter().map(String::as_str))
+    }
+
+    /// Profile the given fields, which may also be record columns
+    pub fn profile_fields<'a>(&self, fields: impl IntoIterator<Item = &'a str>) -> BatchProfile {
+        let fields = fields.into_iter().map(|field| self.profile_field(field)).collect();
+        BatchProfile { records: self.len(), fields }
+    }
+
+    fn profile_field(&self, field: &str) -> FieldProfile {
+        let mut filled = 0;
+        let mut values: Vec<String> = Vec::new();
+        for data in &self.records {
+            let record_values: Vec<String> = match data.extracted_data.get(field) {
+                Some(extracted) => extracted.iter().filter(|value| !value.trim().is_empty()).cloned().collect(),
+                None => Some(column_value(data, field)).filter(|value| !value.is_empty()).into_iter().collect(),
+            };
+            if !record_values.is_empty() {
+                filled += 1;
+            }
+            values.extend(record_values);
+        }
+
+        let lengths = values.iter().map(|value| value.chars().count());
+        let (inferred_type, type_share) = infer_type(&values);
+        FieldProfile {
+            field: field.to_string(),
+            filled,
+            fill_rate: if self.is_empty() { 0.0 } else { filled as f64 / self.len() as f64 },
+            values: values.len(),
+            distinct: values.iter().collect::<BTreeSet<_>>().len(),
+            min_length: lengths.clone().min(),
+            max_length: lengths.max(),
+            inferred_type,
+            type_share,
+        }
+    }
+
+    /// Numeric values of `field`, one per extracted value
+    fn numbers<'a>(&'a self, field: &'a str) -> impl Iterator<Item = f64> + 'a {
+        self.records.iter().flat_map(move |data| match data.extracted_data.get(field) {
+            Some(values) => values.iter().filter_map(|value| parse_number(value)).collect::<Vec<_>>(),
+            None => parse_number(&column_value(data, field)).into_iter().collect(),
+        })
+    }
+}
+
+impl From<Vec<ScrapedData>> for ResultSet {
+    fn from(records: Vec<ScrapedData>) -> Self {
+        Self::new(records)
+    }
+}
+
+impl FromIterator<ScrapedData> for ResultSet {
+    fn from_iter<I: IntoIterator<Item = ScrapedData>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for ResultSet {
+    type Item = ScrapedData;
+    type IntoIter = std::vec::IntoIter<ScrapedData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ResultSet {
+    type Item = &'a ScrapedData;
+    type IntoIter = std::slice::Iter<'a, ScrapedData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
+/// Records of a [`ResultSet`] grouped by a field's value, sorted by that value
+#[derive(Debug, Clone, Default)]
+pub struct GroupedResults {
+    groups: BTreeMap<String, ResultSet>,
+}
+
+impl GroupedResults {
+    /// Number of groups
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Whether there are no groups
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// The records of one group
+    pub fn get(&self, key: &str) -> Option<&ResultSet> {
+        self.groups.get(key)
+    }
+
+    /// The groups by key
+    pub fn groups(&self) -> &BTreeMap<String, ResultSet> {
+        &self.groups
+    }
+
+    /// Take the groups back out
+    pub fn into_groups(self) -> BTreeMap<String, ResultSet> {
+        self.groups
+    }
+
+    /// Apply an aggregation to every group
+    pub fn aggregate<T, F>(&self, aggregation: F) -> BTreeMap<String, T>
+    where
+        F: Fn(&ResultSet) -> T,
+    {
+        self.groups.iter().map(|(key, group)| (key.clone(), aggregation(group))).collect()
+    }
+
+    /// Records per group
+    pub fn count(&self) -> BTreeMap<String, usize> {
+        self.aggregate(ResultSet::count)
+    }
+
+    /// Sum of the numeric values of `field` per group
+    pub fn sum(&self, field: &str) -> BTreeMap<String, f64> {
+        self.aggregate(|group| group.sum(field))
+    }
+
+    /// Mean of the numeric values of `field` per group
+    pub fn mean(&self, field: &str) -> BTreeMap<String, Option<f64>> {
+        self.aggregate(|group| group.mean(field))
+    }
+
+    /// Smallest numeric value of `field` per group
+    pub fn min(&self, field: &str) -> BTreeMap<String, Option<f64>> {
+        self.aggregate(|group| group.min(field))
+    }
+
+    /// Largest numeric value of `field` per group
+    pub fn max(&self, field: &str) -> BTreeMap<String, Option<f64>> {
+        self.aggregate(|group| group.max(field))
+    }
+}
+
+/// Data-quality summary of a batch, one entry per field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchProfile {
+    /// Records in the batch
+    pub records: usize,
+    /// Profiles in field name order
+    pub fields: Vec<FieldProfile>,
+}
+
+impl BatchProfile {
+    /// Profile of one field
+    pub fn field(&self, name: &str) -> Option<&FieldProfile> {
+        self.fields.iter().find(|profile| profile.field == name)
+    }
+}
+
+/// Fill rate, cardinality, lengths and likely type of one field across a batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldProfile {
+    /// Field name
+    pub field: String,
+    /// Records with at least one non-empty value
+    pub filled: usize,
+    /// Share of records with a value (0.0-1.0)
+    pub fill_rate: f64,
+    /// Non-empty values across all records
+    pub values: usize,
+    /// Distinct non-empty values
+    pub distinct: usize,
+    /// Shortest value in characters
+    pub min_length: Option<usize>,
+    /// Longest value in characters
+    pub max_length: Option<usize>,
+    /// Type most values look like
+    pub inferred_type: FieldType,
+    /// Share of values matching `inferred_type` (0.0-1.0)
+    pub type_share: f64,
+}
+
+/// Type guessed from a field's values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    /// No values to judge
+    Empty,
+    /// `true`/`false` or `yes`/`no`
+    Boolean,
+    /// Whole numbers
+    Integer,
+    /// Numbers with a fractional part, or a mix with whole numbers
+    Number,
+    /// RFC 3339 timestamps or `YYYY-MM-DD` dates
+    Date,
+    /// Absolute URLs
+    Url,
+    /// Anything else
+    Text,
+}
+
+impl FieldType {
+    /// Type of a single value
+    fn of(value: &str) -> Self {
+        let value = value.trim();
+        if matches!(value.to_ascii_lowercase().as_str(), "true" | "false" | "yes" | "no") {
+            FieldType::Boolean
+        } else if value.replace(',', "").parse::<i64>().is_ok() {
+            FieldType::Integer
+        } else if parse_number(value).is_some() {
+            FieldType::Number
+        } else if DateTime::parse_from_rfc3339(value).is_ok() || NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+            FieldType::Date
+        } else if Url::parse(value).is_ok_and(|url| url.has_host()) {
+            FieldType::Url
+        } else {
+            FieldType::Text
+        }
+    }
+}
+
+/// Most common type among values, with integers counting as numbers when
+/// both occur, and the share of values it covers
+fn infer_type(values: &[String]) -> (FieldType, f64) {
+    if values.is_empty() {
+        return (FieldType::Empty, 0.0);
+    }
+    let mut counts: BTreeMap<FieldType, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(FieldType::of(value)).or_default() += 1;
+    }
+    if let Some(integers) = counts.get(&FieldType::Integer).copied().filter(|_| counts.contains_key(&FieldType::Number)) {
+        counts.remove(&FieldType::Integer);
+        *counts.entry(FieldType::Number).or_default() += integers;
+    }
+    // Ties go to the type declared first in `FieldType`
+    let (field_type, count) = counts.into_iter().fold((FieldType::Text, 0), |best, (field_type, count)| {
+        if count > best.1 { (field_type, count) } else { best }
+    });
+    (field_type, count as f64 / values.len() as f64)
+}
+
+/// Parse a number, allowing surrounding whitespace and thousands separators
+fn parse_number(value: &str) -> Option<f64> {
+    let value = value.trim().replace(',', "");
+    value.parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(url: &str, status_code: u16, prices: &[&str]) -> ScrapedData {
+        let mut data = ScrapedData::new(url.to_string());
+        data.status_code = status_code;
+        if !prices.is_empty() {
+            data.extracted_data.insert("price".to_string(), prices.iter().map(|price| price.to_string()).collect());
+        }
+        data
+    }
+
+    fn results() -> ResultSet {
+        vec![
+            record("https://shop.example/a", 200, &["10", "1,000"]),
+            record("https://shop.example/b", 404, &[]),
+            record("https://blog.example/", 200, &["n/a", "5.5"]),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn test_group_by_and_aggregate() {
+        let grouped = results().group_by("host");
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped.count(), BTreeMap::from([("blog.example".to_string(), 1), ("shop.example".to_string(), 2)]));
+        assert_eq!(grouped.sum("price")["shop.example"], 1010.0);
+        assert_eq!(grouped.max("price")["blog.example"], Some(5.5));
+        assert_eq!(grouped.aggregate(|group| group.distinct("status_code"))["shop.example"], vec!["200", "404"]);
+
+        let by_status = results().group_by("status_code");
+        assert_eq!(by_status.get("404").unwrap().mean("price"), None);
+    }
+
+    #[test]
+    fn test_filter_and_aggregate() {
+        let ok = results().where_eq("status_code", "200");
+        assert_eq!(ok.count(), 2);
+        assert_eq!(ok.min("price"), Some(5.5));
+        assert_eq!(ok.mean("price"), Some(1015.5 / 3.0));
+        assert_eq!(ok.values("host"), vec!["shop.example", "blog.example"]);
+
+        let shop = results().filter_by(vec![RecordFilter::keep_if_matches("url", "^https://shop")]).unwrap();
+        assert_eq!(shop.len(), 2);
+        assert!(results().filter_by(vec![RecordFilter::keep_if_matches("url", "(")]).is_err());
+
+        let priced = results().filter(|data| data.extracted_data.contains_key("price"));
+        assert_eq!(priced.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn test_profile() {
+        let mut records = results().into_inner();
+        records[0].extracted_data.insert("published".to_string(), vec!["2025-06-01".to_string()]);
+        records[1].extracted_data.insert("published".to_string(), vec!["2025-06-02T10:00:00Z".to_string(), " ".to_string()]);
+        records[2].extracted_data.insert("link".to_string(), vec!["https://blog.example/post".to_string()]);
+        let profile = ResultSet::new(records).profile();
+
+        assert_eq!(profile.records, 3);
+        assert_eq!(profile.fields.iter().map(|field| field.field.as_str()).collect::<Vec<_>>(), ["link", "price", "published"]);
+
+        let price = profile.field("price").unwrap();
+        assert_eq!((price.filled, price.values, price.distinct), (2, 4, 4));
+        assert!((price.fill_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!((price.min_length, price.max_length), (Some(2), Some(5)));
+        assert_eq!((price.inferred_type, price.type_share), (FieldType::Number, 0.75));
+
+        let published = profile.field("published").unwrap();
+        assert_eq!((published.filled, published.values), (2, 2));
+        assert_eq!((published.inferred_type, published.type_share), (FieldType::Date, 1.0));
+        assert_eq!(profile.field("link").unwrap().inferred_type, FieldType::Url);
+
+        let columns = results().profile_fields(["status_code", "missing"]);
+        assert_eq!(columns.fields[0].inferred_type, FieldType::Integer);
+        assert_eq!(columns.fields[0].distinct, 2);
+        assert_eq!((columns.fields[1].filled, columns.fields[1].inferred_type), (0, FieldType::Empty));
+    }
+}
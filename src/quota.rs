@@ -0,0 +1,328 @@
+//! Scrape quotas per API consumer
+//!
+//! Services embedding the crate can give each tenant (API key, user, team) a
+//! [`Quota`] of requests and downloaded bytes per window and consult a
+//! [`QuotaManager`] before dispatching scrapes on the tenant's behalf. Once a
+//! limit is reached, [`QuotaManager::acquire`] fails with
+//! [`FerrisFetcherError::QuotaExceeded`], carrying the tenant's usage and when
+//! the window resets.
+//!
+//! Windows are fixed and aligned to multiples of their length since the Unix
+//! epoch, so every process sharing a [`QuotaStore`] agrees on them. Usage is
+//! kept in memory by default; implement [`QuotaStore`] to share it, e.g. with
+//! an increment on a key per tenant and window in Redis.
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{FerrisFetcherError, Result};
+use crate::scraper::FerrisFetcher;
+use crate::types::ScrapedData;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// Limits of one consumer within each window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quota {
+    /// Requests allowed per window, unlimited if `None`
+    pub max_requests: Option<u64>,
+    /// Downloaded bytes allowed per window, unlimited if `None`
+    pub max_bytes: Option<u64>,
+    /// Length of a window
+    pub window: Duration,
+}
+
+impl Quota {
+    /// An unlimited quota counted over windows of `window`
+    pub fn per(window: Duration) -> Self {
+        Self { max_requests: None, max_bytes: None, window }
+    }
+
+    /// Allow at most `max_requests` requests per window
+    pub fn with_max_requests(mut self, max_requests: u64) -> Self {
+        self.max_requests = Some(max_requests);
+        self
+    }
+
+    /// Allow at most `max_bytes` downloaded bytes per window
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Start of the window containing `time`
+    fn window_start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let window = (self.window.as_millis() as i64).max(1);
+        let start = time.timestamp_millis().div_euclid(window) * window;
+        DateTime::from_timestamp_millis(start).unwrap_or(time)
+    }
+}
+
+/// Requests and bytes used within a window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    /// Requests counted
+    pub requests: u64,
+    /// Downloaded bytes counted
+    pub bytes: u64,
+}
+
+/// Persists usage per consumer and window
+///
+/// `consume` must add to the usage atomically, so that concurrent callers
+/// sharing a store never lose counts.
+#[async_trait]
+pub trait QuotaStore: fmt::Debug + Send + Sync {
+    /// Usage of `key` in the window starting at `window`
+    async fn usage(&self, key: &str, window: DateTime<Utc>) -> Result<QuotaUsage>;
+
+    /// Add to the usage of `key` in the window starting at `window`, returning the new total
+    async fn consume(&self, key: &str, window: DateTime<Utc>, usage: QuotaUsage) -> Result<QuotaUsage>;
+}
+
+/// Usage kept in this process, forgetting past windows
+#[derive(Debug, Default)]
+pub struct MemoryQuotaStore {
+    usage: Mutex<HashMap<String, (DateTime<Utc>, QuotaUsage)>>,
+}
+
+impl MemoryQuotaStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl QuotaStore for MemoryQuotaStore {
+    async fn usage(&self, key: &str, window: DateTime<Utc>) -> Result<QuotaUsage> {
+        let usage = self.usage.lock().expect("quota store lock poisoned");
+        Ok(usage.get(key).filter(|(start, _)| *start == window).map(|(_, usage)| *usage).unwrap_or_default())
+    }
+
+    async fn consume(&self, key: &str, window: DateTime<Utc>, added: QuotaUsage) -> Result<QuotaUsage> {
+        let mut usage = self.usage.lock().expect("quota store lock poisoned");
+        let entry = usage.entry(key.to_string()).or_insert((window, QuotaUsage::default()));
+        if entry.0 != window {
+            *entry = (window, QuotaUsage::default());
+        }
+        entry.1.requests += added.requests;
+        entry.1.bytes += added.bytes;
+        Ok(entry.1)
+    }
+}
+
+/// A consumer's usage against its quota in the current window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaStatus {
+    /// Consumer the quota belongs to
+    pub key: String,
+    /// Requests used in the window
+    pub requests: u64,
+    /// Downloaded bytes used in the window
+    pub bytes: u64,
+    /// Requests allowed per window, unlimited if `None`
+    pub max_requests: Option<u64>,
+    /// Downloaded bytes allowed per window, unlimited if `None`
+    pub max_bytes: Option<u64>,
+    /// When the window ends and usage starts from zero
+    pub resets_at: DateTime<Utc>,
+}
+
+impl QuotaStatus {
+    /// Requests left in the window, `None` if unlimited
+    pub fn remaining_requests(&self) -> Option<u64> {
+        self.max_requests.map(|max| max.saturating_sub(self.requests))
+    }
+
+    /// Bytes left in the window, `None` if unlimited
+    pub fn remaining_bytes(&self) -> Option<u64> {
+        self.max_bytes.map(|max| max.saturating_sub(self.bytes))
+    }
+
+    /// Whether no further request may be dispatched in the window
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_requests() == Some(0) || self.remaining_bytes() == Some(0)
+    }
+
+    /// Time until the window resets, measured from `now`
+    pub fn retry_after(&self, now: DateTime<Utc>) -> Duration {
+        (self.resets_at - now).to_std().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for QuotaStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit = |max: Option<u64>| max.map_or_else(|| "unlimited".to_string(), |max| max.to_string());
+        write!(
+            f,
+            "{} used {}/{} requests and {}/{} bytes, resets at {}",
+            self.key,
+            self.requests,
+            limit(self.max_requests),
+            self.bytes,
+            limit(self.max_bytes),
+            self.resets_at.to_rfc3339(),
+        )
+    }
+}
+
+/// Enforces request and byte quotas per consumer
+#[derive(Debug, Clone)]
+pub struct QuotaManager {
+    default_quota: Quota,
+    quotas: HashMap<String, Quota>,
+    store: Arc<dyn QuotaStore>,
+    clock: Arc<dyn Clock>,
+}
+
+impl QuotaManager {
+    /// Apply `default_quota` to every consumer without a quota of its own
+    pub fn new(default_quota: Quota) -> Self {
+        Self {
+            default_quota,
+            quotas: HashMap::new(),
+            store: Arc::new(MemoryQuotaStore::new()),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Give one consumer a quota of its own
+    pub fn with_quota(mut self, key: &str, quota: Quota) -> Self {
+        self.quotas.insert(key.to_string(), quota);
+        self
+    }
+
+    /// Keep usage in `store`, e.g. one shared by several processes
+    pub fn with_store(mut self, store: Arc<dyn QuotaStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Place windows using `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Quota applying to a consumer
+    pub fn quota(&self, key: &str) -> &Quota {
+        self.quotas.get(key).unwrap_or(&self.default_quota)
+    }
+
+    /// A consumer's usage in the current window, without counting anything
+    pub async fn status(&self, key: &str) -> Result<QuotaStatus> {
+        let quota = self.quota(key);
+        let window = quota.window_start(self.clock.now());
+        let usage = self.store.usage(key, window).await?;
+        Ok(self.status_of(key, quota, window, usage))
+    }
+
+    /// Count one request for a consumer, failing with
+    /// [`FerrisFetcherError::QuotaExceeded`] if its quota does not allow it
+    ///
+    /// A request is refused once the byte limit has been reached as well;
+    /// refused requests still count towards the window's usage.
+    pub async fn acquire(&self, key: &str) -> Result<QuotaStatus> {
+        let quota = self.quota(key);
+        let window = quota.window_start(self.clock.now());
+        let usage = self.store.consume(key, window, QuotaUsage { requests: 1, bytes: 0 }).await?;
+        let status = self.status_of(key, quota, window, usage);
+
+        let over_requests = quota.max_requests.is_some_and(|max| usage.requests > max);
+        let over_bytes = quota.max_bytes.is_some_and(|max| usage.bytes >= max);
+        if over_requests || over_bytes {
+            debug!("Refused request for {}: {}", key, status);
+            return Err(FerrisFetcherError::QuotaExceeded(status));
+        }
+        Ok(status)
+    }
+
+    /// Count downloaded bytes for a consumer
+    pub async fn record_bytes(&self, key: &str, bytes: u64) -> Result<QuotaStatus> {
+        let quota = self.quota(key);
+        let window = quota.window_start(self.clock.now());
+        let usage = self.store.consume(key, window, QuotaUsage { requests: 0, bytes }).await?;
+        Ok(self.status_of(key, quota, window, usage))
+    }
+
+    /// Scrape `url` on behalf of a consumer, counting the request and the
+    /// page's body size against its quota
+    pub async fn scrape(&self, key: &str, fetcher: &FerrisFetcher, url: &str) -> Result<ScrapedData> {
+        self.acquire(key).await?;
+        let data = fetcher.scrape(url).await?;
+        self.record_bytes(key, data.content.len() as u64).await?;
+        Ok(data)
+    }
+
+    fn status_of(&self, key: &str, quota: &Quota, window: DateTime<Utc>, usage: QuotaUsage) -> QuotaStatus {
+        let window_length = TimeDelta::from_std(quota.window).unwrap_or(TimeDelta::MAX);
+        QuotaStatus {
+            key: key.to_string(),
+            requests: usage.requests,
+            bytes: usage.bytes,
+            max_requests: quota.max_requests,
+            max_bytes: quota.max_bytes,
+            resets_at: window.checked_add_signed(window_length).unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::config::Config;
+    use chrono::TimeZone;
+    use crate::test_support::serve_page;
+
+    #[tokio::test]
+    async fn test_quota_windows() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 30).unwrap();
+        let clock = Arc::new(ManualClock::new(start));
+        let quotas = QuotaManager::new(Quota::per(Duration::from_secs(60)).with_max_requests(2))
+            .with_quota("premium", Quota::per(Duration::from_secs(60)).with_max_requests(10))
+            .with_clock(clock.clone());
+
+        assert_eq!(quotas.acquire("free").await.unwrap().remaining_requests(), Some(1));
+        assert!(quotas.acquire("free").await.unwrap().is_exhausted());
+        let FerrisFetcherError::QuotaExceeded(status) = quotas.acquire("free").await.unwrap_err() else {
+            panic!("expected the quota to be exceeded");
+        };
+        assert_eq!(status.key, "free");
+        assert_eq!(status.resets_at, Utc.with_ymd_and_hms(2025, 1, 1, 10, 1, 0).unwrap());
+        assert_eq!(status.retry_after(start), Duration::from_secs(30));
+        assert_eq!(
+            status.to_string(),
+            "free used 3/2 requests and 0/unlimited bytes, resets at 2025-01-01T10:01:00+00:00"
+        );
+
+        // Other consumers are counted separately
+        assert_eq!(quotas.acquire("premium").await.unwrap().remaining_requests(), Some(9));
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(quotas.status("free").await.unwrap().requests, 0);
+        assert!(quotas.acquire("free").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_quota_scrape_bytes() {
+        let url = format!("{}/", serve_page("<html><title>Quota</title></html>"));
+
+        let fetcher = FerrisFetcher::with_config(Config::default().without_rate_limit()).unwrap();
+        let quotas = QuotaManager::new(Quota::per(Duration::from_secs(3600)).with_max_bytes(40));
+        let data = quotas.scrape("tenant", &fetcher, &url).await.unwrap();
+        assert_eq!(data.title.as_deref(), Some("Quota"));
+        assert_eq!(quotas.status("tenant").await.unwrap().bytes, data.content.len() as u64);
+
+        quotas.scrape("tenant", &fetcher, &url).await.unwrap();
+        let error = quotas.scrape("tenant", &fetcher, &url).await.unwrap_err();
+        assert!(matches!(error, FerrisFetcherError::QuotaExceeded(ref status) if status.remaining_bytes() == Some(0)));
+        assert_eq!(error.category(), "Quota");
+        assert!(!error.is_retryable());
+    }
+}
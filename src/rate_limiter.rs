@@ -0,0 +1,226 @@
+//! Per-host token-bucket rate limiting
+//!
+//! Every host gets its own bucket holding up to `requests_per_period`
+//! tokens, refilled evenly over `period`, so hosts are throttled
+//! independently instead of sharing one global delay. Requests to the same
+//! host are additionally spaced by `delay_between_requests`.
+//!
+//! Limits with an [`AdaptiveRate`] scale each host's refill rate by a fraction
+//! that [`RateLimiter::observe`] lowers when the host answers 429/503 or
+//! slows down sharply, and raises again as responses stay healthy.
+
+use crate::types::{AdaptiveRate, RateLimit};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket of one host
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    next_request: Instant,
+    /// Share of the configured rate currently allowed
+    fraction: f64,
+    /// Rolling average response latency in milliseconds
+    latency_ms: Option<f64>,
+}
+
+/// Weight of the newest response in a host's rolling latency average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Thread-safe per-host rate limiter
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    default: Option<RateLimit>,
+    overrides: HashMap<String, RateLimit>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Limit every host by `default`, and the given hosts (and their
+    /// subdomains) by their overrides
+    pub fn new(default: Option<RateLimit>, overrides: HashMap<String, RateLimit>) -> Self {
+        Self {
+            default,
+            overrides: overrides.into_iter().map(|(host, limit)| (host.to_lowercase(), limit)).collect(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether any host is limited
+    pub fn is_enabled(&self) -> bool {
+        self.default.is_some() || !self.overrides.is_empty()
+    }
+
+    /// Limit applying to a host: its own override, the closest parent
+    /// domain's override, or the default
+    pub fn limit_for(&self, host: &str) -> Option<&RateLimit> {
+        let host = host.to_lowercase();
+        let mut domain = host.as_str();
+        loop {
+            if let Some(limit) = self.overrides.get(domain) {
+                return Some(limit);
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return self.default.as_ref(),
+            }
+        }
+    }
+
+    /// Take a token for a request to a host, returning how long to wait before sending it
+    pub fn reserve(&self, host: &str) -> Duration {
+        let Some(limit) = self.limit_for(host) else { return Duration::ZERO };
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        let bucket = buckets.entry(host.to_lowercase()).or_insert(Bucket {
+            tokens: f64::from(limit.requests_per_period.max(1)),
+            refilled_at: now,
+            next_request: now,
+            fraction: 1.0,
+            latency_ms: None,
+        });
+        let capacity = (f64::from(limit.requests_per_period.max(1)) * bucket.fraction).max(1.0);
+        let per_second = full_rate(limit) * bucket.fraction;
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity) - 1.0;
+        bucket.refilled_at = now;
+
+        let for_token = if bucket.tokens < 0.0 { Duration::from_secs_f64(-bucket.tokens / per_second) } else { Duration::ZERO };
+        let wait = for_token.max(bucket.next_request.saturating_duration_since(now));
+        bucket.next_request = now + wait + limit.delay_between_requests;
+        wait
+    }
+
+    /// Feed a host's response back into its adaptive limit, if it has one
+    ///
+    /// A 429 or 503 status, or a latency above [`AdaptiveRate::latency_spike`]
+    /// times the host's average, slows the host down; any other response speeds it up.
+    pub fn observe(&self, host: &str, status: u16, latency: Duration) {
+        let Some(adaptive) = self.limit_for(host).and_then(|limit| limit.adaptive.as_ref()) else { return };
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        let Some(bucket) = buckets.get_mut(&host.to_lowercase()) else { return };
+
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        let spike = bucket.latency_ms.is_some_and(|average| latency_ms > average * adaptive.latency_spike);
+        bucket.latency_ms = Some(match bucket.latency_ms {
+            Some(average) => average + LATENCY_SMOOTHING * (latency_ms - average),
+            None => latency_ms,
+        });
+        bucket.fraction = adjusted_fraction(adaptive, bucket.fraction, matches!(status, 429 | 503) || spike);
+    }
+
+    /// Requests per second currently allowed to a host, `None` if it is not limited
+    pub fn effective_rate(&self, host: &str) -> Option<f64> {
+        let limit = self.limit_for(host)?;
+        let buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        let fraction = buckets.get(&host.to_lowercase()).map_or(1.0, |bucket| bucket.fraction);
+        Some(full_rate(limit) * fraction)
+    }
+
+    /// Requests per second currently allowed to every host requested so far
+    pub fn effective_rates(&self) -> HashMap<String, f64> {
+        let hosts: Vec<String> = self.buckets.lock().expect("rate limiter lock poisoned").keys().cloned().collect();
+        hosts.into_iter().filter_map(|host| Some((host.clone(), self.effective_rate(&host)?))).collect()
+    }
+
+    /// Forget all buckets
+    pub fn reset(&self) {
+        self.buckets.lock().expect("rate limiter lock poisoned").clear();
+    }
+}
+
+/// Requests per second a limit allows at full speed
+fn full_rate(limit: &RateLimit) -> f64 {
+    f64::from(limit.requests_per_period.max(1)) / limit.period.as_secs_f64().max(f64::EPSILON)
+}
+
+/// Next rate fraction of a host: multiplied down when it pushes back, stepped up otherwise
+fn adjusted_fraction(adaptive: &AdaptiveRate, fraction: f64, push_back: bool) -> f64 {
+    if push_back {
+        (fraction * adaptive.decrease).max(adaptive.min_fraction)
+    } else {
+        (fraction + adaptive.increase).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(requests: u32, period: Duration) -> RateLimit {
+        RateLimit { requests_per_period: requests, period, delay_between_requests: Duration::ZERO, adaptive: None }
+    }
+
+    #[test]
+    fn test_rate_limiter_buckets() {
+        let overrides = HashMap::from([("Slow.example".to_string(), limit(1, Duration::from_secs(10)))]);
+        let limiter = RateLimiter::new(Some(limit(2, Duration::from_secs(1))), overrides);
+
+        assert_eq!(limiter.reserve("a.example"), Duration::ZERO);
+        assert_eq!(limiter.reserve("a.example"), Duration::ZERO);
+        let third = limiter.reserve("a.example");
+        assert!(third > Duration::from_millis(450) && third <= Duration::from_millis(500));
+        assert!(limiter.reserve("a.example") > Duration::from_millis(950));
+
+        // Other hosts are not held up
+        assert_eq!(limiter.reserve("b.example"), Duration::ZERO);
+
+        assert_eq!(limiter.limit_for("api.slow.example").unwrap().requests_per_period, 1);
+        assert_eq!(limiter.reserve("api.slow.example"), Duration::ZERO);
+        assert!(limiter.reserve("api.slow.example") > Duration::from_secs(9));
+
+        let spaced = RateLimiter::new(
+            Some(RateLimit { delay_between_requests: Duration::from_millis(300), ..limit(100, Duration::from_secs(1)) }),
+            HashMap::new(),
+        );
+        assert_eq!(spaced.reserve("c.example"), Duration::ZERO);
+        assert!(spaced.reserve("c.example") > Duration::from_millis(250));
+
+        assert!(!RateLimiter::default().is_enabled());
+        assert_eq!(RateLimiter::default().reserve("a.example"), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_adaptive_rate() {
+        let adaptive = AdaptiveRate { increase: 0.25, ..AdaptiveRate::default() };
+        let limiter = RateLimiter::new(Some(limit(10, Duration::from_secs(1)).with_adaptive(adaptive)), HashMap::new());
+        assert_eq!(limiter.effective_rate("a.example"), Some(10.0));
+
+        // Responses only count once the host has a bucket
+        limiter.observe("a.example", 429, Duration::from_millis(10));
+        assert_eq!(limiter.effective_rate("a.example"), Some(10.0));
+
+        limiter.reserve("a.example");
+        limiter.observe("a.example", 429, Duration::from_millis(10));
+        assert_eq!(limiter.effective_rate("a.example"), Some(5.0));
+        limiter.observe("a.example", 503, Duration::from_millis(10));
+        assert_eq!(limiter.effective_rate("a.example"), Some(2.5));
+
+        // Healthy responses add the rate back, up to the configured limit
+        limiter.observe("a.example", 200, Duration::from_millis(10));
+        assert_eq!(limiter.effective_rate("a.example"), Some(5.0));
+        for _ in 0..5 {
+            limiter.observe("a.example", 200, Duration::from_millis(10));
+        }
+        assert_eq!(limiter.effective_rates(), HashMap::from([("a.example".to_string(), 10.0)]));
+
+        // A latency spike slows down like a 429
+        limiter.observe("a.example", 200, Duration::from_millis(500));
+        assert_eq!(limiter.effective_rate("a.example"), Some(5.0));
+
+        // The floor keeps a trickle of requests going
+        for _ in 0..10 {
+            limiter.observe("a.example", 429, Duration::from_millis(10));
+        }
+        assert_eq!(limiter.effective_rate("a.example"), Some(0.5));
+
+        // Fixed limits ignore responses
+        let fixed = RateLimiter::new(Some(limit(10, Duration::from_secs(1))), HashMap::new());
+        fixed.reserve("a.example");
+        fixed.observe("a.example", 429, Duration::from_millis(10));
+        assert_eq!(fixed.effective_rate("a.example"), Some(10.0));
+    }
+}
@@ -0,0 +1,224 @@
+//! Declarative filters applied to records before they reach exporters and sinks
+//!
+//! A [`FilterChain`] evaluates [`RecordFilter`]s in order and drops a record
+//! as soon as one rejects it. Wrap an exporter or sink in [`Filtered`] to
+//! apply a chain without writing a custom wrapper.
+
+use crate::error::{FerrisFetcherError, Result};
+use crate::export::{column_value, Exporter, RecordSink};
+use crate::types::ScrapedData;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A single keep/drop condition
+///
+/// Fields are extracted values or record columns such as `url`, `title` and
+/// `status_code` (see [`column_value`]); a field is present when non-empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RecordFilter {
+    /// Keep only records where the field is present
+    KeepIfPresent { field: String },
+    /// Drop records where the field is present
+    DropIfPresent { field: String },
+    /// Keep only records with one of these status codes
+    KeepIfStatus { codes: Vec<u16> },
+    /// Keep only records where the field matches a regex
+    KeepIfMatches { field: String, pattern: String },
+    /// Drop records whose field value was already seen
+    DropDuplicatesBy { field: String },
+}
+
+impl RecordFilter {
+    /// Keep records where `field` is present
+    pub fn keep_if_present(field: &str) -> Self {
+        RecordFilter::KeepIfPresent { field: field.to_string() }
+    }
+
+    /// Drop records where `field` is present
+    pub fn drop_if_present(field: &str) -> Self {
+        RecordFilter::DropIfPresent { field: field.to_string() }
+    }
+
+    /// Keep records with one of the given status codes
+    pub fn keep_if_status(codes: &[u16]) -> Self {
+        RecordFilter::KeepIfStatus { codes: codes.to_vec() }
+    }
+
+    /// Keep records where `field` matches `pattern`
+    pub fn keep_if_matches(field: &str, pattern: &str) -> Self {
+        RecordFilter::KeepIfMatches {
+            field: field.to_string(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    /// Drop records whose `field` value was already seen
+    pub fn drop_duplicates_by(field: &str) -> Self {
+        RecordFilter::DropDuplicatesBy { field: field.to_string() }
+    }
+}
+
+/// A compiled filter with its state
+#[derive(Debug, Clone)]
+enum Compiled {
+    Stateless(RecordFilter),
+    Matches { field: String, regex: Regex },
+    Duplicates { field: String, seen: HashSet<String> },
+}
+
+/// Ordered set of filters deciding which records are kept
+#[derive(Debug, Clone, Default)]
+pub struct FilterChain {
+    filters: Vec<Compiled>,
+    dropped: u64,
+}
+
+impl FilterChain {
+    /// Compile filters, failing on invalid regexes
+    pub fn new(filters: Vec<RecordFilter>) -> Result<Self> {
+        let filters = filters
+            .into_iter()
+            .map(|filter| match filter {
+                RecordFilter::KeepIfMatches { field, pattern } => {
+                    let regex = Regex::new(&pattern).map_err(|e| {
+                        FerrisFetcherError::ConfigError(format!("Invalid filter pattern '{}': {}", pattern, e))
+                    })?;
+                    Ok(Compiled::Matches { field, regex })
+                }
+                RecordFilter::DropDuplicatesBy { field } => Ok(Compiled::Duplicates { field, seen: HashSet::new() }),
+                filter => Ok(Compiled::Stateless(filter)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { filters, dropped: 0 })
+    }
+
+    /// Decide whether to keep a record, updating duplicate tracking
+    pub fn accept(&mut self, data: &ScrapedData) -> bool {
+        let keep = self.filters.iter_mut().all(|filter| match filter {
+            Compiled::Stateless(RecordFilter::KeepIfPresent { field }) => !column_value(data, field).is_empty(),
+            Compiled::Stateless(RecordFilter::DropIfPresent { field }) => column_value(data, field).is_empty(),
+            Compiled::Stateless(RecordFilter::KeepIfStatus { codes }) => codes.contains(&data.status_code),
+            Compiled::Stateless(_) => true,
+            Compiled::Matches { field, regex } => regex.is_match(&column_value(data, field)),
+            Compiled::Duplicates { field, seen } => seen.insert(column_value(data, field)),
+        });
+
+        if !keep {
+            self.dropped += 1;
+        }
+        keep
+    }
+
+    /// Number of records dropped so far
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Exporter or sink that only passes on records accepted by a filter chain
+#[derive(Debug)]
+pub struct Filtered<T> {
+    inner: T,
+    chain: FilterChain,
+}
+
+impl<T> Filtered<T> {
+    /// Filter the records reaching `inner`
+    pub fn new(inner: T, chain: FilterChain) -> Self {
+        Self { inner, chain }
+    }
+
+    /// Get the filter chain
+    pub fn chain(&self) -> &FilterChain {
+        &self.chain
+    }
+
+    /// Get the wrapped exporter or sink
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Exporter> Exporter for Filtered<T> {
+    fn export(&mut self, data: &ScrapedData) -> Result<()> {
+        if self.chain.accept(data) {
+            self.inner.export(data)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+}
+
+#[async_trait]
+impl<T: RecordSink> RecordSink for Filtered<T> {
+    async fn send(&mut self, data: &ScrapedData) -> Result<()> {
+        if self.chain.accept(data) {
+            self.inner.send(data).await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{FileExporter, JsonlFormat};
+
+    fn record(url: &str, status: u16, price: Option<&str>, sku: &str) -> ScrapedData {
+        let mut data = ScrapedData::new(url.to_string());
+        data.status_code = status;
+        if let Some(price) = price {
+            data.add_extracted_data("price", vec![price.to_string()]);
+        }
+        data.add_extracted_data("sku", vec![sku.to_string()]);
+        data
+    }
+
+    #[test]
+    fn test_filtered_exporter() {
+        let filters: Vec<RecordFilter> = serde_json::from_str(
+            r#"[
+                {"op": "keep_if_status", "codes": [200]},
+                {"op": "keep_if_present", "field": "price"},
+                {"op": "keep_if_matches", "field": "url", "pattern": "^https://shop\\."},
+                {"op": "drop_duplicates_by", "field": "sku"}
+            ]"#,
+        ).unwrap();
+        assert_eq!(filters[3], RecordFilter::drop_duplicates_by("sku"));
+
+        let mut exporter = Filtered::new(
+            FileExporter::new(Vec::new(), JsonlFormat),
+            FilterChain::new(filters).unwrap(),
+        );
+        exporter.export_all(&[
+            record("https://shop.example/1", 200, Some("10"), "A"),
+            record("https://shop.example/2", 404, Some("10"), "B"),
+            record("https://shop.example/3", 200, None, "C"),
+            record("https://blog.example/4", 200, Some("10"), "D"),
+            record("https://shop.example/5", 200, Some("12"), "A"),
+            record("https://shop.example/6", 200, Some("15"), "F"),
+        ]).unwrap();
+        exporter.finish().unwrap();
+
+        assert_eq!(exporter.chain().dropped(), 4);
+        let output = String::from_utf8(exporter.into_inner().into_inner()).unwrap();
+        let urls: Vec<String> = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["url"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(urls, vec!["https://shop.example/1", "https://shop.example/6"]);
+
+        assert!(FilterChain::new(vec![RecordFilter::keep_if_matches("url", "(")]).is_err());
+    }
+}
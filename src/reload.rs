@@ -0,0 +1,174 @@
+//! Hot reloading of extraction rules from a file (`hot-reload` feature)
+//!
+//! [`DataExtractor::watch`] loads a JSON rule file and polls it for changes.
+//! Each valid new version is swapped in atomically, so fetchers attached with
+//! [`FerrisFetcher::with_rule_watcher`](crate::scraper::FerrisFetcher::with_rule_watcher)
+//! use the new selectors from their next scrape on.
+
+use crate::error::{FerrisFetcherError, Result};
+use crate::extractor::DataExtractor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// How often the rule file is checked for changes by default
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Extractor slot shared between a watcher and the fetchers using it
+pub(crate) type SharedExtractor = Arc<RwLock<Arc<DataExtractor>>>;
+
+/// Background task keeping an extractor in sync with a rule file
+///
+/// The task stops when the watcher is dropped.
+#[derive(Debug)]
+pub struct RuleWatcher {
+    path: PathBuf,
+    current: SharedExtractor,
+    reloads: Arc<AtomicU64>,
+    task: JoinHandle<()>,
+}
+
+impl DataExtractor {
+    /// Load rules from a JSON file and reload them whenever the file changes
+    ///
+    /// Must be called from within a Tokio runtime. Files that fail to parse or
+    /// validate are logged and ignored, keeping the previous rules active.
+    pub fn watch(path: impl AsRef<Path>) -> Result<RuleWatcher> {
+        Self::watch_with_interval(path, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Like [`DataExtractor::watch`], checking the file every `interval`
+    pub fn watch_with_interval(path: impl AsRef<Path>, interval: Duration) -> Result<RuleWatcher> {
+        let path = path.as_ref().to_path_buf();
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+            FerrisFetcherError::ConfigError("Rule watching requires a Tokio runtime".to_string())
+        })?;
+
+        let contents = std::fs::read_to_string(&path)?;
+        let extractor = load_rules(&contents)?;
+        let current: SharedExtractor = Arc::new(RwLock::new(Arc::new(extractor)));
+        let reloads = Arc::new(AtomicU64::new(0));
+
+        let task = runtime.spawn(poll_rule_file(
+            path.clone(),
+            contents,
+            interval,
+            Arc::clone(&current),
+            Arc::clone(&reloads),
+        ));
+
+        Ok(RuleWatcher { path, current, reloads, task })
+    }
+}
+
+impl RuleWatcher {
+    /// Get the currently active rules
+    pub fn current(&self) -> Arc<DataExtractor> {
+        Arc::clone(&self.current.read().expect("rule watcher lock poisoned"))
+    }
+
+    /// Get the watched file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of times new rules were swapped in
+    pub fn reload_count(&self) -> u64 {
+        self.reloads.load(Ordering::Relaxed)
+    }
+
+    /// Get the slot fetchers read their rules from
+    pub(crate) fn shared(&self) -> SharedExtractor {
+        Arc::clone(&self.current)
+    }
+}
+
+impl Drop for RuleWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Parse and validate a rule file
+fn load_rules(contents: &str) -> Result<DataExtractor> {
+    let extractor = DataExtractor::from_json(contents)?;
+    let report = extractor.validation_report();
+    if report.is_ok() {
+        Ok(extractor)
+    } else {
+        Err(FerrisFetcherError::ValidationFailed(report))
+    }
+}
+
+/// Re-read the rule file every `interval` and swap in changed rules
+async fn poll_rule_file(
+    path: PathBuf,
+    mut last_contents: String,
+    interval: Duration,
+    current: SharedExtractor,
+    reloads: Arc<AtomicU64>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("Failed to read rule file {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if contents == last_contents {
+            continue;
+        }
+
+        match load_rules(&contents) {
+            Ok(mut extractor) => {
+                let mut slot = current.write().expect("rule watcher lock poisoned");
+                extractor.share_anchors_with(&slot);
+                *slot = Arc::new(extractor);
+                reloads.fetch_add(1, Ordering::Relaxed);
+                info!("Reloaded {} extraction rules from {}", slot.rule_count(), path.display());
+            }
+            Err(e) => warn!("Keeping previous rules; {} is invalid: {}", path.display(), e),
+        }
+        last_contents = contents;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_reloads_changed_rules() {
+        let path = std::env::temp_dir().join(format!("ferrisfetcher-rules-{}.json", std::process::id()));
+        std::fs::write(&path, r#"[{"name": "title", "selector": "h1", "extraction_type": "Text", "multiple": false, "attribute": null}]"#).unwrap();
+
+        let watcher = DataExtractor::watch_with_interval(&path, Duration::from_millis(10)).unwrap();
+        assert!(watcher.current().has_rule("title"));
+
+        std::fs::write(&path, "not json").unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(watcher.current().has_rule("title"));
+        assert_eq!(watcher.reload_count(), 0);
+
+        std::fs::write(&path, r#"[{"name": "price", "selector": ".price", "extraction_type": "Text", "multiple": false, "attribute": null}]"#).unwrap();
+        for _ in 0..100 {
+            if watcher.reload_count() > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(watcher.current().has_rule("price"));
+        assert!(!watcher.current().has_rule("title"));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,260 @@
+//! Replacement selector suggestions for extraction rules broken by page changes
+//!
+//! When a rule matches, the extractor can remember an [`RuleAnchor`] describing
+//! the matched element. If the rule later matches nothing, the anchor's text and
+//! attribute values are compared against every element of the new document to
+//! find the element that most likely moved, and selectors for it are suggested.
+
+use crate::html_parser::HtmlParser;
+use scraper::ElementRef;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Maximum length of anchor text and attribute values kept for comparison
+const MAX_VALUE_LEN: usize = 200;
+
+/// Minimum similarity score for an element to be suggested
+const MIN_SCORE: f64 = 0.5;
+
+/// Number of ancestors walked when building a structural path selector
+const MAX_PATH_DEPTH: usize = 4;
+
+/// Attributes that identify an element well enough to select on
+const STABLE_ATTRIBUTES: &[&str] = &["itemprop", "name", "property", "aria-label", "role", "type", "rel"];
+
+/// Elements never suggested as replacements
+const SKIPPED_ELEMENTS: &[&str] = &["html", "head", "body", "script", "style", "noscript", "template"];
+
+/// Description of an element a rule last matched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleAnchor {
+    /// Tag name of the element
+    pub tag: String,
+    /// Whitespace-normalised text content
+    pub text: String,
+    /// Attribute values
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl RuleAnchor {
+    /// Capture the anchor of a matched element
+    pub fn from_element(element: ElementRef) -> Self {
+        let text = normalize(&element.text().collect::<String>());
+        let attributes = HtmlParser::element_attributes(element)
+            .into_iter()
+            .filter(|(name, value)| name != "style" && value.len() <= MAX_VALUE_LEN)
+            .collect();
+
+        Self {
+            tag: element.value().name().to_string(),
+            text: truncate(&text),
+            attributes,
+        }
+    }
+
+    /// Similarity between this anchor and an element, from 0.0 to 1.0
+    pub fn similarity(&self, element: ElementRef) -> f64 {
+        let candidate = Self::from_element(element);
+        let text_score = if self.text.is_empty() {
+            0.0
+        } else if self.text == candidate.text {
+            1.0
+        } else {
+            jaccard(self.text.split_whitespace(), candidate.text.split_whitespace())
+        };
+
+        let attribute_score = if self.attributes.is_empty() {
+            0.0
+        } else {
+            let total: f64 = self.attributes
+                .iter()
+                .map(|(name, value)| match candidate.attributes.get(name) {
+                    Some(other) if name == "class" => jaccard(value.split_whitespace(), other.split_whitespace()),
+                    Some(other) if other == value => 1.0,
+                    _ => 0.0,
+                })
+                .sum();
+            total / self.attributes.len() as f64
+        };
+
+        let tag_score = if self.tag == candidate.tag { 1.0 } else { 0.0 };
+
+        match (self.text.is_empty(), self.attributes.is_empty()) {
+            (true, true) => 0.0,
+            (true, false) => 0.9 * attribute_score + 0.1 * tag_score,
+            (false, true) => 0.9 * text_score + 0.1 * tag_score,
+            (false, false) => 0.5 * text_score + 0.4 * attribute_score + 0.1 * tag_score,
+        }
+    }
+}
+
+/// A suggested replacement selector for a broken rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectorSuggestion {
+    /// CSS selector matching the candidate element
+    pub selector: String,
+    /// Similarity of the candidate to the rule's anchor, from 0.0 to 1.0
+    pub score: f64,
+    /// Number of elements the selector matches in the new document
+    pub matches: usize,
+    /// Text of the candidate element
+    pub text: String,
+}
+
+/// Find the elements most similar to an anchor and suggest selectors for them
+///
+/// Returns at most `limit` suggestions, best first.
+pub fn suggest_selectors(parser: &HtmlParser, anchor: &RuleAnchor, limit: usize) -> Vec<SelectorSuggestion> {
+    let mut candidates: Vec<(f64, ElementRef)> = parser.document()
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|element| !SKIPPED_ELEMENTS.contains(&element.value().name()))
+        .map(|element| (anchor.similarity(element), element))
+        .filter(|(score, _)| *score >= MIN_SCORE)
+        .collect();
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut seen = HashSet::new();
+    let mut suggestions = Vec::new();
+    for (score, element) in candidates {
+        if suggestions.len() >= limit {
+            break;
+        }
+
+        if let Some((selector, matches)) = best_selector(parser, element) {
+            if seen.insert(selector.clone()) {
+                suggestions.push(SelectorSuggestion {
+                    selector,
+                    score,
+                    matches,
+                    text: truncate(&normalize(&element.text().collect::<String>())),
+                });
+            }
+        }
+    }
+
+    suggestions
+}
+
+/// Pick the selector matching `element` that matches the fewest other elements
+fn best_selector(parser: &HtmlParser, element: ElementRef) -> Option<(String, usize)> {
+    let tag = element.value().name();
+    let mut selectors = Vec::new();
+
+    if let Some(id) = element.value().id().filter(|id| is_identifier(id)) {
+        selectors.push(format!("#{}", id));
+    }
+
+    let classes: Vec<&str> = element.value().classes().filter(|class| is_identifier(class)).collect();
+    if !classes.is_empty() {
+        selectors.push(format!("{}.{}", tag, classes.join(".")));
+    }
+
+    for (name, value) in element.value().attrs() {
+        if (STABLE_ATTRIBUTES.contains(&name) || name.starts_with("data-")) && !value.contains(['"', '\\']) {
+            selectors.push(format!("{}[{}=\"{}\"]", tag, name, value));
+        }
+    }
+
+    selectors.push(structural_path(element));
+
+    selectors
+        .into_iter()
+        .filter_map(|selector| {
+            let matches = parser.select(&selector).ok()?;
+            matches.contains(&element).then_some((selector, matches.len()))
+        })
+        .min_by_key(|(_, matches)| *matches)
+}
+
+/// Build a `parent > tag:nth-of-type(n)` path anchored at the nearest ancestor with an id
+fn structural_path(element: ElementRef) -> String {
+    let mut parts = Vec::new();
+    let mut current = Some(element);
+
+    while let Some(el) = current {
+        if let Some(id) = el.value().id().filter(|id| is_identifier(id)) {
+            parts.push(format!("#{}", id));
+            break;
+        }
+
+        let name = el.value().name();
+        if parts.len() >= MAX_PATH_DEPTH || name == "body" || name == "html" {
+            parts.push(name.to_string());
+            break;
+        }
+
+        let position = el.prev_siblings()
+            .filter_map(ElementRef::wrap)
+            .filter(|sibling| sibling.value().name() == name)
+            .count() + 1;
+        parts.push(format!("{}:nth-of-type({})", name, position));
+        current = el.parent().and_then(ElementRef::wrap);
+    }
+
+    parts.reverse();
+    parts.join(" > ")
+}
+
+/// Check whether a name can be used in a selector without escaping
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Jaccard similarity of two token sets
+fn jaccard<'a>(a: impl Iterator<Item = &'a str>, b: impl Iterator<Item = &'a str>) -> f64 {
+    let a: HashSet<&str> = a.collect();
+    let b: HashSet<&str> = b.collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        0.0
+    } else {
+        a.intersection(&b).count() as f64 / union as f64
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_VALUE_LEN) {
+        Some((index, _)) => text[..index].to_string(),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_selectors_after_redesign() {
+        let old = HtmlParser::new(r#"<div><span class="price" itemprop="price">$10</span></div>"#).unwrap();
+        let anchor = RuleAnchor::from_element(old.select_first(".price").unwrap());
+
+        let new = HtmlParser::new(r#"
+            <div id="product">
+                <span class="label">Price</span>
+                <span class="amount" itemprop="price">$10</span>
+            </div>
+        "#).unwrap();
+
+        let suggestions = suggest_selectors(&new, &anchor, 3);
+        assert_eq!(suggestions[0].selector, "span.amount");
+        assert_eq!(suggestions[0].matches, 1);
+        assert_eq!(suggestions[0].text, "$10");
+        assert!(suggestions[0].score >= MIN_SCORE);
+    }
+
+    #[test]
+    fn test_structural_path() {
+        let parser = HtmlParser::new(r#"<main id="app"><p>One</p><p>Two</p></main>"#).unwrap();
+        let second = parser.select("p").unwrap()[1];
+        assert_eq!(structural_path(second), "#app > p:nth-of-type(2)");
+        assert_eq!(best_selector(&parser, second), Some(("#app > p:nth-of-type(2)".to_string(), 1)));
+    }
+}
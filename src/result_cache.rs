@@ -0,0 +1,233 @@
+//! Time-limited cache of scrape results
+//!
+//! [`ResultCache`] keeps final [`ScrapedData`] keyed by URL and the rules
+//! fingerprint that produced it, in a bounded in-memory LRU and optionally on
+//! disk, so repeated requests within the freshness window skip the fetch.
+
+use crate::error::Result;
+use crate::memory::{MemoryBudget, MemoryKind, MemoryReservation};
+use crate::types::ScrapedData;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// A result held in memory
+#[derive(Debug)]
+struct MemoryEntry {
+    data: ScrapedData,
+    stored_at: Instant,
+    last_used: Instant,
+    _reservation: Option<MemoryReservation>,
+}
+
+/// A result stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct DiskEntry {
+    url: String,
+    fingerprint: u64,
+    stored_at: DateTime<Utc>,
+    data: ScrapedData,
+}
+
+/// Bounded in-memory and optional on-disk cache of scrape results with a TTL
+#[derive(Debug)]
+pub struct ResultCache {
+    entries: Mutex<HashMap<(String, u64), MemoryEntry>>,
+    max_entries: usize,
+    ttl: Duration,
+    disk_dir: Option<PathBuf>,
+    memory: Option<Arc<MemoryBudget>>,
+}
+
+impl ResultCache {
+    /// Keep up to `max_entries` results in memory for `ttl`
+    pub fn new(max_entries: usize, ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+            ttl,
+            disk_dir: None,
+            memory: None,
+        }
+    }
+
+    /// Also store results as JSON files in `dir`, surviving restarts
+    pub fn with_disk(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        self.disk_dir = Some(dir.as_ref().to_path_buf());
+        Ok(self)
+    }
+
+    /// Count results held in memory against a memory budget
+    pub fn with_memory_budget(mut self, budget: Arc<MemoryBudget>) -> Self {
+        self.memory = Some(budget);
+        self
+    }
+
+    /// Get a fresh result for a URL scraped with the given rules fingerprint
+    pub fn get(&self, url: &str, fingerprint: u64) -> Option<ScrapedData> {
+        let key = (url.to_string(), fingerprint);
+        {
+            let mut entries = self.entries.lock().expect("result cache lock poisoned");
+            match entries.get_mut(&key) {
+                Some(entry) if entry.stored_at.elapsed() <= self.ttl => {
+                    entry.last_used = Instant::now();
+                    return Some(entry.data.clone());
+                }
+                Some(_) => {
+                    entries.remove(&key);
+                }
+                None => {}
+            }
+        }
+
+        let path = self.disk_path(url, fingerprint)?;
+        let entry: DiskEntry = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
+        let age = (Utc::now() - entry.stored_at).to_std().unwrap_or_default();
+        if entry.url != url || entry.fingerprint != fingerprint || age > self.ttl {
+            return None;
+        }
+
+        debug!("Loaded cached result for {} from disk", url);
+        self.insert_memory(key, entry.data.clone(), Instant::now().checked_sub(age));
+        Some(entry.data)
+    }
+
+    /// Store a result
+    pub fn insert(&self, url: &str, fingerprint: u64, data: &ScrapedData) -> Result<()> {
+        self.insert_memory((url.to_string(), fingerprint), data.clone(), None);
+
+        if let Some(path) = self.disk_path(url, fingerprint) {
+            let entry = DiskEntry {
+                url: url.to_string(),
+                fingerprint,
+                stored_at: Utc::now(),
+                data: data.clone(),
+            };
+            std::fs::write(path, serde_json::to_vec(&entry)?)?;
+        }
+        Ok(())
+    }
+
+    fn insert_memory(&self, key: (String, u64), data: ScrapedData, stored_at: Option<Instant>) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().expect("result cache lock poisoned");
+        if !entries.contains_key(&key) && entries.len() >= self.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        let reservation = self.memory.as_ref().map(|budget| budget.reserve(MemoryKind::Cache, data.approximate_size()));
+        entries.insert(key, MemoryEntry { data, stored_at: stored_at.unwrap_or(now), last_used: now, _reservation: reservation });
+    }
+
+    /// Drop the cached result for a URL and fingerprint
+    pub fn invalidate(&self, url: &str, fingerprint: u64) {
+        self.entries.lock().expect("result cache lock poisoned").remove(&(url.to_string(), fingerprint));
+        if let Some(path) = self.disk_path(url, fingerprint) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to remove cached result {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    /// Drop every cached result
+    pub fn clear(&self) -> Result<()> {
+        self.entries.lock().expect("result cache lock poisoned").clear();
+        if let Some(dir) = &self.disk_dir {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of results held in memory
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("result cache lock poisoned").len()
+    }
+
+    /// Whether no results are held in memory
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn disk_path(&self, url: &str, fingerprint: u64) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        (url, fingerprint).hash(&mut hasher);
+        self.disk_dir.as_ref().map(|dir| dir.join(format!("{:016x}.json", hasher.finish())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_cache() {
+        let cache = ResultCache::new(2, Duration::from_secs(60));
+        let page = |url: &str| ScrapedData::new(url.to_string());
+
+        cache.insert("https://example.com/a", 1, &page("https://example.com/a")).unwrap();
+        cache.insert("https://example.com/b", 1, &page("https://example.com/b")).unwrap();
+        assert!(cache.get("https://example.com/a", 1).is_some());
+        assert!(cache.get("https://example.com/a", 2).is_none());
+
+        cache.insert("https://example.com/c", 1, &page("https://example.com/c")).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("https://example.com/b", 1).is_none());
+
+        let expired = ResultCache::new(10, Duration::ZERO);
+        expired.insert("https://example.com/a", 1, &page("https://example.com/a")).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(expired.get("https://example.com/a", 1).is_none());
+        assert!(expired.is_empty());
+    }
+
+    #[test]
+    fn test_result_cache_memory_budget() {
+        let budget = Arc::new(MemoryBudget::unlimited());
+        let cache = ResultCache::new(1, Duration::from_secs(60)).with_memory_budget(Arc::clone(&budget));
+        let mut page = ScrapedData::new("https://example.com/a".to_string());
+        page.content = "x".repeat(1000);
+
+        cache.insert("https://example.com/a", 1, &page).unwrap();
+        assert_eq!(budget.used_by(MemoryKind::Cache), page.approximate_size());
+        cache.insert("https://example.com/b", 1, &ScrapedData::new("https://example.com/b".to_string())).unwrap();
+        assert_eq!(budget.used_by(MemoryKind::Cache), "https://example.com/b".len());
+        cache.clear().unwrap();
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn test_result_cache_disk() {
+        let dir = std::env::temp_dir().join(format!("ferrisfetcher-result-cache-{}", std::process::id()));
+        let cache = ResultCache::new(10, Duration::from_secs(60)).with_disk(&dir).unwrap();
+        cache.insert("https://example.com/a", 7, &ScrapedData::new("https://example.com/a".to_string())).unwrap();
+
+        let reopened = ResultCache::new(10, Duration::from_secs(60)).with_disk(&dir).unwrap();
+        assert_eq!(reopened.get("https://example.com/a", 7).unwrap().url, "https://example.com/a");
+        assert_eq!(reopened.len(), 1);
+
+        reopened.invalidate("https://example.com/a", 7);
+        assert!(ResultCache::new(10, Duration::from_secs(60)).with_disk(&dir).unwrap().get("https://example.com/a", 7).is_none());
+
+        cache.clear().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
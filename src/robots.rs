@@ -0,0 +1,330 @@
+//! robots.txt parsing and per-host caching
+//!
+//! [`RobotsRules`] follows RFC 9309: the most specific matching user-agent
+//! group applies, the longest matching rule wins (`allow` on ties), and `*`
+//! and `$` work as wildcards and end anchors. [`RobotsCache`] keeps parsed
+//! rules per origin and spaces requests by each host's `Crawl-delay`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Longest `Crawl-delay` honoured; larger values are clamped to it
+pub const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+
+/// Whether scrapes honour robots.txt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RobotsPolicy {
+    /// Fetch pages regardless of robots.txt
+    #[default]
+    Ignore,
+    /// Skip disallowed paths and wait out each host's `Crawl-delay`
+    Respect,
+}
+
+/// One `allow` or `disallow` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// Rules for a set of user agents
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+/// Parsed robots.txt file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsRules {
+    groups: Vec<Group>,
+    sitemaps: Vec<String>,
+}
+
+impl RobotsRules {
+    /// Parse the contents of a robots.txt file
+    pub fn parse(text: &str) -> Self {
+        let mut rules = Self::default();
+        let mut in_agent_lines = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_agent_lines {
+                        rules.groups.push(Group::default());
+                    }
+                    in_agent_lines = true;
+                    if let Some(group) = rules.groups.last_mut() {
+                        group.agents.push(value.to_lowercase());
+                    }
+                }
+                "sitemap" => rules.sitemaps.push(value.to_string()),
+                key => {
+                    in_agent_lines = false;
+                    let Some(group) = rules.groups.last_mut() else { continue };
+                    match key {
+                        "allow" | "disallow" if !value.is_empty() => group.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        }),
+                        "crawl-delay" => {
+                            group.crawl_delay = value.parse::<f64>().ok()
+                                .filter(|delay| *delay >= 0.0)
+                                .and_then(|delay| Duration::try_from_secs_f64(delay.min(MAX_CRAWL_DELAY.as_secs_f64())).ok());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        rules
+    }
+
+    /// Rules that allow everything (e.g. when robots.txt does not exist)
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Rules that disallow everything (e.g. when robots.txt is unreachable)
+    pub fn disallow_all() -> Self {
+        Self {
+            groups: vec![Group {
+                agents: vec!["*".to_string()],
+                rules: vec![Rule { allow: false, pattern: "/".to_string() }],
+                crawl_delay: None,
+            }],
+            sitemaps: Vec::new(),
+        }
+    }
+
+    /// Groups applying to a user agent: those naming its product token most
+    /// specifically, or the `*` groups
+    fn groups_for(&self, user_agent: &str) -> Vec<&Group> {
+        let token = user_agent
+            .split(['/', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let best = self.groups
+            .iter()
+            .flat_map(|group| &group.agents)
+            .filter(|agent| agent.as_str() != "*" && !agent.is_empty() && token.contains(agent.as_str()))
+            .max_by_key(|agent| agent.len());
+
+        match best {
+            Some(best) => self.groups.iter().filter(|group| group.agents.contains(best)).collect(),
+            None => self.groups.iter().filter(|group| group.agents.iter().any(|agent| agent == "*")).collect(),
+        }
+    }
+
+    /// Whether a user agent may fetch a path (including its query string)
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+
+        self.groups_for(user_agent)
+            .into_iter()
+            .flat_map(|group| &group.rules)
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// `Crawl-delay` for a user agent, if set
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.groups_for(user_agent).into_iter().filter_map(|group| group.crawl_delay).max()
+    }
+
+    /// Sitemap URLs listed in the file
+    pub fn sitemaps(&self) -> &[String] {
+        &self.sitemaps
+    }
+}
+
+/// Match a rule pattern against the start of a path
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else { return false };
+
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let is_last = i + 1 == parts.len();
+        if is_last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    !anchored || rest.is_empty()
+}
+
+/// Cached rules for one origin
+#[derive(Debug)]
+struct CacheEntry {
+    rules: Arc<RobotsRules>,
+    fetched_at: Instant,
+}
+
+/// Per-origin cache of robots.txt rules and crawl-delay schedule
+#[derive(Debug)]
+pub struct RobotsCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    next_request: Mutex<HashMap<String, Instant>>,
+    ttl: Duration,
+}
+
+impl Default for RobotsCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(24 * 60 * 60))
+    }
+}
+
+impl RobotsCache {
+    /// Keep rules for `ttl` before fetching them again
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            next_request: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Get fresh cached rules for an origin (`scheme://host[:port]`)
+    pub fn get(&self, origin: &str) -> Option<Arc<RobotsRules>> {
+        let entries = self.entries.lock().expect("robots cache lock poisoned");
+        entries
+            .get(origin)
+            .filter(|entry| entry.fetched_at.elapsed() < self.ttl)
+            .map(|entry| Arc::clone(&entry.rules))
+    }
+
+    /// Store the rules of an origin
+    pub fn insert(&self, origin: &str, rules: RobotsRules) -> Arc<RobotsRules> {
+        let rules = Arc::new(rules);
+        self.entries.lock().expect("robots cache lock poisoned").insert(
+            origin.to_string(),
+            CacheEntry { rules: Arc::clone(&rules), fetched_at: Instant::now() },
+        );
+        rules
+    }
+
+    /// Reserve the next request slot for an origin, returning how long to wait for it
+    ///
+    /// Delays above [`MAX_CRAWL_DELAY`] are clamped to it.
+    pub fn reserve_slot(&self, origin: &str, crawl_delay: Duration) -> Duration {
+        let crawl_delay = crawl_delay.min(MAX_CRAWL_DELAY);
+        let now = Instant::now();
+        let mut next_request = self.next_request.lock().expect("robots cache lock poisoned");
+        let slot = next_request.get(origin).copied().filter(|next| *next > now).unwrap_or(now);
+        next_request.insert(origin.to_string(), slot + crawl_delay);
+        slot - now
+    }
+
+    /// Number of cached origins
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("robots cache lock poisoned").len()
+    }
+
+    /// Whether no rules are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all cached rules
+    pub fn clear(&self) {
+        self.entries.lock().expect("robots cache lock poisoned").clear();
+        self.next_request.lock().expect("robots cache lock poisoned").clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "
+        # Example
+        User-agent: *
+        Disallow: /private/
+        Allow: /private/public-*.html$
+        Disallow: /*?session=
+        Crawl-delay: 2
+
+        User-agent: FerrisFetcher
+        User-agent: other-bot
+        Disallow: /no-ferris
+        Crawl-delay: 0.5
+
+        Sitemap: https://example.com/sitemap.xml
+    ";
+
+    #[test]
+    fn test_robots_rules() {
+        let rules = RobotsRules::parse(ROBOTS);
+        let generic = "Mozilla/5.0 (compatible)";
+
+        assert!(rules.is_allowed(generic, "/"));
+        assert!(!rules.is_allowed(generic, "/private/data"));
+        assert!(rules.is_allowed(generic, "/private/public-page.html"));
+        assert!(!rules.is_allowed(generic, "/private/public-page.html?x=1"));
+        assert!(!rules.is_allowed(generic, "/shop?session=abc"));
+        assert_eq!(rules.crawl_delay(generic), Some(Duration::from_secs(2)));
+
+        assert!(rules.is_allowed("FerrisFetcher/0.1.0", "/private/data"));
+        assert!(!rules.is_allowed("FerrisFetcher/0.1.0", "/no-ferris/page"));
+        assert_eq!(rules.crawl_delay("FerrisFetcher/0.1.0"), Some(Duration::from_millis(500)));
+        assert_eq!(rules.sitemaps(), ["https://example.com/sitemap.xml"]);
+
+        assert!(!RobotsRules::disallow_all().is_allowed(generic, "/"));
+        assert!(RobotsRules::disallow_all().is_allowed(generic, "/robots.txt"));
+        assert!(RobotsRules::allow_all().is_allowed(generic, "/anything"));
+    }
+
+    #[test]
+    fn test_hostile_crawl_delay() {
+        let delay = |value: &str| RobotsRules::parse(&format!("User-agent: *\nCrawl-delay: {}", value)).crawl_delay("bot");
+        assert_eq!(delay("inf"), Some(MAX_CRAWL_DELAY));
+        assert_eq!(delay("1e30"), Some(MAX_CRAWL_DELAY));
+        assert_eq!(delay("NaN"), None);
+        assert_eq!(delay("-1"), None);
+
+        let cache = RobotsCache::default();
+        assert_eq!(cache.reserve_slot("https://example.com", Duration::MAX), Duration::ZERO);
+        assert!(cache.reserve_slot("https://example.com", Duration::MAX) <= MAX_CRAWL_DELAY);
+    }
+
+    #[test]
+    fn test_robots_cache() {
+        let cache = RobotsCache::default();
+        assert!(cache.get("https://example.com").is_none());
+        cache.insert("https://example.com", RobotsRules::parse(ROBOTS));
+        assert!(cache.get("https://example.com").is_some());
+
+        let delay = Duration::from_secs(2);
+        assert_eq!(cache.reserve_slot("https://example.com", delay), Duration::ZERO);
+        assert!(cache.reserve_slot("https://example.com", delay) > Duration::from_millis(1900));
+        assert!(cache.reserve_slot("https://example.com", delay) > Duration::from_millis(3900));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Compact one-line syntax for extraction rules
+//!
+//! A rule is written as `name: selector @type modifiers...`:
+//!
+//! - `@text` (default), `@html`, `@outer_html`, `@attrs` or `@attr(name)`
+//! - `first` (default) or `all`
+//! - `skip(n)` and `max(n)` to window the matches
+//! - `join(separator)`, `last`, `unique` or `sum` to aggregate the matches
+//! - `trim`, `lowercase`, `decode_entities`, `capture(regex)` and
+//!   `replace(regex => replacement)` to transform each value, in order
+//! - `group(name)` to add the rule to a group, repeatable
+//! - `if_exists(selector)` and `if_url(regex)` conditions
+//!
+//! In rule files, each non-empty line holds one rule and `#` starts a comment line.
+
+use crate::error::{FerrisFetcherError, Result};
+use crate::extractor::ExtractionRuleBuilder;
+use crate::types::{Aggregate, ExtractionRule, ExtractionType, Transform};
+use std::str::FromStr;
+
+/// Parse a single rule
+///
+/// ```
+/// use ferrisfetcher::{rule_dsl, ExtractionType};
+///
+/// let rule = rule_dsl::parse_rule("links: a[href] @attr(href) all").unwrap();
+/// assert_eq!(rule.selector, "a[href]");
+/// assert!(matches!(rule.extraction_type, ExtractionType::Attribute));
+/// assert_eq!(rule.attribute.as_deref(), Some("href"));
+/// assert!(rule.multiple);
+/// ```
+pub fn parse_rule(line: &str) -> Result<ExtractionRule> {
+    let (name, rest) = line.split_once(':').ok_or_else(|| invalid(line, "expected 'name: selector'"))?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(invalid(line, "rule name must be a single word"));
+    }
+
+    let (selector, options) = split_options(rest);
+    let selector = selector.trim();
+    if selector.is_empty() {
+        return Err(invalid(line, "missing selector"));
+    }
+    scraper::Selector::parse(selector)
+        .map_err(|e| FerrisFetcherError::InvalidSelector(format!("{}: {}", selector, e)))?;
+
+    let mut builder = ExtractionRuleBuilder::new(name, selector);
+    let mut has_type = false;
+    for token in tokens(options).map_err(|message| invalid(line, message))? {
+        let (keyword, argument) = match token.split_once('(') {
+            Some((keyword, argument)) => match argument.strip_suffix(')') {
+                Some(argument) => (keyword, Some(argument)),
+                None => return Err(invalid(line, &format!("unexpected text after ')' in '{}'", token))),
+            },
+            None => (token, None),
+        };
+
+        if let Some(kind) = keyword.strip_prefix('@') {
+            if has_type {
+                return Err(invalid(line, "more than one extraction type"));
+            }
+            has_type = true;
+            builder = match (kind, argument) {
+                ("text", None) => builder.extraction_type(ExtractionType::Text),
+                ("html", None) => builder.extraction_type(ExtractionType::Html),
+                ("outer_html", None) => builder.extraction_type(ExtractionType::OuterHtml),
+                ("attrs", None) => builder.extraction_type(ExtractionType::Attributes),
+                ("attr", Some(attribute)) if !attribute.trim().is_empty() => {
+                    builder.extraction_type(ExtractionType::Attribute).attribute(attribute.trim())
+                }
+                _ => return Err(invalid(line, &format!("unknown extraction type '{}'", token))),
+            };
+            continue;
+        }
+
+        builder = match (keyword, argument) {
+            ("first", None) => builder.multiple(false),
+            ("all", None) => builder.multiple(true),
+            ("skip", Some(count)) => builder.skip(number(line, count)?),
+            ("max", Some(count)) => builder.max_matches(number(line, count)?),
+            ("join", Some(separator)) => builder.multiple(true).aggregate(Aggregate::Join(separator.to_string())),
+            ("last", None) => builder.multiple(true).aggregate(Aggregate::Last),
+            ("unique", None) => builder.multiple(true).aggregate(Aggregate::Unique),
+            ("sum", None) => builder.multiple(true).aggregate(Aggregate::Sum),
+            ("trim", None) => builder.transform(Transform::Trim),
+            ("lowercase", None) => builder.transform(Transform::Lowercase),
+            ("decode_entities", None) => builder.transform(Transform::DecodeEntities),
+            ("capture", Some(pattern)) if !pattern.is_empty() => builder.transform(Transform::Capture(pattern.to_string())),
+            ("replace", Some(argument)) => match argument.split_once(" => ") {
+                Some((pattern, replacement)) if !pattern.is_empty() => builder.transform(Transform::Replace {
+                    pattern: pattern.to_string(),
+                    replacement: replacement.to_string(),
+                }),
+                _ => return Err(invalid(line, &format!("expected 'replace(regex => replacement)' in '{}'", token))),
+            },
+            ("group", Some(group)) if !group.trim().is_empty() => builder.group(group.trim()),
+            ("if_exists", Some(selector)) if !selector.trim().is_empty() => builder.only_if_selector_exists(selector.trim()),
+            ("if_url", Some(pattern)) if !pattern.trim().is_empty() => builder.only_if_url_matches(pattern.trim()),
+            _ => return Err(invalid(line, &format!("unknown modifier '{}'", token))),
+        };
+    }
+
+    Ok(builder.build())
+}
+
+/// Parse a rule file with one rule per line, skipping blank and `#` comment lines
+pub fn parse_rules(text: &str) -> Result<Vec<ExtractionRule>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            parse_rule(line).map_err(|e| FerrisFetcherError::ConfigError(format!("line {}: {}", index + 1, e)))
+        })
+        .collect()
+}
+
+impl FromStr for ExtractionRule {
+    type Err = FerrisFetcherError;
+
+    fn from_str(line: &str) -> Result<Self> {
+        parse_rule(line)
+    }
+}
+
+/// Split the selector from the options, which start at the first `@` or
+/// keyword token outside brackets and quotes
+fn split_options(rest: &str) -> (&str, &str) {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut token_start = true;
+    for (index, c) in rest.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth = depth.saturating_sub(1),
+            (None, '@') if depth == 0 && token_start => return (&rest[..index], &rest[index..]),
+            _ => {}
+        }
+        token_start = quote.is_none() && depth == 0 && c.is_whitespace();
+        if token_start && is_modifier_start(&rest[index + c.len_utf8()..]) {
+            return (&rest[..index], &rest[index..]);
+        }
+    }
+    (rest, "")
+}
+
+/// Whether the remaining text starts with a modifier keyword rather than more selector
+fn is_modifier_start(text: &str) -> bool {
+    let word_end = text.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(text.len());
+    let word = &text[..word_end];
+    match word {
+        "first" | "all" | "last" | "unique" | "sum" | "trim" | "lowercase" | "decode_entities" => word_end == text.len() || text[word_end..].starts_with(char::is_whitespace),
+        "skip" | "max" | "join" | "group" | "if_exists" | "if_url" | "capture" | "replace" => text[word_end..].starts_with('('),
+        _ => false,
+    }
+}
+
+/// Split options into tokens, keeping parenthesized arguments whole
+fn tokens(options: &str) -> std::result::Result<Vec<&str>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut depth = 0usize;
+    for (index, c) in options.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Err("unbalanced ')'"),
+            ')' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    tokens.push(&options[begin..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(index);
+    }
+    if depth > 0 {
+        return Err("unbalanced '('");
+    }
+    if let Some(begin) = start {
+        tokens.push(&options[begin..]);
+    }
+    Ok(tokens)
+}
+
+fn number(line: &str, text: &str) -> Result<usize> {
+    text.trim().parse().map_err(|_| invalid(line, &format!("'{}' is not a count", text)))
+}
+
+fn invalid(line: &str, message: &str) -> FerrisFetcherError {
+    FerrisFetcherError::ConfigError(format!("Invalid rule '{}': {}", line.trim(), message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RuleCondition;
+
+    #[test]
+    fn test_parse_rule() {
+        let rule: ExtractionRule = "price: .price @text first".parse().unwrap();
+        assert_eq!(rule.name, "price");
+        assert_eq!(rule.selector, ".price");
+        assert!(matches!(rule.extraction_type, ExtractionType::Text));
+        assert!(!rule.multiple);
+
+        let rule = parse_rule("items: ul > li:nth-child(2n) a all skip(1) max(5) group(seo) if_url(^https://shop\\.)").unwrap();
+        assert_eq!(rule.selector, "ul > li:nth-child(2n) a");
+        assert!(rule.multiple);
+        assert_eq!((rule.skip, rule.max_matches), (1, Some(5)));
+        assert_eq!(rule.groups, vec!["seo"]);
+        assert!(matches!(&rule.conditions[0], RuleCondition::UrlMatches(pattern) if pattern == "^https://shop\\."));
+
+        let rule = parse_rule("alt: img[alt='a @b'] @attr( alt ) if_exists(main img)").unwrap();
+        assert_eq!(rule.selector, "img[alt='a @b']");
+        assert_eq!(rule.attribute.as_deref(), Some("alt"));
+        assert!(matches!(&rule.conditions[0], RuleCondition::SelectorExists(selector) if selector == "main img"));
+
+        let rule = parse_rule("tags: .tag join(, )").unwrap();
+        assert!(rule.multiple);
+        assert_eq!(rule.aggregate, Some(Aggregate::Join(", ".to_string())));
+        assert_eq!(parse_rule("total: td.price sum").unwrap().aggregate, Some(Aggregate::Sum));
+
+        let rule = parse_rule(r"price: .price trim capture(\$(\d+(\.\d+)?)) replace(\. => ,)").unwrap();
+        assert_eq!(rule.transforms, vec![
+            Transform::Trim,
+            Transform::Capture(r"\$(\d+(\.\d+)?)".to_string()),
+            Transform::Replace { pattern: r"\.".to_string(), replacement: ",".to_string() },
+        ]);
+
+        // Selectors may use modifier names as tags or classes
+        assert_eq!(parse_rule("x: .all first").unwrap().selector, ".all");
+    }
+
+    #[test]
+    fn test_parse_rule_errors() {
+        assert!(parse_rule("no selector").is_err());
+        assert!(parse_rule("price: ").is_err());
+        assert!(parse_rule("price: .price @bogus").is_err());
+        assert!(parse_rule("price: .price @attr").is_err());
+        assert!(parse_rule("price: .price @text @html").is_err());
+        assert!(parse_rule("price: .price max(lots)").is_err());
+        assert!(parse_rule("price: .price replace(,)").is_err());
+        assert!(matches!(parse_rule("price: div[[ @text"), Err(FerrisFetcherError::InvalidSelector(_))));
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parse_rules("# product page\ntitle: h1\n\n  links: a @attr(href) all\n").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].name, "links");
+
+        let error = parse_rules("title: h1\nbroken").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Versioned serialization of scraped results
+//!
+//! Serialized [`ScrapedData`] is meant to be stored long-term, so its field
+//! names are a stable format: fields are only added, with `#[serde(default)]`,
+//! and a renamed field keeps its old name as a serde alias. Changes that old
+//! readers cannot ignore bump [`SCHEMA_VERSION`]; the previous layout stays
+//! described by a frozen struct such as [`ScrapedDataV1`], and
+//! [`ScrapedData::from_json`] reads every supported version.
+
+use crate::error::Result;
+use crate::types::ScrapedData;
+use chrono::{DateTime, Utc};
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Schema version written by this release
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Version assumed for records written before `schema_version` existed
+pub(crate) fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// Original layout of [`ScrapedData`], without a `schema_version` field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapedDataV1 {
+    /// The URL that was scraped
+    pub url: String,
+    /// Page title if available
+    pub title: Option<String>,
+    /// Raw HTML content
+    pub content: String,
+    /// Extracted metadata
+    pub metadata: HashMap<String, Value>,
+    /// Structured data extracted using rules
+    pub extracted_data: HashMap<String, Vec<String>>,
+    /// When the scraping occurred
+    pub timestamp: DateTime<Utc>,
+    /// HTTP status code
+    pub status_code: u16,
+    /// Response headers
+    pub headers: HashMap<String, String>,
+    /// Time taken to scrape (in milliseconds)
+    pub scrape_time_ms: u64,
+}
+
+impl From<ScrapedDataV1> for ScrapedData {
+    fn from(v1: ScrapedDataV1) -> Self {
+        let mut data = ScrapedData::new(v1.url);
+        data.title = v1.title;
+        data.content = v1.content;
+        data.metadata = v1.metadata;
+        data.extracted_data = v1.extracted_data;
+        data.timestamp = v1.timestamp;
+        data.status_code = v1.status_code;
+        data.headers = v1.headers;
+        data.scrape_time_ms = v1.scrape_time_ms;
+        data
+    }
+}
+
+/// Downgrade for consumers that still expect the original layout
+impl From<&ScrapedData> for ScrapedDataV1 {
+    fn from(data: &ScrapedData) -> Self {
+        Self {
+            url: data.url.clone(),
+            title: data.title.clone(),
+            content: data.content.clone(),
+            metadata: data.metadata.clone(),
+            extracted_data: data.extracted_data.clone(),
+            timestamp: data.timestamp,
+            status_code: data.status_code,
+            headers: data.headers.clone(),
+            scrape_time_ms: data.scrape_time_ms,
+        }
+    }
+}
+
+impl ScrapedData {
+    /// Read a serialized record of any supported schema version
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    /// Read a record of any supported schema version from a JSON value,
+    /// failing on versions newer than [`SCHEMA_VERSION`]
+    ///
+    /// Fields added since version 1 all have defaults, so older records
+    /// (including those without `schema_version`) read without loss of data.
+    pub fn from_value(value: Value) -> Result<Self> {
+        let version = match value.get("schema_version") {
+            None => legacy_schema_version(),
+            Some(version) => version.as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| serde_json::Error::custom(format!("invalid schema_version {}", version)))?,
+        };
+
+        let mut data: ScrapedData = match version {
+            1..=SCHEMA_VERSION => serde_json::from_value(value)?,
+            _ => return Err(serde_json::Error::custom(format!(
+                "unsupported ScrapedData schema version {} (newest supported is {})", version, SCHEMA_VERSION
+            )).into()),
+        };
+        data.schema_version = SCHEMA_VERSION;
+        // Records written before redirects were tracked only know the requested URL
+        if data.final_url.is_empty() {
+            data.final_url = data.url.clone();
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_serialized_field_names() {
+        let value = serde_json::to_value(ScrapedData::new("https://example.com/".to_string())).unwrap();
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        // Renaming or removing any of these breaks stored results
+        assert_eq!(keys, [
+            "content", "depth", "discovered_via", "errors", "extract_time_ms", "extracted_data", "fetch_time_ms",
+            "final_url", "frames", "headers", "items", "metadata", "original_url", "page_type", "parent_url",
+            "parse_time_ms", "redirects", "rule_set", "run_id", "schema_version", "scrape_time_ms", "status_code",
+            "timestamp", "timings", "title", "url", "warnings",
+        ]);
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_from_json_versions() {
+        let v1 = json!({
+            "url": "https://example.com/",
+            "title": "Example",
+            "content": "<html></html>",
+            "metadata": {},
+            "extracted_data": {"price": ["9.99"]},
+            "timestamp": "2024-01-02T03:04:05Z",
+            "status_code": 200,
+            "headers": {},
+            "scrape_time_ms": 12,
+        });
+        let data = ScrapedData::from_json(&v1.to_string()).unwrap();
+        assert_eq!(data.schema_version, SCHEMA_VERSION);
+        assert_eq!(data.get_first_value("price").map(String::as_str), Some("9.99"));
+        assert_eq!(data.final_url, "https://example.com/");
+        assert_eq!(serde_json::to_value(ScrapedDataV1::from(&data)).unwrap(), v1);
+
+        let current = serde_json::to_string(&data).unwrap();
+        assert_eq!(ScrapedData::from_json(&current).unwrap().url, "https://example.com/");
+
+        let mut future = serde_json::to_value(&data).unwrap();
+        future["schema_version"] = json!(SCHEMA_VERSION + 1);
+        assert!(ScrapedData::from_value(future).is_err());
+    }
+}
@@ -0,0 +1,435 @@
+//! HTTP facade serving scrapes, crawls and crawl jobs (`server` feature)
+//!
+//! [`ScrapeServer`] exposes a [`FerrisFetcher`] as a JSON API, so the crate
+//! can be deployed as a standalone scraping microservice:
+//!
+//! | Request | Response |
+//! |---------|----------|
+//! | `GET /health` | `{"status": "ok"}` |
+//! | `GET /stats` | [`RequestStats`](crate::RequestStats) of the fetcher |
+//! | `POST /scrape` `{"url", "rules"?}` | the [`ScrapedData`] of the page |
+//! | `POST /crawl` `{"seeds", "max_depth"?, "max_pages"?, "rules"?}` | `202` with the new [`JobSummary`] |
+//! | `GET /jobs` | summaries of all jobs, oldest first |
+//! | `GET /jobs/{id}` | the job's [`JobSummary`] |
+//! | `GET /jobs/{id}/results` | pages the job scraped so far |
+//! | `DELETE /jobs/{id}` | cancels a running job and returns its summary |
+//!
+//! `rules` are extraction rules in the [`rule_dsl`](crate::rule_dsl) syntax,
+//! added to the fetcher's own rules for that request or job. Errors are
+//! answered as `{"error", "category"}` with a status matching the error.
+
+use crate::crawler::{CrawlOptions, Crawler};
+use crate::error::{FerrisFetcherError, Result};
+use crate::rule_dsl;
+use crate::scraper::FerrisFetcher;
+use crate::types::ScrapedData;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use hyper::body::HttpBody;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tracing::{debug, info, warn};
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Jobs kept unless configured otherwise
+const DEFAULT_MAX_JOBS: usize = 100;
+
+/// Lifecycle of a crawl job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Pages are still being crawled
+    Running,
+    /// The crawl reached its limits or ran out of links
+    Finished,
+    /// The job was cancelled before finishing
+    Cancelled,
+}
+
+/// Progress of a crawl job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobSummary {
+    /// Job identifier
+    pub id: u64,
+    /// URLs the crawl started from
+    pub seeds: Vec<String>,
+    /// Lifecycle state
+    pub state: JobState,
+    /// Pages scraped
+    pub pages: usize,
+    /// Pages that failed, with their errors
+    pub failures: Vec<JobFailure>,
+    /// When the job started
+    pub started_at: DateTime<Utc>,
+    /// When the job finished or was cancelled
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// A page a crawl job could not scrape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobFailure {
+    /// URL of the page
+    pub url: String,
+    /// Error message
+    pub error: String,
+}
+
+/// A crawl job and the pages it scraped
+#[derive(Debug)]
+struct Job {
+    summary: JobSummary,
+    results: Vec<ScrapedData>,
+    task: Option<tokio::task::AbortHandle>,
+}
+
+/// Body of `POST /scrape`
+#[derive(Debug, Deserialize)]
+struct ScrapeRequest {
+    url: String,
+    #[serde(default)]
+    rules: Vec<String>,
+}
+
+/// Body of `POST /crawl`
+#[derive(Debug, Deserialize)]
+struct CrawlRequest {
+    seeds: Vec<String>,
+    max_depth: Option<usize>,
+    max_pages: Option<usize>,
+    #[serde(default)]
+    rules: Vec<String>,
+}
+
+/// State shared by all connections
+#[derive(Debug)]
+struct Shared {
+    fetcher: FerrisFetcher,
+    jobs: Mutex<BTreeMap<u64, Job>>,
+    max_jobs: usize,
+}
+
+/// JSON API over a fetcher, for running FerrisFetcher as a service
+#[derive(Debug, Clone)]
+pub struct ScrapeServer {
+    shared: Arc<Shared>,
+}
+
+impl ScrapeServer {
+    /// Serve scrapes and crawls with `fetcher`
+    pub fn new(fetcher: FerrisFetcher) -> Self {
+        Self::with_max_jobs(fetcher, DEFAULT_MAX_JOBS)
+    }
+
+    /// Like [`new`](Self::new), keeping at most `max_jobs` jobs; the oldest
+    /// finished or cancelled jobs are forgotten first
+    pub fn with_max_jobs(fetcher: FerrisFetcher, max_jobs: usize) -> Self {
+        Self {
+            shared: Arc::new(Shared { fetcher, jobs: Mutex::new(BTreeMap::new()), max_jobs: max_jobs.max(1) }),
+        }
+    }
+
+    /// Serve connections accepted on `listener` until the task is dropped
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        self.serve_with_shutdown(listener, std::future::pending()).await
+    }
+
+    /// Serve connections accepted on `listener` until `shutdown` completes,
+    /// then finish the requests in progress
+    ///
+    /// Running jobs are cancelled once the server stops.
+    pub async fn serve_with_shutdown(self, listener: TcpListener, shutdown: impl Future<Output = ()>) -> Result<()> {
+        let incoming = hyper::server::conn::AddrIncoming::from_listener(listener)
+            .map_err(|e| FerrisFetcherError::NetworkError(e.to_string()))?;
+        info!("Scrape server listening on {}", incoming.local_addr());
+
+        let server = self.clone();
+        let make_service = make_service_fn(move |_| {
+            let server = server.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle(request).await) }
+                }))
+            }
+        });
+        let result = hyper::Server::builder(incoming)
+            .serve(make_service)
+            .with_graceful_shutdown(shutdown)
+            .await
+            .map_err(|e| FerrisFetcherError::NetworkError(e.to_string()));
+
+        for job in self.shared.jobs.lock().expect("job table poisoned").values_mut() {
+            if let Some(task) = job.task.take() {
+                task.abort();
+            }
+        }
+        info!("Scrape server stopped");
+        result
+    }
+
+    /// Answer one request, e.g. when mounting the API in another server
+    pub async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+        debug!("{} {}", method, path);
+
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let result = match (&method, segments.as_slice()) {
+            (&Method::GET, ["health"]) => Ok(json(StatusCode::OK, &serde_json::json!({ "status": "ok" }))),
+            (&Method::GET, ["stats"]) => Ok(json(StatusCode::OK, &self.shared.fetcher.get_stats().await)),
+            (&Method::POST, ["scrape"]) => self.scrape(request).await,
+            (&Method::POST, ["crawl"]) => self.crawl(request).await,
+            (&Method::GET, ["jobs"]) => Ok(json(StatusCode::OK, &self.summaries())),
+            (&Method::GET, ["jobs", id]) => self.with_job(id, |job| json(StatusCode::OK, &job.summary)),
+            (&Method::GET, ["jobs", id, "results"]) => self.with_job(id, |job| json(StatusCode::OK, &job.results)),
+            (&Method::DELETE, ["jobs", id]) => self.cancel(id),
+            _ => Err((StatusCode::NOT_FOUND, format!("No route for {} {}", method, path), "Routing")),
+        };
+        result.unwrap_or_else(|(status, message, category)| {
+            json(status, &serde_json::json!({ "error": message, "category": category }))
+        })
+    }
+
+    async fn scrape(&self, request: Request<Body>) -> std::result::Result<Response<Body>, Rejection> {
+        let body: ScrapeRequest = read_json(request).await?;
+        let fetcher = self.fetcher_with(&body.rules)?;
+        let data = fetcher.scrape(&body.url).await.map_err(|e| reject(&e))?;
+        Ok(json(StatusCode::OK, &data))
+    }
+
+    async fn crawl(&self, request: Request<Body>) -> std::result::Result<Response<Body>, Rejection> {
+        let body: CrawlRequest = read_json(request).await?;
+        if body.seeds.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "A crawl needs at least one seed URL".to_string(), "Request"));
+        }
+        let fetcher = self.fetcher_with(&body.rules)?;
+        let mut options = CrawlOptions::new();
+        if let Some(max_depth) = body.max_depth {
+            options = options.with_max_depth(max_depth);
+        }
+        if let Some(max_pages) = body.max_pages {
+            options = options.with_max_pages(max_pages);
+        }
+
+        let mut jobs = self.shared.jobs.lock().expect("job table poisoned");
+        let id = jobs.keys().next_back().map_or(1, |last| last + 1);
+        let summary = JobSummary {
+            id,
+            seeds: body.seeds.clone(),
+            state: JobState::Running,
+            pages: 0,
+            failures: Vec::new(),
+            started_at: fetcher.config().clock.now(),
+            finished_at: None,
+        };
+
+        let shared = Arc::clone(&self.shared);
+        let clock = Arc::clone(&fetcher.config().clock);
+        let task = tokio::spawn(async move {
+            let seeds: Vec<&str> = body.seeds.iter().map(String::as_str).collect();
+            let mut pages = std::pin::pin!(Crawler::new(fetcher).crawl(&seeds, options));
+            while let Some(page) = pages.next().await {
+                let mut jobs = shared.jobs.lock().expect("job table poisoned");
+                let Some(job) = jobs.get_mut(&id) else { return };
+                match page.result {
+                    Ok(data) => {
+                        job.summary.pages += 1;
+                        job.results.push(data);
+                    }
+                    Err(e) => job.summary.failures.push(JobFailure { url: page.url, error: e.to_string() }),
+                }
+            }
+            if let Some(job) = shared.jobs.lock().expect("job table poisoned").get_mut(&id) {
+                job.summary.state = JobState::Finished;
+                job.summary.finished_at = Some(clock.now());
+                job.task = None;
+                info!("Crawl job {} finished with {} pages", id, job.summary.pages);
+            }
+        });
+
+        let response = json(StatusCode::ACCEPTED, &summary);
+        jobs.insert(id, Job { summary, results: Vec::new(), task: Some(task.abort_handle()) });
+        evict(&mut jobs, self.shared.max_jobs);
+        Ok(response)
+    }
+
+    fn cancel(&self, id: &str) -> std::result::Result<Response<Body>, Rejection> {
+        let mut jobs = self.shared.jobs.lock().expect("job table poisoned");
+        let job = find_job(&mut jobs, id)?;
+        if let Some(task) = job.task.take() {
+            task.abort();
+            job.summary.state = JobState::Cancelled;
+            job.summary.finished_at = Some(self.shared.fetcher.config().clock.now());
+            info!("Crawl job {} cancelled", id);
+        }
+        Ok(json(StatusCode::OK, &job.summary))
+    }
+
+    fn with_job(&self, id: &str, respond: impl FnOnce(&Job) -> Response<Body>) -> std::result::Result<Response<Body>, Rejection> {
+        let mut jobs = self.shared.jobs.lock().expect("job table poisoned");
+        Ok(respond(find_job(&mut jobs, id)?))
+    }
+
+    fn summaries(&self) -> Vec<JobSummary> {
+        self.shared.jobs.lock().expect("job table poisoned").values().map(|job| job.summary.clone()).collect()
+    }
+
+    /// The fetcher with a request's extra rules added
+    fn fetcher_with(&self, rules: &[String]) -> std::result::Result<FerrisFetcher, Rejection> {
+        let mut fetcher = self.shared.fetcher.handle();
+        for line in rules {
+            let rule = rule_dsl::parse_rule(line).map_err(|e| reject(&e))?;
+            fetcher.add_extraction_rule(rule);
+        }
+        Ok(fetcher)
+    }
+}
+
+/// Status, message and category of a refused request
+type Rejection = (StatusCode, String, &'static str);
+
+/// Status answering a failed scrape
+fn reject(error: &FerrisFetcherError) -> Rejection {
+    let status = match error {
+        FerrisFetcherError::InvalidUrl(_)
+        | FerrisFetcherError::ConfigError(_)
+        | FerrisFetcherError::ExtractionError(_)
+        | FerrisFetcherError::InvalidSelector(_)
+        | FerrisFetcherError::ValidationFailed(_) => StatusCode::BAD_REQUEST,
+        FerrisFetcherError::RobotsDisallowed(_) => StatusCode::FORBIDDEN,
+        FerrisFetcherError::RateLimitExceeded(_) | FerrisFetcherError::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+        FerrisFetcherError::TimeoutError(_) => StatusCode::GATEWAY_TIMEOUT,
+        FerrisFetcherError::IoError(_) | FerrisFetcherError::JsonError(_) | FerrisFetcherError::TaskCancelled => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    (status, error.to_string(), error.category())
+}
+
+/// Look up a job by the identifier in a path
+fn find_job<'a>(jobs: &'a mut BTreeMap<u64, Job>, id: &str) -> std::result::Result<&'a mut Job, Rejection> {
+    id.parse()
+        .ok()
+        .and_then(|id| jobs.get_mut(&id))
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No job {}", id), "Routing"))
+}
+
+/// Forget the oldest jobs that are no longer running while there are too many
+fn evict(jobs: &mut BTreeMap<u64, Job>, max_jobs: usize) {
+    while jobs.len() > max_jobs {
+        let Some(id) = jobs.iter().find(|(_, job)| job.task.is_none()).map(|(id, _)| *id) else {
+            warn!("{} crawl jobs are running, more than the {} kept", jobs.len(), max_jobs);
+            return;
+        };
+        jobs.remove(&id);
+    }
+}
+
+/// Read a JSON request body of at most [`MAX_BODY_BYTES`]
+async fn read_json<T: DeserializeOwned>(request: Request<Body>) -> std::result::Result<T, Rejection> {
+    let mut body = request.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string(), "Request"))?;
+        if bytes.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err((StatusCode::PAYLOAD_TOO_LARGE, format!("Request body exceeds {} bytes", MAX_BODY_BYTES), "Request"));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    serde_json::from_slice(&bytes).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid request body: {}", e), "Request"))
+}
+
+/// A JSON response
+fn json<T: Serialize + ?Sized>(status: StatusCode, value: &T) -> Response<Body> {
+    let (status, body) = match serde_json::to_vec(value) {
+        Ok(body) => (status, body),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({ "error": e.to_string(), "category": "JSON" }).to_string().into_bytes()),
+    };
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("static response parts are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::{json, Value};
+    use crate::test_support::{serve, StubResponse};
+
+    #[tokio::test]
+    async fn test_scrape_server() {
+        let site_url = serve(|request| {
+            StubResponse::ok(match request.path.as_str() {
+                "/" => r#"<html><title>Home</title><a href="/mug">Mug</a><span class="price">5</span></html>"#,
+                _ => r#"<html><title>Mug</title><span class="price">9</span></html>"#,
+            })
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api = format!("http://{}", listener.local_addr().unwrap());
+        let fetcher = FerrisFetcher::with_config(Config::default().without_rate_limit()).unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(ScrapeServer::new(fetcher).serve_with_shutdown(listener, async {
+            let _ = stopped.await;
+        }));
+        let client = reqwest::Client::new();
+
+        let health: Value = client.get(format!("{}/health", api)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(health["status"], "ok");
+
+        let response = client.post(format!("{}/scrape", api))
+            .json(&json!({ "url": format!("{}/mug", site_url), "rules": ["price: .price"] }))
+            .send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let data: ScrapedData = response.json().await.unwrap();
+        assert_eq!((data.title.as_deref(), data.extracted_data["price"].as_slice()), (Some("Mug"), ["9".to_string()].as_slice()));
+
+        let response = client.post(format!("{}/scrape", api)).json(&json!({ "url": "not a url" })).send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        assert_eq!(response.json::<Value>().await.unwrap()["category"], "URL");
+        let response = client.post(format!("{}/scrape", api)).json(&json!({ "url": site_url, "rules": ["broken"] })).send().await.unwrap();
+        assert_eq!(response.status(), 400);
+
+        let response = client.post(format!("{}/crawl", api))
+            .json(&json!({ "seeds": [format!("{}/", site_url)], "max_depth": 1, "rules": ["price: .price"] }))
+            .send().await.unwrap();
+        assert_eq!(response.status(), 202);
+        let job: JobSummary = response.json().await.unwrap();
+        assert_eq!(job.state, JobState::Running);
+
+        let job = loop {
+            let job: JobSummary = client.get(format!("{}/jobs/{}", api, job.id)).send().await.unwrap().json().await.unwrap();
+            if job.state != JobState::Running {
+                break job;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        };
+        assert_eq!((job.state, job.pages, job.failures.len()), (JobState::Finished, 2, 0));
+        let mut results: Vec<ScrapedData> = client.get(format!("{}/jobs/{}/results", api, job.id)).send().await.unwrap().json().await.unwrap();
+        results.sort_by_key(|data| data.depth);
+        assert_eq!(results[1].extracted_data["price"], ["9"]);
+
+        let jobs: Vec<JobSummary> = client.get(format!("{}/jobs", api)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(jobs, [job]);
+        assert_eq!(client.get(format!("{}/jobs/42", api)).send().await.unwrap().status(), 404);
+        assert_eq!(client.get(format!("{}/nowhere", api)).send().await.unwrap().status(), 404);
+        let stats: Value = client.get(format!("{}/stats", api)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(stats["total_requests"], 3);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,296 @@
+//! Long-lived scrape service driven over a channel
+//!
+//! A [`ScraperService`] owns a [`FerrisFetcher`] on a task of its own and
+//! takes [`ServiceCommand`]s from any number of [`ServiceHandle`]s. Scrapes
+//! run concurrently on the service's task set, and every outcome is emitted
+//! as a [`ServiceEvent`], so an application can hand URLs to the service from
+//! one place and consume results in another. Rule and configuration changes
+//! apply to scrapes started after them.
+//!
+//! ```no_run
+//! # async fn example() -> ferrisfetcher::Result<()> {
+//! use ferrisfetcher::{FerrisFetcher, ScraperService, ServiceEvent};
+//!
+//! let (service, mut events) = ScraperService::new(FerrisFetcher::new()?).spawn();
+//! service.submit("https://example.com").await?;
+//! while let Some(event) = events.recv().await {
+//!     if let ServiceEvent::Scraped(data) = event {
+//!         println!("{}", data.url);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::config::Config;
+use crate::error::{FerrisFetcherError, Result};
+use crate::extractor::DataExtractor;
+use crate::scraper::FerrisFetcher;
+use crate::types::{ExtractionRule, RequestStats, ScrapedData};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+/// Commands and events buffered unless configured otherwise
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Request sent to a running [`ScraperService`]
+#[derive(Debug)]
+pub enum ServiceCommand {
+    /// Scrape a URL, answering on `reply` if given; the outcome is also emitted as an event
+    ScrapeUrl {
+        /// URL to scrape
+        url: String,
+        /// Receives the scrape result
+        reply: Option<oneshot::Sender<Result<ScrapedData>>>,
+    },
+    /// Add extraction rules, replacing rules of the same name
+    AddRules {
+        /// Rules to add
+        rules: Vec<ExtractionRule>,
+        /// Receives whether the rules were valid and added
+        reply: Option<oneshot::Sender<Result<()>>>,
+    },
+    /// Replace the configuration, keeping the rules
+    ///
+    /// The fetcher gets a new HTTP client, so statistics start afresh.
+    UpdateConfig {
+        /// New configuration
+        config: Box<Config>,
+        /// Receives whether the configuration was valid and applied
+        reply: Option<oneshot::Sender<Result<()>>>,
+    },
+    /// Report the request statistics
+    GetStats {
+        /// Receives the statistics
+        reply: oneshot::Sender<RequestStats>,
+    },
+    /// Finish the scrapes in flight and stop
+    Shutdown,
+}
+
+/// Something that happened in a [`ScraperService`]
+#[derive(Debug, Clone)]
+pub enum ServiceEvent {
+    /// A page was scraped
+    Scraped(Box<ScrapedData>),
+    /// Scraping a URL failed
+    Failed {
+        /// URL that failed
+        url: String,
+        /// Error message
+        error: String,
+    },
+    /// Extraction rules were added, by name
+    RulesAdded(Vec<String>),
+    /// A new configuration was applied
+    ConfigUpdated,
+    /// The service stopped; no further events follow
+    Stopped,
+}
+
+/// Sends commands to a running [`ScraperService`]; cheap to clone
+///
+/// The service stops once every handle is dropped. Methods fail with
+/// [`FerrisFetcherError::TaskCancelled`] if the service has stopped.
+#[derive(Debug, Clone)]
+pub struct ServiceHandle {
+    commands: mpsc::Sender<ServiceCommand>,
+}
+
+impl ServiceHandle {
+    /// Send a raw command
+    pub async fn send(&self, command: ServiceCommand) -> Result<()> {
+        self.commands.send(command).await.map_err(|_| FerrisFetcherError::TaskCancelled)
+    }
+
+    /// Queue a URL, whose outcome is only emitted as an event
+    pub async fn submit(&self, url: &str) -> Result<()> {
+        self.send(ServiceCommand::ScrapeUrl { url: url.to_string(), reply: None }).await
+    }
+
+    /// Scrape a URL and wait for the result
+    pub async fn scrape(&self, url: &str) -> Result<ScrapedData> {
+        let (reply, result) = oneshot::channel();
+        self.send(ServiceCommand::ScrapeUrl { url: url.to_string(), reply: Some(reply) }).await?;
+        result.await.map_err(|_| FerrisFetcherError::TaskCancelled)?
+    }
+
+    /// Add extraction rules, failing if any of them is invalid
+    pub async fn add_rules(&self, rules: Vec<ExtractionRule>) -> Result<()> {
+        let (reply, result) = oneshot::channel();
+        self.send(ServiceCommand::AddRules { rules, reply: Some(reply) }).await?;
+        result.await.map_err(|_| FerrisFetcherError::TaskCancelled)?
+    }
+
+    /// Replace the configuration, failing if it is invalid
+    pub async fn update_config(&self, config: Config) -> Result<()> {
+        let (reply, result) = oneshot::channel();
+        self.send(ServiceCommand::UpdateConfig { config: Box::new(config), reply: Some(reply) }).await?;
+        result.await.map_err(|_| FerrisFetcherError::TaskCancelled)?
+    }
+
+    /// Get the request statistics
+    pub async fn stats(&self) -> Result<RequestStats> {
+        let (reply, result) = oneshot::channel();
+        self.send(ServiceCommand::GetStats { reply }).await?;
+        result.await.map_err(|_| FerrisFetcherError::TaskCancelled)
+    }
+
+    /// Ask the service to stop once the scrapes in flight finish
+    pub async fn shutdown(&self) -> Result<()> {
+        self.send(ServiceCommand::Shutdown).await
+    }
+}
+
+/// Actor owning a fetcher and serving [`ServiceCommand`]s
+#[derive(Debug)]
+pub struct ScraperService {
+    fetcher: FerrisFetcher,
+    capacity: usize,
+}
+
+impl ScraperService {
+    /// Create a service around `fetcher`
+    pub fn new(fetcher: FerrisFetcher) -> Self {
+        Self { fetcher, capacity: DEFAULT_CAPACITY }
+    }
+
+    /// Buffer up to `capacity` commands and as many events
+    ///
+    /// Sending a command waits while the command buffer is full. Events are
+    /// dropped, with a warning, while the event buffer is full; wait on
+    /// [`ServiceHandle::scrape`] for results that must not be lost.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Start the service on the current runtime, returning a handle and the event stream
+    pub fn spawn(self) -> (ServiceHandle, mpsc::Receiver<ServiceEvent>) {
+        let (commands, command_rx) = mpsc::channel(self.capacity);
+        let (event_tx, events) = mpsc::channel(self.capacity);
+        tokio::spawn(self.run(command_rx, event_tx));
+        (ServiceHandle { commands }, events)
+    }
+
+    async fn run(mut self, mut commands: mpsc::Receiver<ServiceCommand>, events: mpsc::Sender<ServiceEvent>) {
+        let emit = |event: ServiceEvent| {
+            if let Err(mpsc::error::TrySendError::Full(_)) = events.try_send(event) {
+                warn!("Service event buffer full, dropping an event");
+            }
+        };
+        let mut scrapes = JoinSet::new();
+        info!("Scraper service started");
+
+        loop {
+            tokio::select! {
+                command = commands.recv() => match command {
+                    Some(ServiceCommand::Shutdown) | None => break,
+                    Some(command) => self.handle(command, &mut scrapes, &emit).await,
+                },
+                Some(finished) = scrapes.join_next(), if !scrapes.is_empty() => {
+                    if let Ok(event) = finished {
+                        emit(event);
+                    }
+                }
+            }
+        }
+
+        while let Some(finished) = scrapes.join_next().await {
+            if let Ok(event) = finished {
+                emit(event);
+            }
+        }
+        emit(ServiceEvent::Stopped);
+        info!("Scraper service stopped");
+    }
+
+    async fn handle(&mut self, command: ServiceCommand, scrapes: &mut JoinSet<ServiceEvent>, emit: &impl Fn(ServiceEvent)) {
+        match command {
+            ServiceCommand::ScrapeUrl { url, reply } => {
+                debug!("Service scraping {}", url);
+                let fetcher = self.fetcher.handle();
+                scrapes.spawn(async move {
+                    let result = fetcher.scrape(&url).await;
+                    let event = match &result {
+                        Ok(data) => ServiceEvent::Scraped(Box::new(data.clone())),
+                        Err(error) => ServiceEvent::Failed { url, error: error.to_string() },
+                    };
+                    if let Some(reply) = reply {
+                        let _ = reply.send(result);
+                    }
+                    event
+                });
+            }
+            ServiceCommand::AddRules { rules, reply } => {
+                let result = DataExtractor::with_rules(rules.clone()).validate_rules();
+                if result.is_ok() {
+                    let names = rules.iter().map(|rule| rule.name.clone()).collect();
+                    for rule in rules {
+                        self.fetcher.add_extraction_rule(rule);
+                    }
+                    emit(ServiceEvent::RulesAdded(names));
+                }
+                if let Some(reply) = reply {
+                    let _ = reply.send(result);
+                }
+            }
+            ServiceCommand::UpdateConfig { config, reply } => {
+                let result = config.validate().and_then(|()| self.fetcher.reconfigure(*config));
+                let result = result.map(|fetcher| {
+                    self.fetcher = fetcher;
+                    emit(ServiceEvent::ConfigUpdated);
+                });
+                if let Some(reply) = reply {
+                    let _ = reply.send(result);
+                }
+            }
+            ServiceCommand::GetStats { reply } => {
+                let _ = reply.send(self.fetcher.get_stats().await);
+            }
+            ServiceCommand::Shutdown => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::ExtractionRuleBuilder;
+    use crate::test_support::serve_page;
+
+    #[tokio::test]
+    async fn test_scraper_service() {
+        let base = serve_page(r#"<html><title>Served</title><span class="price">9</span></html>"#);
+
+        let fetcher = FerrisFetcher::with_config(Config::default().without_rate_limit()).unwrap();
+        let (service, mut events) = ScraperService::new(fetcher).spawn();
+
+        let data = service.scrape(&format!("{}/a", base)).await.unwrap();
+        assert_eq!(data.title.as_deref(), Some("Served"));
+        assert!(matches!(events.recv().await, Some(ServiceEvent::Scraped(data)) if data.title.as_deref() == Some("Served")));
+
+        assert!(service.add_rules(vec![ExtractionRuleBuilder::new("broken", "").build()]).await.is_err());
+        service.add_rules(vec![ExtractionRuleBuilder::new("price", ".price").build()]).await.unwrap();
+        assert!(matches!(events.recv().await, Some(ServiceEvent::RulesAdded(names)) if names == ["price"]));
+
+        service.submit(&format!("{}/b", base)).await.unwrap();
+        let Some(ServiceEvent::Scraped(data)) = events.recv().await else { panic!("expected a scraped page") };
+        assert_eq!(data.extracted_data["price"], ["9"]);
+        assert_eq!(service.stats().await.unwrap().total_requests, 2);
+
+        service.update_config(Config::default().without_rate_limit().with_run_id("job-7")).await.unwrap();
+        assert!(matches!(events.recv().await, Some(ServiceEvent::ConfigUpdated)));
+        let data = service.scrape(&format!("{}/c", base)).await.unwrap();
+        assert_eq!(data.run_id.as_deref(), Some("job-7"));
+        assert_eq!(data.extracted_data["price"], ["9"]);
+        assert_eq!(service.stats().await.unwrap().total_requests, 1);
+
+        service.shutdown().await.unwrap();
+        assert!(matches!(events.recv().await, Some(ServiceEvent::Scraped(_))));
+        assert!(matches!(events.recv().await, Some(ServiceEvent::Stopped)));
+        assert!(events.recv().await.is_none());
+        assert!(matches!(service.submit(&base).await, Err(FerrisFetcherError::TaskCancelled)));
+    }
+}
@@ -0,0 +1,216 @@
+//! Google Sheets sink (`google-sheets` feature)
+//!
+//! [`SheetsSink`] appends one row per record to a sheet through the Sheets
+//! API `values:append` endpoint, batching rows and backing off when the API
+//! answers with rate-limit or server errors.
+
+use crate::client::parse_retry_after;
+use crate::error::{FerrisFetcherError, Result};
+use crate::export::{column_value, RecordSink};
+use crate::types::ScrapedData;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Sheets API base URL
+const SHEETS_API: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+
+/// Sink appending records as rows of a Google Sheet
+#[derive(Debug, Clone)]
+pub struct SheetsSink {
+    client: reqwest::Client,
+    api_base: String,
+    spreadsheet_id: String,
+    sheet: String,
+    access_token: String,
+    columns: Vec<String>,
+    batch_size: usize,
+    max_retries: u32,
+    header_row: bool,
+    header_written: bool,
+    pending: Vec<Vec<String>>,
+}
+
+impl SheetsSink {
+    /// Append to `sheet` of a spreadsheet using an OAuth access token with the
+    /// `spreadsheets` scope
+    ///
+    /// Rows hold the `url`, `title`, `status_code` and `timestamp` columns
+    /// until other columns are configured (see [`column_value`]).
+    pub fn new(spreadsheet_id: &str, sheet: &str, access_token: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_base: SHEETS_API.to_string(),
+            spreadsheet_id: spreadsheet_id.to_string(),
+            sheet: sheet.to_string(),
+            access_token: access_token.to_string(),
+            columns: ["url", "title", "status_code", "timestamp"].iter().map(|c| c.to_string()).collect(),
+            batch_size: 100,
+            max_retries: 5,
+            header_row: false,
+            header_written: false,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Set the columns written for each record
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|column| column.to_string()).collect();
+        self
+    }
+
+    /// Set how many rows are sent per API request
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set how often a rate-limited or failed request is retried
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Write the column names as a row before the first batch
+    pub fn with_header_row(mut self) -> Self {
+        self.header_row = true;
+        self
+    }
+
+    /// Replace the access token, e.g. after refreshing it
+    pub fn set_access_token(&mut self, access_token: &str) {
+        self.access_token = access_token.to_string();
+    }
+
+    /// Use a different API base URL (e.g. an emulator)
+    pub fn api_base(mut self, api_base: &str) -> Self {
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Number of rows waiting to be sent
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn append_url(&self) -> String {
+        format!(
+            "{}/{}/values/{}:append?valueInputOption=RAW&insertDataOption=INSERT_ROWS",
+            self.api_base,
+            urlencode(&self.spreadsheet_id),
+            urlencode(&self.sheet)
+        )
+    }
+
+    fn row(&self, data: &ScrapedData) -> Vec<String> {
+        self.columns.iter().map(|column| column_value(data, column)).collect()
+    }
+
+    /// Request body for the pending rows
+    fn request_body(&self) -> Value {
+        let mut values = Vec::with_capacity(self.pending.len() + 1);
+        if self.header_row && !self.header_written {
+            values.push(self.columns.clone());
+        }
+        values.extend(self.pending.iter().cloned());
+        json!({ "majorDimension": "ROWS", "values": values })
+    }
+
+    /// Post a batch, retrying rate-limited and server errors with backoff
+    async fn post(&self, body: &Value) -> Result<()> {
+        let mut delay = Duration::from_secs(1);
+        let mut attempt = 0;
+
+        loop {
+            let response = self.client
+                .post(self.append_url())
+                .bearer_auth(&self.access_token)
+                .json(body)
+                .send()
+                .await?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+
+            let retryable = status.as_u16() == 429 || status.is_server_error();
+            if !retryable || attempt >= self.max_retries {
+                let message = response.text().await.unwrap_or_default();
+                return Err(if status.as_u16() == 429 {
+                    FerrisFetcherError::RateLimitExceeded(delay)
+                } else {
+                    FerrisFetcherError::NetworkError(format!("Sheets API returned {}: {}", status, message))
+                });
+            }
+
+            let wait = parse_retry_after(response.headers()).unwrap_or(delay);
+            warn!("Sheets API returned {}, retrying in {:?}", status, wait);
+            tokio::time::sleep(wait).await;
+
+            delay = (delay * 2).min(Duration::from_secs(64));
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl RecordSink for SheetsSink {
+    async fn send(&mut self, data: &ScrapedData) -> Result<()> {
+        let row = self.row(data);
+        self.pending.push(row);
+        if self.pending.len() >= self.batch_size {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let body = self.request_body();
+        self.post(&body).await?;
+        debug!("Appended {} rows to sheet '{}'", self.pending.len(), self.sheet);
+        self.pending.clear();
+        self.header_written = true;
+        Ok(())
+    }
+}
+
+/// Percent-encode a path segment
+fn urlencode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheets_request() {
+        let mut sink = SheetsSink::new("abc123", "Price log", "token")
+            .columns(&["url", "price"])
+            .with_header_row();
+
+        let mut data = ScrapedData::new("https://shop.example/1".to_string());
+        data.add_extracted_data("price", vec!["$10".to_string()]);
+        sink.pending.push(sink.row(&data));
+
+        assert_eq!(
+            sink.append_url(),
+            "https://sheets.googleapis.com/v4/spreadsheets/abc123/values/Price%20log:append?valueInputOption=RAW&insertDataOption=INSERT_ROWS"
+        );
+        assert_eq!(
+            sink.request_body()["values"],
+            json!([["url", "price"], ["https://shop.example/1", "$10"]])
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Template-based rendering of scraped records
+//!
+//! [`Template`] understands a small Handlebars subset: `{{field}}` (escaped
+//! for HTML output), `{{{field}}}` (raw), `{{#if field}}…{{else}}…{{/if}}` and
+//! `{{#each field}}…{{this}}…{{@index}}…{{/each}}`. Fields are extracted
+//! values, `url`, `title`, `status_code`, `timestamp`, `content` and
+//! `metadata.<key>`. [`TemplateFormat`] plugs templates into the exporters to
+//! produce HTML, Markdown or text digests.
+
+use crate::error::{FerrisFetcherError, Result};
+use crate::export::{column_value, escape_xml, RecordFormat};
+use crate::types::ScrapedData;
+
+/// Parsed template fragment
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Field { name: String, raw: bool },
+    If { name: String, then: Vec<Node>, otherwise: Vec<Node> },
+    Each { name: String, body: Vec<Node> },
+}
+
+/// Compiled record template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+    escape_html: bool,
+}
+
+impl Template {
+    /// Compile a template that inserts values verbatim (Markdown, text)
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut tags = Tokens { rest: source };
+        let (nodes, end) = parse_nodes(&mut tags)?;
+        if let Some(end) = end {
+            return Err(template_error(&format!("unexpected {{{{{}}}}}", end)));
+        }
+        Ok(Self { nodes, escape_html: false })
+    }
+
+    /// Compile a template that HTML-escapes `{{field}}` values
+    pub fn html(source: &str) -> Result<Self> {
+        Ok(Self { escape_html: true, ..Self::parse(source)? })
+    }
+
+    /// Render a record
+    pub fn render(&self, data: &ScrapedData) -> String {
+        let mut output = String::new();
+        self.render_nodes(&self.nodes, data, None, &mut output);
+        output
+    }
+
+    fn render_nodes(&self, nodes: &[Node], data: &ScrapedData, item: Option<(usize, &str)>, output: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Field { name, raw } => {
+                    let value = match (name.as_str(), item) {
+                        ("this", Some((_, value))) => value.to_string(),
+                        ("@index", Some((index, _))) => index.to_string(),
+                        _ => field_values(data, name).join(", "),
+                    };
+                    if self.escape_html && !raw {
+                        output.push_str(&escape_xml(&value));
+                    } else {
+                        output.push_str(&value);
+                    }
+                }
+                Node::If { name, then, otherwise } => {
+                    let truthy = match (name.as_str(), item) {
+                        ("this", Some((_, value))) => !value.is_empty(),
+                        _ => field_values(data, name).iter().any(|value| !value.is_empty()),
+                    };
+                    self.render_nodes(if truthy { then } else { otherwise }, data, item, output);
+                }
+                Node::Each { name, body } => {
+                    for (index, value) in field_values(data, name).iter().enumerate() {
+                        self.render_nodes(body, data, Some((index, value)), output);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Values of a template field for a record
+fn field_values(data: &ScrapedData, name: &str) -> Vec<String> {
+    if let Some(values) = data.extracted_data.get(name) {
+        return values.clone();
+    }
+    if let Some(key) = name.strip_prefix("metadata.") {
+        return match data.metadata.get(key) {
+            Some(serde_json::Value::String(value)) => vec![value.clone()],
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .map(|value| value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()))
+                .collect(),
+            Some(serde_json::Value::Null) | None => Vec::new(),
+            Some(value) => vec![value.to_string()],
+        };
+    }
+
+    let value = match name {
+        "content" => data.content.clone(),
+        _ => column_value(data, name),
+    };
+    if value.is_empty() { Vec::new() } else { vec![value] }
+}
+
+/// Splits template source into text and `{{…}}` tags
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+enum Token<'a> {
+    Text(&'a str),
+    Tag { body: &'a str, raw: bool },
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Result<Option<Token<'a>>> {
+        if self.rest.is_empty() {
+            return Ok(None);
+        }
+        let Some(start) = self.rest.find("{{") else {
+            let text = self.rest;
+            self.rest = "";
+            return Ok(Some(Token::Text(text)));
+        };
+        if start > 0 {
+            let text = &self.rest[..start];
+            self.rest = &self.rest[start..];
+            return Ok(Some(Token::Text(text)));
+        }
+
+        let raw = self.rest.starts_with("{{{");
+        let (open, close) = if raw { ("{{{", "}}}") } else { ("{{", "}}") };
+        let inner = &self.rest[open.len()..];
+        let end = inner
+            .find(close)
+            .ok_or_else(|| template_error(&format!("unclosed tag near '{}'", truncate(self.rest))))?;
+        self.rest = &inner[end + close.len()..];
+        Ok(Some(Token::Tag { body: inner[..end].trim(), raw }))
+    }
+}
+
+/// Parse nodes until the end of input or a closing/`else` tag, which is returned
+fn parse_nodes(tokens: &mut Tokens<'_>) -> Result<(Vec<Node>, Option<String>)> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next()? {
+        let (body, raw) = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text.to_string()));
+                continue;
+            }
+            Token::Tag { body, raw } => (body, raw),
+        };
+
+        if body.starts_with('/') || body == "else" {
+            return Ok((nodes, Some(body.to_string())));
+        }
+        if let Some(name) = body.strip_prefix("#if ") {
+            let name = name.trim().to_string();
+            let (then, end) = parse_nodes(tokens)?;
+            let otherwise = if end.as_deref() == Some("else") {
+                let (otherwise, end) = parse_nodes(tokens)?;
+                expect_close(end, "if")?;
+                otherwise
+            } else {
+                expect_close(end, "if")?;
+                Vec::new()
+            };
+            nodes.push(Node::If { name, then, otherwise });
+        } else if let Some(name) = body.strip_prefix("#each ") {
+            let name = name.trim().to_string();
+            let (inner, end) = parse_nodes(tokens)?;
+            expect_close(end, "each")?;
+            nodes.push(Node::Each { name, body: inner });
+        } else if body.starts_with('#') || body.is_empty() {
+            return Err(template_error(&format!("unsupported tag '{{{{{}}}}}'", body)));
+        } else {
+            nodes.push(Node::Field { name: body.to_string(), raw });
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn expect_close(end: Option<String>, block: &str) -> Result<()> {
+    match end {
+        Some(end) if end.strip_prefix('/').map(str::trim) == Some(block) => Ok(()),
+        Some(end) => Err(template_error(&format!("expected {{{{/{}}}}}, found {{{{{}}}}}", block, end))),
+        None => Err(template_error(&format!("missing {{{{/{}}}}}", block))),
+    }
+}
+
+fn template_error(message: &str) -> FerrisFetcherError {
+    FerrisFetcherError::ConfigError(format!("Invalid template: {}", message))
+}
+
+fn truncate(text: &str) -> &str {
+    text.char_indices().nth(20).map_or(text, |(index, _)| &text[..index])
+}
+
+/// Record format rendering each record through a template
+///
+/// An optional header and footer wrap every file, e.g. to build a digest page.
+#[derive(Debug, Clone)]
+pub struct TemplateFormat {
+    template: Template,
+    extension: String,
+    header: String,
+    footer: String,
+}
+
+impl TemplateFormat {
+    /// Render records into files with the given extension (`html`, `md`, `txt`, …)
+    ///
+    /// Values are HTML-escaped when the extension is `html` or `htm`.
+    pub fn new(source: &str, extension: &str) -> Result<Self> {
+        let extension = extension.trim_start_matches('.').to_string();
+        let template = if matches!(extension.as_str(), "html" | "htm") {
+            Template::html(source)?
+        } else {
+            Template::parse(source)?
+        };
+
+        Ok(Self {
+            template,
+            extension,
+            header: String::new(),
+            footer: String::new(),
+        })
+    }
+
+    /// Text written at the start of every file
+    pub fn with_header(mut self, header: &str) -> Self {
+        self.header = header.to_string();
+        self
+    }
+
+    /// Text written at the end of every file
+    pub fn with_footer(mut self, footer: &str) -> Self {
+        self.footer = footer.to_string();
+        self
+    }
+}
+
+impl RecordFormat for TemplateFormat {
+    fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    fn encode(&self, data: &ScrapedData) -> Result<Vec<u8>> {
+        Ok(self.template.render(data).into_bytes())
+    }
+
+    fn header(&self) -> Vec<u8> {
+        self.header.clone().into_bytes()
+    }
+
+    fn footer(&self) -> Vec<u8> {
+        self.footer.clone().into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{Exporter, FileExporter};
+
+    fn product() -> ScrapedData {
+        let mut data = ScrapedData::new("https://shop.example/mug".to_string());
+        data.title = Some("Mugs & Cups".to_string());
+        data.add_extracted_data("tags", vec!["kitchen".to_string(), "<sale>".to_string()]);
+        data.metadata.insert("lang".to_string(), serde_json::json!("en"));
+        data
+    }
+
+    #[test]
+    fn test_template_render() {
+        let template = Template::parse(
+            "# {{title}} ({{metadata.lang}})\n{{#each tags}}{{@index}}. {{this}}\n{{/each}}{{#if price}}{{price}}{{else}}no price{{/if}}",
+        ).unwrap();
+        assert_eq!(template.render(&product()), "# Mugs & Cups (en)\n0. kitchen\n1. <sale>\nno price");
+
+        let html = Template::html("<h1>{{title}}</h1>{{{title}}}").unwrap();
+        assert_eq!(html.render(&product()), "<h1>Mugs &amp; Cups</h1>Mugs & Cups");
+
+        assert!(Template::parse("{{#if title}}open").is_err());
+        assert!(Template::parse("{{#each tags}}{{/if}}").is_err());
+        assert!(Template::parse("{{title").is_err());
+    }
+
+    #[test]
+    fn test_template_format_export() {
+        let format = TemplateFormat::new("<li><a href=\"{{url}}\">{{title}}</a></li>\n", "html")
+            .unwrap()
+            .with_header("<ul>\n")
+            .with_footer("</ul>\n");
+        let mut exporter = FileExporter::new(Vec::new(), format);
+        exporter.export(&product()).unwrap();
+        exporter.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(exporter.into_inner()).unwrap(),
+            "<ul>\n<li><a href=\"https://shop.example/mug\">Mugs &amp; Cups</a></li>\n</ul>\n"
+        );
+    }
+}
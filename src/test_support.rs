@@ -0,0 +1,142 @@
+//! Stub HTTP server shared by the tests
+//!
+//! The server runs on its own threads, so it serves async tests and tests
+//! that block on a runtime of their own alike. Every response closes its
+//! connection.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+
+/// A request received by the stub server
+#[derive(Debug, Clone)]
+pub(crate) struct StubRequest {
+    /// Method, e.g. `GET`
+    pub method: String,
+    /// Path and query, e.g. `/a?x=1`
+    pub path: String,
+    /// Headers with lowercased names
+    pub headers: Vec<(String, String)>,
+    /// Body, empty when the request had none
+    pub body: String,
+}
+
+impl StubRequest {
+    /// Value of a header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response sent by the stub server
+#[derive(Debug, Clone)]
+pub(crate) struct StubResponse {
+    status: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl StubResponse {
+    /// An empty response with a status line such as `"404 Not Found"`
+    pub fn status(status: &str) -> Self {
+        Self { status: status.to_string(), headers: Vec::new(), body: String::new() }
+    }
+
+    /// A `200 OK` response with `body`
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::status("200 OK").with_body(body)
+    }
+
+    /// A `200 OK` HTML response with `body`
+    pub fn html(body: impl Into<String>) -> Self {
+        Self::ok(body).with_header("Content-Type", "text/html")
+    }
+
+    /// Add a header
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Replace the body
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Serialize for `method`, leaving out the body of HEAD and bodiless statuses
+    fn to_bytes(&self, method: &str) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\n", self.status);
+        for (name, value) in &self.headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        let bodiless = self.status.starts_with('1') || self.status.starts_with("204") || self.status.starts_with("304");
+        if !bodiless {
+            response.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+        response.push_str("Connection: close\r\n\r\n");
+        if !bodiless && method != "HEAD" {
+            response.push_str(&self.body);
+        }
+        response.into_bytes()
+    }
+}
+
+/// Read one request from `stream`
+fn read_request(stream: &TcpStream) -> std::io::Result<StubRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("GET").to_string();
+    let path = parts.next().unwrap_or("/").to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let mut request = StubRequest { method, path, headers, body: String::new() };
+    let length = request.header("content-length").and_then(|length| length.parse().ok()).unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    request.body = String::from_utf8_lossy(&body).into_owned();
+    Ok(request)
+}
+
+/// Serve `handler` on a local port until the test process exits, returning
+/// the base URL, e.g. `http://127.0.0.1:4000`
+///
+/// Each connection is handled on a thread of its own, so a slow handler
+/// does not hold up concurrent requests.
+pub(crate) fn serve<F>(handler: F) -> String
+where
+    F: Fn(&StubRequest) -> StubResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub server");
+    let base = format!("http://{}", listener.local_addr().expect("stub server address"));
+    let handler = Arc::new(handler);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let handler = Arc::clone(&handler);
+            std::thread::spawn(move || {
+                if let Ok(request) = read_request(&stream) {
+                    let _ = stream.write_all(&handler(&request).to_bytes(&request.method));
+                }
+            });
+        }
+    });
+    base
+}
+
+/// Serve the same `body` at every path with `200 OK`
+pub(crate) fn serve_page(body: &'static str) -> String {
+    serve(move |_| StubResponse::ok(body))
+}
@@ -0,0 +1,230 @@
+//! Keyword and n-gram frequency analysis of page text
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Languages with built-in stopword lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Language {
+    /// Map an ISO 639-1 code or BCP 47 tag (`en`, `de-AT`) to a language
+    pub fn from_code(code: &str) -> Option<Self> {
+        let primary = code.trim().split(['-', '_']).next()?.to_lowercase();
+        match primary.as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            "fr" => Some(Language::French),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    /// Stopwords ignored when counting keywords
+    pub fn stopwords(&self) -> &'static [&'static str] {
+        match self {
+            Language::English => &[
+                "a", "about", "above", "after", "again", "all", "also", "am", "an", "and", "any", "are", "as",
+                "at", "be", "because", "been", "before", "being", "below", "between", "both", "but", "by",
+                "can", "could", "did", "do", "does", "doing", "down", "during", "each", "few", "for", "from",
+                "further", "had", "has", "have", "having", "he", "her", "here", "hers", "him", "his", "how",
+                "i", "if", "in", "into", "is", "it", "its", "just", "me", "more", "most", "my", "no", "nor",
+                "not", "now", "of", "off", "on", "once", "only", "or", "other", "our", "ours", "out", "over",
+                "own", "same", "she", "should", "so", "some", "such", "than", "that", "the", "their",
+                "theirs", "them", "then", "there", "these", "they", "this", "those", "through", "to", "too",
+                "under", "until", "up", "very", "was", "we", "were", "what", "when", "where", "which",
+                "while", "who", "whom", "why", "will", "with", "would", "you", "your", "yours",
+            ],
+            Language::German => &[
+                "aber", "alle", "als", "also", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "bist",
+                "da", "damit", "dann", "das", "dass", "dein", "dem", "den", "der", "des", "die", "dies",
+                "diese", "dir", "doch", "du", "durch", "ein", "eine", "einem", "einen", "einer", "es", "für",
+                "hat", "hatte", "ich", "ihr", "im", "in", "ist", "ja", "kann", "kein", "mein", "mit", "nach",
+                "nicht", "noch", "nur", "oder", "ohne", "sich", "sie", "sind", "so", "um", "und", "uns",
+                "unter", "vom", "von", "vor", "war", "was", "wenn", "werden", "wie", "wir", "wird", "zu", "zum",
+                "zur",
+            ],
+            Language::French => &[
+                "a", "au", "aux", "avec", "ce", "ces", "dans", "de", "des", "du", "elle", "en", "est", "et",
+                "eux", "il", "ils", "je", "la", "le", "les", "leur", "lui", "ma", "mais", "me", "mes", "moi",
+                "mon", "ne", "nos", "notre", "nous", "on", "ou", "par", "pas", "pour", "qu", "que", "qui",
+                "sa", "se", "ses", "son", "sont", "sur", "ta", "te", "tes", "toi", "ton", "tu", "un", "une",
+                "vos", "votre", "vous",
+            ],
+            Language::Spanish => &[
+                "a", "al", "algo", "como", "con", "de", "del", "el", "ella", "en", "entre", "era", "es",
+                "esta", "este", "esto", "fue", "ha", "hay", "la", "las", "le", "lo", "los", "mas", "me", "mi",
+                "muy", "no", "nos", "o", "para", "pero", "por", "que", "se", "si", "sin", "sobre", "su",
+                "sus", "también", "te", "tu", "un", "una", "uno", "y", "ya", "yo",
+            ],
+        }
+    }
+}
+
+/// A term and how often it occurs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TermFrequency {
+    /// The word or space-separated n-gram
+    pub term: String,
+    /// Number of occurrences
+    pub count: usize,
+}
+
+/// Most frequent keywords and n-grams of a text
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeywordReport {
+    /// Most frequent single words
+    pub keywords: Vec<TermFrequency>,
+    /// Most frequent multi-word phrases
+    pub ngrams: Vec<TermFrequency>,
+}
+
+/// Configurable keyword and n-gram frequency analyzer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordAnalyzer {
+    /// Stopword language; `None` uses the page language, falling back to English
+    pub language: Option<Language>,
+    /// Number of terms to keep per list
+    pub top_n: usize,
+    /// Largest phrase length to count (2 = bigrams)
+    pub max_ngram: usize,
+    /// Minimum word length in characters
+    pub min_word_length: usize,
+}
+
+impl Default for KeywordAnalyzer {
+    fn default() -> Self {
+        Self {
+            language: None,
+            top_n: 20,
+            max_ngram: 3,
+            min_word_length: 3,
+        }
+    }
+}
+
+impl KeywordAnalyzer {
+    /// Create an analyzer with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a fixed stopword language
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Set the number of terms to keep per list
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    /// Set the largest phrase length to count (1 disables n-grams)
+    pub fn with_max_ngram(mut self, max_ngram: usize) -> Self {
+        self.max_ngram = max_ngram;
+        self
+    }
+
+    /// Set the minimum word length
+    pub fn with_min_word_length(mut self, min_word_length: usize) -> Self {
+        self.min_word_length = min_word_length;
+        self
+    }
+
+    /// Analyze text using the configured language
+    pub fn analyze(&self, text: &str) -> KeywordReport {
+        self.analyze_with_language(text, self.language.unwrap_or(Language::English))
+    }
+
+    /// Analyze text, using `page_language` when no language is configured
+    pub fn analyze_page(&self, text: &str, page_language: Option<&str>) -> KeywordReport {
+        let language = self.language
+            .or_else(|| page_language.and_then(Language::from_code))
+            .unwrap_or(Language::English);
+        self.analyze_with_language(text, language)
+    }
+
+    fn analyze_with_language(&self, text: &str, language: Language) -> KeywordReport {
+        let stopwords = language.stopwords();
+        let lowered = text.to_lowercase();
+        let tokens: Vec<&str> = lowered
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        let is_keyword = |token: &str| {
+            token.chars().count() >= self.min_word_length
+                && !token.chars().all(|c| c.is_ascii_digit())
+                && !stopwords.contains(&token)
+        };
+
+        let mut keywords: HashMap<String, usize> = HashMap::new();
+        for token in tokens.iter().filter(|token| is_keyword(token)) {
+            *keywords.entry(token.to_string()).or_insert(0) += 1;
+        }
+
+        let mut ngrams: HashMap<String, usize> = HashMap::new();
+        for size in 2..=self.max_ngram {
+            for window in tokens.windows(size) {
+                if window.iter().all(|token| is_keyword(token)) {
+                    *ngrams.entry(window.join(" ")).or_insert(0) += 1;
+                }
+            }
+        }
+
+        KeywordReport {
+            keywords: self.top(keywords, 1),
+            ngrams: self.top(ngrams, 2),
+        }
+    }
+
+    /// Sort terms by frequency and keep the top entries occurring at least `min_count` times
+    fn top(&self, counts: HashMap<String, usize>, min_count: usize) -> Vec<TermFrequency> {
+        let mut terms: Vec<TermFrequency> = counts
+            .into_iter()
+            .filter(|(_, count)| *count >= min_count)
+            .map(|(term, count)| TermFrequency { term, count })
+            .collect();
+        terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+        terms.truncate(self.top_n);
+        terms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_analysis() {
+        let text = "Rust web scraping is fast. Web scraping with Rust is safe, \
+                    and web scraping in 2024 is popular. The crawler is fast.";
+
+        let report = KeywordAnalyzer::new().with_top_n(3).analyze(text);
+        assert_eq!(report.keywords[0], TermFrequency { term: "scraping".to_string(), count: 3 });
+        assert_eq!(report.keywords[1], TermFrequency { term: "web".to_string(), count: 3 });
+        assert_eq!(report.keywords[2], TermFrequency { term: "fast".to_string(), count: 2 });
+        assert!(!report.keywords.iter().any(|t| t.term == "the" || t.term == "2024"));
+
+        assert_eq!(report.ngrams[0], TermFrequency { term: "web scraping".to_string(), count: 3 });
+        assert!(report.ngrams.iter().all(|t| t.count >= 2));
+    }
+
+    #[test]
+    fn test_page_language() {
+        let text = "Die schnelle Suche und die schnelle Antwort";
+        let report = KeywordAnalyzer::new().analyze_page(text, Some("de-DE"));
+        assert_eq!(report.keywords[0].term, "schnelle");
+        assert!(!report.keywords.iter().any(|t| t.term == "und" || t.term == "die"));
+
+        assert_eq!(Language::from_code("fr_CA"), Some(Language::French));
+        assert_eq!(Language::from_code("xx"), None);
+    }
+}
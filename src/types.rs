@@ -1,13 +1,25 @@
 //! Common data types and structures for FerrisFetcher
 
-use chrono::{DateTime, Utc};
+use crate::chunking::{self, TextChunk};
+use crate::clock::RandomSource;
+use crate::error::{FerrisFetcherError, Result};
+use crate::health::HostHealth;
+use crate::extractor::TransformChain;
+use crate::html_parser::{CssSelector, HtmlParser};
+use crate::schema::SCHEMA_VERSION;
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Main structure containing scraped data from a web page
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScrapedData {
+    /// Layout version of the serialized record (see [`crate::schema`])
+    #[serde(default = "crate::schema::legacy_schema_version")]
+    pub schema_version: u32,
     /// The URL that was scraped
     pub url: String,
     /// Page title if available
@@ -18,29 +30,133 @@ pub struct ScrapedData {
     pub metadata: HashMap<String, serde_json::Value>,
     /// Structured data extracted using rules
     pub extracted_data: HashMap<String, Vec<String>>,
+    /// Records extracted by item rules, by rule name
+    #[serde(default)]
+    pub items: HashMap<String, Vec<HashMap<String, String>>>,
     /// When the scraping occurred
     pub timestamp: DateTime<Utc>,
     /// HTTP status code
     pub status_code: u16,
     /// Response headers
     pub headers: HashMap<String, String>,
-    /// Time taken to scrape (in milliseconds)
+    /// Total time taken to scrape, including iframes (in milliseconds)
     pub scrape_time_ms: u64,
+    /// Time spent fetching the response, body included (in milliseconds)
+    #[serde(default)]
+    pub fetch_time_ms: u64,
+    /// Time spent parsing the document, metadata and classification included (in milliseconds)
+    #[serde(default)]
+    pub parse_time_ms: u64,
+    /// Time spent running extraction rules and the LLM fallback (in milliseconds)
+    #[serde(default)]
+    pub extract_time_ms: u64,
+    /// Rule set version that produced `extracted_data`, if the extractor was versioned
+    #[serde(default)]
+    pub rule_set: Option<RuleSetVersion>,
+    /// Documents fetched from iframes embedded in this page
+    #[serde(default)]
+    pub frames: Vec<ScrapedData>,
+    /// Page type assigned by the configured classifier
+    #[serde(default)]
+    pub page_type: Option<PageType>,
+    /// Processing steps that failed; the HTTP-level data is still present
+    #[serde(default)]
+    pub errors: Vec<ScrapeWarning>,
+    /// Non-fatal data-quality issues noticed while scraping
+    #[serde(default)]
+    pub warnings: Vec<ScrapeWarning>,
+    /// Where the time of the request went
+    #[serde(default)]
+    pub timings: RequestTimings,
+    /// Number of links followed from a crawl seed to reach the page
+    #[serde(default)]
+    pub depth: usize,
+    /// Page the URL was found on (`None` for seeds and direct scrapes)
+    #[serde(default)]
+    pub parent_url: Option<String>,
+    /// How the page was reached (`None` for direct scrapes)
+    #[serde(default)]
+    pub discovered_via: Option<Discovery>,
+    /// Identifier of the job that scraped the page
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// URL originally requested, when the result is its `rel=canonical` page instead
+    #[serde(default)]
+    pub original_url: Option<String>,
+    /// URL of the response, after any redirects
+    #[serde(default)]
+    pub final_url: String,
+    /// Redirects followed from `url` to `final_url`, in order
+    #[serde(default)]
+    pub redirects: Vec<RedirectHop>,
+}
+
+/// A redirect followed on the way to a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedirectHop {
+    /// URL that answered with the redirect
+    pub url: String,
+    /// Redirect status code
+    pub status: u16,
+}
+
+/// How a scraped page was reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Discovery {
+    /// Given as a starting URL
+    Seed,
+    /// Followed from a link on the parent page
+    Link,
+    /// Embedded in the parent page as an iframe
+    Iframe,
+    /// Listed in the sitemap recorded as the parent
+    Sitemap,
+}
+
+impl Discovery {
+    /// Lowercase name used in exports
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Discovery::Seed => "seed",
+            Discovery::Link => "link",
+            Discovery::Iframe => "iframe",
+            Discovery::Sitemap => "sitemap",
+        }
+    }
 }
 
 impl ScrapedData {
     /// Create new scraped data
     pub fn new(url: String) -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
+            final_url: url.clone(),
             url,
             title: None,
             content: String::new(),
             metadata: HashMap::new(),
             extracted_data: HashMap::new(),
+            items: HashMap::new(),
             timestamp: Utc::now(),
             status_code: 0,
             headers: HashMap::new(),
             scrape_time_ms: 0,
+            fetch_time_ms: 0,
+            parse_time_ms: 0,
+            extract_time_ms: 0,
+            rule_set: None,
+            frames: Vec::new(),
+            page_type: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            timings: RequestTimings::default(),
+            depth: 0,
+            parent_url: None,
+            discovered_via: None,
+            run_id: None,
+            original_url: None,
+            redirects: Vec::new(),
         }
     }
     
@@ -54,6 +170,90 @@ impl ScrapedData {
         self.metadata.insert(key.to_string(), value);
     }
     
+    /// Stable identifier of the record: 16 hex digits hashed from the
+    /// normalized URL and the run ID, or the timestamp outside of a run
+    ///
+    /// The URL is normalized by dropping the fragment and sorting the query
+    /// parameters, so re-crawling a page in the same run yields the same ID.
+    pub fn id(&self) -> String {
+        let url = match url::Url::parse(self.url.trim()) {
+            Ok(mut url) => {
+                url.set_fragment(None);
+                let mut pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+                if pairs.is_empty() {
+                    url.set_query(None);
+                } else {
+                    pairs.sort();
+                    url.query_pairs_mut().clear().extend_pairs(pairs);
+                }
+                url.to_string()
+            }
+            Err(_) => self.url.trim().to_string(),
+        };
+        let scope = match &self.run_id {
+            Some(run_id) => run_id.clone(),
+            None => self.timestamp.to_rfc3339(),
+        };
+
+        // FNV-1a, unlike `DefaultHasher`, is guaranteed to stay the same across builds
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in url.bytes().chain([0]).chain(scope.bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        format!("{:016x}", hash)
+    }
+    
+    /// Record where the page sits in a crawl; embedded frames share its depth
+    pub fn set_origin(&mut self, depth: usize, parent_url: Option<String>, discovered_via: Discovery) {
+        self.depth = depth;
+        self.parent_url = parent_url;
+        self.discovered_via = Some(discovered_via);
+        for frame in &mut self.frames {
+            frame.depth = depth;
+        }
+    }
+    
+    /// Check whether any processing step failed, leaving the data incomplete
+    pub fn is_partial(&self) -> bool {
+        !self.errors.is_empty()
+    }
+    
+    /// Record a failed processing step
+    pub fn add_error(&mut self, kind: WarningKind, message: impl Into<String>) {
+        self.errors.push(ScrapeWarning::new(kind, message));
+    }
+    
+    /// Record a non-fatal issue
+    pub fn add_warning(&mut self, kind: WarningKind, message: impl Into<String>) {
+        self.warnings.push(ScrapeWarning::new(kind, message));
+    }
+    
+    /// Check whether a warning of the given kind was recorded
+    pub fn has_warning(&self, kind: WarningKind) -> bool {
+        self.warnings.iter().any(|warning| warning.kind == kind)
+    }
+    
+    /// Classify the HTTP response status
+    pub fn status(&self) -> ResponseStatus {
+        ResponseStatus::from_response(self.status_code, &self.headers)
+    }
+    
+    /// Check whether the page answered with a success status but reads like an error page
+    pub fn is_soft_404(&self) -> bool {
+        if self.status() != ResponseStatus::Success {
+            return false;
+        }
+        if let Some(page_type) = self.page_type {
+            return page_type == PageType::Error;
+        }
+
+        self.title.as_deref().is_some_and(|title| {
+            let title = title.to_lowercase();
+            SOFT_404_PHRASES.iter().any(|phrase| title.contains(phrase))
+        })
+    }
+    
     /// Get all extracted values for a key
     pub fn get_extracted_values(&self, key: &str) -> Option<&Vec<String>> {
         self.extracted_data.get(key)
@@ -63,9 +263,179 @@ impl ScrapedData {
     pub fn get_first_value(&self, key: &str) -> Option<&String> {
         self.extracted_data.get(key).and_then(|values| values.first())
     }
+
+    /// Approximate heap size in bytes, for memory budgeting
+    pub fn approximate_size(&self) -> usize {
+        let strings = |map: &HashMap<String, String>| map.iter().map(|(key, value)| key.len() + value.len()).sum::<usize>();
+        self.url.len()
+            + self.content.len()
+            + self.title.as_ref().map_or(0, String::len)
+            + self.parent_url.as_ref().map_or(0, String::len)
+            + self.run_id.as_ref().map_or(0, String::len)
+            + strings(&self.headers)
+            + self.metadata.iter().map(|(key, value)| key.len() + value.to_string().len()).sum::<usize>()
+            + self.extracted_data.iter().map(|(key, values)| key.len() + values.iter().map(String::len).sum::<usize>()).sum::<usize>()
+            + self.items.iter().map(|(key, items)| key.len() + items.iter().map(strings).sum::<usize>()).sum::<usize>()
+            + self.frames.iter().map(ScrapedData::approximate_size).sum::<usize>()
+    }
+
+    /// Split the readable page content into chunks for embedding pipelines
+    ///
+    /// Chunks break at headings and paragraphs, hold at most `max_tokens`
+    /// whitespace-separated tokens, and repeat `overlap` tokens between
+    /// consecutive chunks of the same section. Each chunk carries the page
+    /// URL, title and enclosing headings.
+    pub fn text_chunks(&self, max_tokens: usize, overlap: usize) -> Result<Vec<TextChunk>> {
+        let parser = HtmlParser::new(&self.content)?;
+        let blocks = chunking::text_blocks(&parser);
+        Ok(chunking::chunk_blocks(&blocks, max_tokens, overlap, &self.url, self.title.as_deref()))
+    }
+}
+
+/// Timing breakdown of the request of a scrape
+///
+/// The phases do not overlap. reqwest 0.11 offers no way to wrap its
+/// connector, so TCP connects, TLS handshakes and retries are part of `ttfb_ms`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestTimings {
+    /// Waiting for a host pause, a concurrency slot and the rate limit
+    pub queued_ms: u64,
+    /// Resolving host names, `None` if the request reused a connection or named an IP address
+    #[serde(default)]
+    pub dns_ms: Option<u64>,
+    /// From sending the request until the response headers arrived, DNS lookups excluded
+    pub ttfb_ms: u64,
+    /// Reading the response body
+    pub download_ms: u64,
+    /// Attempts made, including retries
+    pub attempts: u32,
+}
+
+impl RequestTimings {
+    /// Sum of all phases in milliseconds
+    pub fn total_ms(&self) -> u64 {
+        self.queued_ms + self.dns_ms.unwrap_or(0) + self.ttfb_ms + self.download_ms
+    }
+}
+
+/// A problem encountered while processing a scraped page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrapeWarning {
+    /// What kind of problem occurred
+    pub kind: WarningKind,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl ScrapeWarning {
+    /// Create a new warning
+    pub fn new(kind: WarningKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+/// Kinds of problems recorded on scraped data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WarningKind {
+    /// The response body could not be read or decoded
+    BodyRead,
+    /// The HTML document could not be parsed
+    Parse,
+    /// An extraction rule could not be evaluated
+    Extraction,
+    /// An extraction rule matched nothing
+    RuleNoMatch,
+    /// A custom metadata extractor failed
+    Metadata,
+    /// No charset was declared in the response headers, so it was guessed
+    CharsetGuessed,
+    /// The body was shorter than its declared `Content-Length`
+    TruncatedBody,
+    /// The request was redirected to a different host
+    CrossHostRedirect,
+    /// The page's declared canonical URL could not be fetched
+    CanonicalFetch,
+}
+
+/// Kind of page, as detected by a [`PageClassifier`](crate::classify::PageClassifier)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PageType {
+    /// Single product detail page
+    Product,
+    /// Article, news story or blog post
+    Article,
+    /// Category, search result or index page listing several items
+    Listing,
+    /// Sign-in page
+    Login,
+    /// Error page (error status or "not found" content)
+    Error,
+    /// Anything else
+    Other,
+}
+
+/// Title phrases marking a soft 404 when no page type was assigned
+const SOFT_404_PHRASES: &[&str] = &["404", "not found", "page not found", "no longer available"];
+
+/// Status codes servers use to refuse scrapers
+const BLOCKED_STATUS_CODES: &[u16] = &[401, 403, 407, 429, 451];
+
+/// Outcome of an HTTP response, derived from its status code and headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ResponseStatus {
+    /// 2xx response
+    Success,
+    /// 3xx response other than 304 (only seen when redirects are not followed)
+    Redirected,
+    /// 304 response to a conditional request
+    NotModified,
+    /// Access refused: 401, 403, 407, 429, 451 or a bot challenge
+    Blocked,
+    /// Any other 4xx response
+    ClientError,
+    /// 5xx response
+    ServerError,
+    /// Informational or non-standard status code
+    Other,
+}
+
+impl ResponseStatus {
+    /// Classify a status code and the response headers
+    pub fn from_response(status_code: u16, headers: &HashMap<String, String>) -> Self {
+        let challenged = headers.get("cf-mitigated").is_some_and(|value| value.eq_ignore_ascii_case("challenge"));
+
+        match status_code {
+            _ if challenged => Self::Blocked,
+            code if BLOCKED_STATUS_CODES.contains(&code) => Self::Blocked,
+            304 => Self::NotModified,
+            200..=299 => Self::Success,
+            300..=399 => Self::Redirected,
+            400..=499 => Self::ClientError,
+            500..=599 => Self::ServerError,
+            _ => Self::Other,
+        }
+    }
+
+    /// Check whether the response carries usable content
+    pub fn is_success(&self) -> bool {
+        *self == Self::Success
+    }
+
+    /// Check whether the status is an error (blocked, client or server error)
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Blocked | Self::ClientError | Self::ServerError)
+    }
 }
 
 /// Configuration for retry policies
+///
+/// A response is retried when its status is in `retry_statuses` or `retry_if`
+/// accepts it; transport errors are always retryable. Either way only requests
+/// whose method is in `retry_methods` are retried, which leaves out `POST` and
+/// `PATCH` by default.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryPolicy {
     /// Maximum number of retry attempts
@@ -78,6 +448,21 @@ pub struct RetryPolicy {
     pub exponential_backoff: bool,
     /// Backoff multiplier for exponential backoff
     pub backoff_multiplier: f64,
+    /// Response statuses that are retried
+    #[serde(default = "RetryPolicy::default_statuses")]
+    pub retry_statuses: Vec<u16>,
+    /// Request methods that are retried
+    #[serde(default = "RetryPolicy::default_methods")]
+    pub retry_methods: Vec<HttpMethod>,
+    /// Additional condition under which a response is retried
+    #[serde(skip)]
+    pub retry_if: Option<RetryPredicate>,
+    /// Randomisation of backoff delays
+    #[serde(default)]
+    pub jitter: Jitter,
+    /// Cap on retries across all requests of a client
+    #[serde(default)]
+    pub budget: Option<RetryBudget>,
 }
 
 impl Default for RetryPolicy {
@@ -88,10 +473,378 @@ impl Default for RetryPolicy {
             max_delay: Duration::from_millis(10000),
             exponential_backoff: true,
             backoff_multiplier: 2.0,
+            retry_statuses: Self::default_statuses(),
+            retry_methods: Self::default_methods(),
+            retry_if: None,
+            jitter: Jitter::None,
+            budget: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Timeouts, rate limiting and transient server errors
+    fn default_statuses() -> Vec<u16> {
+        vec![408, 425, 429, 500, 502, 503, 504]
+    }
+
+    /// The idempotent methods
+    fn default_methods() -> Vec<HttpMethod> {
+        vec![HttpMethod::Get, HttpMethod::Head, HttpMethod::Put, HttpMethod::Delete, HttpMethod::Options]
+    }
+
+    /// Retry responses with these statuses instead of the defaults
+    pub fn with_retry_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retry_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Also retry responses the predicate accepts
+    pub fn with_retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&reqwest::Response) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Some(RetryPredicate::new(predicate));
+        self
+    }
+
+    /// Allow or forbid retrying requests with a method
+    pub fn with_method_retry(mut self, method: HttpMethod, enabled: bool) -> Self {
+        self.retry_methods.retain(|retried| *retried != method);
+        if enabled {
+            self.retry_methods.push(method);
+        }
+        self
+    }
+
+    /// Randomise backoff delays so concurrent retries spread out
+    pub fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Allow at most `max_retries` retries per `window` across all requests
+    pub fn with_retry_budget(mut self, max_retries: u32, window: Duration) -> Self {
+        self.budget = Some(RetryBudget { max_retries, window });
+        self
+    }
+
+    /// Whether a response should be retried
+    pub fn retries_response(&self, response: &reqwest::Response) -> bool {
+        self.retry_statuses.contains(&response.status().as_u16())
+            || self.retry_if.as_ref().is_some_and(|predicate| predicate.matches(response))
+    }
+
+    /// Whether requests with a method are retried
+    pub fn retries_method(&self, method: &reqwest::Method) -> bool {
+        self.retry_methods.iter().any(|retried| retried.as_str() == method.as_str())
+    }
+}
+
+/// Randomisation applied to a backoff delay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Jitter {
+    /// Use the delay as computed
+    #[default]
+    None,
+    /// A random delay between zero and the computed delay
+    Full,
+    /// Half the computed delay plus a random share of the other half
+    Equal,
+}
+
+impl Jitter {
+    /// Randomise a delay
+    pub fn apply(self, delay: Duration) -> Duration {
+        self.apply_with(delay, &RandomSource::system())
+    }
+
+    /// Randomise a delay with numbers drawn from `random`
+    pub fn apply_with(self, delay: Duration, random: &RandomSource) -> Duration {
+        let random_share = |max: Duration| max.mul_f64(random.fraction());
+        match self {
+            Jitter::None => delay,
+            Jitter::Full => random_share(delay),
+            Jitter::Equal => delay / 2 + random_share(delay - delay / 2),
+        }
+    }
+}
+
+/// Limit on retries per time window, shared by all requests of a client
+///
+/// Once the budget is spent, failed attempts are not retried by the policy
+/// until older retries leave the window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryBudget {
+    /// Retries allowed within `window`
+    pub max_retries: u32,
+    /// Length of the sliding window
+    pub window: Duration,
+}
+
+/// Callback marking responses for retry, see [`RetryPolicy::with_retry_if`]
+#[derive(Clone)]
+pub struct RetryPredicate(Arc<RetryPredicateFn>);
+
+/// Signature of [`RetryPredicate`] callbacks
+type RetryPredicateFn = dyn Fn(&reqwest::Response) -> bool + Send + Sync;
+
+impl RetryPredicate {
+    /// Wrap a callback
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&reqwest::Response) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(predicate))
+    }
+
+    /// Ask the callback whether to retry a response
+    pub fn matches(&self, response: &reqwest::Response) -> bool {
+        (self.0)(response)
+    }
+}
+
+impl fmt::Debug for RetryPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RetryPredicate")
+    }
+}
+
+/// Scheduling of the URLs in a batch scrape
+///
+/// Each request waits `delay` plus a random share of `jitter` before it
+/// starts, and the URL order can be shuffled so requests to one host are
+/// spread over the batch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchOptions {
+    /// Fixed delay before each request
+    pub delay: Duration,
+    /// Upper bound of the random delay added before each request
+    pub jitter: Duration,
+    /// Whether to randomise the URL order
+    pub shuffle: bool,
+    /// Seed for shuffling and jitter, for reproducible schedules
+    pub seed: Option<u64>,
+    /// Move URLs of unhealthy or paused hosts to the end of the batch
+    #[serde(default)]
+    pub deprioritize_unhealthy: bool,
+    /// Return results in input order instead of completion order
+    #[serde(default)]
+    pub preserve_order: bool,
+}
+
+impl BatchOptions {
+    /// Create options that start every URL immediately, in order
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait a fixed delay before each request
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Add a random delay of up to `jitter` before each request
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Scrape URLs in random order
+    pub fn shuffled(mut self) -> Self {
+        self.shuffle = true;
+        self
+    }
+
+    /// Use a fixed random seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Scrape URLs of unhealthy hosts after the others (see [`crate::health`])
+    pub fn deprioritize_unhealthy(mut self) -> Self {
+        self.deprioritize_unhealthy = true;
+        self
+    }
+
+    /// Return results aligned with the input URL order
+    ///
+    /// Results are reassembled by input index once the batch finishes, so
+    /// a slow URL does not hold back the others while they run.
+    pub fn preserve_order(mut self, preserve: bool) -> Self {
+        self.preserve_order = preserve;
+        self
+    }
+
+    /// Order the URLs and pick the delay before each one
+    ///
+    /// Entries are `(input index, url, delay)` in the order to start them.
+    pub fn schedule<'a>(&self, urls: &[&'a str]) -> Vec<(usize, &'a str, Duration)> {
+        self.schedule_with(urls, &RandomSource::system())
+    }
+
+    /// Like [`schedule`](Self::schedule), drawing from `random` unless a seed is set
+    pub fn schedule_with<'a>(&self, urls: &[&'a str], random: &RandomSource) -> Vec<(usize, &'a str, Duration)> {
+        use rand::seq::SliceRandom;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = match self.seed {
+            Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+            None => random.fork(),
+        };
+
+        let mut order: Vec<(usize, &str)> = urls.iter().copied().enumerate().collect();
+        if self.shuffle {
+            order.shuffle(&mut rng);
+        }
+
+        let jitter_ms = self.jitter.as_millis() as u64;
+        order
+            .into_iter()
+            .map(|(index, url)| {
+                let jitter = if jitter_ms == 0 { 0 } else { rng.gen_range(0..=jitter_ms) };
+                (index, url, self.delay + Duration::from_millis(jitter))
+            })
+            .collect()
+    }
+}
+
+/// Result of scraping one URL of a batch
+#[derive(Debug)]
+pub enum ScrapeOutcome {
+    /// The page was scraped
+    Scraped(Box<ScrapedData>),
+    /// Scraping failed with an error
+    Failed { url: String, error: FerrisFetcherError },
+    /// The scrape task panicked
+    Panicked { url: String, message: String },
+}
+
+impl ScrapeOutcome {
+    /// URL the outcome belongs to
+    pub fn url(&self) -> &str {
+        match self {
+            ScrapeOutcome::Scraped(data) => &data.url,
+            ScrapeOutcome::Failed { url, .. } | ScrapeOutcome::Panicked { url, .. } => url,
+        }
+    }
+
+    /// Whether the page was scraped
+    pub fn is_scraped(&self) -> bool {
+        matches!(self, ScrapeOutcome::Scraped(_))
+    }
+
+    /// Take the scraped page, if any
+    pub fn into_data(self) -> Option<ScrapedData> {
+        match self {
+            ScrapeOutcome::Scraped(data) => Some(*data),
+            _ => None,
+        }
+    }
+
+    /// Take the scraped page or the error, reporting a panic as [`FerrisFetcherError::Panicked`]
+    pub fn into_result(self) -> Result<ScrapedData> {
+        match self {
+            ScrapeOutcome::Scraped(data) => Ok(*data),
+            ScrapeOutcome::Failed { error, .. } => Err(error),
+            ScrapeOutcome::Panicked { url, message } => Err(FerrisFetcherError::Panicked { url, message }),
         }
     }
 }
 
+/// Scraped pages of a batch together with the URLs that did not produce one
+#[derive(Debug, Default)]
+pub struct BatchResults {
+    /// Scraped pages, in input order
+    pub scraped: Vec<ScrapedData>,
+    /// URLs whose scrape failed, with the error, in input order
+    pub failed: Vec<(String, FerrisFetcherError)>,
+    /// URLs whose scrape panicked, with the panic message
+    pub panicked: Vec<(String, String)>,
+    /// URLs not started because the error hook aborted the batch
+    pub not_started: Vec<String>,
+}
+
+impl BatchResults {
+    /// Sort the outcomes of a batch over `urls` into successes and failures
+    pub fn from_outcomes(urls: &[&str], outcomes: Vec<ScrapeOutcome>) -> Self {
+        let mut results = Self::default();
+        let mut finished: HashMap<String, usize> = HashMap::new();
+        for outcome in outcomes {
+            *finished.entry(outcome.url().to_string()).or_default() += 1;
+            match outcome {
+                ScrapeOutcome::Scraped(data) => results.scraped.push(*data),
+                ScrapeOutcome::Failed { url, error } => results.failed.push((url, error)),
+                ScrapeOutcome::Panicked { url, message } => results.panicked.push((url, message)),
+            }
+        }
+
+        for url in urls {
+            match finished.get_mut(*url) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => results.not_started.push(url.to_string()),
+            }
+        }
+        results
+    }
+
+    /// Whether every URL was scraped
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.panicked.is_empty() && self.not_started.is_empty()
+    }
+
+    /// The abort error returned by the error hook, if it stopped the batch
+    pub fn abort_error(&self) -> Option<&FerrisFetcherError> {
+        self.failed.iter().map(|(_, error)| error).find(|error| matches!(error, FerrisFetcherError::Aborted(_)))
+    }
+}
+
+/// What to do after a failed request attempt, as decided by an [`ErrorHook`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Retry after the given delay, even if the retry policy is exhausted
+    Retry(Duration),
+    /// Give up on this URL and return the error
+    Skip,
+    /// Give up on this URL and stop any batch it belongs to
+    Abort,
+    /// Fall back to the configured [`RetryPolicy`]
+    UsePolicy,
+}
+
+/// Callback deciding how to handle a failed request attempt
+///
+/// Receives the error, the request URL and the 1-based attempt number.
+#[derive(Clone)]
+pub struct ErrorHook(Arc<ErrorHookFn>);
+
+/// Signature of [`ErrorHook`] callbacks
+type ErrorHookFn = dyn Fn(&FerrisFetcherError, &str, u32) -> RetryDecision + Send + Sync;
+
+impl ErrorHook {
+    /// Wrap a callback
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&FerrisFetcherError, &str, u32) -> RetryDecision + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+
+    /// Ask the callback how to handle a failed attempt
+    pub fn decide(&self, error: &FerrisFetcherError, url: &str, attempt: u32) -> RetryDecision {
+        (self.0)(error, url, attempt)
+    }
+}
+
+impl fmt::Debug for ErrorHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErrorHook")
+    }
+}
+
 /// Rule for extracting data from HTML
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractionRule {
@@ -105,6 +858,116 @@ pub struct ExtractionRule {
     pub multiple: bool,
     /// Optional attribute to extract (for Attribute extraction type)
     pub attribute: Option<String>,
+    /// Named groups this rule belongs to (e.g. "seo", "pricing")
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Page predicates that must all hold for the rule to run
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    /// Number of leading matches to skip
+    #[serde(default)]
+    pub skip: usize,
+    /// Maximum number of matches to keep (for multiple extraction)
+    #[serde(default)]
+    pub max_matches: Option<usize>,
+    /// Reduction of multiple matches to a single value
+    #[serde(default)]
+    pub aggregate: Option<Aggregate>,
+    /// Post-processing steps applied in order to each matched value
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// `selector` pre-parsed, used instead of parsing it for every page while the two agree
+    #[serde(skip)]
+    pub compiled_selector: Option<CssSelector>,
+    /// `transforms` compiled, used instead of compiling them for every page while the two agree
+    #[serde(skip)]
+    pub compiled_transforms: Option<TransformChain>,
+}
+
+/// Rule extracting a list of records from repeated page structures
+///
+/// Every element matching `selector` yields one record, built by evaluating
+/// the `fields` rules relative to that element. A field with an empty or
+/// `:scope` selector reads the element itself. Multiple values of a field are
+/// joined by newlines unless the field aggregates them; records without any
+/// field value are dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemRule {
+    /// Name of the item list
+    pub name: String,
+    /// CSS selector of the repeated container elements
+    pub selector: String,
+    /// Rules producing the fields of each record
+    pub fields: Vec<ExtractionRule>,
+    /// Named groups this rule belongs to
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Maximum number of records to keep
+    #[serde(default)]
+    pub max_items: Option<usize>,
+    /// `selector` pre-parsed, used instead of parsing it for every page while the two agree
+    #[serde(skip)]
+    pub compiled_selector: Option<CssSelector>,
+}
+
+impl ItemRule {
+    /// Create an item rule without fields
+    pub fn new(name: &str, selector: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            selector: selector.to_string(),
+            fields: Vec::new(),
+            groups: Vec::new(),
+            max_items: None,
+            compiled_selector: None,
+        }
+    }
+
+    /// Add a field rule, evaluated relative to each container
+    pub fn field(mut self, rule: ExtractionRule) -> Self {
+        self.fields.push(rule);
+        self
+    }
+
+    /// Add the rule to a named group
+    pub fn group(mut self, group: &str) -> Self {
+        self.groups.push(group.to_string());
+        self
+    }
+
+    /// Keep at most `max` records
+    pub fn max_items(mut self, max: usize) -> Self {
+        self.max_items = Some(max);
+        self
+    }
+}
+
+/// Page predicate gating whether an extraction rule runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RuleCondition {
+    /// Run only if the CSS selector matches at least one element
+    SelectorExists(String),
+    /// Run only if the page URL matches the regular expression
+    UrlMatches(String),
+}
+
+/// Identifies the rule set (name and version) that produced extracted data
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RuleSetVersion {
+    /// Name of the rule set
+    pub name: String,
+    /// Version number of the rule set
+    pub version: u32,
+}
+
+impl RuleSetVersion {
+    /// Create a new rule set version marker
+    pub fn new(name: impl Into<String>, version: u32) -> Self {
+        Self {
+            name: name.into(),
+            version,
+        }
+    }
 }
 
 /// Types of data extraction
@@ -118,10 +981,112 @@ pub enum ExtractionType {
     Attribute,
     /// Extract the element's own HTML
     OuterHtml,
+    /// Extract all attributes of the element as a JSON object
+    Attributes,
+    /// Evaluate the selector as a JSONPath expression on JSON responses (see [`crate::json_extractor`])
+    JsonPath,
+}
+
+/// Reduction of the values matched by a rule to a single value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Aggregate {
+    /// Concatenate the values with a separator
+    Join(String),
+    /// Keep the first value
+    First,
+    /// Keep the last value
+    Last,
+    /// Drop repeated values, keeping the first occurrence of each
+    Unique,
+    /// Add up the values that hold a number, ignoring the others
+    Sum,
+}
+
+impl Aggregate {
+    /// Apply the aggregation to extracted values
+    pub fn apply(&self, values: Vec<String>) -> Vec<String> {
+        if values.is_empty() {
+            return values;
+        }
+        match self {
+            Aggregate::Join(separator) => vec![values.join(separator)],
+            Aggregate::First => values.into_iter().take(1).collect(),
+            Aggregate::Last => values.into_iter().last().into_iter().collect(),
+            Aggregate::Unique => {
+                let mut seen = std::collections::HashSet::new();
+                values.into_iter().filter(|value| seen.insert(value.clone())).collect()
+            }
+            Aggregate::Sum => {
+                let numbers: Vec<f64> = values.iter().filter_map(|value| parse_number(value)).collect();
+                if numbers.is_empty() {
+                    return Vec::new();
+                }
+                vec![numbers.iter().sum::<f64>().to_string()]
+            }
+        }
+    }
+}
+
+/// Post-processing step applied to each value a rule extracts
+///
+/// Transforms run in order before skipping, limiting and aggregation; a
+/// value that ends up empty is dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Transform {
+    /// Keep the first capture group of a regular expression (or the whole
+    /// match if it has none), dropping values that do not match
+    Capture(String),
+    /// Remove leading and trailing whitespace
+    Trim,
+    /// Convert to lowercase
+    Lowercase,
+    /// Decode HTML character references such as `&amp;` and `&#8364;`
+    DecodeEntities,
+    /// Replace every match of a regular expression; `$1` refers to capture groups
+    Replace {
+        /// Regular expression to search for
+        pattern: String,
+        /// Replacement text
+        replacement: String,
+    },
+}
+
+impl Transform {
+    /// Regular expression used by the transform, if any
+    pub fn pattern(&self) -> Option<&str> {
+        match self {
+            Transform::Capture(pattern) | Transform::Replace { pattern, .. } => Some(pattern),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a number, ignoring currency symbols, units and thousands separators
+fn parse_number(value: &str) -> Option<f64> {
+    let digits: String = value
+        .trim()
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit() && !matches!(c, '-' | '.'))
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | ','))
+        .filter(|c| *c != ',')
+        .collect();
+    digits.parse().ok()
+}
+
+/// Automatic page metadata computed on every scrape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MetadataLevel {
+    /// Only the title
+    None,
+    /// Title plus head metadata: description, keywords, canonical URL and JSON-LD
+    Basic,
+    /// Basic metadata plus link, image and form counts and content metrics
+    #[default]
+    Full,
 }
 
 /// HTTP method types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[derive(Default)]
 pub enum HttpMethod {
     #[default]
@@ -134,6 +1099,21 @@ pub enum HttpMethod {
     Patch,
 }
 
+impl HttpMethod {
+    /// Method name as sent on the wire
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Patch => "PATCH",
+        }
+    }
+}
+
 /// Request statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequestStats {
@@ -149,6 +1129,204 @@ pub struct RequestStats {
     pub avg_response_time_ms: f64,
     /// Total time spent scraping
     pub total_time_ms: u64,
+    /// Per-minute activity, oldest first, capped at [`RequestStats::MAX_HISTORY_BUCKETS`]
+    #[serde(default)]
+    pub history: Vec<StatsBucket>,
+    /// Requests and downloaded bytes per host
+    #[serde(default)]
+    pub hosts: HashMap<String, HostStats>,
+    /// Rolling health score per host
+    #[serde(default)]
+    pub health: HashMap<String, HostHealth>,
+    /// Requests answered from the HTTP cache, fresh or revalidated with `304 Not Modified`
+    #[serde(default)]
+    pub cache_hits: u64,
+    /// Cacheable requests the HTTP cache could not answer
+    #[serde(default)]
+    pub cache_misses: u64,
+    /// Requests per second the rate limiter currently allows per host
+    #[serde(default)]
+    pub effective_rates: HashMap<String, f64>,
+    /// Request phases of the scraped pages
+    #[serde(default)]
+    pub phases: PhaseTotals,
+}
+
+/// Request phases summed over fetched pages, telling slow hosts from slow servers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTotals {
+    /// Pages whose phases were recorded
+    pub requests: u64,
+    /// Pages that needed a DNS lookup
+    pub dns_lookups: u64,
+    /// Total time spent queued in milliseconds
+    pub queued_ms: u64,
+    /// Total time spent resolving host names in milliseconds
+    pub dns_ms: u64,
+    /// Total time to first byte in milliseconds
+    pub ttfb_ms: u64,
+    /// Total time spent reading bodies in milliseconds
+    pub download_ms: u64,
+}
+
+impl PhaseTotals {
+    /// Add the phases of one page
+    pub fn add(&mut self, timings: &RequestTimings) {
+        self.requests += 1;
+        self.queued_ms += timings.queued_ms;
+        if let Some(dns_ms) = timings.dns_ms {
+            self.dns_lookups += 1;
+            self.dns_ms += dns_ms;
+        }
+        self.ttfb_ms += timings.ttfb_ms;
+        self.download_ms += timings.download_ms;
+    }
+
+    /// Average time spent queued per page
+    pub fn avg_queued_ms(&self) -> f64 {
+        average(self.queued_ms, self.requests)
+    }
+
+    /// Average time per DNS lookup
+    pub fn avg_dns_ms(&self) -> f64 {
+        average(self.dns_ms, self.dns_lookups)
+    }
+
+    /// Average time to first byte per page
+    pub fn avg_ttfb_ms(&self) -> f64 {
+        average(self.ttfb_ms, self.requests)
+    }
+
+    /// Average body download time per page
+    pub fn avg_download_ms(&self) -> f64 {
+        average(self.download_ms, self.requests)
+    }
+}
+
+/// Mean of a total over a count, zero when nothing was counted
+fn average(total: u64, count: u64) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+/// Requests and downloaded bytes for a single host
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostStats {
+    /// Requests made to the host
+    pub requests: u64,
+    /// Successful requests made to the host
+    pub successful_requests: u64,
+    /// Failed requests made to the host
+    pub failed_requests: u64,
+    /// Bytes downloaded from the host
+    pub bytes: u64,
+    /// Request phases of the pages scraped from the host
+    #[serde(default)]
+    pub phases: PhaseTotals,
+    /// Total response time of the host's requests in milliseconds
+    #[serde(default)]
+    pub total_time_ms: u64,
+    /// Response times of the latest requests, for percentiles
+    #[serde(skip)]
+    latencies_ms: VecDeque<u64>,
+}
+
+impl HostStats {
+    /// Response times kept per host for percentiles
+    pub const MAX_LATENCY_SAMPLES: usize = 1024;
+
+    /// Record the response time of a request to the host
+    pub fn record_latency(&mut self, duration: Duration) {
+        let elapsed_ms = duration.as_millis() as u64;
+        self.total_time_ms += elapsed_ms;
+        if self.latencies_ms.len() == Self::MAX_LATENCY_SAMPLES {
+            self.latencies_ms.pop_front();
+        }
+        self.latencies_ms.push_back(elapsed_ms);
+    }
+
+    /// Response time in milliseconds below which `percentile` (0.0-1.0) of the
+    /// latest requests completed, `None` before any request
+    pub fn latency_percentile(&self, percentile: f64) -> Option<u64> {
+        let mut sorted: Vec<u64> = self.latencies_ms.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = (percentile.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied()
+    }
+
+    /// Summary of the host's requests and latencies
+    pub fn summary(&self) -> DomainStats {
+        DomainStats {
+            requests: self.requests,
+            successful_requests: self.successful_requests,
+            failed_requests: self.failed_requests,
+            bytes: self.bytes,
+            avg_latency_ms: average(self.total_time_ms, self.requests),
+            p50_latency_ms: self.latency_percentile(0.5).unwrap_or(0),
+            p95_latency_ms: self.latency_percentile(0.95).unwrap_or(0),
+        }
+    }
+}
+
+/// Requests, failures, bytes and latency of one host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomainStats {
+    /// Requests made to the host
+    pub requests: u64,
+    /// Successful requests made to the host
+    pub successful_requests: u64,
+    /// Failed requests made to the host
+    pub failed_requests: u64,
+    /// Bytes downloaded from the host
+    pub bytes: u64,
+    /// Average response time in milliseconds
+    pub avg_latency_ms: f64,
+    /// Median response time of the latest requests in milliseconds
+    pub p50_latency_ms: u64,
+    /// 95th percentile response time of the latest requests in milliseconds
+    pub p95_latency_ms: u64,
+}
+
+impl DomainStats {
+    /// Share of requests that failed (0.0-1.0)
+    pub fn failure_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.failed_requests as f64 / self.requests as f64
+        }
+    }
+}
+
+/// Request activity within one minute
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsBucket {
+    /// Start of the minute
+    pub start: DateTime<Utc>,
+    /// Requests completed during the minute
+    pub requests: u64,
+    /// Successful requests completed during the minute
+    pub successful_requests: u64,
+    /// Failed requests completed during the minute
+    pub failed_requests: u64,
+    /// Bytes downloaded during the minute
+    pub bytes: u64,
+    /// Total response time of the minute's requests in milliseconds
+    pub total_time_ms: u64,
+}
+
+impl StatsBucket {
+    /// Average response time of the minute's requests
+    pub fn avg_response_time_ms(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.total_time_ms as f64 / self.requests as f64
+        }
+    }
 }
 
 impl Default for RequestStats {
@@ -167,7 +1345,135 @@ impl RequestStats {
             total_bytes: 0,
             avg_response_time_ms: 0.0,
             total_time_ms: 0,
+            history: Vec::new(),
+            hosts: HashMap::new(),
+            health: HashMap::new(),
+            cache_hits: 0,
+            cache_misses: 0,
+            effective_rates: HashMap::new(),
+            phases: PhaseTotals::default(),
+        }
+    }
+    
+    /// Number of per-minute buckets kept (one day)
+    pub const MAX_HISTORY_BUCKETS: usize = 24 * 60;
+    
+    /// Record whether the HTTP cache answered a request
+    pub fn record_cache(&mut self, hit: bool) {
+        if hit {
+            self.cache_hits += 1;
+        } else {
+            self.cache_misses += 1;
+        }
+    }
+    
+    /// Record a completed request in the totals and its minute bucket
+    pub fn record(&mut self, success: bool, duration: Duration, bytes: Option<u64>, at: DateTime<Utc>) {
+        let elapsed_ms = duration.as_millis() as u64;
+        let bytes = bytes.unwrap_or(0);
+
+        self.total_requests += 1;
+        if success {
+            self.successful_requests += 1;
+        } else {
+            self.failed_requests += 1;
+        }
+        self.total_bytes += bytes;
+        self.total_time_ms += elapsed_ms;
+        self.avg_response_time_ms = self.total_time_ms as f64 / self.total_requests as f64;
+
+        let start = at.duration_trunc(TimeDelta::minutes(1)).unwrap_or(at);
+        let bucket = match self.history.iter_mut().rev().find(|bucket| bucket.start == start) {
+            Some(bucket) => bucket,
+            None => {
+                let position = self.history.partition_point(|bucket| bucket.start < start);
+                self.history.insert(position, StatsBucket {
+                    start,
+                    requests: 0,
+                    successful_requests: 0,
+                    failed_requests: 0,
+                    bytes: 0,
+                    total_time_ms: 0,
+                });
+                &mut self.history[position]
+            }
+        };
+
+        bucket.requests += 1;
+        if success {
+            bucket.successful_requests += 1;
+        } else {
+            bucket.failed_requests += 1;
+        }
+        bucket.bytes += bytes;
+        bucket.total_time_ms += elapsed_ms;
+
+        if self.history.len() > Self::MAX_HISTORY_BUCKETS {
+            let excess = self.history.len() - Self::MAX_HISTORY_BUCKETS;
+            self.history.drain(..excess);
+        }
+    }
+    
+    /// Record a completed request against its host
+    pub fn record_host(&mut self, host: &str, success: bool, bytes: Option<u64>) {
+        let host_stats = self.hosts.entry(host.to_lowercase()).or_default();
+        host_stats.requests += 1;
+        if success {
+            host_stats.successful_requests += 1;
+        } else {
+            host_stats.failed_requests += 1;
         }
+        host_stats.bytes += bytes.unwrap_or(0);
+    }
+    
+    /// Requests, failures, bytes and latency percentiles per host
+    pub fn by_domain(&self) -> HashMap<String, DomainStats> {
+        self.hosts.iter().map(|(host, stats)| (host.clone(), stats.summary())).collect()
+    }
+    
+    /// Record body bytes downloaded from a host that were not known when the
+    /// request completed (e.g. chunked responses without a `Content-Length`)
+    pub fn record_bytes(&mut self, host: &str, bytes: u64) {
+        self.total_bytes += bytes;
+        self.hosts.entry(host.to_lowercase()).or_default().bytes += bytes;
+        if let Some(bucket) = self.history.last_mut() {
+            bucket.bytes += bytes;
+        }
+    }
+    
+    /// Record the request phases of a scraped page, overall and against its host
+    pub fn record_phases(&mut self, host: Option<&str>, timings: &RequestTimings) {
+        self.phases.add(timings);
+        if let Some(host) = host {
+            self.hosts.entry(host.to_lowercase()).or_default().phases.add(timings);
+        }
+    }
+    
+    /// Get the per-minute time series, oldest first
+    pub fn time_series(&self) -> &[StatsBucket] {
+        &self.history
+    }
+    
+    /// Export the per-minute time series as CSV
+    pub fn time_series_csv(&self) -> String {
+        let mut csv = String::from("minute,requests,successful_requests,failed_requests,bytes,avg_response_time_ms\n");
+        for bucket in &self.history {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{:.1}\n",
+                bucket.start.to_rfc3339(),
+                bucket.requests,
+                bucket.successful_requests,
+                bucket.failed_requests,
+                bucket.bytes,
+                bucket.avg_response_time_ms(),
+            ));
+        }
+        csv
+    }
+    
+    /// Export the per-minute time series as a JSON array
+    pub fn time_series_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.history)?)
     }
     
     /// Calculate success rate
@@ -189,6 +1495,9 @@ pub struct RateLimit {
     pub period: Duration,
     /// Delay between requests to stay within limits
     pub delay_between_requests: Duration,
+    /// Slow down and speed back up per host based on its responses
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveRate>,
 }
 
 impl Default for RateLimit {
@@ -197,6 +1506,227 @@ impl Default for RateLimit {
             requests_per_period: 10,
             period: Duration::from_secs(60),
             delay_between_requests: Duration::from_millis(1000),
+            adaptive: None,
+        }
+    }
+}
+
+impl RateLimit {
+    /// Adapt the rate to each host's responses, never exceeding this limit
+    pub fn with_adaptive(mut self, adaptive: AdaptiveRate) -> Self {
+        self.adaptive = Some(adaptive);
+        self
+    }
+}
+
+/// Additive-increase/multiplicative-decrease tuning of an adaptive [`RateLimit`]
+///
+/// The rate of a host is a fraction of the configured limit: a `429 Too Many
+/// Requests`, `503 Service Unavailable` or a latency spike multiplies it by
+/// `decrease`, and every other response adds `increase` back, up to the full limit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveRate {
+    /// Factor applied to the rate when a host pushes back (0.0-1.0)
+    pub decrease: f64,
+    /// Fraction of the configured rate regained per healthy response
+    pub increase: f64,
+    /// Lowest fraction of the configured rate a host is slowed down to
+    pub min_fraction: f64,
+    /// Latency, relative to the host's rolling average, counting as a spike
+    pub latency_spike: f64,
+}
+
+impl Default for AdaptiveRate {
+    fn default() -> Self {
+        Self {
+            decrease: 0.5,
+            increase: 0.05,
+            min_fraction: 0.05,
+            latency_spike: 3.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_jitter() {
+        let delay = Duration::from_millis(1000);
+        assert_eq!(Jitter::None.apply(delay), delay);
+        for _ in 0..20 {
+            assert!(Jitter::Full.apply(delay) <= delay);
+            let equal = Jitter::Equal.apply(delay);
+            assert!(equal >= delay / 2 && equal <= delay);
         }
+        assert_eq!(Jitter::Equal.apply(Duration::ZERO), Duration::ZERO);
+
+        let seeded = |seed| {
+            let random = RandomSource::seeded(seed);
+            (0..5).map(|_| Jitter::Full.apply_with(delay, &random)).collect::<Vec<_>>()
+        };
+        assert_eq!(seeded(3), seeded(3));
+    }
+
+    #[test]
+    fn test_response_status() {
+        let mut data = ScrapedData::new("https://example.com/missing".to_string());
+        data.status_code = 200;
+        data.title = Some("Page Not Found".to_string());
+        assert_eq!(data.status(), ResponseStatus::Success);
+        assert!(data.is_soft_404());
+
+        data.page_type = Some(PageType::Article);
+        assert!(!data.is_soft_404());
+
+        data.status_code = 404;
+        assert_eq!(data.status(), ResponseStatus::ClientError);
+        assert!(!data.is_soft_404());
+
+        let codes = [(301, ResponseStatus::Redirected), (304, ResponseStatus::NotModified),
+                     (429, ResponseStatus::Blocked), (503, ResponseStatus::ServerError), (101, ResponseStatus::Other)];
+        for (code, expected) in codes {
+            assert_eq!(ResponseStatus::from_response(code, &HashMap::new()), expected);
+        }
+
+        let headers = HashMap::from([("cf-mitigated".to_string(), "challenge".to_string())]);
+        assert_eq!(ResponseStatus::from_response(200, &headers), ResponseStatus::Blocked);
+    }
+
+    #[test]
+    fn test_stats_time_series() {
+        let mut stats = RequestStats::new();
+        let minute = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        stats.record(true, Duration::from_millis(100), Some(500), minute + TimeDelta::seconds(5));
+        stats.record(false, Duration::from_millis(300), None, minute + TimeDelta::seconds(50));
+        stats.record(true, Duration::from_millis(200), Some(1000), minute + TimeDelta::seconds(70));
+
+        assert_eq!(stats.total_requests, 3);
+        assert_eq!(stats.total_bytes, 1500);
+        assert_eq!(stats.avg_response_time_ms, 200.0);
+
+        let series = stats.time_series();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].start, minute);
+        assert_eq!(series[0].requests, 2);
+        assert_eq!(series[0].failed_requests, 1);
+        assert_eq!(series[0].avg_response_time_ms(), 200.0);
+        assert_eq!(series[1].bytes, 1000);
+
+        let csv = stats.time_series_csv();
+        assert_eq!(csv.lines().count(), 3);
+        assert_eq!(csv.lines().nth(1), Some("2024-05-01T12:00:00+00:00,2,1,1,500,200.0"));
+
+        let json: serde_json::Value = serde_json::from_str(&stats.time_series_json().unwrap()).unwrap();
+        assert_eq!(json[1]["requests"], 1);
+    }
+
+    #[test]
+    fn test_phase_totals() {
+        let mut stats = RequestStats::new();
+        stats.record_phases(Some("Example.com"), &RequestTimings { queued_ms: 10, dns_ms: Some(4), ttfb_ms: 100, download_ms: 20, attempts: 1 });
+        stats.record_phases(Some("example.com"), &RequestTimings { queued_ms: 0, dns_ms: None, ttfb_ms: 300, download_ms: 40, attempts: 2 });
+        stats.record_phases(None, &RequestTimings { ttfb_ms: 200, attempts: 1, ..RequestTimings::default() });
+
+        assert_eq!(stats.phases.requests, 3);
+        assert_eq!(stats.phases.avg_dns_ms(), 4.0);
+        assert_eq!(stats.phases.avg_ttfb_ms(), 200.0);
+        assert_eq!(stats.phases.avg_download_ms(), 20.0);
+        let host = &stats.hosts["example.com"].phases;
+        assert_eq!((host.requests, host.avg_ttfb_ms(), host.avg_queued_ms()), (2, 200.0, 5.0));
+        assert_eq!(PhaseTotals::default().avg_dns_ms(), 0.0);
+    }
+
+    #[test]
+    fn test_domain_stats() {
+        let mut stats = RequestStats::new();
+        let host = stats.hosts.entry("slow.example".to_string()).or_default();
+        for ms in 1..=100 {
+            host.requests += 1;
+            host.record_latency(Duration::from_millis(ms));
+        }
+        host.successful_requests = 90;
+        host.failed_requests = 10;
+        host.bytes = 4096;
+        assert_eq!(HostStats::default().latency_percentile(0.5), None);
+
+        let domains = stats.by_domain();
+        let slow = &domains["slow.example"];
+        assert_eq!((slow.requests, slow.bytes), (100, 4096));
+        assert_eq!((slow.p50_latency_ms, slow.p95_latency_ms), (50, 95));
+        assert_eq!(slow.avg_latency_ms, 50.5);
+        assert_eq!(slow.failure_rate(), 0.1);
+
+        let host = stats.hosts.get_mut("slow.example").unwrap();
+        for _ in 0..HostStats::MAX_LATENCY_SAMPLES {
+            host.record_latency(Duration::from_millis(1));
+        }
+        assert_eq!(host.latency_percentile(1.0), Some(1));
+    }
+
+    #[test]
+    fn test_batch_schedule() {
+        let urls: Vec<String> = (0..20).map(|i| format!("https://example.com/{}", i)).collect();
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+
+        let in_order = BatchOptions::new().schedule(&urls);
+        assert!(in_order.iter().enumerate().all(|(i, (index, url, delay))| *index == i && *url == urls[i] && delay.is_zero()));
+
+        let options = BatchOptions::new()
+            .with_delay(Duration::from_millis(100))
+            .with_jitter(Duration::from_millis(50))
+            .shuffled()
+            .with_seed(7);
+        let schedule = options.schedule(&urls);
+        assert_eq!(schedule, options.schedule(&urls));
+        assert_ne!(schedule.iter().map(|(_, url, _)| *url).collect::<Vec<_>>(), urls);
+        assert!(schedule.iter().all(|(index, url, _)| urls[*index] == *url));
+        assert!(schedule.iter().all(|(_, _, delay)| (100..=150).contains(&delay.as_millis())));
+
+        let mut sorted: Vec<&str> = schedule.iter().map(|(_, url, _)| *url).collect();
+        sorted.sort();
+        let mut expected = urls.clone();
+        expected.sort();
+        assert_eq!(sorted, expected);
+
+        // Without a seed of its own, a seeded random source makes the schedule reproducible
+        let unseeded = BatchOptions::new().with_jitter(Duration::from_millis(50)).shuffled();
+        let from = |seed| unseeded.schedule_with(&urls, &RandomSource::seeded(seed));
+        assert_eq!(from(11), from(11));
+        assert_eq!(options.schedule_with(&urls, &RandomSource::seeded(11)), schedule);
+    }
+
+    #[test]
+    fn test_record_id() {
+        let mut data = ScrapedData::new("https://Example.com/p?b=2&a=1#reviews".to_string());
+        data.run_id = Some("nightly-42".to_string());
+        let mut recrawled = ScrapedData::new("https://example.com/p?a=1&b=2".to_string());
+        recrawled.run_id = Some("nightly-42".to_string());
+        assert_eq!(data.id(), recrawled.id());
+        assert_eq!(data.id().len(), 16);
+
+        recrawled.run_id = Some("nightly-43".to_string());
+        assert_ne!(data.id(), recrawled.id());
+
+        let mut unscoped = ScrapedData::new("https://example.com/p".to_string());
+        let id = unscoped.id();
+        assert_eq!(unscoped.id(), id);
+        unscoped.timestamp += TimeDelta::seconds(1);
+        assert_ne!(unscoped.id(), id);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let values = || vec!["$1,200.50".to_string(), "n/a".to_string(), "-0.5 kg".to_string(), "$1,200.50".to_string()];
+        assert_eq!(Aggregate::Join(" | ".into()).apply(values()), vec!["$1,200.50 | n/a | -0.5 kg | $1,200.50"]);
+        assert_eq!(Aggregate::First.apply(values()), vec!["$1,200.50"]);
+        assert_eq!(Aggregate::Last.apply(values()), vec!["$1,200.50"]);
+        assert_eq!(Aggregate::Unique.apply(values()), vec!["$1,200.50", "n/a", "-0.5 kg"]);
+        assert_eq!(Aggregate::Sum.apply(values()), vec!["2400.5"]);
+        assert!(Aggregate::Sum.apply(vec!["none".to_string()]).is_empty());
+        assert!(Aggregate::Join(",".into()).apply(Vec::new()).is_empty());
     }
 }
@@ -0,0 +1,262 @@
+//! Reading URL lists from files and standard input
+//!
+//! [`UrlSource`] reads plain text (one URL per line, `#` comments allowed),
+//! a CSV column or a JSONL field, keeping valid `http(s)` URLs in input order
+//! without duplicates. Rejected lines are kept for reporting.
+
+use crate::error::{FerrisFetcherError, Result};
+use futures::stream::{self, Stream};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+use url::Url;
+
+/// CSV column holding the URLs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// Column with this header (case-insensitive); the first row is a header
+    Name(String),
+    /// Zero-based column index; every row is data
+    Index(usize),
+}
+
+/// Layout of a URL list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlFormat {
+    /// One URL per line
+    Lines,
+    /// One column of a CSV file
+    Csv(CsvColumn),
+    /// A field of every JSON object, with `.` separating nested keys
+    Jsonl(String),
+}
+
+impl UrlFormat {
+    /// Guess the format from a file extension: `.csv` reads the `url` column,
+    /// `.jsonl`/`.ndjson` the `url` field and anything else lines
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+            Some("csv") => UrlFormat::Csv(CsvColumn::Name("url".to_string())),
+            Some("jsonl") | Some("ndjson") => UrlFormat::Jsonl("url".to_string()),
+            _ => UrlFormat::Lines,
+        }
+    }
+}
+
+/// An input entry that was not accepted as a URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedUrl {
+    /// One-based line (or CSV record) number
+    pub line: usize,
+    /// The offending value
+    pub value: String,
+    /// Why it was rejected
+    pub reason: String,
+}
+
+/// Validated, de-duplicated list of URLs
+#[derive(Debug, Clone, Default)]
+pub struct UrlSource {
+    urls: Vec<String>,
+    rejected: Vec<RejectedUrl>,
+    duplicates: usize,
+}
+
+impl UrlSource {
+    /// Read a file, choosing the format from its extension (see [`UrlFormat::from_path`])
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        Self::from_file_with_format(path, UrlFormat::from_path(path))
+    }
+
+    /// Read a file in the given format
+    pub fn from_file_with_format(path: impl AsRef<Path>, format: UrlFormat) -> Result<Self> {
+        Self::from_reader(std::fs::File::open(path)?, format)
+    }
+
+    /// Read one URL per line from standard input
+    pub fn from_stdin() -> Result<Self> {
+        Self::from_stdin_with_format(UrlFormat::Lines)
+    }
+
+    /// Read standard input in the given format
+    pub fn from_stdin_with_format(format: UrlFormat) -> Result<Self> {
+        Self::from_reader(std::io::stdin().lock(), format)
+    }
+
+    /// Read from any reader
+    pub fn from_reader(mut reader: impl Read, format: UrlFormat) -> Result<Self> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+
+        let mut source = Self::default();
+        let mut seen = HashSet::new();
+        for (line, value) in Self::values(&input, &format)? {
+            match Self::validate(&value) {
+                Ok(url) if seen.insert(url.clone()) => source.urls.push(url),
+                Ok(_) => source.duplicates += 1,
+                Err(reason) => source.rejected.push(RejectedUrl { line, value, reason }),
+            }
+        }
+        Ok(source)
+    }
+
+    /// Candidate URLs with their line numbers
+    fn values(input: &str, format: &UrlFormat) -> Result<Vec<(usize, String)>> {
+        let lines = input.lines().enumerate().map(|(index, line)| (index + 1, line.trim()));
+
+        match format {
+            UrlFormat::Lines => Ok(lines
+                .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+                .map(|(number, line)| (number, line.to_string()))
+                .collect()),
+            UrlFormat::Jsonl(field) => lines
+                .filter(|(_, line)| !line.is_empty())
+                .map(|(number, line)| {
+                    let record: Value = serde_json::from_str(line)?;
+                    let value = field
+                        .split('.')
+                        .try_fold(&record, |value, key| value.get(key))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default();
+                    Ok((number, value.to_string()))
+                })
+                .collect(),
+            UrlFormat::Csv(column) => {
+                let mut records = parse_csv(input).into_iter().enumerate().map(|(index, row)| (index + 1, row));
+                let index = match column {
+                    CsvColumn::Index(index) => *index,
+                    CsvColumn::Name(name) => {
+                        let header = records.next().map(|(_, row)| row).unwrap_or_default();
+                        header
+                            .iter()
+                            .position(|column| column.trim().eq_ignore_ascii_case(name))
+                            .ok_or_else(|| FerrisFetcherError::ConfigError(format!("CSV has no '{}' column", name)))?
+                    }
+                };
+                Ok(records
+                    .filter(|(_, row)| row.iter().any(|field| !field.trim().is_empty()))
+                    .map(|(number, row)| (number, row.get(index).map(|field| field.trim().to_string()).unwrap_or_default()))
+                    .collect())
+            }
+        }
+    }
+
+    /// Normalise a URL, accepting only `http` and `https`
+    fn validate(value: &str) -> std::result::Result<String, String> {
+        if value.is_empty() {
+            return Err("empty value".to_string());
+        }
+        let url = Url::parse(value).map_err(|e| e.to_string())?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("unsupported scheme '{}'", url.scheme()));
+        }
+        Ok(url.to_string())
+    }
+
+    /// Accepted URLs in input order
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Entries that were not valid URLs
+    pub fn rejected(&self) -> &[RejectedUrl] {
+        &self.rejected
+    }
+
+    /// Number of duplicate URLs dropped
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// Number of accepted URLs
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    /// Whether no URL was accepted
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Take the accepted URLs
+    pub fn into_urls(self) -> Vec<String> {
+        self.urls
+    }
+
+    /// Stream the accepted URLs, e.g. into [`crate::FerrisFetcher::scrape_stream`]
+    pub fn into_stream(self) -> impl Stream<Item = String> {
+        stream::iter(self.urls)
+    }
+}
+
+/// Split CSV text into records, honouring quoted fields
+fn parse_csv(input: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_url_source_formats() {
+        let text = "# seeds\nhttps://example.com/a\n\nhttps://example.com/a\nftp://example.com/file\nnot a url\nhttps://EXAMPLE.com\n";
+        let source = UrlSource::from_reader(Cursor::new(text), UrlFormat::Lines).unwrap();
+        assert_eq!(source.urls(), ["https://example.com/a", "https://example.com/"]);
+        assert_eq!(source.duplicates(), 1);
+        assert_eq!(source.rejected().len(), 2);
+        assert_eq!(source.rejected()[0].line, 5);
+
+        let csv = "name,URL\n\"Shop, Inc.\",https://shop.example/\n\"Say \"\"hi\"\"\",\n";
+        let source = UrlSource::from_reader(Cursor::new(csv), UrlFormat::Csv(CsvColumn::Name("url".to_string()))).unwrap();
+        assert_eq!(source.urls(), ["https://shop.example/"]);
+        assert_eq!(source.rejected()[0].reason, "empty value");
+
+        let jsonl = "{\"page\": {\"url\": \"https://a.example/x\"}}\n{\"page\": {}}\n";
+        let source = UrlSource::from_reader(Cursor::new(jsonl), UrlFormat::Jsonl("page.url".to_string())).unwrap();
+        assert_eq!(source.into_urls(), vec!["https://a.example/x"]);
+        assert!(UrlSource::from_reader(Cursor::new("{oops"), UrlFormat::Jsonl("url".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_url_source_from_file() {
+        let path = std::env::temp_dir().join(format!("ferrisfetcher-urls-{}.csv", std::process::id()));
+        std::fs::write(&path, "id,url\n1,https://example.com/1\n2,https://example.com/2\n").unwrap();
+
+        let source = UrlSource::from_file(&path).unwrap();
+        assert_eq!(source.len(), 2);
+        assert!(UrlSource::from_file_with_format(&path, UrlFormat::Csv(CsvColumn::Name("link".to_string()))).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+//! Expansion of URL templates into enumerable URL lists
+//!
+//! Placeholders in braces are expanded and combined as a cartesian product,
+//! leftmost placeholder varying slowest:
+//!
+//! - `{1..200}` inclusive numeric range, `{1..200..10}` with a step and
+//!   `{001..200}` zero-padded to the width of the start
+//! - `{a,b,c}` inline alternatives
+//! - `{name}` values of a named parameter, percent-encoded
+//!
+//! `{{` and `}}` stand for literal braces.
+
+use crate::error::{FerrisFetcherError, Result};
+use url::Url;
+
+/// One segment of a parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Range { start: i64, end: i64, step: i64, width: usize },
+    Choices(Vec<String>),
+    Param(String),
+}
+
+/// Parsed URL template
+///
+/// ```
+/// use ferrisfetcher::UrlTemplate;
+///
+/// let urls = UrlTemplate::expand("https://shop.example/{category}?page={1..2}", &[("category", &["mugs", "cups"])]).unwrap();
+/// assert_eq!(urls[1], "https://shop.example/mugs?page=2");
+/// assert_eq!(urls.len(), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlTemplate {
+    parts: Vec<Part>,
+}
+
+impl UrlTemplate {
+    /// Parse a template
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while let Some(index) = rest.find(['{', '}']) {
+            literal.push_str(&rest[..index]);
+            let brace = &rest[index..];
+            if brace.starts_with("{{") || brace.starts_with("}}") {
+                literal.push_str(&brace[..1]);
+                rest = &brace[2..];
+                continue;
+            }
+            if brace.starts_with('}') {
+                return Err(template_error(template, "unmatched '}'"));
+            }
+
+            let end = brace.find('}').ok_or_else(|| template_error(template, "unclosed '{'"))?;
+            if !literal.is_empty() {
+                parts.push(Part::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(Self::parse_placeholder(&brace[1..end]).map_err(|message| template_error(template, &message))?);
+            rest = &brace[end + 1..];
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    fn parse_placeholder(inner: &str) -> std::result::Result<Part, String> {
+        let inner = inner.trim();
+        if inner.is_empty() {
+            return Err("empty placeholder".to_string());
+        }
+
+        if inner.contains("..") {
+            let bounds: Vec<&str> = inner.split("..").map(str::trim).collect();
+            let number = |text: &str| text.parse::<i64>().map_err(|_| format!("invalid range '{{{}}}'", inner));
+            let (start, end, step) = match bounds.as_slice() {
+                [start, end] => (number(start)?, number(end)?, 1),
+                [start, end, step] => (number(start)?, number(end)?, number(step)?),
+                _ => return Err(format!("invalid range '{{{}}}'", inner)),
+            };
+            if step <= 0 {
+                return Err(format!("range step must be positive in '{{{}}}'", inner));
+            }
+            let width = if bounds[0].len() > 1 && bounds[0].starts_with('0') { bounds[0].len() } else { 0 };
+            return Ok(Part::Range { start, end, step, width });
+        }
+
+        if inner.contains(',') {
+            return Ok(Part::Choices(inner.split(',').map(|choice| choice.trim().to_string()).collect()));
+        }
+
+        Ok(Part::Param(inner.to_string()))
+    }
+
+    /// Parse a template and expand it with the given parameters
+    pub fn expand(template: &str, params: &[(&str, &[&str])]) -> Result<Vec<String>> {
+        Self::parse(template)?.urls(params)
+    }
+
+    /// Number of URLs the template expands to
+    pub fn count(&self, params: &[(&str, &[&str])]) -> Result<usize> {
+        Ok(self.alternatives(params)?.iter().map(Vec::len).product())
+    }
+
+    /// Expand into every URL, validating each one
+    pub fn urls(&self, params: &[(&str, &[&str])]) -> Result<Vec<String>> {
+        let alternatives = self.alternatives(params)?;
+        if alternatives.iter().any(Vec::is_empty) {
+            return Ok(Vec::new());
+        }
+
+        let total: usize = alternatives.iter().map(Vec::len).product();
+        let mut urls = Vec::with_capacity(total);
+        let mut indices = vec![0; alternatives.len()];
+        loop {
+            let url: String = alternatives.iter().zip(&indices).map(|(values, &i)| values[i].as_str()).collect();
+            Url::parse(&url)?;
+            urls.push(url);
+
+            // Advance the rightmost placeholder first
+            let mut position = alternatives.len();
+            loop {
+                if position == 0 {
+                    return Ok(urls);
+                }
+                position -= 1;
+                indices[position] += 1;
+                if indices[position] < alternatives[position].len() {
+                    break;
+                }
+                indices[position] = 0;
+            }
+        }
+    }
+
+    /// Possible values of every part
+    fn alternatives(&self, params: &[(&str, &[&str])]) -> Result<Vec<Vec<String>>> {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => Ok(vec![text.clone()]),
+                Part::Choices(choices) => Ok(choices.clone()),
+                Part::Range { start, end, step, width } => {
+                    let format = |n: i64| format!("{:0width$}", n, width = *width);
+                    Ok(if start <= end {
+                        (*start..=*end).step_by(*step as usize).map(format).collect()
+                    } else {
+                        (*end..=*start).rev().step_by(*step as usize).map(format).collect()
+                    })
+                }
+                Part::Param(name) => params
+                    .iter()
+                    .find(|(param, _)| param == name)
+                    .map(|(_, values)| values.iter().map(|value| encode_component(value)).collect())
+                    .ok_or_else(|| FerrisFetcherError::ConfigError(format!("Missing URL template parameter '{}'", name))),
+            })
+            .collect()
+    }
+}
+
+fn template_error(template: &str, message: &str) -> FerrisFetcherError {
+    FerrisFetcherError::ConfigError(format!("Invalid URL template '{}': {}", template, message))
+}
+
+/// Percent-encode a parameter value
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_template_expand() {
+        let urls = UrlTemplate::expand("https://site.com/page/{1..200}", &[]).unwrap();
+        assert_eq!(urls.len(), 200);
+        assert_eq!(urls[199], "https://site.com/page/200");
+
+        let template = UrlTemplate::parse("https://site.com/{lang,de}/{q}/{001..010..3}?x={{y}}").unwrap();
+        assert_eq!(template.count(&[("q", &["red mugs", "cups"])]).unwrap(), 16);
+        let urls = template.urls(&[("q", &["red mugs", "cups"])]).unwrap();
+        assert_eq!(urls[0], "https://site.com/lang/red%20mugs/001?x={y}");
+        assert_eq!(urls[3], "https://site.com/lang/red%20mugs/010?x={y}");
+        assert_eq!(urls[15], "https://site.com/de/cups/010?x={y}");
+
+        assert_eq!(UrlTemplate::expand("https://s.com/{3..1}", &[]).unwrap().last().unwrap(), "https://s.com/1");
+        assert!(UrlTemplate::expand("https://s.com/{missing}", &[]).is_err());
+        assert!(UrlTemplate::parse("https://s.com/{1..x}").is_err());
+        assert!(UrlTemplate::parse("https://s.com/{1..5").is_err());
+        assert!(UrlTemplate::parse("https://s.com/{1..5..0}").is_err());
+    }
+}
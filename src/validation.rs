@@ -0,0 +1,116 @@
+//! Collected validation problems for configurations and extraction rules
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a validation issue refers to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueSource {
+    /// A configuration option
+    Config,
+    /// The extraction rule with the given name
+    Rule(String),
+}
+
+/// A single validation problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// What the issue refers to
+    pub source: IssueSource,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            IssueSource::Config => write!(f, "config: {}", self.message),
+            IssueSource::Rule(name) => write!(f, "rule '{}': {}", name, self.message),
+        }
+    }
+}
+
+/// Every problem found while validating a configuration and its rules
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Problems in the order they were found
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a configuration problem
+    pub fn config_issue(&mut self, message: impl Into<String>) {
+        self.issues.push(ValidationIssue {
+            source: IssueSource::Config,
+            message: message.into(),
+        });
+    }
+
+    /// Record a problem with an extraction rule
+    pub fn rule_issue(&mut self, rule: &str, message: impl Into<String>) {
+        self.issues.push(ValidationIssue {
+            source: IssueSource::Rule(rule.to_string()),
+            message: message.into(),
+        });
+    }
+
+    /// Append the issues of another report
+    pub fn merge(&mut self, other: ValidationReport) {
+        self.issues.extend(other.issues);
+    }
+
+    /// Check whether no problems were found
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of problems found
+    pub fn len(&self) -> usize {
+        self.issues.len()
+    }
+
+    /// Check whether the report is empty
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Issues concerning a single rule
+    pub fn rule_issues(&self, rule: &str) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| matches!(&issue.source, IssueSource::Rule(name) if name == rule))
+            .collect()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let issues: Vec<String> = self.issues.iter().map(ToString::to_string).collect();
+        write!(f, "{}", issues.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_report() {
+        let mut report = ValidationReport::new();
+        assert!(report.is_ok());
+
+        report.config_issue("Timeout cannot be zero");
+        let mut rules = ValidationReport::new();
+        rules.rule_issue("price", "invalid selector");
+        report.merge(rules);
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report.rule_issues("price").len(), 1);
+        assert_eq!(report.to_string(), "config: Timeout cannot be zero; rule 'price': invalid selector");
+    }
+}
@@ -0,0 +1,269 @@
+//! Isolated on-disk working directories for scraping jobs
+//!
+//! Each [`JobWorkspace`] owns `<base>/<job id>/` with separate `cache`,
+//! `cookies`, `checkpoints` and `downloads` subdirectories. A lock file
+//! prevents two running jobs from sharing a directory, and the
+//! [`CleanupPolicy`] decides what happens to the directory when the
+//! workspace is dropped.
+
+use crate::error::{FerrisFetcherError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Name of the lock file marking a workspace as in use
+const LOCK_FILE: &str = ".lock";
+
+/// Distinguishes temporary workspaces created within one process
+static TEMPORARY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Kind of state kept in a workspace subdirectory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WorkDir {
+    /// Cached responses
+    Cache,
+    /// Persisted cookie jars
+    Cookies,
+    /// Crawl checkpoints for resuming
+    Checkpoints,
+    /// Downloaded files
+    Downloads,
+}
+
+impl WorkDir {
+    /// All subdirectory kinds
+    pub const ALL: [WorkDir; 4] = [WorkDir::Cache, WorkDir::Cookies, WorkDir::Checkpoints, WorkDir::Downloads];
+
+    /// Name of the subdirectory
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorkDir::Cache => "cache",
+            WorkDir::Cookies => "cookies",
+            WorkDir::Checkpoints => "checkpoints",
+            WorkDir::Downloads => "downloads",
+        }
+    }
+}
+
+/// What to do with a workspace when it is dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CleanupPolicy {
+    /// Always delete the directory
+    Always,
+    /// Delete the directory only if the job was marked successful
+    OnSuccess,
+    /// Keep the directory
+    Never,
+}
+
+/// Working directory owned by a single job
+#[derive(Debug)]
+pub struct JobWorkspace {
+    job_id: String,
+    root: PathBuf,
+    policy: CleanupPolicy,
+    succeeded: bool,
+    removed: bool,
+}
+
+impl JobWorkspace {
+    /// Create or reopen the workspace of `job_id` under `base`
+    ///
+    /// Fails if another live workspace holds the directory.
+    pub fn create(base: impl AsRef<Path>, job_id: &str) -> Result<Self> {
+        if job_id.is_empty() || job_id.contains(['/', '\\']) || job_id == "." || job_id == ".." {
+            return Err(FerrisFetcherError::ConfigError(format!("Invalid job id '{}'", job_id)));
+        }
+
+        let root = base.as_ref().join(job_id);
+        for dir in WorkDir::ALL {
+            fs::create_dir_all(root.join(dir.name()))?;
+        }
+
+        let mut lock = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(root.join(LOCK_FILE))
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::AlreadyExists => FerrisFetcherError::ConfigError(
+                    format!("Workspace {} is already in use", root.display())
+                ),
+                _ => e.into(),
+            })?;
+        writeln!(lock, "{}", std::process::id())?;
+
+        debug!("Opened job workspace {}", root.display());
+        Ok(Self {
+            job_id: job_id.to_string(),
+            root,
+            policy: CleanupPolicy::OnSuccess,
+            succeeded: false,
+            removed: false,
+        })
+    }
+
+    /// Create a fresh workspace in the system temp directory, deleted when dropped
+    pub fn temporary(job_id: &str) -> Result<Self> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let unique = format!(
+            "{}-{}-{}-{}",
+            job_id,
+            std::process::id(),
+            nanos,
+            TEMPORARY_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        Ok(Self::create(std::env::temp_dir().join("ferrisfetcher"), &unique)?
+            .with_cleanup(CleanupPolicy::Always))
+    }
+
+    /// Set the cleanup policy (default: [`CleanupPolicy::OnSuccess`])
+    pub fn with_cleanup(mut self, policy: CleanupPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Get the job id
+    pub fn job_id(&self) -> &str {
+        &self.job_id
+    }
+
+    /// Get the workspace root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get a subdirectory of the workspace
+    pub fn dir(&self, dir: WorkDir) -> PathBuf {
+        self.root.join(dir.name())
+    }
+
+    /// Get the response cache directory
+    pub fn cache_dir(&self) -> PathBuf {
+        self.dir(WorkDir::Cache)
+    }
+
+    /// Get the cookie jar directory
+    pub fn cookies_dir(&self) -> PathBuf {
+        self.dir(WorkDir::Cookies)
+    }
+
+    /// Get the checkpoint directory
+    pub fn checkpoints_dir(&self) -> PathBuf {
+        self.dir(WorkDir::Checkpoints)
+    }
+
+    /// Get the downloads directory
+    pub fn downloads_dir(&self) -> PathBuf {
+        self.dir(WorkDir::Downloads)
+    }
+
+    /// Get the path of a file inside a subdirectory
+    pub fn file(&self, dir: WorkDir, name: &str) -> PathBuf {
+        self.dir(dir).join(name)
+    }
+
+    /// Mark the job as successful, allowing [`CleanupPolicy::OnSuccess`] cleanup
+    pub fn mark_succeeded(&mut self) {
+        self.succeeded = true;
+    }
+
+    /// Delete the workspace now, regardless of the cleanup policy
+    pub fn remove(mut self) -> Result<()> {
+        self.removed = true;
+        fs::remove_dir_all(&self.root)?;
+        Ok(())
+    }
+
+    /// Delete unlocked job directories under `base` not modified within `max_age`
+    ///
+    /// Cleans up after jobs that were killed or kept their workspace.
+    /// Returns the number of directories removed.
+    pub fn prune(base: impl AsRef<Path>, max_age: Duration) -> Result<usize> {
+        let entries = match fs::read_dir(base.as_ref()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if !path.is_dir() || path.join(LOCK_FILE).exists() {
+                continue;
+            }
+
+            let age = fs::metadata(&path)?
+                .modified()?
+                .elapsed()
+                .unwrap_or_default();
+            if age > max_age {
+                fs::remove_dir_all(&path)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+impl Drop for JobWorkspace {
+    fn drop(&mut self) {
+        if self.removed {
+            return;
+        }
+
+        let delete = match self.policy {
+            CleanupPolicy::Always => true,
+            CleanupPolicy::OnSuccess => self.succeeded,
+            CleanupPolicy::Never => false,
+        };
+        let result = if delete {
+            fs::remove_dir_all(&self.root)
+        } else {
+            fs::remove_file(self.root.join(LOCK_FILE))
+        };
+        if let Err(e) = result {
+            warn!("Failed to clean up job workspace {}: {}", self.root.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_isolation_and_cleanup() {
+        let base = std::env::temp_dir().join(format!("ferrisfetcher-workspace-test-{}", std::process::id()));
+
+        let mut workspace = JobWorkspace::create(&base, "job-a").unwrap();
+        assert!(workspace.checkpoints_dir().is_dir());
+        assert_eq!(workspace.file(WorkDir::Downloads, "a.pdf"), base.join("job-a/downloads/a.pdf"));
+        assert!(JobWorkspace::create(&base, "job-a").is_err());
+        assert!(JobWorkspace::create(&base, "../escape").is_err());
+
+        let other = JobWorkspace::create(&base, "job-b").unwrap().with_cleanup(CleanupPolicy::Never);
+        drop(other);
+        assert!(base.join("job-b/cache").is_dir());
+        assert!(!base.join("job-b").join(LOCK_FILE).exists());
+
+        // Kept workspaces are unlocked and can be reopened or pruned
+        assert_eq!(JobWorkspace::prune(&base, Duration::ZERO).unwrap(), 1);
+        assert!(!base.join("job-b").exists());
+
+        workspace.mark_succeeded();
+        drop(workspace);
+        assert!(!base.join("job-a").exists());
+
+        let temporary = JobWorkspace::temporary("scratch").unwrap();
+        let root = temporary.root().to_path_buf();
+        drop(temporary);
+        assert!(!root.exists());
+
+        fs::remove_dir_all(&base).ok();
+    }
+}
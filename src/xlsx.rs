@@ -0,0 +1,440 @@
+//! Excel workbook (XLSX) export
+//!
+//! Records are buffered and written as one workbook on
+//! [`Exporter::finish`], with one worksheet per host, per rule group or a
+//! single sheet. Each row holds the URL, title, status and timestamp of a
+//! record followed by one column per extracted field.
+
+use crate::error::Result;
+use crate::export::{escape_xml, Exporter, FIXED_COLUMNS};
+use crate::extractor::DataExtractor;
+use crate::types::ScrapedData;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Maximum length of a worksheet name
+const MAX_SHEET_NAME_LEN: usize = 31;
+
+/// Sheet holding fields outside every rule group
+const UNGROUPED_SHEET: &str = "Other";
+
+/// How records are distributed over worksheets
+#[derive(Debug, Clone)]
+pub enum SheetLayout {
+    /// All records on one sheet
+    Single,
+    /// One sheet per host
+    ByHost,
+    /// One sheet per rule group, holding that group's fields; the map gives the groups of each field
+    ByGroup(HashMap<String, Vec<String>>),
+}
+
+/// A buffered worksheet row
+#[derive(Debug, Clone)]
+struct Row {
+    /// Values of the fixed columns
+    fixed: Vec<String>,
+    /// Extracted field values by field name
+    values: BTreeMap<String, String>,
+}
+
+/// Exporter writing an XLSX workbook
+#[derive(Debug)]
+pub struct XlsxExporter {
+    path: PathBuf,
+    layout: SheetLayout,
+    /// Rows per sheet
+    sheets: BTreeMap<String, Vec<Row>>,
+}
+
+impl XlsxExporter {
+    /// Export to a workbook at `path` with all records on one sheet
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            layout: SheetLayout::Single,
+            sheets: BTreeMap::new(),
+        }
+    }
+
+    /// Put the records of each host on their own sheet
+    pub fn by_host(mut self) -> Self {
+        self.layout = SheetLayout::ByHost;
+        self
+    }
+
+    /// Put the fields of each rule group of `extractor` on their own sheet
+    pub fn by_group(mut self, extractor: &DataExtractor) -> Self {
+        let groups = extractor
+            .rules()
+            .iter()
+            .map(|(name, rule)| (name.clone(), rule.groups.clone()))
+            .collect();
+        self.layout = SheetLayout::ByGroup(groups);
+        self
+    }
+
+    /// Set the sheet layout
+    pub fn layout(mut self, layout: SheetLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sheet names and the fields each receives from a record
+    fn sheets_for(&self, data: &ScrapedData) -> Vec<(String, Vec<String>)> {
+        let fields: Vec<String> = data.extracted_data.keys().cloned().collect();
+        match &self.layout {
+            SheetLayout::Single => vec![("Data".to_string(), fields)],
+            SheetLayout::ByHost => {
+                let host = url::Url::parse(&data.url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+                    .unwrap_or_else(|| "unknown".to_string());
+                vec![(host, fields)]
+            }
+            SheetLayout::ByGroup(groups) => {
+                let mut sheets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+                for field in fields {
+                    match groups.get(&field).filter(|groups| !groups.is_empty()) {
+                        Some(field_groups) => {
+                            for group in field_groups {
+                                sheets.entry(group.clone()).or_default().push(field.clone());
+                            }
+                        }
+                        None => sheets.entry(UNGROUPED_SHEET.to_string()).or_default().push(field),
+                    }
+                }
+                sheets.into_iter().collect()
+            }
+        }
+    }
+
+    /// Render the buffered rows as workbook parts
+    fn workbook_parts(&self) -> Vec<(String, String)> {
+        let mut names = Vec::new();
+        for sheet in self.sheets.keys() {
+            names.push(unique_sheet_name(sheet, &names));
+        }
+        if names.is_empty() {
+            names.push("Data".to_string());
+        }
+
+        let mut parts = vec![
+            ("[Content_Types].xml".to_string(), content_types(names.len())),
+            ("_rels/.rels".to_string(), ROOT_RELS.to_string()),
+            ("xl/workbook.xml".to_string(), workbook(&names)),
+            ("xl/_rels/workbook.xml.rels".to_string(), workbook_rels(names.len())),
+        ];
+
+        let empty = Vec::new();
+        let sheets: Vec<_> = if self.sheets.is_empty() {
+            vec![&empty]
+        } else {
+            self.sheets.values().collect()
+        };
+        for (index, rows) in sheets.into_iter().enumerate() {
+            parts.push((format!("xl/worksheets/sheet{}.xml", index + 1), worksheet(rows)));
+        }
+        parts
+    }
+}
+
+impl Exporter for XlsxExporter {
+    fn export(&mut self, data: &ScrapedData) -> Result<()> {
+        let fixed = vec![
+            data.url.clone(),
+            data.title.clone().unwrap_or_default(),
+            data.status_code.to_string(),
+            data.timestamp.to_rfc3339(),
+        ];
+
+        for (sheet, fields) in self.sheets_for(data) {
+            let values = fields
+                .into_iter()
+                .filter_map(|field| {
+                    let values = data.extracted_data.get(&field)?;
+                    Some((field, values.join("\n")))
+                })
+                .collect();
+            self.sheets.entry(sheet).or_default().push(Row { fixed: fixed.clone(), values });
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let mut zip = StoredZip::new(BufWriter::new(File::create(&self.path)?));
+        for (name, contents) in self.workbook_parts() {
+            zip.add(&name, contents.as_bytes())?;
+        }
+        zip.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+const ROOT_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    r#"</Relationships>"#,
+);
+
+fn content_types(sheets: usize) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+        r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+        r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+        r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    ));
+    for index in 1..=sheets {
+        xml.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+            index
+        ));
+    }
+    xml.push_str("</Types>");
+    xml
+}
+
+fn workbook(names: &[String]) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+        r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+    ));
+    for (index, name) in names.iter().enumerate() {
+        xml.push_str(&format!(
+            r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
+            escape_xml(name),
+            index + 1,
+            index + 1
+        ));
+    }
+    xml.push_str("</sheets></workbook>");
+    xml
+}
+
+fn workbook_rels(sheets: usize) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    ));
+    for index in 1..=sheets {
+        xml.push_str(&format!(
+            r#"<Relationship Id="rId{0}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{0}.xml"/>"#,
+            index
+        ));
+    }
+    xml.push_str("</Relationships>");
+    xml
+}
+
+/// Render a sheet with a header row of the fixed columns and every field seen
+fn worksheet(rows: &[Row]) -> String {
+    let fields: BTreeSet<&String> = rows.iter().flat_map(|row| row.values.keys()).collect();
+
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    ));
+
+    let header: Vec<&str> = FIXED_COLUMNS.iter().copied().chain(fields.iter().map(|field| field.as_str())).collect();
+    push_row(&mut xml, 1, header.iter().map(|cell| Some(*cell)));
+
+    for (index, row) in rows.iter().enumerate() {
+        let cells = row.fixed
+            .iter()
+            .map(|cell| Some(cell.as_str()))
+            .chain(fields.iter().map(|field| row.values.get(*field).map(String::as_str)));
+        push_row(&mut xml, index + 2, cells);
+    }
+
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn push_row<'a>(xml: &mut String, row: usize, cells: impl Iterator<Item = Option<&'a str>>) {
+    xml.push_str(&format!(r#"<row r="{}">"#, row));
+    for (column, cell) in cells.enumerate() {
+        let Some(value) = cell else { continue };
+        let reference = format!("{}{}", column_name(column), row);
+        if !value.is_empty() && value.len() < 16 && value.parse::<i64>().is_ok() {
+            xml.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, value));
+        } else {
+            xml.push_str(&format!(
+                r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                reference,
+                escape_xml(value)
+            ));
+        }
+    }
+    xml.push_str("</row>");
+}
+
+/// Spreadsheet column letters for a zero-based index (0 = A, 26 = AA)
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("column letters are ASCII")
+}
+
+/// Make a valid worksheet name not yet in `taken`
+fn unique_sheet_name(name: &str, taken: &[String]) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\') { '_' } else { c })
+        .take(MAX_SHEET_NAME_LEN)
+        .collect();
+    let cleaned = if cleaned.is_empty() { "Sheet".to_string() } else { cleaned };
+
+    let mut candidate = cleaned.clone();
+    let mut suffix = 2;
+    while taken.iter().any(|name| name.eq_ignore_ascii_case(&candidate)) {
+        let tag = format!(" ({})", suffix);
+        let base: String = cleaned.chars().take(MAX_SHEET_NAME_LEN - tag.len()).collect();
+        candidate = format!("{}{}", base, tag);
+        suffix += 1;
+    }
+    candidate
+}
+
+/// Minimal ZIP writer storing entries uncompressed, as XLSX containers allow
+struct StoredZip<W: Write> {
+    writer: W,
+    offset: u32,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl<W: Write> StoredZip<W> {
+    fn new(writer: W) -> Self {
+        Self { writer, offset: 0, central_directory: Vec::new(), entries: 0 }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name_len = name.len() as u16;
+
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        local.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        local.extend_from_slice(&0u16.to_le_bytes()); // flags
+        local.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        local.extend_from_slice(&0u16.to_le_bytes()); // modification time
+        local.extend_from_slice(&0x21u16.to_le_bytes()); // modification date: 1980-01-01
+        local.extend_from_slice(&crc.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&name_len.to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        local.extend_from_slice(name.as_bytes());
+
+        let central = &mut self.central_directory;
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&local[4..30]);
+        central.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        central.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        central.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        central.extend_from_slice(&self.offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+
+        self.writer.write_all(&local)?;
+        self.writer.write_all(data)?;
+        self.offset += local.len() as u32 + size;
+        self.entries += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<W> {
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        end.extend_from_slice(&0u16.to_le_bytes()); // disk with central directory
+        end.extend_from_slice(&self.entries.to_le_bytes());
+        end.extend_from_slice(&self.entries.to_le_bytes());
+        end.extend_from_slice(&(self.central_directory.len() as u32).to_le_bytes());
+        end.extend_from_slice(&self.offset.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+        self.writer.write_all(&self.central_directory)?;
+        self.writer.write_all(&end)?;
+        Ok(self.writer)
+    }
+}
+
+/// CRC-32 (IEEE) checksum as required by ZIP entries
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::ExtractionRuleBuilder;
+
+    fn record(url: &str, fields: &[(&str, &str)]) -> ScrapedData {
+        let mut data = ScrapedData::new(url.to_string());
+        data.status_code = 200;
+        for (field, value) in fields {
+            data.add_extracted_data(field, vec![value.to_string()]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(unique_sheet_name("a/b", &["a_b".to_string()]), "a_b (2)");
+    }
+
+    #[test]
+    fn test_xlsx_by_group() {
+        let extractor = DataExtractor::with_rules(vec![
+            ExtractionRuleBuilder::new("price", ".price").group("pricing").build(),
+            ExtractionRuleBuilder::new("title", "h1").build(),
+        ]);
+        let path = std::env::temp_dir().join(format!("ferrisfetcher-export-test-{}.xlsx", std::process::id()));
+
+        let mut exporter = XlsxExporter::new(&path).by_group(&extractor);
+        exporter.export(&record("https://shop.example/1", &[("price", "10"), ("title", "A & B")])).unwrap();
+
+        let parts = exporter.workbook_parts();
+        let names: Vec<&str> = parts.iter().map(|(name, _)| name.as_str()).collect();
+        assert!(names.contains(&"xl/worksheets/sheet2.xml"));
+        assert!(parts[2].1.contains(r#"<sheet name="Other" sheetId="1" r:id="rId1"/><sheet name="pricing""#));
+        let pricing = &parts.iter().find(|(name, _)| name == "xl/worksheets/sheet2.xml").unwrap().1;
+        assert!(pricing.contains(r#"<c r="E1" t="inlineStr"><is><t xml:space="preserve">price</t></is></c>"#));
+        assert!(pricing.contains(r#"<c r="E2"><v>10</v></c>"#));
+        assert!(parts[4].1.contains("A &amp; B"));
+
+        exporter.finish().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(&bytes[bytes.len() - 22..bytes.len() - 18], b"PK\x05\x06");
+        std::fs::remove_file(&path).ok();
+    }
+}